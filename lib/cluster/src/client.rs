@@ -13,13 +13,16 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{io, marker::PhantomData, time::Duration};
+use std::{collections::HashMap, io, marker::PhantomData, time::{Duration, Instant}};
 
 use log::debug;
 use tokio::{sync::{mpsc::{self, Receiver, Sender}, Mutex}, time};
 use zeromq::{DealerSocket, Socket, SocketRecv, SocketSend, ZmqMessage};
 
-use crate::{identifier::Identifier, state::StateMessage, ClusterResult, Error, Notification, Request, Response};
+use crate::{identifier::Identifier, metrics::{REQUESTS_SENT, ROUND_TRIP}, state::StateMessage, ClusterResult, Error, Notification, Request, RequestId, Response};
+
+// Requests that didn't get a reply within this time are no longer tracked
+const PENDING_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
 enum ClientMessage<T: Request> {
     State(StateMessage),
@@ -44,11 +47,16 @@ impl <T: Request + 'static, TR: Response + 'static, N: Notification + 'static>Cl
 
         async fn start_receiver_task<T: Request + 'static, TR: Response + 'static, N: Notification + 'static>(
             mut socket: DealerSocket, 
+            endpoint: String,
             mut tx_receiver: Receiver<ClientMessage<T>>, 
             rx_sender: Sender<TR>, 
             notification_sender: Sender<N>
         ) {
             let mut interval = time::interval(Duration::from_millis(100));
+            let mut next_request_id: RequestId = 0;
+            let mut pending_requests = HashMap::<RequestId, Instant>::new();
+            let round_trip = ROUND_TRIP.with_label_values(&[&endpoint]);
+            let requests_sent = REQUESTS_SENT.with_label_values(&[&endpoint]);
 
             tokio::spawn(async move {
                 loop {
@@ -61,6 +69,16 @@ impl <T: Request + 'static, TR: Response + 'static, N: Notification + 'static>Cl
                                     match identifier {
                                         Identifier::Response => {
                                             let response = flexbuffers::from_slice(message.get(1).unwrap()).unwrap();
+
+                                            // Replies carry the id of the request they answer
+                                            if 
+                                                let Some(id) = message.get(2) &&
+                                                let Ok(id) = flexbuffers::from_slice::<RequestId>(id) &&
+                                                let Some(sent_at) = pending_requests.remove(&id)
+                                            {
+                                                round_trip.observe(sent_at.elapsed().as_secs_f64());
+                                            }
+
                                             let _ = rx_sender.send(response).await;
                                         },
                                         Identifier::Notification => {
@@ -90,9 +108,20 @@ impl <T: Request + 'static, TR: Response + 'static, N: Notification + 'static>Cl
                                     let _ = socket.send(msg).await;
                                 },
                                 ClientMessage::Request(request) => {
+                                    let expects_reply = request.expects_reply();
+
                                     let mut msg = ZmqMessage::from(flexbuffers::to_vec(Identifier::Request).unwrap());
                                     msg.push_back(flexbuffers::to_vec(request).unwrap().into());
 
+                                    // Only requests expecting a reply carry an id
+                                    if expects_reply {
+                                        msg.push_back(flexbuffers::to_vec(next_request_id).unwrap().into());
+
+                                        pending_requests.insert(next_request_id, Instant::now());
+                                        next_request_id = next_request_id.wrapping_add(1);
+                                    }
+
+                                    requests_sent.inc();
                                     let _ = socket.send(msg).await;
                                 },
                             }
@@ -103,6 +132,9 @@ impl <T: Request + 'static, TR: Response + 'static, N: Notification + 'static>Cl
                                 debug!("Error sending message: {e:?}");
                                 break;
                             }
+
+                            // Requests might go unanswered, e.g. offers that weren't accepted
+                            pending_requests.retain(|_, sent_at| sent_at.elapsed() < PENDING_REQUEST_TIMEOUT);
                         }
                     }
                 }
@@ -112,7 +144,7 @@ impl <T: Request + 'static, TR: Response + 'static, N: Notification + 'static>Cl
             });
         }
 
-        start_receiver_task::<T, TR, N>(socket, tx_receiver, rx_sender, notification_sender).await;
+        start_receiver_task::<T, TR, N>(socket, uri.to_string(), tx_receiver, rx_sender, notification_sender).await;
 
         Ok((
             Self {
@@ -19,6 +19,7 @@ mod client;
 mod state;
 mod identifier;
 mod message;
+mod metrics;
 
 pub mod notification;
 
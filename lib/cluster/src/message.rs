@@ -15,5 +15,11 @@
 
 use serde::{de::DeserializeOwned, Serialize};
 
-pub trait Request: Serialize + DeserializeOwned + Send {}
-pub trait Response: Serialize + DeserializeOwned + Send {}
\ No newline at end of file
+pub trait Request: Serialize + DeserializeOwned + Send {
+    /// Requests answered using `ClusterServer::reply` should return true,
+    /// so clients can measure the time until the reply arrives.
+    fn expects_reply(&self) -> bool { false }
+}
+pub trait Response: Serialize + DeserializeOwned + Send {}
+/// Identifies a request, so the server can reply to it.
+pub type RequestId = u64;
@@ -0,0 +1,38 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::sync::LazyLock;
+
+use prometheus::{register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec};
+
+pub(crate) static ROUND_TRIP: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "cluster_round_trip_seconds", 
+        "Time between sending a cluster request and receiving its reply",
+        &["endpoint"],
+        vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]
+    ).expect("metric can be registered")
+});
+
+pub(crate) static REQUESTS_SENT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("cluster_requests_sent_total", "Number of requests sent by cluster clients", &["endpoint"])
+        .expect("metric can be registered")
+});
+
+pub(crate) static REQUESTS_RECEIVED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("cluster_requests_received_total", "Number of requests received by cluster servers", &["endpoint"])
+        .expect("metric can be registered")
+});
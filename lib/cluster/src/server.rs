@@ -21,7 +21,7 @@ use log::{debug, info};
 use tokio::{sync::{broadcast::{self, Receiver}, mpsc, Mutex, RwLock}, time};
 use zeromq::{util::PeerIdentity, Endpoint, RouterSocket, Socket, SocketEvent, SocketRecv, SocketSend, ZmqMessage};
 
-use crate::{identifier::Identifier, message::{Request, Response}, notification::Notification, state::StateMessage, ClusterResult, Error};
+use crate::{identifier::Identifier, message::{Request, RequestId, Response}, metrics::REQUESTS_RECEIVED, notification::Notification, state::StateMessage, ClusterResult, Error};
 
 #[derive(Default)]
 struct ClientState {
@@ -48,7 +48,7 @@ pub enum ClusterEvent {
 
 pub struct ClusterServer<T: Request, TR: Response, N: Notification> {
     tx_sender: mpsc::Sender<ZmqMessage>,
-    rx_receiver: Arc<Mutex<mpsc::Receiver<(PeerIdentity, T)>>>,
+    rx_receiver: Arc<Mutex<mpsc::Receiver<(PeerIdentity, Option<RequestId>, T)>>>,
     clients: Arc<RwLock<HashMap<PeerIdentity, ClientState>>>,
     event_sender: broadcast::Sender<ClusterEvent>,
 
@@ -114,13 +114,15 @@ impl <T: Request + 'static, TR: Response, N: Notification>ClusterServer<T, TR, N
 
         async fn start_socket<T: Request + 'static>(
             mut socket: RouterSocket, 
+            endpoint: String,
             tx_sender: mpsc::Sender<ZmqMessage>,
             mut tx_receiver: mpsc::Receiver<ZmqMessage>,
-            rx_sender: mpsc::Sender<(PeerIdentity, T)>,
+            rx_sender: mpsc::Sender<(PeerIdentity, Option<RequestId>, T)>,
             clients: Arc<RwLock<HashMap<PeerIdentity, ClientState>>>
         ) {
             tokio::spawn(async move {
                 let mut interval = time::interval(Duration::from_millis(100));
+                let requests_received = REQUESTS_RECEIVED.with_label_values(&[&endpoint]);
 
                 loop {
                     tokio::select! {
@@ -133,8 +135,13 @@ impl <T: Request + 'static, TR: Response, N: Notification>ClusterServer<T, TR, N
         
                             match identifier {
                                 Identifier::Request => {
+                                    requests_received.inc();
+
                                     let request = flexbuffers::from_slice(message.get(2).unwrap()).unwrap();
-                                    let _ = rx_sender.send((identity, request)).await;
+                                    // Fire-and-forget requests, or peers using the old framing, don't send an id
+                                    let id = message.get(3)
+                                        .and_then(|id| flexbuffers::from_slice::<RequestId>(id).ok());
+                                    let _ = rx_sender.send((identity, id, request)).await;
                                 },
                                 Identifier::State => {
                                     let state = flexbuffers::from_slice::<StateMessage>(message.get(2).unwrap()).unwrap();
@@ -162,7 +169,7 @@ impl <T: Request + 'static, TR: Response, N: Notification>ClusterServer<T, TR, N
         }
         
         start_monitor(socket.monitor(), clients.clone(), event_sender.clone()).await;
-        start_socket::<T>(socket, tx_sender.clone(), tx_receiver, rx_sender, clients.clone()).await;
+        start_socket::<T>(socket, endpoint.to_string(), tx_sender.clone(), tx_receiver, rx_sender, clients.clone()).await;
 
         Ok(Self {
             tx_sender,
@@ -206,7 +213,22 @@ impl <T: Request + 'static, TR: Response, N: Notification>ClusterServer<T, TR, N
             .map_err(|_| Error::IoError(io::Error::from(io::ErrorKind::BrokenPipe)))
     }
 
-    pub async fn recv(&self) -> ClusterResult<(PeerIdentity, T)> {
+    /// Answers the given request. Unlike `send`, the response is matched up with
+    /// the request on the client side, if the request carried an id.
+    pub async fn reply(&self, peer: &PeerIdentity, request: Option<RequestId>, msg: TR) -> ClusterResult<()> {
+        let mut frame = ZmqMessage::from(Bytes::from(peer.clone()));
+        frame.push_back(flexbuffers::to_vec(Identifier::Response)?.into());
+        frame.push_back(flexbuffers::to_vec(msg)?.into());
+
+        if let Some(request) = request {
+            frame.push_back(flexbuffers::to_vec(request)?.into());
+        }
+
+        self.tx_sender.send(frame).await
+            .map_err(|_| Error::IoError(io::Error::from(io::ErrorKind::BrokenPipe)))
+    }
+
+    pub async fn recv(&self) -> ClusterResult<(PeerIdentity, Option<RequestId>, T)> {
         let mut receiver = self.rx_receiver.lock().await;
         receiver.recv().await
             .ok_or(Error::IoError(io::Error::from(io::ErrorKind::BrokenPipe)))
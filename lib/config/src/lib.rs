@@ -18,10 +18,11 @@ mod types;
 use config_crate::File;
 use once_cell::sync::Lazy;
 use glob::glob;
+use serde::de::DeserializeOwned;
 
 pub use types::*;
 
-pub static CLUSTER_CONF: Lazy<ConfClusterConfig> = Lazy::new(|| {
+fn load_config<T: DeserializeOwned>() -> T {
     type Config = ::config_crate::Config;
     
     let mut builder = Config::builder()
@@ -44,33 +45,10 @@ pub static CLUSTER_CONF: Lazy<ConfClusterConfig> = Lazy::new(|| {
     builder
         .build()
         .unwrap()
-        .try_deserialize::<ConfClusterConfig>()
+        .try_deserialize::<T>()
         .expect("Failed to parse config")
-});
+}
 
-pub static REALM_CONF: Lazy<ConfRealmMain> = Lazy::new(|| {
-    type Config = ::config_crate::Config;
-    
-    let mut builder = Config::builder()
-        .add_source(
-            glob("conf/*.toml")
-                .unwrap()
-                .map(|path| File::from(path.unwrap()))
-                .collect::<Vec<_>>(),
-        );
-
-    if cfg!(unix) {
-        builder = builder.add_source(
-            glob("/etc/anotherland/*.toml")
-                .unwrap()
-                .map(|path| File::from(path.unwrap()))
-                .collect::<Vec<_>>(),
-        );
-    }
-        
-    builder
-        .build()
-        .unwrap()
-        .try_deserialize::<ConfRealmMain>()
-        .expect("Failed to parse config")
-});
\ No newline at end of file
+pub static CLUSTER_CONF: Lazy<ConfClusterConfig> = Lazy::new(load_config);
+pub static REALM_CONF: Lazy<ConfRealmMain> = Lazy::new(load_config);
+pub static METRICS_CONF: Lazy<ConfMetricsMain> = Lazy::new(load_config);
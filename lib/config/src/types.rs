@@ -18,6 +18,14 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize, Default)]
 pub struct ConfLoginServer {
     pub one_time_password_duration: Option<i64>,
+    /// Failed logins before an account or address gets locked out.
+    pub failed_login_threshold: Option<u32>,
+    /// Seconds without failures after which the count starts over.
+    pub failed_login_reset: Option<i64>,
+    /// Seconds of the first lockout, doubled with every further failure.
+    pub failed_login_lockout: Option<i64>,
+    /// Upper limit of a lockout in seconds, no matter how many failures followed.
+    pub failed_login_max_lockout: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -26,7 +34,43 @@ pub struct ConfClusterConfig {
     pub login: ConfLoginServer,
 }
 
+/// Restricts zones matching all given criteria to world nodes
+/// carrying at least one of the listed tags.
+#[derive(Debug, Deserialize, Default)]
+pub struct ConfPlacementRule {
+    pub zone: Option<String>,
+    pub zone_type: Option<String>,
+    pub instanced: Option<bool>,
+    pub node_tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ConfPlacement {
+    #[serde(default)]
+    pub rules: Vec<ConfPlacementRule>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ConfCharacters {
+    /// Seconds a deleted character can be restored before it's purged.
+    pub deletion_grace: Option<i64>,
+}
+
 #[derive(Debug, Deserialize)]
-#[allow(unused)]
 pub struct ConfRealmMain {
+    #[serde(default)]
+    pub placement: ConfPlacement,
+    #[serde(default)]
+    pub characters: ConfCharacters,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ConfMetrics {
+    pub listen_address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfMetricsMain {
+    #[serde(default)]
+    pub metrics: ConfMetrics,
 }
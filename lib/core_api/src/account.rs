@@ -15,13 +15,13 @@
 
 use std::fmt::Display;
 
-use account_graphql::{AuthQuery, EmailQuery, FindAccount, FindAccountVariables, RegisterSteamAccount, RegisterSteamAccountVariables, SteamQuery, UsernameQuery};
+use account_graphql::{AuthQuery, BanAccount, BanAccountVariables, DemoteAccount, EmailQuery, FindAccount, FindAccountVariables, PromoteAccount, RegisterEmailAccount, RegisterEmailAccountVariables, RegisterSteamAccount, RegisterSteamAccountVariables, SteamQuery, UnbanAccount, UpdateAccountVariables, UsernameQuery};
 use chrono::{DateTime, Utc};
-use cynic::{http::ReqwestExt, MutationBuilder, QueryBuilder};
+use cynic::{http::ReqwestExt, GraphQlError, MutationBuilder, QueryBuilder};
 use steamworks::SteamId;
 use toolkit::types::Uuid;
 
-use crate::{error::CoreApiResult, CoreApi, CoreApiError};
+use crate::{error::CoreApiResult, schema, CoreApi, CoreApiError};
 
 pub enum Identifier {
     Username(String),
@@ -44,7 +44,7 @@ pub enum AccountQuery {
 }
 
 pub struct Account {
-    _api_base: CoreApi,
+    api_base: CoreApi,
 
     id: Uuid,
     numeric_id: i32,
@@ -53,13 +53,14 @@ pub struct Account {
     last_login: Option<DateTime<Utc>>,
     banned: bool,
     ban_reason: Option<String>,
+    banned_until: Option<DateTime<Utc>>,
     is_gm: bool,
 }
 
 impl Account {
     pub(crate) fn from_graphql(api_base: &CoreApi, account: account_graphql::Account) -> Self {
         Self {
-            _api_base: api_base.clone(),
+            api_base: api_base.clone(),
 
             id: account.id.0.parse().unwrap(),
             numeric_id: account.numeric_id,
@@ -74,6 +75,7 @@ impl Account {
             last_login: account.last_login.map(|date| date.0.parse().unwrap()),
             banned: account.banned,
             ban_reason: account.ban_reason,
+            banned_until: account.banned_until.map(|date| date.0.parse().unwrap()),
             is_gm: account.is_gm,
         }
     }
@@ -85,12 +87,67 @@ impl Account {
     pub fn last_login(&self) -> Option<&DateTime<Utc>> { self.last_login.as_ref() }
     pub fn banned(&self) -> bool { self.banned }
     pub fn ban_reason(&self) -> Option<&str> { self.ban_reason.as_deref() }
+    pub fn banned_until(&self) -> Option<&DateTime<Utc>> { self.banned_until.as_ref() }
     pub fn is_gm(&self) -> bool { self.is_gm }
 
-    pub async fn ban(&mut self, _reason: String) -> CoreApiResult<()> { todo!() }
-    pub async fn unban(&mut self) -> CoreApiResult<()> { todo!() }
-    pub async fn promote(&mut self) -> CoreApiResult<()> { todo!() }
-    pub async fn demote(&mut self) -> CoreApiResult<()> { todo!() }
+    /// Bans the account, permanently if no expiry date is given, 
+    /// and terminates its active session.
+    pub async fn ban(&mut self, reason: String, until: Option<DateTime<Utc>>, issued_by: &str) -> CoreApiResult<()> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(BanAccount::build(BanAccountVariables {
+                id: schema::Uuid(self.id.to_string()),
+                reason: &reason,
+                until: until.map(|until| schema::DateTime(until.to_rfc3339())),
+                issued_by,
+            })).await?;
+
+        self.apply_update(response.data.map(|res| res.ban_account), response.errors)
+    }
+
+    pub async fn unban(&mut self, issued_by: &str) -> CoreApiResult<()> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(UnbanAccount::build(UpdateAccountVariables {
+                id: schema::Uuid(self.id.to_string()),
+                issued_by,
+            })).await?;
+
+        self.apply_update(response.data.map(|res| res.unban_account), response.errors)
+    }
+
+    pub async fn promote(&mut self, issued_by: &str) -> CoreApiResult<()> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(PromoteAccount::build(UpdateAccountVariables {
+                id: schema::Uuid(self.id.to_string()),
+                issued_by,
+            })).await?;
+
+        self.apply_update(response.data.map(|res| res.promote_account), response.errors)
+    }
+
+    pub async fn demote(&mut self, issued_by: &str) -> CoreApiResult<()> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(DemoteAccount::build(UpdateAccountVariables {
+                id: schema::Uuid(self.id.to_string()),
+                issued_by,
+            })).await?;
+
+        self.apply_update(response.data.map(|res| res.demote_account), response.errors)
+    }
+
+    fn apply_update(&mut self, account: Option<Option<account_graphql::Account>>, errors: Option<Vec<GraphQlError>>) -> CoreApiResult<()> {
+        if let Some(errors) = errors {
+            Err(CoreApiError::GraphQl(errors))
+        } else if let Some(Some(account)) = account {
+            *self = Account::from_graphql(&self.api_base, account);
+            Ok(())
+        } else {
+            Err(CoreApiError::AccountNotFound)
+        }
+    }
 }
 
 impl CoreApi {
@@ -109,6 +166,22 @@ impl CoreApi {
         }
     }
 
+    pub async fn register_email_account(&self, username: &str, email: Option<&str>, password: &str) -> CoreApiResult<Account> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(RegisterEmailAccount::build(RegisterEmailAccountVariables {
+                username,
+                email,
+                password: Some(password),
+            })).await?;
+
+        if let Some(account) = response.data.map(|res| res.register_email_account) {
+            Ok(Account::from_graphql(self, account))
+        } else {
+            Err(CoreApiError::GraphQl(response.errors.unwrap()))
+        }
+    }
+
     pub async fn find_account(&self, query: AccountQuery) -> CoreApiResult<Option<Account>> {
         let query = match &query {
             AccountQuery::Username(username) => AuthQuery {
@@ -163,6 +236,13 @@ pub(crate) mod account_graphql {
         pub steam_id: &'a str,
     }
 
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct RegisterEmailAccountVariables<'a> {
+        pub username: &'a str,
+        pub email: Option<&'a str>,
+        pub password: Option<&'a str>,
+    }
+
     #[derive(cynic::QueryVariables, Debug)]
     pub struct FindAccountVariables<'a> {
         pub auth_query: AuthQuery<'a>,
@@ -204,6 +284,55 @@ pub(crate) mod account_graphql {
         pub register_steam_account: Account,
     }
 
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "core_service", graphql_type = "MutationRoot", variables = "RegisterEmailAccountVariables")]
+    pub struct RegisterEmailAccount {
+        #[arguments(username: $username, email: $email, password: $password)]
+        pub register_email_account: Account,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct BanAccountVariables<'a> {
+        pub id: Uuid,
+        pub reason: &'a str,
+        pub until: Option<DateTime>,
+        pub issued_by: &'a str,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct UpdateAccountVariables<'a> {
+        pub id: Uuid,
+        pub issued_by: &'a str,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "core_service", graphql_type = "MutationRoot", variables = "BanAccountVariables")]
+    pub struct BanAccount {
+        #[arguments(id: $id, reason: $reason, until: $until, issuedBy: $issued_by)]
+        pub ban_account: Option<Account>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "core_service", graphql_type = "MutationRoot", variables = "UpdateAccountVariables")]
+    pub struct UnbanAccount {
+        #[arguments(id: $id, issuedBy: $issued_by)]
+        pub unban_account: Option<Account>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "core_service", graphql_type = "MutationRoot", variables = "UpdateAccountVariables")]
+    pub struct PromoteAccount {
+        #[arguments(id: $id, issuedBy: $issued_by)]
+        pub promote_account: Option<Account>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "core_service", graphql_type = "MutationRoot", variables = "UpdateAccountVariables")]
+    pub struct DemoteAccount {
+        #[arguments(id: $id, issuedBy: $issued_by)]
+        pub demote_account: Option<Account>,
+    }
+
     #[derive(cynic::QueryFragment, Debug)]
     #[cynic(schema = "core_service", graphql_type = "QueryRoot", variables = "FindAccountVariables")]
     pub struct FindAccount {
@@ -216,6 +345,7 @@ pub(crate) mod account_graphql {
     pub struct Account {
         pub ban_reason: Option<String>,
         pub banned: bool,
+        pub banned_until: Option<DateTime>,
         pub created: DateTime,
         pub id: Uuid,
         pub identifier: Identifier,
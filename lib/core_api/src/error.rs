@@ -23,6 +23,9 @@ pub enum CoreApiError {
 
     #[error("graphql error")]
     GraphQl(Vec<GraphQlError>),
+
+    #[error("account not found")]
+    AccountNotFound,
 }
 
 pub type CoreApiResult<T> = std::result::Result<T, CoreApiError>;
\ No newline at end of file
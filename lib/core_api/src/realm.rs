@@ -20,11 +20,40 @@ use realm_graphql::{GetRealm, GetRealmVariables, GetRealms};
 
 use crate::{CoreApi, CoreApiError, CoreApiResult};
 
+pub struct RealmChannel {
+    id: i32,
+    population: f64,
+    players: i32,
+    max_players: Option<i32>,
+    endpoint: Option<SocketAddrV4>,
+}
+
+impl RealmChannel {
+    pub(crate) fn from_graphql(channel: realm_graphql::RealmChannel) -> Self {
+        Self {
+            id: channel.id,
+            population: channel.population,
+            players: channel.players,
+            max_players: channel.max_players,
+            endpoint: channel.endpoint.map(|s| s.parse().unwrap()),
+        }
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn population(&self) -> f64 { self.population }
+    pub fn players(&self) -> i32 { self.players }
+    pub fn max_players(&self) -> Option<i32> { self.max_players }
+    pub fn endpoint(&self) -> Option<&SocketAddrV4> { self.endpoint.as_ref() }
+}
+
 pub struct Realm {
     id: i32,
     name: String,
     population: Option<f64>,
+    players: Option<i32>,
+    max_players: Option<i32>,
     endpoint: Option<SocketAddrV4>,
+    channels: Vec<RealmChannel>,
 }
 
 impl Realm {
@@ -33,14 +62,23 @@ impl Realm {
             id: realm.id,
             name: realm.name,
             population: realm.population,
+            players: realm.players,
+            max_players: realm.max_players,
             endpoint: realm.endpoint.map(|s| s.parse().unwrap()),
+            channels: realm.channels.into_iter().map(RealmChannel::from_graphql).collect(),
         }
     }
 
     pub fn id(&self) -> i32 { self.id }
     pub fn name(&self) -> &str { &self.name }
     pub fn population(&self) -> Option<f64> { self.population }
+    pub fn players(&self) -> Option<i32> { self.players }
+    pub fn max_players(&self) -> Option<i32> { self.max_players }
     pub fn endpoint(&self) -> Option<&SocketAddrV4> { self.endpoint.as_ref() }
+    pub fn channels(&self) -> &[RealmChannel] { &self.channels }
+    pub fn channel(&self, id: i32) -> Option<&RealmChannel> {
+        self.channels.iter().find(|channel| channel.id == id)
+    }
 }
 
 impl CoreApi {
@@ -98,6 +136,19 @@ pub(crate) mod realm_graphql {
         pub id: i32,
         pub name: String,
         pub population: Option<f64>,
+        pub players: Option<i32>,
+        pub max_players: Option<i32>,
+        pub endpoint: Option<String>,
+        pub channels: Vec<RealmChannel>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "core_service")]
+    pub struct RealmChannel {
+        pub id: i32,
+        pub population: f64,
+        pub players: i32,
+        pub max_players: Option<i32>,
         pub endpoint: Option<String>,
     }
 }
@@ -67,15 +67,19 @@ pub enum LoginError {
     WrongCredentials,
     Banned,
     ServerLocked,
+    TooManyAttempts,
 }
 
 impl CoreApi {
-    pub async fn login_username<'a>(&self, username_or_mail: &'a str, password: &'a str) -> CoreApiResult<Result<Session, LoginError>> {
+    /// Logs in with username and password. Failed attempts are counted against
+    /// the account and, if given, the client address.
+    pub async fn login_username<'a>(&self, username_or_mail: &'a str, password: &'a str, client_address: Option<&'a str>) -> CoreApiResult<Result<Session, LoginError>> {
         let response = self.0.client
             .post(self.0.base_url.clone())
             .run_graphql(LoginUsername::build(LoginUsernameVariables {
                 username_or_mail,
-                password
+                password,
+                client_address,
             })).await?;
 
         if let Some(result) = response.data.map(|res| res.create_session) {
@@ -87,6 +91,7 @@ impl CoreApi {
                         session_graphql::AuthError::Banned => LoginError::Banned,
                         session_graphql::AuthError::ServerLocked => LoginError::ServerLocked,
                         session_graphql::AuthError::WrongCredentials => LoginError::WrongCredentials,
+                        session_graphql::AuthError::TooManyAttempts => LoginError::TooManyAttempts,
                     }
                 ))
             }
@@ -115,6 +120,7 @@ impl CoreApi {
                         session_graphql::AuthError::Banned => LoginError::Banned,
                         session_graphql::AuthError::ServerLocked => LoginError::ServerLocked,
                         session_graphql::AuthError::WrongCredentials => LoginError::WrongCredentials,
+                        session_graphql::AuthError::TooManyAttempts => LoginError::TooManyAttempts,
                     }
                 ))
             }
@@ -153,6 +159,7 @@ pub(crate) mod session_graphql {
     pub struct LoginUsernameVariables<'a> {
         pub username_or_mail: &'a str,
         pub password: &'a str,
+        pub client_address: Option<&'a str>,
     }
 
     #[derive(cynic::QueryVariables, Debug)]
@@ -183,7 +190,7 @@ pub(crate) mod session_graphql {
     #[derive(cynic::QueryFragment, Debug)]
     #[cynic(schema = "core_service", graphql_type = "MutationRoot", variables = "LoginUsernameVariables")]
     pub struct LoginUsername {
-        #[arguments(auth: { emailAuth: { password: $password, usernameOrMail: $username_or_mail } })]
+        #[arguments(auth: { emailAuth: { password: $password, usernameOrMail: $username_or_mail, clientAddress: $client_address } })]
         pub create_session: AuthResult,
     }
 
@@ -211,6 +218,7 @@ pub(crate) mod session_graphql {
         Banned,
         ServerLocked,
         WrongCredentials,
+        TooManyAttempts,
     }
 
     #[derive(cynic::QueryFragment, Debug)]
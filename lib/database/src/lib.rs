@@ -16,7 +16,11 @@
 mod record;
 mod error;
 mod mongoext;
+mod metrics;
+mod datetime;
 
 pub use record::*;
 pub use error::*;
-pub use mongoext::*;
\ No newline at end of file
+pub use mongoext::*;
+pub use metrics::*;
+pub use datetime::*;
\ No newline at end of file
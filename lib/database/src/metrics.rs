@@ -0,0 +1,63 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::sync::LazyLock;
+
+use mongodb::{event::{command::CommandEvent, EventHandler}, options::ClientOptions, Client};
+use prometheus::{register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec};
+
+use crate::DBResult;
+
+static COMMAND_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "mongodb_command_duration_seconds", 
+        "Duration of MongoDB commands",
+        &["command"],
+        vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]
+    ).expect("metric can be registered")
+});
+
+static COMMAND_FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("mongodb_command_failures_total", "Number of failed MongoDB commands", &["command"])
+        .expect("metric can be registered")
+});
+
+/// Connects to MongoDB, recording the timing of every command
+/// that is issued through the returned client.
+pub async fn connect_instrumented(uri: &str) -> DBResult<Client> {
+    let mut options = ClientOptions::parse(uri).await?;
+
+    options.command_event_handler = Some(EventHandler::callback(|event| {
+        match event {
+            CommandEvent::Succeeded(event) => {
+                COMMAND_DURATION
+                    .with_label_values(&[&event.command_name])
+                    .observe(event.duration.as_secs_f64());
+            },
+            CommandEvent::Failed(event) => {
+                COMMAND_DURATION
+                    .with_label_values(&[&event.command_name])
+                    .observe(event.duration.as_secs_f64());
+                COMMAND_FAILURES
+                    .with_label_values(&[&event.command_name])
+                    .inc();
+            },
+            CommandEvent::Started(_) => (),
+        }
+    }));
+
+    Ok(Client::with_options(options)?)
+}
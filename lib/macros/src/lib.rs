@@ -46,6 +46,8 @@ pub fn service_main(attr: proc_macro::TokenStream, item: proc_macro::TokenStream
 
                 #config
 
+                toolkit::metrics::start_metrics_server();
+
                 #block
             }
         }
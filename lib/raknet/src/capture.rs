@@ -0,0 +1,247 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fs::File, io::{BufReader, BufWriter, ErrorKind, Read, Write}, path::Path, sync::mpsc::{self, Receiver, Sender}, thread::{self, JoinHandle}, time::{SystemTime, UNIX_EPOCH}};
+
+use log::warn;
+
+use crate::{RakNetError, error::Result};
+
+// Capture files start with a magic and a format version, followed by records of
+// [timestamp: u64][direction: u8][length: u32][payload], all little-endian.
+const CAPTURE_MAGIC: &[u8; 4] = b"RNCP";
+const CAPTURE_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureDirection {
+    /// Payload received from the peer
+    Received,
+    /// Payload sent to the peer
+    Sent,
+}
+
+impl CaptureDirection {
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Received => 0,
+            Self::Sent => 1,
+        }
+    }
+
+    fn from_u8(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::Received),
+            1 => Ok(Self::Sent),
+            _ => Err(RakNetError::InvalidCapture),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CaptureRecord {
+    /// Microseconds since the unix epoch
+    pub timestamp: u64,
+    pub direction: CaptureDirection,
+    pub payload: Vec<u8>,
+}
+
+/// Appends decrypted payloads of a single connection to a capture file.
+/// Records are handed to a dedicated writer thread, so capturing never
+/// blocks the connection on file io.
+pub(crate) struct CaptureWriter {
+    sender: Option<Sender<CaptureRecord>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CaptureWriter {
+    pub(crate) fn create(path: &Path) -> Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)
+            .map_err(RakNetError::CaptureError)?);
+
+        writer.write_all(CAPTURE_MAGIC)
+            .and_then(|_| writer.write_all(&[CAPTURE_VERSION]))
+            .and_then(|_| writer.flush())
+            .map_err(RakNetError::CaptureError)?;
+
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("raknet-capture".to_string())
+            .spawn(move || write_records(writer, receiver))
+            .map_err(RakNetError::CaptureError)?;
+
+        Ok(Self {
+            sender: Some(sender),
+            thread: Some(thread),
+        })
+    }
+
+    pub(crate) fn write(&self, direction: CaptureDirection, payload: &[u8]) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_micros() as u64)
+            .unwrap_or_default();
+
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(CaptureRecord {
+                timestamp,
+                direction,
+                payload: payload.to_vec(),
+            });
+        }
+    }
+
+    /// Closes the capture and waits until all records are on disk.
+    #[cfg(test)]
+    fn close(mut self) {
+        self.sender.take();
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for CaptureWriter {
+    fn drop(&mut self) {
+        // Closing the channel lets the writer thread drain the 
+        // remaining records and exit. Sockets are dropped on async 
+        // runtime threads, so the writer thread is detached instead
+        // of joined.
+        self.sender.take();
+        self.thread.take();
+    }
+}
+
+fn write_records(mut writer: BufWriter<File>, receiver: Receiver<CaptureRecord>) {
+    while let Ok(mut record) = receiver.recv() {
+        loop {
+            let res = writer.write_all(&record.timestamp.to_le_bytes())
+                .and_then(|_| writer.write_all(&[record.direction.to_u8()]))
+                .and_then(|_| writer.write_all(&(record.payload.len() as u32).to_le_bytes()))
+                .and_then(|_| writer.write_all(&record.payload));
+
+            if let Err(e) = res {
+                warn!("Failed to write capture record: {e}");
+            }
+
+            match receiver.try_recv() {
+                Ok(next) => record = next,
+                Err(_) => break,
+            }
+        }
+
+        // Flush once the backlog is written, so captures of crashed 
+        // services are still usable
+        if let Err(e) = writer.flush() {
+            warn!("Failed to flush capture file: {e}");
+        }
+    }
+}
+
+/// Reads the records of a capture file written by a capturing [`crate::RakNetSocket`].
+pub struct CaptureReader<R: Read>(R);
+
+impl CaptureReader<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)
+            .map_err(RakNetError::CaptureError)?))
+    }
+}
+
+impl <R: Read> CaptureReader<R> {
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header)
+            .map_err(RakNetError::CaptureError)?;
+
+        if &header[..4] != CAPTURE_MAGIC || header[4] != CAPTURE_VERSION {
+            return Err(RakNetError::InvalidCapture);
+        }
+
+        Ok(Self(reader))
+    }
+
+    fn read_record(&mut self) -> Result<Option<CaptureRecord>> {
+        let mut timestamp = [0u8; 8];
+        match self.0.read_exact(&mut timestamp) {
+            Ok(_) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(RakNetError::CaptureError(e)),
+        }
+
+        let mut direction = [0u8; 1];
+        let mut len = [0u8; 4];
+
+        self.0.read_exact(&mut direction)
+            .and_then(|_| self.0.read_exact(&mut len))
+            .map_err(RakNetError::CaptureError)?;
+
+        let mut payload = vec![0; u32::from_le_bytes(len) as usize];
+        self.0.read_exact(&mut payload)
+            .map_err(RakNetError::CaptureError)?;
+
+        Ok(Some(CaptureRecord {
+            timestamp: u64::from_le_bytes(timestamp),
+            direction: CaptureDirection::from_u8(direction[0])?,
+            payload,
+        }))
+    }
+}
+
+impl <R: Read> Iterator for CaptureReader<R> {
+    type Item = Result<CaptureRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn read_written_records() {
+        let path = std::env::temp_dir().join(format!("raknet-capture-{}.rncap", uuid::Uuid::new_v4()));
+
+        let writer = CaptureWriter::create(&path).unwrap();
+        writer.write(CaptureDirection::Received, &[1, 2, 3]);
+        writer.write(CaptureDirection::Sent, &[]);
+        writer.close();
+
+        let records = CaptureReader::open(&path).unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].direction, CaptureDirection::Received);
+        assert_eq!(records[0].payload, vec![1, 2, 3]);
+        assert_eq!(records[1].direction, CaptureDirection::Sent);
+        assert!(records[1].payload.is_empty());
+        assert!(records[0].timestamp <= records[1].timestamp);
+    }
+
+    #[test]
+    fn reject_invalid_header() {
+        assert!(matches!(
+            CaptureReader::new(Cursor::new(b"NOPE\x01".to_vec())),
+            Err(RakNetError::InvalidCapture)
+        ));
+    }
+}
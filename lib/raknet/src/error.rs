@@ -31,12 +31,20 @@ pub enum RakNetError {
     PacketSizeExceedsMTU,
     #[error("connection handshake failed")]
     HandshakeFailed,
+    #[error("remote rsa public key mismatch")]
+    RsaPublicKeyMismatch,
+    #[error("connection refused by remote")]
+    ConnectionRefused,
     #[error("decryption checksum mismatch")]
     DecryptionFailed,
     #[error("connection was closed")]
     ConnectionClosed,
     #[error("socket error")]
     SocketError,
+    #[error("failed to access capture file")]
+    CaptureError(std::io::Error),
+    #[error("invalid capture file")]
+    InvalidCapture,
 }
 
 pub type Result<T> = std::result::Result<T, RakNetError>;
@@ -29,9 +29,12 @@ mod frame;
 mod fragment;
 mod encryption;
 mod util;
+mod metrics;
+mod capture;
 
 pub use definitions::*;
 pub use listener::*;
 pub use socket::*;
 pub use error::*;
 pub use reliability::Reliability;
+pub use capture::{CaptureDirection, CaptureReader, CaptureRecord};
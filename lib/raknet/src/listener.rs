@@ -13,10 +13,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, io, net::SocketAddr, sync::Arc, time::{Duration, SystemTime}};
+use std::{collections::HashMap, io, net::SocketAddr, path::PathBuf, sync::Arc, time::{Duration, SystemTime}};
 
-use log::{debug, error, info};
-use rsa::RsaPrivateKey;
+use log::{debug, error, info, warn};
+use rsa::{RsaPrivateKey, RsaPublicKey};
 use tokio::{net::{ToSocketAddrs, UdpSocket}, sync::{mpsc::{channel, Receiver, Sender}, Mutex, Notify, Semaphore}, time::sleep};
 
 use crate::{buffer::RakNetWriter, error::{RakNetError, Result}, packet::read_open_connection_request,PacketID, RakNetSocket, RECV_BUFFER_SIZE};
@@ -33,6 +33,7 @@ pub struct RakNetListener {
     connection_sender: Sender<RakNetSocket>,
     rsa_key: Option<RsaPrivateKey>,
     all_sessions_closed_notifier: Arc<Notify>,
+    capture_dir: Option<PathBuf>,
 }
 
 impl RakNetListener {
@@ -51,7 +52,8 @@ impl RakNetListener {
             connection_receiver,
             connection_sender,
             rsa_key: None,
-            all_sessions_closed_notifier: Arc::new(Notify::new())
+            all_sessions_closed_notifier: Arc::new(Notify::new()),
+            capture_dir: None,
         };
 
         ret.drop_watcher().await;
@@ -67,6 +69,22 @@ impl RakNetListener {
         self.rsa_key = Some(rsa);
     }
 
+    /// Captures the traffic of every accepted connection into
+    /// a file per connection within the given directory.
+    pub fn enable_capture(&mut self, dir: impl Into<PathBuf>) {
+        self.capture_dir = Some(dir.into());
+    }
+
+    pub fn rsa_public_key(&self) -> Option<RsaPublicKey> {
+        self.rsa_key.as_ref().map(|key| key.to_public_key())
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.socket.as_ref()
+            .and_then(|socket| socket.local_addr().ok())
+            .ok_or(RakNetError::SocketError)
+    }
+
     async fn drop_watcher(&self) {
         let close_notifier = self.close_notifier.clone();
         let drop_notifier = self.drop_notifier.clone();
@@ -104,6 +122,7 @@ impl RakNetListener {
             let sessions = self.sessions.clone();
             let connection_sender = self.connection_sender.clone();
             let rsa_key = self.rsa_key.clone();
+            let capture_dir = self.capture_dir.clone();
 
             let (reaper_sender, reaper_receiver) = channel::<SocketAddr>(10);
 
@@ -173,9 +192,10 @@ impl RakNetListener {
                                 let connection_sender = connection_sender.clone();
                                 let socket = socket.clone();
                                 let rsa_key = rsa_key.clone();
+                                let capture_dir = capture_dir.clone();
 
                                 tokio::spawn(async move {
-                                    if let Ok(socket) = RakNetSocket::open(
+                                    if let Ok(mut socket) = RakNetSocket::open(
                                         &addr, 
                                         &socket, 
                                         receiver, 
@@ -188,6 +208,13 @@ impl RakNetListener {
                                         reaper_sender.clone(),
                                         rsa_key
                                     ).await {
+                                        if 
+                                            let Some(dir) = capture_dir &&
+                                            let Err(e) = socket.capture_to(dir.join(format!("{}.rncap", socket.id())))
+                                        {
+                                            warn!("Failed to capture connection {addr}: {e}");
+                                        }
+
                                         let _ = connection_sender.send(socket).await;
                                     }
                                 });
@@ -0,0 +1,39 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::sync::LazyLock;
+
+use prometheus::{register_int_counter, register_int_gauge, IntCounter, IntGauge};
+
+pub(crate) static CONNECTIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("raknet_connections", "Number of established RakNet connections")
+        .expect("metric can be registered")
+});
+
+pub(crate) static MESSAGES_SENT: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("raknet_messages_sent_total", "Number of messages sent for the first time")
+        .expect("metric can be registered")
+});
+
+pub(crate) static MESSAGES_RESENT: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("raknet_messages_resent_total", "Number of reliable messages resent after a timeout")
+        .expect("metric can be registered")
+});
+
+pub(crate) static HANDSHAKES_FAILED: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("raknet_handshakes_failed_total", "Number of connection attempts that failed during the handshake")
+        .expect("metric can be registered")
+});
@@ -13,12 +13,12 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::net::SocketAddr;
+use std::net::{Ipv4Addr, SocketAddr};
 
-use rsa::{traits::PublicKeyParts, RsaPublicKey};
+use rsa::{traits::PublicKeyParts, BigUint, RsaPublicKey};
 use uuid::Uuid;
 
-use crate::{buffer::{RakNetReader, RakNetWriter}, error::Result, util::BinaryAddress, PacketID};
+use crate::{buffer::{RakNetReader, RakNetWriter}, error::Result, util::BinaryAddress, PacketID, RakNetError};
 
 #[derive(Debug)]
 pub struct ConnectionRequest {
@@ -40,6 +40,76 @@ pub fn write_secured_connection_response(writer: &mut RakNetWriter, cookie: &[u8
     Ok(())
 }
 
+pub fn read_secured_connection_response(buf: &[u8]) -> Result<([u8; 20], RsaPublicKey)> {
+    let mut buf = RakNetReader::new(buf);
+
+    let mut syn_cookie = [0u8; 20];
+    buf.read(&mut syn_cookie)?;
+
+    let e = buf.read_u32()?;
+
+    let mut n = vec![0u8; buf.remaining()];
+    buf.read(&mut n)?;
+
+    let key = RsaPublicKey::new(BigUint::from_bytes_le(&n), BigUint::from(e))
+        .map_err(|_| RakNetError::HandshakeFailed)?;
+
+    Ok((syn_cookie, key))
+}
+
+pub fn write_secured_connection_confirmation(writer: &mut RakNetWriter, cookie: &[u8], message: &BigUint) -> Result<()> {
+    let mut message = message.to_bytes_le();
+    message.resize(64, 0);
+
+    writer.write_u8(PacketID::SecuredConnectionConfirmation.to_u8());
+    writer.write(cookie);
+    writer.write(&message);
+
+    Ok(())
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct ConnectionRequestAccepted {
+    pub external_addr: SocketAddr,
+    pub server_addr: SocketAddr,
+    pub guid: Uuid,
+}
+
+pub fn read_connection_request_accepted(buf: &[u8]) -> Result<ConnectionRequestAccepted> {
+    let mut buf = RakNetReader::new(buf);
+
+    fn read_addr(buf: &mut RakNetReader) -> Result<SocketAddr> {
+        let mut ip = [0u8; 4];
+        buf.read(&mut ip)?;
+
+        Ok(SocketAddr::new(Ipv4Addr::from(ip).into(), buf.read_u16()?))
+    }
+
+    let external_addr = read_addr(&mut buf)?;
+    let _ = buf.read_u16()?;
+    let server_addr = read_addr(&mut buf)?;
+
+    let mut guid = [0u8; 16];
+    buf.read(&mut guid)?;
+
+    Ok(ConnectionRequestAccepted {
+        external_addr,
+        server_addr,
+        guid: Uuid::from_bytes(guid),
+    })
+}
+
+pub fn write_new_incoming_connection(writer: &mut RakNetWriter, peer_addr: SocketAddr, local_addr: SocketAddr) -> Result<()> {
+    writer.write_u8(PacketID::NewIncomingConnection.to_u8());
+    writer.write(&peer_addr.ip().to_bytes());
+    writer.write_u16(peer_addr.port());
+    writer.write(&local_addr.ip().to_bytes());
+    writer.write_u16(local_addr.port());
+
+    Ok(())
+}
+
 pub fn write_connection_request_accepted(writer: &mut RakNetWriter, peer_addr: SocketAddr, local_addr: SocketAddr, guid: Uuid) -> Result<()> {
     writer.write_u8(PacketID::ConnectionRequestAccepted.to_u8());
     writer.write(&peer_addr.ip().to_bytes());
@@ -18,7 +18,7 @@ use std::{collections::{hash_map::Entry, HashMap}, net::SocketAddr, time::Durati
 
 use log::debug;
 
-use crate::{error::{RakNetError, Result}, fragment::FragmentQ, frame::{Message, Order, Split}};
+use crate::{error::{RakNetError, Result}, fragment::FragmentQ, frame::{Message, Order, Split}, metrics::{MESSAGES_RESENT, MESSAGES_SENT}};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Reliability {
@@ -422,6 +422,8 @@ impl SendQ {
                         p.3 + 1
                     );
 
+                    MESSAGES_RESENT.inc();
+
                     ret.push(p.0.clone());
                     p.1 = true;
                     p.2 = tick;
@@ -450,6 +452,8 @@ impl SendQ {
                 
                 ret.push(frame);
             }
+
+            MESSAGES_SENT.inc_by(ret.len() as u64);
         }
 
         ret
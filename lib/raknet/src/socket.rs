@@ -13,15 +13,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{net::SocketAddr, sync::Arc, time::{Duration, Instant, SystemTime}};
+use std::{io, net::{Ipv4Addr, Ipv6Addr, SocketAddr}, path::Path, sync::Arc, time::{Duration, Instant, SystemTime}};
 
 use log::{debug, trace, warn};
-use rsa::{hazmat::rsa_decrypt_and_check, rand_core::{OsRng, RngCore}, BigUint, RsaPrivateKey};
+use rsa::{hazmat::{rsa_decrypt_and_check, rsa_encrypt}, rand_core::{OsRng, RngCore}, traits::PublicKeyParts, BigUint, RsaPrivateKey, RsaPublicKey};
 use sha1::{Sha1, Digest};
-use tokio::{net::UdpSocket, sync::{mpsc::{channel, Receiver, Sender}, oneshot, Mutex, Notify}, time::sleep};
+use tokio::{net::{lookup_host, ToSocketAddrs, UdpSocket}, sync::{mpsc::{channel, Receiver, Sender}, oneshot, Mutex, Notify}, time::{sleep, timeout}};
 use uuid::Uuid;
 
-use crate::{buffer::{RakNetReader, RakNetWriter}, encryption::{aes_decrypt, aes_encrypt, EncryptionHanshakeContext}, error::Result, frame::{Message, MessageFrame}, packet::{write_connection_request_accepted, write_secured_connection_response}, reliability::{RecvQ, Reliability, SendQ}, util::cur_timestamp, PacketID, RakNetError};
+use crate::{buffer::{RakNetReader, RakNetWriter}, capture::{CaptureDirection, CaptureWriter}, encryption::{aes_decrypt, aes_encrypt, EncryptionHanshakeContext}, error::Result, frame::{Message, MessageFrame}, packet::{read_connection_request_accepted, read_secured_connection_response, write_connection_request_accepted, write_new_incoming_connection, write_secured_connection_confirmation, write_secured_connection_response}, metrics::{CONNECTIONS, HANDSHAKES_FAILED}, reliability::{RecvQ, Reliability, SendQ}, util::cur_timestamp, PacketID, RakNetError, RECV_BUFFER_SIZE};
 
 #[derive(Debug, Clone, Copy)]
 #[allow(dead_code)]
@@ -34,10 +34,22 @@ enum ConnectionState {
     HandlingConnectionRequest,
     UnverifiedSender,
     SetEncryptionOnMultiple16BytePacket(u128),
+    SetEncryptionOnConnectionAccepted(u128),
     Connected,
 }
 
+/// Which side of the connection a socket is driving.
+enum Role {
+    Server(Option<Box<EncryptionHanshakeContext>>),
+    Client(Option<RsaPublicKey>),
+}
+
 const RECEIVE_TIMEOUT: u64 = 60000;
+const PING_INTERVAL: u64 = 5000;
+const CONNECT_TIMEOUT: u64 = 10000;
+const OPEN_CONNECTION_ATTEMPTS: usize = 10;
+const OPEN_CONNECTION_RETRY_MILLIS: u64 = 500;
+const RAKNET_PROTOCOL_VERSION: u8 = 3;
 
 pub struct RakNetSocket {
     id: Uuid,
@@ -48,17 +60,119 @@ pub struct RakNetSocket {
     incoming_notify: Arc<Notify>,
     incoming_receiver: Mutex<Receiver<Vec<u8>>>,
     outgoing_sender: Sender<(Vec<u8>, Reliability, oneshot::Sender<Result<()>>)>,
+    capture: Option<CaptureWriter>,
 }
 
 impl RakNetSocket {
     pub(crate) async fn open(
         addr: &SocketAddr,
         s: &Arc<UdpSocket>,
-        mut receiver: Receiver<Vec<u8>>,
+        receiver: Receiver<Vec<u8>>,
         mtu: u16,
         reaper: Sender<SocketAddr>,
         rsa_key: Option<RsaPrivateKey>,
     ) -> Result<Self> {
+        let role = Role::Server(
+            rsa_key.map(|rsa_key| Box::new(EncryptionHanshakeContext::new(*addr, rsa_key)))
+        );
+
+        let socket = Self::spawn(
+            addr,
+            s,
+            receiver,
+            mtu,
+            Some(reaper),
+            ConnectionState::UnverifiedSender,
+            role
+        );
+
+        socket.wait_connected().await
+    }
+
+    /// Connects to a RakNet server, running the Otherland flavoured handshake.
+    /// 
+    /// If `rsa_pubkey` is set, the server must negotiate a secured connection
+    /// using exactly this key. Otherwise any key offered by the server is accepted
+    /// and unsecured servers are allowed as well.
+    pub async fn connect<A: ToSocketAddrs>(addr: A, rsa_pubkey: Option<RsaPublicKey>) -> Result<Self> {
+        let peer_addr = lookup_host(addr).await
+            .map_err(|_| RakNetError::SocketError)?
+            .next()
+            .ok_or(RakNetError::SocketError)?;
+
+        let bind_addr: SocketAddr = if peer_addr.is_ipv4() {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+
+        let s = Arc::new(UdpSocket::bind(bind_addr).await
+            .map_err(|_| RakNetError::BindAddressError)?);
+
+        open_connection(&s, peer_addr).await?;
+
+        // Forward datagrams from our peer to the connection loop
+        let (sender, receiver) = channel::<Vec<u8>>(10);
+
+        {
+            let s = s.clone();
+
+            tokio::spawn(async move {
+                let mut buf = [0u8; RECV_BUFFER_SIZE];
+
+                loop {
+                    tokio::select! {
+                        r = s.recv_from(&mut buf) => {
+                            match r {
+                                Ok((size, addr)) => {
+                                    if addr == peer_addr && size > 0 && sender.send(buf[..size].to_vec()).await.is_err() {
+                                        break;
+                                    }
+                                },
+                                Err(e) => {
+                                    // See RakNetListener::listen
+                                    if e.kind() == io::ErrorKind::ConnectionReset { continue; }
+                                    debug!("Client recv error: {e}");
+                                    break;
+                                }
+                            }
+                        },
+                        _ = sender.closed() => break,
+                    }
+                }
+            });
+        }
+
+        let socket = Self::spawn(
+            &peer_addr, 
+            &s, 
+            receiver, 
+            1024, 
+            None, 
+            ConnectionState::RequestedConntection, 
+            Role::Client(rsa_pubkey)
+        );
+
+        socket.send(&[PacketID::ConnectionRequest.to_u8()], Reliability::Reliable).await?;
+
+        match timeout(Duration::from_millis(CONNECT_TIMEOUT), socket.wait_connected()).await {
+            Ok(res) => res,
+            Err(_) => {
+                HANDSHAKES_FAILED.inc();
+                Err(RakNetError::HandshakeFailed)
+            },
+        }
+    }
+
+    fn spawn(
+        addr: &SocketAddr,
+        s: &Arc<UdpSocket>,
+        mut receiver: Receiver<Vec<u8>>,
+        mtu: u16,
+        reaper: Option<Sender<SocketAddr>>,
+        initial_state: ConnectionState,
+        mut role: Role,
+    ) -> Self {
         let (incoming_sender, incoming_receiver) = channel(10);
         let (outgoing_sender, mut outgoing_receiver) = channel(10);
 
@@ -84,24 +198,25 @@ impl RakNetSocket {
             incoming_notify: incoming_notify.clone(),
             incoming_receiver: Mutex::new(incoming_receiver),
             outgoing_sender,
+            capture: None,
         };
 
         let peer_addr = *addr;
         let local_addr = s.local_addr().unwrap();
-        let mut encryption_context = rsa_key
-            .map(|rsa_key| EncryptionHanshakeContext::new(peer_addr, rsa_key));
         let s = s.clone();
         
         let guid = socket.id;
 
         tokio::spawn(async move {
-            let mut state = ConnectionState::UnverifiedSender;
+            let mut state = initial_state;
             let mut recvq = RecvQ::new();
             let mut sendq = SendQ::new(mtu);
 
             let mut aes_key: u128 = 0;
             let mut encryption_active = false;
             let mut last_heartbeat_time = Instant::now();
+            let mut last_ping_time = Instant::now();
+            let mut connected = false;
             let mut remote_time = Duration::default();
 
             'net_loop: while !matches!(state, ConnectionState::Disconnected) {
@@ -132,7 +247,7 @@ impl RakNetSocket {
                         if let Some(mut buf) = buf {
                             if buf.len() > 2 {
                                 if 
-                                    buf.len() % 16 == 0 && 
+                                    buf.len().is_multiple_of(16) && 
                                     let ConnectionState::SetEncryptionOnMultiple16BytePacket(_) = state
                                 {
                                     let mut test_buf = buf.clone();
@@ -197,17 +312,31 @@ impl RakNetSocket {
 
                                 // Flush receive queue
                                 for f in recvq.flush() {
-                                    match Self::handle(
-                                        state,
-                                        guid,
-                                        &f, 
-                                        reference_time,
-                                        &peer_addr, 
-                                        &local_addr, 
-                                        &mut sendq, 
-                                        &incoming_sender,
-                                        encryption_context.as_mut()
-                                    ).await {
+                                    let res = match &mut role {
+                                        Role::Server(encryption_context) => Self::handle(
+                                            state,
+                                            guid,
+                                            &f, 
+                                            reference_time,
+                                            &peer_addr, 
+                                            &local_addr, 
+                                            &mut sendq, 
+                                            &incoming_sender,
+                                            encryption_context.as_deref_mut()
+                                        ).await,
+                                        Role::Client(rsa_pubkey) => Self::handle_client(
+                                            state,
+                                            &f,
+                                            reference_time,
+                                            &peer_addr,
+                                            &local_addr,
+                                            &mut sendq,
+                                            &incoming_sender,
+                                            rsa_pubkey.as_ref()
+                                        ).await,
+                                    };
+
+                                    match res {
                                         Ok(Some(next_state)) => {
                                             match next_state {
                                                 ConnectionState::DisconnectAsap => {
@@ -228,9 +357,22 @@ impl RakNetSocket {
                                                     ].to_vec());
                                                 },
                                                 ConnectionState::Connected => {
+                                                    // The client encrypts everything following the 
+                                                    // server's connection request acceptance.
+                                                    if matches!(role, Role::Client(_)) && aes_key != 0 {
+                                                        trace!("Turning on encryption");
+                                                        encryption_active = true;
+                                                    }
+
+                                                    if !connected {
+                                                        CONNECTIONS.inc();
+                                                        connected = true;
+                                                    }
+
                                                     incoming_notify.notify_one();
                                                 },
-                                                ConnectionState::SetEncryptionOnMultiple16BytePacket(key) => {
+                                                ConnectionState::SetEncryptionOnMultiple16BytePacket(key) |
+                                                ConnectionState::SetEncryptionOnConnectionAccepted(key) => {
                                                     aes_key = key;
                                                 },
                                                 _ => (),
@@ -263,6 +405,20 @@ impl RakNetSocket {
                     }
                 }
 
+                // keep the connection alive, servers don't ping on their own
+                if 
+                    matches!(role, Role::Client(_)) &&
+                    matches!(state, ConnectionState::Connected) &&
+                    (Instant::now() - last_ping_time).as_millis() as u64 > PING_INTERVAL
+                {
+                    let mut writer = RakNetWriter::new();
+                    writer.write_u8(PacketID::InternalPing.to_u8());
+                    writer.write_u32(cur_timestamp(reference_time).as_millis() as u32);
+
+                    let _ = sendq.insert(Reliability::Unreliable, writer.take_buffer());
+                    last_ping_time = Instant::now();
+                }
+
                 // flush sendq
                 let frames = sendq.flush(cur_timestamp(reference_time), &peer_addr);
 
@@ -295,18 +451,29 @@ impl RakNetSocket {
                 }
             }
 
-            let _ = reaper.send(peer_addr).await;
+            if connected {
+                CONNECTIONS.dec();
+            }
+
+            if let Some(reaper) = reaper {
+                let _ = reaper.send(peer_addr).await;
+            }
         });
 
+        socket
+    }
+
+    async fn wait_connected(self) -> Result<Self> {
         // wait for incomming notify or close
-        let incoming_notify = socket.incoming_notify.clone();
-        let close_notifier = socket.close_notifier.clone();
+        let incoming_notify = self.incoming_notify.clone();
+        let close_notifier = self.close_notifier.clone();
     
         tokio::select! {
             _ = incoming_notify.notified() => {
-                Ok(socket)
+                Ok(self)
             },
             _ = close_notifier.notified() => {
+                HANDSHAKES_FAILED.inc();
                 Err(RakNetError::HandshakeFailed)
             }
         }
@@ -316,7 +483,17 @@ impl RakNetSocket {
         self.close_notifier.notify_one();
     }
 
+    /// Writes every payload sent or received from now on to a capture file.
+    pub fn capture_to(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        self.capture = Some(CaptureWriter::create(path.as_ref())?);
+        Ok(())
+    }
+
     pub async fn send(&self, buf: &[u8], r: Reliability) -> Result<()> {
+        if let Some(capture) = &self.capture {
+            capture.write(CaptureDirection::Sent, buf);
+        }
+
         let (res_sender, res_receiver) = oneshot::channel();
 
         self.outgoing_sender.send((buf.to_vec(), r, res_sender)).await
@@ -328,7 +505,13 @@ impl RakNetSocket {
 
     pub async fn recv(&self) -> Result<Vec<u8>> {
         match self.incoming_receiver.lock().await.recv().await {
-            Some(p) => Ok(p),
+            Some(p) => {
+                if let Some(capture) = &self.capture {
+                    capture.write(CaptureDirection::Received, &p);
+                }
+
+                Ok(p)
+            },
             None => {
                 Err(RakNetError::ConnectionClosed)
             }
@@ -496,6 +679,114 @@ impl RakNetSocket {
             }
         }
     }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_client(
+        state: ConnectionState,
+        frame: &Message,
+        reference_time: SystemTime,
+        peer_addr: &SocketAddr,
+        local_addr: &SocketAddr,
+        sendq: &mut SendQ,
+        user_data_sender: &Sender<Vec<u8>>,
+        rsa_pubkey: Option<&RsaPublicKey>,
+    ) -> Result<Option<ConnectionState>> {
+        trace!("{state:?} - RX: {frame:?}");
+
+        if frame.data().is_empty() { return Ok(None); }
+
+        match PacketID::from(frame.data()[0]) {
+            PacketID::SecuredConnectionResponse => {
+                if matches!(state, ConnectionState::Connected) {
+                    return Ok(None);
+                }
+
+                let (syn_cookie, server_key) = read_secured_connection_response(&frame.data()[1..])?;
+
+                if 
+                    let Some(expected_key) = rsa_pubkey &&
+                    (expected_key.n() != server_key.n() || expected_key.e() != server_key.e())
+                {
+                    warn!("Server offered an unexpected RSA public key");
+                    return Err(RakNetError::RsaPublicKeyMismatch);
+                }
+
+                // Random number, kept below the modulus by leaving the top byte empty
+                let mut random_number = [0u8; 64];
+                OsRng.fill_bytes(&mut random_number[..63]);
+
+                let message = rsa_encrypt(&server_key, &BigUint::from_bytes_le(&random_number))
+                    .map_err(|_| RakNetError::HandshakeFailed)?;
+
+                let mut aes_key = [0u8; 16];
+                for i in 0..aes_key.len() {
+                    aes_key[i] = syn_cookie[i] ^ random_number[i];
+                }
+
+                let mut writer = RakNetWriter::new();
+                write_secured_connection_confirmation(&mut writer, &syn_cookie, &message)?;
+
+                sendq.insert(Reliability::Reliable, writer.take_buffer())?;
+
+                Ok(Some(ConnectionState::SetEncryptionOnConnectionAccepted(u128::from_le_bytes(aes_key))))
+            },
+            PacketID::ConnectionRequestAccepted => {
+                match state {
+                    ConnectionState::RequestedConntection if rsa_pubkey.is_some() => {
+                        warn!("Server refused to establish a secured connection");
+                        Err(RakNetError::RsaPublicKeyMismatch)
+                    },
+                    ConnectionState::RequestedConntection |
+                    ConnectionState::SetEncryptionOnConnectionAccepted(_) => {
+                        let _ = read_connection_request_accepted(&frame.data()[1..])?;
+
+                        let mut writer = RakNetWriter::new();
+                        write_new_incoming_connection(&mut writer, *peer_addr, *local_addr)?;
+
+                        sendq.insert(Reliability::Reliable, writer.take_buffer())?;
+
+                        Ok(Some(ConnectionState::Connected))
+                    },
+                    _ => Ok(None),
+                }
+            },
+            PacketID::ConnectedPong => Ok(None),
+            PacketID::DisconnectionNotification => {
+                Ok(Some(ConnectionState::Disconnecting))
+            },
+            PacketID::ConnectionBanned |
+            PacketID::InvalidPassword |
+            PacketID::RsaPublicKeyMismatch |
+            PacketID::ConnectionAttemptFailed => {
+                debug!("Connection refused: {:?}", PacketID::from(frame.data()[0]));
+                Ok(Some(ConnectionState::DisconnectAsapSilently))
+            },
+            PacketID::InternalPing => {
+                let mut buf = RakNetReader::new(frame.data());
+                let _ = buf.read_u8()?;
+                let send_ping_time = buf.read_u32()?;
+
+                let mut buf = RakNetWriter::new();
+                buf.write_u8(PacketID::ConnectedPong.to_u8());
+                buf.write_u32(send_ping_time);
+                buf.write_u32(cur_timestamp(reference_time).as_millis() as u32);
+
+                sendq.insert(Reliability::Unreliable, buf.take_buffer())?;
+
+                Ok(None)
+            },
+            PacketID::User(_) if matches!(state, ConnectionState::Connected) => {
+                match user_data_sender.send(frame.data().to_vec()).await {
+                    Ok(_) => Ok(None),
+                    Err(_) => Ok(Some(ConnectionState::DisconnectAsap))
+                }
+            },
+            id => {
+                warn!("Unexpected packet id {id:?} in state {state:?}");
+                Ok(None)
+            }
+        }
+    }
 }
 
 impl Drop for RakNetSocket {
@@ -527,4 +818,93 @@ async fn handle_connection_request(
     }
 
     Ok(Some(ConnectionState::HandlingConnectionRequest))
-}
\ No newline at end of file
+}
+async fn open_connection(s: &UdpSocket, peer_addr: SocketAddr) -> Result<()> {
+    let mut buf = [0u8; RECV_BUFFER_SIZE];
+
+    for _ in 0..OPEN_CONNECTION_ATTEMPTS {
+        s.send_to(&[PacketID::OpenConnectionRequest.to_u8(), RAKNET_PROTOCOL_VERSION], peer_addr).await
+            .map_err(|_| RakNetError::SocketError)?;
+
+        let (size, addr) = match timeout(
+            Duration::from_millis(OPEN_CONNECTION_RETRY_MILLIS), 
+            s.recv_from(&mut buf)
+        ).await {
+            Ok(Ok(res)) => res,
+            Ok(Err(e)) if e.kind() == io::ErrorKind::ConnectionReset => continue,
+            Ok(Err(_)) => return Err(RakNetError::SocketError),
+            Err(_) => continue,
+        };
+
+        if addr != peer_addr || size == 0 { continue; }
+
+        match PacketID::from(buf[0]) {
+            // A previous request did make it through, but the reply got lost.
+            PacketID::OpenConnectionReply | PacketID::AlreadyConnected => return Ok(()),
+            PacketID::NoFreeIncomingConnections => return Err(RakNetError::ConnectionRefused),
+            id => trace!("Unexpected reply to open connection request: {id:?}"),
+        }
+    }
+
+    Err(RakNetError::HandshakeFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RakNetListener;
+
+    use super::*;
+
+    async fn echo_roundtrip(secured: bool) {
+        let mut listener = RakNetListener::bind("127.0.0.1:0").await.unwrap();
+        if secured {
+            listener.generate_random_rsa_key();
+        }
+
+        let server_addr = listener.local_addr().unwrap();
+        let pubkey = listener.rsa_public_key();
+
+        listener.listen(10).await;
+
+        tokio::spawn(async move {
+            let socket = listener.accept().await.unwrap();
+            let msg = socket.recv().await.unwrap();
+            socket.send(&msg, Reliability::ReliableOrdered).await.unwrap();
+            let _ = socket.recv().await;
+        });
+
+        let client = RakNetSocket::connect(server_addr, pubkey).await.unwrap();
+
+        let payload = [0xFFu8, 0x01, 0x02, 0x03, 0x04];
+        client.send(&payload, Reliability::ReliableOrdered).await.unwrap();
+
+        let echo = timeout(Duration::from_secs(5), client.recv()).await
+            .expect("echo timed out")
+            .unwrap();
+
+        assert_eq!(echo, payload);
+    }
+
+    #[tokio::test]
+    async fn test_client_connect_insecure() {
+        echo_roundtrip(false).await;
+    }
+
+    #[tokio::test]
+    async fn test_client_connect_secured() {
+        echo_roundtrip(true).await;
+    }
+
+    #[tokio::test]
+    async fn test_client_rejects_unexpected_key() {
+        let mut listener = RakNetListener::bind("127.0.0.1:0").await.unwrap();
+        listener.generate_random_rsa_key();
+
+        let server_addr = listener.local_addr().unwrap();
+        listener.listen(10).await;
+
+        let other_key = RsaPrivateKey::new(&mut rand::thread_rng(), 512).unwrap().to_public_key();
+
+        assert!(RakNetSocket::connect(server_addr, Some(other_key)).await.is_err());
+    }
+}
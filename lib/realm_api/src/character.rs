@@ -13,7 +13,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use character_graphql::{CreateCharacterInAccount, CreateCharacterInAccountVariables, DeleteCharacter, DeleteCharacterVariables, GetAccountCharacter, GetAccountCharacterVariables, GetCharacter, GetCharacterVariables, GetCharactersForAccount, GetCharactersForAccountVariables, UpdateCharacterDataDiff, UpdateCharacterDataDiffVariables};
+use character_graphql::{CreateCharacterInAccount, CreateCharacterInAccountVariables, DeleteCharacter, DeleteCharacterVariables, GetAccountCharacter, RestoreCharacter, ScheduleCharacterDeletion, GetAccountCharacterVariables, GetCharacter, GetCharacterVariables, GetCharactersForAccount, GetCharactersForAccountVariables, UpdateCharacterDataDiff, UpdateCharacterDataDiffVariables};
 use cynic::{http::ReqwestExt, MutationBuilder, QueryBuilder};
 use log::debug;
 use obj_params::{GameObjectData, GenericParamSet};
@@ -125,6 +125,24 @@ impl Character {
             unreachable!()
         }
     }
+
+    /// Marks the character for deletion, it can be restored
+    /// until the realm's grace period has passed.
+    pub async fn schedule_deletion(&self) -> RealmApiResult<()> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(ScheduleCharacterDeletion::build(DeleteCharacterVariables {
+                id: self.id
+            })).await?;
+
+        if let Some(ScheduleCharacterDeletion { .. }) = response.data {
+            Ok(())
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
 }
 
 impl Character {
@@ -141,6 +159,24 @@ impl Character {
 }
 
 impl RealmApi {
+    pub async fn restore_character(&self, id: &Uuid) -> RealmApiResult<Option<Character>> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(RestoreCharacter::build(DeleteCharacterVariables {
+                id: *id
+            })).await?;
+
+        if let Some(RestoreCharacter { restore_character }) = response.data {
+            restore_character
+                .map(|character| Character::from_graphql(self, character))
+                .transpose()
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
     pub async fn get_character(&self, id: &Uuid) -> RealmApiResult<Option<Character>> {
         let response = self.0.client
             .post(self.0.base_url.clone())
@@ -353,6 +389,21 @@ pub(crate) mod character_graphql {
         #[allow(dead_code)]
         pub delete_character: Option<Character>,
     }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "DeleteCharacterVariables")]
+    pub struct ScheduleCharacterDeletion {
+        #[arguments(id: $id)]
+        #[allow(dead_code)]
+        pub schedule_character_deletion: Option<Character>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "DeleteCharacterVariables")]
+    pub struct RestoreCharacter {
+        #[arguments(id: $id)]
+        pub restore_character: Option<Character>,
+    }
     
     #[derive(cynic::QueryFragment, Debug)]
     #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "CreateCharacterInAccountVariables")]
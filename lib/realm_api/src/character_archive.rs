@@ -0,0 +1,154 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use cynic::{http::ReqwestExt, QueryBuilder, MutationBuilder};
+use character_archive_graphql::{ExportAccount, ExportAccountVariables, ExportCharacter, ExportCharacterVariables, ImportCharacters, ImportCharactersVariables};
+use serde_json::Value;
+use toolkit::types::Uuid;
+
+use crate::{schema::Json, RealmApi, RealmApiError, RealmApiResult};
+
+pub use character_archive_graphql::ImportedCharacter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameConflict {
+    #[default]
+    Fail,
+    Rename,
+}
+
+impl From<NameConflict> for character_archive_graphql::NameConflict {
+    fn from(value: NameConflict) -> Self {
+        match value {
+            NameConflict::Fail => character_archive_graphql::NameConflict::Fail,
+            NameConflict::Rename => character_archive_graphql::NameConflict::Rename,
+        }
+    }
+}
+
+impl RealmApi {
+    /// Returns an archive of the character, which can be imported into any realm.
+    pub async fn export_character(&self, id: Uuid) -> RealmApiResult<Value> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(ExportCharacter::build(ExportCharacterVariables {
+                id,
+            })).await?;
+
+        if let Some(ExportCharacter { export_character }) = response.data {
+            Ok(export_character.0)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Returns an archive of all characters of the account.
+    pub async fn export_account(&self, account_id: Uuid) -> RealmApiResult<Value> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(ExportAccount::build(ExportAccountVariables {
+                account_id,
+            })).await?;
+
+        if let Some(ExportAccount { export_account }) = response.data {
+            Ok(export_account.0)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub async fn import_characters(&self, account_id: Uuid, archive: Value, name_conflict: NameConflict) -> RealmApiResult<Vec<ImportedCharacter>> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(ImportCharacters::build(ImportCharactersVariables {
+                account_id,
+                archive: Json(archive),
+                name_conflict: name_conflict.into(),
+            })).await?;
+
+        if let Some(ImportCharacters { import_characters }) = response.data {
+            Ok(import_characters)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+pub(crate) mod character_archive_graphql {
+    use toolkit::types::Uuid;
+
+    use crate::schema::*;
+
+    #[derive(cynic::Enum, Clone, Copy, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub enum NameConflict {
+        Fail,
+        Rename,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct ExportCharacterVariables {
+        pub id: Uuid,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "QueryRoot", variables = "ExportCharacterVariables")]
+    pub struct ExportCharacter {
+        #[arguments(id: $id)]
+        pub export_character: Json,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct ExportAccountVariables {
+        pub account_id: Uuid,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "QueryRoot", variables = "ExportAccountVariables")]
+    pub struct ExportAccount {
+        #[arguments(accountId: $account_id)]
+        pub export_account: Json,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct ImportCharactersVariables {
+        pub account_id: Uuid,
+        pub archive: Json,
+        pub name_conflict: NameConflict,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "ImportCharactersVariables")]
+    pub struct ImportCharacters {
+        #[arguments(accountId: $account_id, archive: $archive, nameConflict: $name_conflict)]
+        pub import_characters: Vec<ImportedCharacter>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct ImportedCharacter {
+        pub original_id: Uuid,
+        pub id: Uuid,
+        pub name: String,
+        pub renamed: bool,
+        pub dropped_items: i32,
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use cynic::{http::ReqwestExt, MutationBuilder};
+use chat_moderation_graphql::{ModerateChat, ModerateChatVariables, MuteCharacter, MuteCharacterVariables, UnmuteCharacter, UnmuteCharacterVariables};
+use toolkit::types::Uuid;
+
+use crate::{RealmApi, RealmApiError, RealmApiResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatChannel {
+    Local,
+    Yell,
+    Shout,
+    Whisper,
+    Party,
+    Clan,
+    ClanOfficer,
+    Broadcast,
+}
+
+impl From<ChatChannel> for chat_moderation_graphql::ChatChannel {
+    fn from(value: ChatChannel) -> Self {
+        match value {
+            ChatChannel::Local => chat_moderation_graphql::ChatChannel::Local,
+            ChatChannel::Yell => chat_moderation_graphql::ChatChannel::Yell,
+            ChatChannel::Shout => chat_moderation_graphql::ChatChannel::Shout,
+            ChatChannel::Whisper => chat_moderation_graphql::ChatChannel::Whisper,
+            ChatChannel::Party => chat_moderation_graphql::ChatChannel::Party,
+            ChatChannel::Clan => chat_moderation_graphql::ChatChannel::Clan,
+            ChatChannel::ClanOfficer => chat_moderation_graphql::ChatChannel::ClanOfficer,
+            ChatChannel::Broadcast => chat_moderation_graphql::ChatChannel::Broadcast,
+        }
+    }
+}
+
+impl RealmApi {
+    /// Runs a chat message through the realm's moderation and returns the text to deliver.
+    /// Rejected messages are reported as GraphQL errors meant for the sender.
+    pub async fn moderate_chat(&self, session_id: Uuid, channel: ChatChannel, message: &str) -> RealmApiResult<String> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(ModerateChat::build(ModerateChatVariables {
+                session_id,
+                channel: channel.into(),
+                message,
+            })).await?;
+
+        if let Some(ModerateChat { moderate_chat }) = response.data {
+            Ok(moderate_chat)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Mutes the account owning the named character and returns a confirmation.
+    pub async fn mute_character(&self, character_name: &str, issued_by: Uuid, minutes: i32, reason: &str) -> RealmApiResult<String> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(MuteCharacter::build(MuteCharacterVariables {
+                character_name,
+                issued_by,
+                minutes,
+                reason,
+            })).await?;
+
+        if let Some(MuteCharacter { mute_character }) = response.data {
+            Ok(mute_character)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Lifts all active mutes of the account owning the named character.
+    pub async fn unmute_character(&self, character_name: &str) -> RealmApiResult<String> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(UnmuteCharacter::build(UnmuteCharacterVariables {
+                character_name,
+            })).await?;
+
+        if let Some(UnmuteCharacter { unmute_character }) = response.data {
+            Ok(unmute_character)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+pub(crate) mod chat_moderation_graphql {
+    use toolkit::types::Uuid;
+
+    use crate::schema::*;
+
+    #[derive(cynic::Enum, Clone, Copy, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub enum ChatChannel {
+        Local,
+        Yell,
+        Shout,
+        Whisper,
+        Party,
+        Clan,
+        ClanOfficer,
+        Broadcast,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct ModerateChatVariables<'a> {
+        pub session_id: Uuid,
+        pub channel: ChatChannel,
+        pub message: &'a str,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "ModerateChatVariables")]
+    pub struct ModerateChat {
+        #[arguments(sessionId: $session_id, channel: $channel, message: $message)]
+        pub moderate_chat: String,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct MuteCharacterVariables<'a> {
+        pub character_name: &'a str,
+        pub issued_by: Uuid,
+        pub minutes: i32,
+        pub reason: &'a str,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "MuteCharacterVariables")]
+    pub struct MuteCharacter {
+        #[arguments(characterName: $character_name, issuedBy: $issued_by, minutes: $minutes, reason: $reason)]
+        pub mute_character: String,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct UnmuteCharacterVariables<'a> {
+        pub character_name: &'a str,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "UnmuteCharacterVariables")]
+    pub struct UnmuteCharacter {
+        #[arguments(characterName: $character_name)]
+        pub unmute_character: String,
+    }
+}
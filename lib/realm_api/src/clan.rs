@@ -0,0 +1,86 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use clan_graphql::{GetClanMembership, GetClanMembershipVariables};
+use cynic::{http::ReqwestExt, QueryBuilder};
+use toolkit::types::Uuid;
+
+use crate::{proto::{self, ClanMembership}, RealmApi, RealmApiError, RealmApiResult};
+
+impl From<clan_graphql::ClanRank> for proto::ClanRank {
+    fn from(value: clan_graphql::ClanRank) -> Self {
+        match value {
+            clan_graphql::ClanRank::Leader => proto::ClanRank::Leader,
+            clan_graphql::ClanRank::Officer => proto::ClanRank::Officer,
+            clan_graphql::ClanRank::Member => proto::ClanRank::Member,
+        }
+    }
+}
+
+impl RealmApi {
+    pub async fn get_clan_membership(&self, character_id: Uuid) -> RealmApiResult<Option<ClanMembership>> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(GetClanMembership::build(GetClanMembershipVariables {
+                character_id
+            })).await?;
+
+        if let Some(GetClanMembership { clan_membership }) = response.data {
+            Ok(clan_membership.map(|membership| ClanMembership {
+                id: membership.id,
+                name: membership.name,
+                rank: membership.rank.into(),
+            }))
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+pub(crate) mod clan_graphql {
+    use toolkit::types::Uuid;
+
+    use crate::schema::*;
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct GetClanMembershipVariables {
+        pub character_id: Uuid,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "QueryRoot", variables = "GetClanMembershipVariables")]
+    pub struct GetClanMembership {
+        #[arguments(characterId: $character_id)]
+        pub clan_membership: Option<ClanMembership>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct ClanMembership {
+        pub id: Uuid,
+        pub name: String,
+        pub rank: ClanRank,
+    }
+
+    #[derive(cynic::Enum, Clone, Copy, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub enum ClanRank {
+        Leader,
+        Officer,
+        Member,
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use cynic::{http::ReqwestExt, MutationBuilder};
+use gm_audit_graphql::{LogGmCommand, LogGmCommandVariables};
+use toolkit::types::Uuid;
+
+use crate::{RealmApi, RealmApiError, RealmApiResult};
+
+impl RealmApi {
+    /// Appends a game master command to the realm's audit log.
+    pub async fn log_gm_command(&self, account_id: Uuid, character_id: Uuid, zone_id: Uuid, command: String, arguments: Vec<String>) -> RealmApiResult<()> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(LogGmCommand::build(LogGmCommandVariables {
+                account_id,
+                character_id,
+                zone_id,
+                command: &command,
+                arguments,
+            })).await?;
+
+        if response.data.is_some() {
+            Ok(())
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+pub(crate) mod gm_audit_graphql {
+    use toolkit::types::Uuid;
+
+    use crate::schema::*;
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct LogGmCommandVariables<'a> {
+        pub account_id: Uuid,
+        pub character_id: Uuid,
+        pub zone_id: Uuid,
+        pub command: &'a str,
+        pub arguments: Vec<String>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "LogGmCommandVariables")]
+    pub struct LogGmCommand {
+        #[arguments(accountId: $account_id, characterId: $character_id, zoneId: $zone_id, command: $command, arguments: $arguments)]
+        #[allow(dead_code)]
+        pub log_gm_command: bool,
+    }
+}
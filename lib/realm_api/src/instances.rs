@@ -14,11 +14,27 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use cynic::{http::ReqwestExt, MutationBuilder};
-use instance_graphql::{JoinInstance, JoinInstanceVariables};
+use instance_graphql::{EnterDungeon, EnterDungeonVariables, JoinInstance, JoinInstanceVariables};
 use toolkit::types::Uuid;
 
 use crate::{ClusterNode, RealmApi, RealmApiError, RealmApiResult};
 
+/// Who shares a private dungeon instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DungeonScope {
+    Party,
+    Character,
+}
+
+impl From<DungeonScope> for instance_graphql::DungeonScope {
+    fn from(value: DungeonScope) -> Self {
+        match value {
+            DungeonScope::Party => instance_graphql::DungeonScope::Party,
+            DungeonScope::Character => instance_graphql::DungeonScope::Character,
+        }
+    }
+}
+
 pub struct Instance {
     pub key: Option<Uuid>,
     pub zone: Uuid,
@@ -53,6 +69,25 @@ impl RealmApi {
             unreachable!()
         }
     }
+
+    /// Returns the private instance of a dungeon zone the character should travel to.
+    pub async fn enter_dungeon(&self, character_id: Uuid, zone: Uuid, scope: DungeonScope) -> RealmApiResult<Uuid> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(EnterDungeon::build(EnterDungeonVariables {
+                character_id,
+                zone_id: zone,
+                scope: scope.into(),
+            })).await?;
+
+        if let Some(EnterDungeon { enter_dungeon }) = response.data {
+            Ok(enter_dungeon)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
 }
 
 pub(crate) mod instance_graphql {
@@ -93,6 +128,27 @@ pub(crate) mod instance_graphql {
         pub port: i32,
     }
 
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct EnterDungeonVariables {
+        pub character_id: Uuid,
+        pub zone_id: Uuid,
+        pub scope: DungeonScope,
+    }
+
+    #[derive(cynic::Enum, Clone, Copy, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub enum DungeonScope {
+        Party,
+        Character,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "EnterDungeonVariables")]
+    pub struct EnterDungeon {
+        #[arguments(characterId: $character_id, scope: $scope, zoneId: $zone_id)]
+        pub enter_dungeon: Uuid,
+    }
+
     #[derive(cynic::QueryFragment, Debug)]
     #[cynic(schema = "realm_manager_service")]
     pub struct Instance {
@@ -14,7 +14,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use cynic::{http::ReqwestExt, MutationBuilder, QueryBuilder};
-use item_storage_graphql::{GetOrCreateStorage, GetOrCreateStorageVariables, GetStorage, GetStorageVariables, StorageDestroyItem, StorageDestroyItemVariables, StorageEquipItem, StorageEquipItemVariables, StorageInsertItem, StorageInsertItemVariables, StorageMoveItem, StorageMoveItemVariables, StoragePurchaseItemVariables, StoragePurchaseitem, StorageUneqipItem, StorageUneqipItemVariables};
+use item_storage_graphql::{GetOrCreateStorage, GetOrCreateStorageVariables, GetStorage, GetStorageVariables, StorageDestroyItem, StorageDestroyItemVariables, StorageEquipItem, StorageEquipItemVariables, StorageInsertItem, StorageInsertItemVariables, StorageMoveItem, StorageMoveItemVariables, StoragePurchaseCart, StoragePurchaseCartVariables, StoragePurchaseItemVariables, StoragePurchaseitem, StorageAddBits, StorageAddBitsVariables, StorageAddSoma, StorageAddSomaVariables, StorageBuybackItem, StorageSellItem, StorageSellItemVariables, StorageUneqipItem, StorageUneqipItemVariables};
 use obj_params::{GameObjectData, GenericParamSet};
 use toolkit::{types::Uuid, NativeParam};
 
@@ -139,6 +139,7 @@ pub struct ItemStorage {
     pub capacity: i32,
     pub bling: Option<i32>,
     pub game_cash: Option<i32>,
+    pub soma: Option<Vec<i32>>,
     pub items: Vec<Item>,
 }
 
@@ -153,6 +154,7 @@ impl TryFrom<item_storage_graphql::ItemStorage> for ItemStorage {
             capacity: value.capacity,
             bling: value.bling,
             game_cash: value.game_cash,
+            soma: value.soma,
             items: value.items.into_iter().map(Item::try_from).collect::<Result<Vec<_>,_>>()?,
         })
     }
@@ -162,6 +164,7 @@ pub struct StorageResult {
     pub storage_id: Uuid,
     pub bling: Option<i32>,
     pub game_cash: Option<i32>,
+    pub soma: Option<Vec<i32>>,
     pub changed_items: Option<Vec<Item>>,
     pub removed_items: Option<Vec<Uuid>>,
     pub error: Option<(String, Option<NativeParam>)>,
@@ -175,6 +178,7 @@ impl TryFrom<item_storage_graphql::StorageResult> for StorageResult {
             storage_id: value.storage_id,
             bling: value.bling,
             game_cash: value.game_cash,
+            soma: value.soma,
             changed_items: value.changed_items
                 .map(|items| 
                     items.into_iter()
@@ -369,6 +373,100 @@ impl ItemStorageId {
             unreachable!()
         }
     }
+
+    /// Purchases all cart entries at once. Entries can either be cash shop
+    /// items or item templates listed in `vendor_items`.
+    pub async fn purchase_cart(&self, items: Vec<Uuid>, vendor_items: Vec<Uuid>, tag: Option<String>) -> RealmApiResult<StorageResult> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(StoragePurchaseCart::build(StoragePurchaseCartVariables {
+                id: self.id,
+                items,
+                vendor_items,
+                tag,
+            })).await?;
+
+        if let Some(StoragePurchaseCart { storage_purchase_cart }) = response.data {
+            Ok(storage_purchase_cart.try_into()?)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub async fn sell_item(&self, item_id: Uuid, tag: Option<String>) -> RealmApiResult<StorageResult> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(StorageSellItem::build(StorageSellItemVariables {
+                id: self.id,
+                item_id,
+                tag,
+            })).await?;
+
+        if let Some(StorageSellItem { storage_sell_item }) = response.data {
+            Ok(storage_sell_item.try_into()?)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub async fn add_bits(&self, amount: i32, tag: Option<String>) -> RealmApiResult<StorageResult> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(StorageAddBits::build(StorageAddBitsVariables {
+                id: self.id,
+                amount,
+                tag,
+            })).await?;
+
+        if let Some(StorageAddBits { storage_add_bits }) = response.data {
+            Ok(storage_add_bits.try_into()?)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub async fn add_soma(&self, soma_type: i32, amount: i32, tag: Option<String>) -> RealmApiResult<StorageResult> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(StorageAddSoma::build(StorageAddSomaVariables {
+                id: self.id,
+                soma_type,
+                amount,
+                tag,
+            })).await?;
+
+        if let Some(StorageAddSoma { storage_add_soma }) = response.data {
+            Ok(storage_add_soma.try_into()?)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub async fn buyback_item(&self, item_id: Uuid, tag: Option<String>) -> RealmApiResult<StorageResult> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(StorageBuybackItem::build(StorageSellItemVariables {
+                id: self.id,
+                item_id,
+                tag,
+            })).await?;
+
+        if let Some(StorageBuybackItem { storage_buyback_item }) = response.data {
+            Ok(storage_buyback_item.try_into()?)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
 }
 
 impl RealmApi {
@@ -459,7 +557,14 @@ pub(crate) mod item_storage_graphql {
     }
 
     #[derive(cynic::QueryVariables, Debug)]
-    #[allow(dead_code)]
+    pub struct StoragePurchaseCartVariables {
+        pub id: Uuid,
+        pub items: Vec<Uuid>,
+        pub vendor_items: Vec<Uuid>,
+        pub tag: Option<String>,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
     pub struct StorageSellItemVariables {
         pub id: Uuid,
         pub item_id: Uuid,
@@ -514,6 +619,21 @@ pub(crate) mod item_storage_graphql {
         pub tag: Option<String>,
     }
 
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct StorageAddBitsVariables {
+        pub amount: i32,
+        pub id: Uuid,
+        pub tag: Option<String>,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct StorageAddSomaVariables {
+        pub amount: i32,
+        pub id: Uuid,
+        pub soma_type: i32,
+        pub tag: Option<String>,
+    }
+
     #[derive(cynic::QueryVariables, Debug)]
     pub struct GetStorageVariables {
         pub id: Uuid
@@ -536,12 +656,25 @@ pub(crate) mod item_storage_graphql {
 
     #[derive(cynic::QueryFragment, Debug)]
     #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "StorageSellItemVariables")]
-    #[allow(dead_code)]
     pub struct StorageSellItem {
         #[arguments(id: $id, itemId: $item_id, tag: $tag)]
         pub storage_sell_item: StorageResult,
     }
 
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "StorageSellItemVariables")]
+    pub struct StorageBuybackItem {
+        #[arguments(id: $id, itemId: $item_id, tag: $tag)]
+        pub storage_buyback_item: StorageResult,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "StoragePurchaseCartVariables")]
+    pub struct StoragePurchaseCart {
+        #[arguments(id: $id, items: $items, tag: $tag, vendorItems: $vendor_items)]
+        pub storage_purchase_cart: StorageResult,
+    }
+
     #[derive(cynic::QueryFragment, Debug)]
     #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "StoragePurchaseItemVariables")]
     pub struct StoragePurchaseitem {
@@ -600,6 +733,20 @@ pub(crate) mod item_storage_graphql {
         pub storage_deposit_bling: StorageResult,
     }
 
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "StorageAddBitsVariables")]
+    pub struct StorageAddBits {
+        #[arguments(amount: $amount, id: $id, tag: $tag)]
+        pub storage_add_bits: StorageResult,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "StorageAddSomaVariables")]
+    pub struct StorageAddSoma {
+        #[arguments(amount: $amount, id: $id, somaType: $soma_type, tag: $tag)]
+        pub storage_add_soma: StorageResult,
+    }
+
     #[derive(cynic::QueryFragment, Debug)]
     #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "GetOrCreateStorageVariables")]
     pub struct GetOrCreateStorage {
@@ -619,6 +766,7 @@ pub(crate) mod item_storage_graphql {
     pub struct ItemStorage {
         pub bling: Option<i32>,
         pub game_cash: Option<i32>,
+        pub soma: Option<Vec<i32>>,
         pub capacity: i32,
         pub id: Uuid,
         pub name: String,
@@ -656,6 +804,7 @@ pub(crate) mod item_storage_graphql {
     pub struct StorageResult {
         pub bling: Option<i32>,
         pub game_cash: Option<i32>,
+        pub soma: Option<Vec<i32>>,
         pub changed_items: Option<Vec<Item>>,
         pub error: Option<Json>,
         pub removed_items: Option<Vec<Uuid>>,
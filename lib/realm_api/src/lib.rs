@@ -35,6 +35,15 @@ mod navmesh_tile;
 mod queststate;
 mod quest_template;
 mod quest_dialogue;
+mod party;
+mod clan;
+mod social;
+mod gm_audit;
+mod trade;
+mod mail;
+mod loot_table;
+mod chat_moderation;
+mod character_archive;
 
 pub use base::*;
 pub use error::*;
@@ -57,6 +66,12 @@ pub use navmesh_tile::*;
 pub use queststate::*;
 pub use quest_template::*;
 pub use quest_dialogue::*;
+pub use party::*;
+pub use trade::*;
+pub use mail::*;
+pub use loot_table::*;
+pub use chat_moderation::*;
+pub use character_archive::*;
 
 pub(crate) use quest_template::quest_template_graphql;
 
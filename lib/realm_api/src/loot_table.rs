@@ -0,0 +1,468 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use cynic::{http::ReqwestExt, MutationBuilder, QueryBuilder};
+use derive_builder::Builder;
+use loot_table_graphql::{CreateLootTable, CreateLootTableVariables, DeleteLootTable, DeleteLootTableVariables, GetLootTable, GetLootTableVariables, GetLootTables, GetLootTablesVariables, LootTableEntryInput, LootTableInput, UpdateLootTable, UpdateLootTableVariables};
+use toolkit::{record_pagination::{RecordCursor, RecordPage, RecordQuery}, types::Uuid};
+
+use crate::{RealmApi, RealmApiError, RealmApiResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LootKind {
+    Item,
+    Soma,
+    Bits,
+}
+
+impl From<loot_table_graphql::LootKind> for LootKind {
+    fn from(value: loot_table_graphql::LootKind) -> Self {
+        match value {
+            loot_table_graphql::LootKind::Item => LootKind::Item,
+            loot_table_graphql::LootKind::Soma => LootKind::Soma,
+            loot_table_graphql::LootKind::Bits => LootKind::Bits,
+        }
+    }
+}
+
+impl From<LootKind> for loot_table_graphql::LootKind {
+    fn from(value: LootKind) -> Self {
+        match value {
+            LootKind::Item => loot_table_graphql::LootKind::Item,
+            LootKind::Soma => loot_table_graphql::LootKind::Soma,
+            LootKind::Bits => loot_table_graphql::LootKind::Bits,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LootTableEntry {
+    pub kind: LootKind,
+    pub item_name: Option<String>,
+    pub soma_type: Option<i32>,
+    pub min_amount: i32,
+    pub max_amount: i32,
+    pub weight: i32,
+    pub quest_id: Option<i32>,
+}
+
+impl From<loot_table_graphql::LootTableEntry> for LootTableEntry {
+    fn from(value: loot_table_graphql::LootTableEntry) -> Self {
+        Self {
+            kind: value.kind.into(),
+            item_name: value.item_name,
+            soma_type: value.soma_type,
+            min_amount: value.min_amount,
+            max_amount: value.max_amount,
+            weight: value.weight,
+            quest_id: value.quest_id,
+        }
+    }
+}
+
+impl <'a> From<&'a LootTableEntry> for LootTableEntryInput<'a> {
+    fn from(value: &'a LootTableEntry) -> Self {
+        Self {
+            kind: value.kind.into(),
+            item_name: value.item_name.as_deref(),
+            soma_type: value.soma_type,
+            min_amount: value.min_amount,
+            max_amount: value.max_amount,
+            weight: value.weight,
+            quest_id: value.quest_id,
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned", build_fn(private))]
+pub struct LootTableQuery {
+    #[builder(private)]
+    api_base: RealmApi,
+}
+
+impl RecordQuery for LootTableQuery {
+    type Record = LootTable;
+    type Error = RealmApiError;
+
+    async fn query_next(&mut self, after: Option<String>, limit: usize) -> Result<RecordPage<Self::Record>, Self::Error> {
+        let response = self.api_base.0.client
+            .post(self.api_base.0.base_url.clone())
+            .run_graphql(GetLootTables::build(GetLootTablesVariables {
+                after: after.as_deref(),
+                first: Some(limit as i32)
+            })).await?;
+
+        if let Some(GetLootTables { loot_tables }) = response.data {
+            Ok(RecordPage {
+                at_end: !loot_tables.page_info.has_next_page,
+                last_cursor: loot_tables.page_info.end_cursor,
+                records: loot_tables.nodes.into_iter()
+                    .map(|table| LootTable::from_graphql(&self.api_base, table))
+                    .collect(),
+            })
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+impl LootTableQueryBuilder {
+    pub async fn query(self) -> RealmApiResult<RecordCursor<LootTableQuery>> {
+        Ok(RecordCursor::new(self.build().unwrap()))
+    }
+}
+
+#[derive(Builder, Clone)]
+#[builder(pattern = "owned")]
+pub struct LootTable {
+    #[builder(setter(skip))]
+    api_base: Option<RealmApi>,
+
+    pub id: Uuid,
+    pub name: String,
+    #[builder(default)]
+    pub npcs: Vec<Uuid>,
+    #[builder(default)]
+    pub min_level: Option<i32>,
+    #[builder(default)]
+    pub max_level: Option<i32>,
+    pub drop_chance: f64,
+    pub rolls: i32,
+    #[builder(default)]
+    pub entries: Vec<LootTableEntry>,
+}
+
+impl LootTable {
+    pub async fn save(&self) -> RealmApiResult<()> {
+        let api_base = self.api_base.clone().unwrap_or_else(RealmApi::get);
+
+        let response = api_base.0.client
+            .post(api_base.0.base_url.clone())
+            .run_graphql(UpdateLootTable::build(UpdateLootTableVariables {
+                id: self.id,
+                input: self.into(),
+            })).await?;
+
+        if let Some(UpdateLootTable { .. }) = response.data {
+            Ok(())
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub async fn delete(&self) -> RealmApiResult<()> {
+        if let Some(api_base) = &self.api_base {
+            let response = api_base.0.client
+                .post(api_base.0.base_url.clone())
+                .run_graphql(DeleteLootTable::build(DeleteLootTableVariables {
+                    id: self.id
+                })).await?;
+
+            if let Some(DeleteLootTable { .. }) = response.data {
+                Ok(())
+            } else if let Some(errors) = response.errors {
+                Err(RealmApiError::GraphQl(errors))
+            } else {
+                unreachable!()
+            }
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether this table drops for an NPC of the given template and level.
+    pub fn applies_to(&self, npc: Uuid, level: i32) -> bool {
+        (self.npcs.is_empty() || self.npcs.contains(&npc)) &&
+            self.min_level.is_none_or(|min| level >= min) &&
+            self.max_level.is_none_or(|max| level <= max)
+    }
+
+    fn from_graphql(api: &RealmApi, other: loot_table_graphql::LootTable) -> Self {
+        Self {
+            api_base: Some(api.clone()),
+            id: other.id,
+            name: other.name,
+            npcs: other.npcs,
+            min_level: other.min_level,
+            max_level: other.max_level,
+            drop_chance: other.drop_chance,
+            rolls: other.rolls,
+            entries: other.entries.into_iter()
+                .map(LootTableEntry::from)
+                .collect(),
+        }
+    }
+}
+
+impl <'a> From<&'a LootTable> for LootTableInput<'a> {
+    fn from(value: &'a LootTable) -> Self {
+        Self {
+            id: value.id,
+            name: &value.name,
+            npcs: value.npcs.clone(),
+            min_level: value.min_level,
+            max_level: value.max_level,
+            drop_chance: value.drop_chance,
+            rolls: value.rolls,
+            entries: value.entries.iter()
+                .map(LootTableEntryInput::from)
+                .collect(),
+        }
+    }
+}
+
+impl RealmApi {
+    pub async fn get_loot_table(&self, id: Uuid) -> RealmApiResult<Option<LootTable>> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(GetLootTable::build(GetLootTableVariables {
+                id
+            })).await?;
+
+        if let Some(GetLootTable { loot_table }) = response.data {
+            Ok(loot_table.map(|table| LootTable::from_graphql(self, table)))
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub fn query_loot_tables(&self) -> LootTableQueryBuilder {
+        LootTableQueryBuilder::create_empty()
+            .api_base(self.clone())
+    }
+
+    pub async fn create_loot_table(&self, table: LootTable) -> RealmApiResult<LootTable> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(CreateLootTable::build(CreateLootTableVariables {
+                input: (&table).into()
+            })).await?;
+
+        if let Some(CreateLootTable { create_loot_table }) = response.data {
+            Ok(LootTable::from_graphql(self, create_loot_table))
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+pub(crate) mod loot_table_graphql {
+    use toolkit::types::Uuid;
+
+    use crate::schema::*;
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct GetLootTableVariables {
+        pub id: Uuid,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct GetLootTablesVariables<'a> {
+        pub after: Option<&'a str>,
+        pub first: Option<i32>,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct CreateLootTableVariables<'a> {
+        pub input: LootTableInput<'a>,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct UpdateLootTableVariables<'a> {
+        pub id: Uuid,
+        pub input: LootTableInput<'a>,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct DeleteLootTableVariables {
+        pub id: Uuid,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "QueryRoot", variables = "GetLootTablesVariables")]
+    pub struct GetLootTables {
+        #[arguments(after: $after, first: $first)]
+        pub loot_tables: LootTableConnection,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "QueryRoot", variables = "GetLootTableVariables")]
+    pub struct GetLootTable {
+        #[arguments(id: $id)]
+        pub loot_table: Option<LootTable>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "CreateLootTableVariables")]
+    pub struct CreateLootTable {
+        #[arguments(input: $input)]
+        pub create_loot_table: LootTable,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "UpdateLootTableVariables")]
+    pub struct UpdateLootTable {
+        #[arguments(id: $id, input: $input)]
+        #[allow(dead_code)]
+        pub update_loot_table: Option<LootTable>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "DeleteLootTableVariables")]
+    pub struct DeleteLootTable {
+        #[arguments(id: $id)]
+        #[allow(dead_code)]
+        pub delete_loot_table: Option<LootTable>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct LootTableConnection {
+        pub nodes: Vec<LootTable>,
+        pub page_info: PageInfo,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct PageInfo {
+        pub has_next_page: bool,
+        pub end_cursor: Option<String>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct LootTable {
+        pub id: Uuid,
+        pub name: String,
+        pub npcs: Vec<Uuid>,
+        pub min_level: Option<i32>,
+        pub max_level: Option<i32>,
+        pub drop_chance: f64,
+        pub rolls: i32,
+        pub entries: Vec<LootTableEntry>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct LootTableEntry {
+        pub kind: LootKind,
+        pub item_name: Option<String>,
+        pub soma_type: Option<i32>,
+        pub min_amount: i32,
+        pub max_amount: i32,
+        pub weight: i32,
+        pub quest_id: Option<i32>,
+    }
+
+    #[derive(cynic::Enum, Clone, Copy, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub enum LootKind {
+        Item,
+        Soma,
+        Bits,
+    }
+
+    #[derive(cynic::InputObject, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct LootTableInput<'a> {
+        pub id: Uuid,
+        pub name: &'a str,
+        pub npcs: Vec<Uuid>,
+        pub min_level: Option<i32>,
+        pub max_level: Option<i32>,
+        pub drop_chance: f64,
+        pub rolls: i32,
+        pub entries: Vec<LootTableEntryInput<'a>>,
+    }
+
+    #[derive(cynic::InputObject, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct LootTableEntryInput<'a> {
+        pub kind: LootKind,
+        pub item_name: Option<&'a str>,
+        pub soma_type: Option<i32>,
+        pub min_amount: i32,
+        pub max_amount: i32,
+        pub weight: i32,
+        pub quest_id: Option<i32>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use toolkit::types::Uuid;
+
+    use super::{LootTable, LootTableBuilder};
+
+    fn table(npcs: Vec<Uuid>, min_level: Option<i32>, max_level: Option<i32>) -> LootTable {
+        LootTableBuilder::default()
+            .id(Uuid::new())
+            .name("test".to_string())
+            .npcs(npcs)
+            .min_level(min_level)
+            .max_level(max_level)
+            .drop_chance(1.0)
+            .rolls(1)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn unrestricted_table_applies_to_every_npc() {
+        let table = table(vec![], None, None);
+
+        assert!(table.applies_to(Uuid::new(), 1));
+        assert!(table.applies_to(Uuid::new(), 100));
+    }
+
+    #[test]
+    fn npc_list_restricts_table() {
+        let npc = Uuid::new();
+        let table = table(vec![npc], None, None);
+
+        assert!(table.applies_to(npc, 1));
+        assert!(!table.applies_to(Uuid::new(), 1));
+    }
+
+    #[test]
+    fn level_range_is_inclusive() {
+        let npc = Uuid::new();
+        let table = table(vec![], Some(10), Some(20));
+
+        assert!(!table.applies_to(npc, 9));
+        assert!(table.applies_to(npc, 10));
+        assert!(table.applies_to(npc, 20));
+        assert!(!table.applies_to(npc, 21));
+    }
+
+    #[test]
+    fn open_level_bounds() {
+        let npc = Uuid::new();
+
+        assert!(table(vec![], Some(10), None).applies_to(npc, 1000));
+        assert!(!table(vec![], Some(10), None).applies_to(npc, 5));
+        assert!(table(vec![], None, Some(10)).applies_to(npc, 0));
+        assert!(!table(vec![], None, Some(10)).applies_to(npc, 11));
+    }
+}
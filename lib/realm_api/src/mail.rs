@@ -0,0 +1,317 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Utc};
+use cynic::{http::ReqwestExt, MutationBuilder, QueryBuilder};
+use mail_graphql::{DeleteMail, GetMailbox, GetMailboxVariables, MailActionVariables, ReadMail, ReturnMail, SendMail, SendMailVariables, SendSystemMail, SendSystemMailVariables, TakeMailAttachments, TakeMailAttachmentsVariables};
+use toolkit::types::Uuid;
+
+use crate::{ItemRef, RealmApi, RealmApiError, RealmApiResult, StorageResult};
+
+pub struct Mail {
+    pub id: Uuid,
+    pub recipient: Uuid,
+    pub sender: Option<Uuid>,
+    pub sender_name: String,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Option<Uuid>,
+    pub attached_items: i32,
+    pub attached_bits: i32,
+    pub read: bool,
+    pub returned: bool,
+    pub sent: DateTime<Utc>,
+    pub expires: DateTime<Utc>,
+}
+
+impl From<mail_graphql::Mail> for Mail {
+    fn from(value: mail_graphql::Mail) -> Self {
+        Self {
+            id: value.id,
+            recipient: value.recipient,
+            sender: value.sender,
+            sender_name: value.sender_name,
+            subject: value.subject,
+            body: value.body,
+            attachments: value.attachments,
+            attached_items: value.attached_items,
+            attached_bits: value.attached_bits,
+            read: value.read,
+            returned: value.returned,
+            sent: value.sent,
+            expires: value.expires,
+        }
+    }
+}
+
+impl RealmApi {
+    /// Returns all mail of a character, newest first.
+    pub async fn get_mailbox(&self, character_id: Uuid) -> RealmApiResult<Vec<Mail>> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(GetMailbox::build(GetMailboxVariables {
+                character_id,
+            })).await?;
+
+        if let Some(GetMailbox { mailbox }) = response.data {
+            Ok(mailbox.into_iter().map(Mail::from).collect())
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Sends mail from a character. Attachments are taken from `storage_id`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_mail(&self, sender_id: Uuid, storage_id: Uuid, recipient_name: &str, subject: &str, body: &str, items: Vec<Uuid>, bits: i32, tag: Option<String>) -> RealmApiResult<Vec<StorageResult>> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(SendMail::build(SendMailVariables {
+                tag,
+                sender_id,
+                storage_id,
+                recipient_name,
+                subject,
+                body,
+                items,
+                bits,
+            })).await?;
+
+        if let Some(SendMail { send_mail }) = response.data {
+            send_mail.into_iter()
+                .map(StorageResult::try_from)
+                .collect()
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Sends mail on behalf of the realm, with newly created items attached.
+    pub async fn send_system_mail(&self, sender_name: &str, recipient_name: &str, subject: &str, body: &str, items: Vec<ItemRef<'_>>, bits: i32) -> RealmApiResult<Mail> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(SendSystemMail::build(SendSystemMailVariables {
+                sender_name,
+                recipient_name,
+                subject,
+                body,
+                items: items.into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<_>, _>>()?,
+                bits,
+            })).await?;
+
+        if let Some(SendSystemMail { send_system_mail }) = response.data {
+            Ok(send_system_mail.into())
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub async fn read_mail(&self, character_id: Uuid, mail_id: Uuid) -> RealmApiResult<Mail> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(ReadMail::build(MailActionVariables {
+                character_id,
+                mail_id,
+            })).await?;
+
+        if let Some(ReadMail { read_mail }) = response.data {
+            Ok(read_mail.into())
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Moves all attachments of a mail into the given storage.
+    pub async fn take_mail_attachments(&self, character_id: Uuid, mail_id: Uuid, storage_id: Uuid, tag: Option<String>) -> RealmApiResult<Vec<StorageResult>> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(TakeMailAttachments::build(TakeMailAttachmentsVariables {
+                tag,
+                character_id,
+                mail_id,
+                storage_id,
+            })).await?;
+
+        if let Some(TakeMailAttachments { take_mail_attachments }) = response.data {
+            take_mail_attachments.into_iter()
+                .map(StorageResult::try_from)
+                .collect()
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub async fn return_mail(&self, character_id: Uuid, mail_id: Uuid) -> RealmApiResult<()> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(ReturnMail::build(MailActionVariables {
+                character_id,
+                mail_id,
+            })).await?;
+
+        if response.data.is_some() {
+            Ok(())
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub async fn delete_mail(&self, character_id: Uuid, mail_id: Uuid) -> RealmApiResult<()> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(DeleteMail::build(MailActionVariables {
+                character_id,
+                mail_id,
+            })).await?;
+
+        if response.data.is_some() {
+            Ok(())
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+pub(crate) mod mail_graphql {
+    use chrono::{DateTime, Utc};
+    use toolkit::types::Uuid;
+
+    use crate::{schema::*, item_storage_graphql::{ItemRef, StorageResult}};
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct GetMailboxVariables {
+        pub character_id: Uuid,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct SendMailVariables<'a> {
+        pub tag: Option<String>,
+        pub sender_id: Uuid,
+        pub storage_id: Uuid,
+        pub recipient_name: &'a str,
+        pub subject: &'a str,
+        pub body: &'a str,
+        pub items: Vec<Uuid>,
+        pub bits: i32,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct SendSystemMailVariables<'a> {
+        pub sender_name: &'a str,
+        pub recipient_name: &'a str,
+        pub subject: &'a str,
+        pub body: &'a str,
+        pub items: Vec<ItemRef<'a>>,
+        pub bits: i32,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct MailActionVariables {
+        pub character_id: Uuid,
+        pub mail_id: Uuid,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct TakeMailAttachmentsVariables {
+        pub tag: Option<String>,
+        pub character_id: Uuid,
+        pub mail_id: Uuid,
+        pub storage_id: Uuid,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct Mail {
+        pub id: Uuid,
+        pub recipient: Uuid,
+        pub sender: Option<Uuid>,
+        pub sender_name: String,
+        pub subject: String,
+        pub body: String,
+        pub attachments: Option<Uuid>,
+        pub attached_items: i32,
+        pub attached_bits: i32,
+        pub read: bool,
+        pub returned: bool,
+        pub sent: DateTime<Utc>,
+        pub expires: DateTime<Utc>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "QueryRoot", variables = "GetMailboxVariables")]
+    pub struct GetMailbox {
+        #[arguments(characterId: $character_id)]
+        pub mailbox: Vec<Mail>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "SendMailVariables")]
+    pub struct SendMail {
+        #[arguments(tag: $tag, senderId: $sender_id, storageId: $storage_id, recipientName: $recipient_name, subject: $subject, body: $body, items: $items, bits: $bits)]
+        pub send_mail: Vec<StorageResult>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "SendSystemMailVariables")]
+    pub struct SendSystemMail {
+        #[arguments(senderName: $sender_name, recipientName: $recipient_name, subject: $subject, body: $body, items: $items, bits: $bits)]
+        pub send_system_mail: Mail,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "MailActionVariables")]
+    pub struct ReadMail {
+        #[arguments(characterId: $character_id, mailId: $mail_id)]
+        pub read_mail: Mail,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "TakeMailAttachmentsVariables")]
+    pub struct TakeMailAttachments {
+        #[arguments(tag: $tag, characterId: $character_id, mailId: $mail_id, storageId: $storage_id)]
+        pub take_mail_attachments: Vec<StorageResult>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "MailActionVariables")]
+    pub struct ReturnMail {
+        #[arguments(characterId: $character_id, mailId: $mail_id)]
+        #[allow(dead_code)]
+        pub return_mail: bool,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "MailActionVariables")]
+    pub struct DeleteMail {
+        #[arguments(characterId: $character_id, mailId: $mail_id)]
+        #[allow(dead_code)]
+        pub delete_mail: bool,
+    }
+}
@@ -27,6 +27,7 @@ pub struct ClusterNode {
     pub id: Uuid,
     pub ty: NodeType,
     pub addr: ClusterAddress,
+    pub channel: Option<u32>,
 }
 
 impl ClusterNode {
@@ -50,7 +51,8 @@ impl ClusterNode {
                 NodeAddress::Unknown => {
                     unimplemented!()
                 },
-            }
+            },
+            channel: other.channel.map(|channel| channel as u32),
         })
     }
 }
@@ -136,6 +138,7 @@ pub(crate) mod node_graphql {
         pub addr: NodeAddress,
         pub id: Uuid,
         pub ty: NodeType,
+        pub channel: Option<i32>,
     }
     
     #[derive(cynic::QueryFragment, Debug)]
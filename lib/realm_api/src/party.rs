@@ -0,0 +1,84 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use cynic::{http::ReqwestExt, QueryBuilder};
+use party_graphql::{GetPartyForCharacter, GetPartyForCharacterVariables};
+use toolkit::types::Uuid;
+
+use crate::{RealmApi, RealmApiError, RealmApiResult};
+
+pub struct Party {
+    id: Uuid,
+    leader: Uuid,
+    members: Vec<Uuid>,
+}
+
+impl Party {
+    fn from_graphql(other: party_graphql::Party) -> Self {
+        Party {
+            id: other.id,
+            leader: other.leader,
+            members: other.members,
+        }
+    }
+
+    pub fn id(&self) -> &Uuid { &self.id }
+    pub fn leader(&self) -> &Uuid { &self.leader }
+    pub fn members(&self) -> &[Uuid] { &self.members }
+}
+
+impl RealmApi {
+    pub async fn get_party_for_character(&self, character_id: Uuid) -> RealmApiResult<Option<Party>> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(GetPartyForCharacter::build(GetPartyForCharacterVariables {
+                character_id
+            })).await?;
+
+        if let Some(GetPartyForCharacter { party_for_character }) = response.data {
+            Ok(party_for_character.map(Party::from_graphql))
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+pub(crate) mod party_graphql {
+    use toolkit::types::Uuid;
+
+    use crate::schema::*;
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct GetPartyForCharacterVariables {
+        pub character_id: Uuid,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "QueryRoot", variables = "GetPartyForCharacterVariables")]
+    pub struct GetPartyForCharacter {
+        #[arguments(characterId: $character_id)]
+        pub party_for_character: Option<Party>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct Party {
+        pub id: Uuid,
+        pub leader: Uuid,
+        pub members: Vec<Uuid>,
+    }
+}
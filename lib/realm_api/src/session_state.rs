@@ -23,6 +23,8 @@ pub struct SessionState {
     id: Uuid,
     avatar: AvatarId,
     character: Uuid,
+    speed_violations: u32,
+    height_violations: u32,
 }
 
 impl SessionState {
@@ -32,12 +34,16 @@ impl SessionState {
             avatar: other.avatar.parse()
                 .map_err(|_| anyhow::Error::msg("invalid avatar id"))?,
             character: other.character,
+            speed_violations: other.speed_violations as u32,
+            height_violations: other.height_violations as u32,
         })
     }
 
     pub fn id(&self) -> &Uuid { &self.id }
     pub fn avatar(&self) -> &AvatarId { &self.avatar }
     pub fn character(&self) -> &Uuid { &self.character }
+    pub fn speed_violations(&self) -> u32 { self.speed_violations }
+    pub fn height_violations(&self) -> u32 { self.height_violations }
 }
 
 impl RealmApi {
@@ -115,5 +121,7 @@ pub(crate) mod session_ext_graphql {
         pub id: Uuid,
         pub avatar: String,
         pub character: Uuid,
+        pub speed_violations: i32,
+        pub height_violations: i32,
     }
 }
\ No newline at end of file
@@ -0,0 +1,56 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use cynic::{http::ReqwestExt, QueryBuilder};
+use social_graphql::{GetIgnoredCharacters, GetIgnoredCharactersVariables};
+use toolkit::types::Uuid;
+
+use crate::{RealmApi, RealmApiError, RealmApiResult};
+
+impl RealmApi {
+    pub async fn get_ignored_characters(&self, character_id: Uuid) -> RealmApiResult<Vec<Uuid>> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(GetIgnoredCharacters::build(GetIgnoredCharactersVariables {
+                character_id
+            })).await?;
+
+        if let Some(GetIgnoredCharacters { ignored_characters }) = response.data {
+            Ok(ignored_characters)
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+pub(crate) mod social_graphql {
+    use toolkit::types::Uuid;
+
+    use crate::schema::*;
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct GetIgnoredCharactersVariables {
+        pub character_id: Uuid,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "QueryRoot", variables = "GetIgnoredCharactersVariables")]
+    pub struct GetIgnoredCharacters {
+        #[arguments(characterId: $character_id)]
+        pub ignored_characters: Vec<Uuid>,
+    }
+}
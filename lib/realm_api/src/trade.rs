@@ -0,0 +1,92 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use cynic::{http::ReqwestExt, MutationBuilder};
+use toolkit::types::Uuid;
+use trade_graphql::{ExecuteTrade, ExecuteTradeVariables};
+
+use crate::{RealmApi, RealmApiError, RealmApiResult, StorageResult};
+
+/// What one side of a trade hands over.
+pub struct TradeOffer {
+    pub character_id: Uuid,
+    pub storage_id: Uuid,
+    pub items: Vec<Uuid>,
+    pub bits: i32,
+}
+
+impl From<TradeOffer> for trade_graphql::TradeOffer {
+    fn from(value: TradeOffer) -> Self {
+        Self {
+            character_id: value.character_id,
+            storage_id: value.storage_id,
+            items: value.items,
+            bits: value.bits,
+        }
+    }
+}
+
+impl RealmApi {
+    /// Exchanges both offers atomically. The results contain the updates
+    /// for both storages, or a single result carrying the client error.
+    pub async fn execute_trade(&self, left: TradeOffer, right: TradeOffer, tag: Option<String>) -> RealmApiResult<Vec<StorageResult>> {
+        let response = self.0.client
+            .post(self.0.base_url.clone())
+            .run_graphql(ExecuteTrade::build(ExecuteTradeVariables {
+                left: left.into(),
+                right: right.into(),
+                tag,
+            })).await?;
+
+        if let Some(ExecuteTrade { execute_trade }) = response.data {
+            execute_trade.into_iter()
+                .map(StorageResult::try_from)
+                .collect()
+        } else if let Some(errors) = response.errors {
+            Err(RealmApiError::GraphQl(errors))
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+pub(crate) mod trade_graphql {
+    use toolkit::types::Uuid;
+
+    use crate::{schema::*, item_storage_graphql::StorageResult};
+
+    #[derive(cynic::InputObject, Debug)]
+    #[cynic(schema = "realm_manager_service")]
+    pub struct TradeOffer {
+        pub character_id: Uuid,
+        pub storage_id: Uuid,
+        pub items: Vec<Uuid>,
+        pub bits: i32,
+    }
+
+    #[derive(cynic::QueryVariables, Debug)]
+    pub struct ExecuteTradeVariables {
+        pub left: TradeOffer,
+        pub right: TradeOffer,
+        pub tag: Option<String>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug)]
+    #[cynic(schema = "realm_manager_service", graphql_type = "MutationRoot", variables = "ExecuteTradeVariables")]
+    pub struct ExecuteTrade {
+        #[arguments(left: $left, right: $right, tag: $tag)]
+        pub execute_trade: Vec<StorageResult>,
+    }
+}
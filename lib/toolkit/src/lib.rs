@@ -32,6 +32,7 @@ pub use retry::*;
 pub mod types;
 pub mod string_parsers;
 pub mod record_pagination;
+pub mod metrics;
 
 // reexports
 pub use env_logger;
@@ -39,4 +40,5 @@ pub use dotenvy;
 pub use config;
 pub use once_cell;
 pub use anyhow;
-pub use bson;
\ No newline at end of file
+pub use bson;
+pub use prometheus;
\ No newline at end of file
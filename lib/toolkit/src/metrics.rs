@@ -0,0 +1,63 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::METRICS_CONF;
+use log::{error, info};
+use poem::{handler, http::StatusCode, listener::TcpListener, get, IntoResponse, Response, Route, Server};
+use prometheus::{Encoder, TextEncoder};
+
+/// Starts the prometheus exporter, if a listen address is configured.
+/// 
+/// The address is read from the `[metrics]` section of the config and can be
+/// overridden per service with the `METRICS_BIND_ADDR` environment variable,
+/// which is required when running multiple services on the same host.
+pub fn start_metrics_server() {
+    let Some(listen_address) = std::env::var("METRICS_BIND_ADDR")
+        .ok()
+        .or_else(|| METRICS_CONF.metrics.listen_address.clone())
+        .filter(|addr| !addr.is_empty())
+    else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        info!("Starting metrics server on http://{listen_address}/metrics");
+
+        let app = Route::new()
+            .at("/metrics", get(metrics));
+
+        if let Err(e) = Server::new(TcpListener::bind(listen_address))
+            .run(app)
+            .await
+        {
+            error!("Metrics server error: {e}");
+        }
+    });
+}
+
+#[handler]
+fn metrics() -> Response {
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+
+    if let Err(e) = encoder.encode(&prometheus::gather(), &mut buffer) {
+        error!("Failed to encode metrics: {e}");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    Response::builder()
+        .content_type(encoder.format_type())
+        .body(buffer)
+}
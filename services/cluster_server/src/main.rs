@@ -13,7 +13,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, sync::Arc};
 
 use clap::Parser;
 use cluster_context::{ClusterContext, Message};
@@ -22,7 +22,7 @@ use error::ClusterFrontendResult;
 use once_cell::sync::Lazy;
 use protocol::{CPkt, CPktChat, CpktChatChatType, OtherlandPacket};
 use raknet::RakNetListener;
-use realm_api::{proto::{Destination, NodeAddress, NodeType, RealmClient, RealmRequest}, RealmApi};
+use realm_api::{proto::{Destination, NodeAddress, NodeType, RealmClient, RealmRequest, DEFAULT_CHANNEL}, RealmApi};
 use reqwest::Url;
 use log::{error, info};
 use router::Router;
@@ -53,6 +53,14 @@ struct Cli {
 
     #[arg(long, default_value_t = false)]
     insecure: bool,
+
+    /// Write the decrypted traffic of every client session into this directory
+    #[arg(long, env = "CAPTURE_DIR")]
+    capture_dir: Option<PathBuf>,
+
+    /// Realm channel served by this cluster node, between 1 and 255
+    #[arg(long, env = "CHANNEL", default_value_t = DEFAULT_CHANNEL, value_parser = clap::value_parser!(u32).range(1..=255))]
+    channel: u32,
 }
 
 static ARGS: Lazy<Cli> = Lazy::new(Cli::parse);
@@ -83,13 +91,21 @@ async fn main() -> ClusterFrontendResult<()> {
         if !ARGS.insecure {
             listener.generate_random_rsa_key();
         }
+
+        if let Some(capture_dir) = &ARGS.capture_dir {
+            std::fs::create_dir_all(capture_dir)
+                .map_err(anyhow::Error::from)?;
+            info!("Capturing client sessions to {}", capture_dir.display());
+
+            listener.enable_capture(capture_dir);
+        }
         
         listener.listen(100).await;
 
         info!("Server started...");
 
         // notify realm server we're online
-        realm_client.send(RealmRequest::RegisterNode(NodeType::Cluster, NodeAddress::Public(ARGS.public_addr))).await?;
+        realm_client.send(RealmRequest::RegisterNode(NodeType::Cluster, NodeAddress::Public(ARGS.public_addr), Some(ARGS.channel))).await?;
 
         loop {
             select! {
@@ -110,6 +126,10 @@ async fn main() -> ClusterFrontendResult<()> {
                                 let Ok(Some(session)) = core_api.get_session(&pkt.session_id).await
                             {
                                 let session_id = *session.id();
+
+                                if ARGS.capture_dir.is_some() {
+                                    info!("Capturing session {session_id} as {}.rncap", socket.id());
+                                }
             
                                 match ClusterContext::create_and_start(
                                     core_api, 
@@ -31,34 +31,34 @@ pub async fn run_core_server(server: Arc<CoreServer>, status_registry: Arc<Realm
                         cluster::ClusterEvent::Accepted(_, _) => (),
                         cluster::ClusterEvent::Disconnected(identity) => {
                             if let Some((id, endpoints)) = registered_realm_endpoints.remove(&identity) {
-                                for endpoint in endpoints {
-                                    status_registry.unregister_endpoint(id, endpoint).await;
+                                for (channel, endpoint) in endpoints {
+                                    status_registry.unregister_endpoint(id, channel, endpoint).await;
                                 }
                             }
                         },
                     }
                 },
-                Ok((identity, msg)) = server.recv() => {
+                Ok((identity, _, msg)) = server.recv() => {
                     match msg {
-                        CoreRequest::ConnectRealm(id, endpoint) => {
+                        CoreRequest::ConnectRealm(id, channel, endpoint) => {
                             let entry = registered_realm_endpoints.entry(identity)
                                 .or_insert((id, vec![]));
 
-                            entry.1.push(endpoint);
-                            status_registry.register_endpoint(id, endpoint).await;
+                            entry.1.push((channel, endpoint));
+                            status_registry.register_endpoint(id, channel, endpoint).await;
                         },
-                        CoreRequest::DisconnectRealm(id, endpoint) => {
+                        CoreRequest::DisconnectRealm(id, channel, endpoint) => {
                             if let Some(entry) = registered_realm_endpoints.get_mut(&identity) {
-                                entry.1.retain_mut(|compare_endpoint| compare_endpoint != &endpoint);
+                                entry.1.retain_mut(|compare| compare != &(channel, endpoint));
                             }
 
-                            if !status_registry.unregister_endpoint(id, endpoint).await {
+                            if !status_registry.unregister_endpoint(id, channel, endpoint).await {
                                 registered_realm_endpoints.remove(&identity);
                             }
                         },
-                        CoreRequest::UpdateRealmPopulation(population) => {
+                        CoreRequest::UpdateRealmPopulation { channel, players, max_players } => {
                             if let Some((id, _)) = registered_realm_endpoints.get(&identity) {
-                                status_registry.update_population(id, population).await;
+                                status_registry.update_capacity(id, channel, players, max_players).await;
                             }
                         },
                     }
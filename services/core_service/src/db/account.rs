@@ -75,6 +75,8 @@ pub struct Account {
     pub last_login: Option<DateTime<Utc>>,
     pub banned: bool,
     pub ban_reason: Option<String>,
+    #[serde(default)]
+    pub banned_until: Option<DateTime<Utc>>,
     pub is_gm: bool,
 }
 
@@ -86,6 +88,11 @@ impl Account {
         }
     }
 
+    /// Timed bans are lifted lazily, once they are past their expiry date.
+    pub fn is_banned(&self) -> bool {
+        self.banned && self.banned_until.is_none_or(|until| until > Utc::now())
+    }
+
     pub async fn get_by_steam_id(db: &Database, steam_id: &str) -> DBResult<Option<Account>> {
         let collection = Self::collection(db);
         Ok(collection.find_one(doc! {"credentials.Steam.steam_id": {"$eq": steam_id}}).await?)
@@ -119,6 +126,7 @@ impl Account {
             last_login: None,
             banned: false,
             ban_reason: None,
+            banned_until: None,
             is_gm: false,
         };
 
@@ -0,0 +1,108 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bson::doc;
+use chrono::{DateTime, Utc};
+use database::{DBResult, DatabaseRecord};
+use futures::TryStreamExt;
+use mongodb::{options::IndexOptions, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use toolkit::types::Uuid;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AccountAction {
+    Ban,
+    Unban,
+    Promote,
+    Demote,
+}
+
+/// Records privileged changes to an account and who issued them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountAuditEntry {
+    pub id: Uuid,
+    pub account: Uuid,
+    pub action: AccountAction,
+    pub issued_by: String,
+    pub reason: Option<String>,
+    pub until: Option<DateTime<Utc>>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl AccountAuditEntry {
+    pub async fn record(
+        db: &Database, 
+        account: Uuid, 
+        action: AccountAction, 
+        issued_by: String, 
+        reason: Option<String>, 
+        until: Option<DateTime<Utc>>
+    ) -> DBResult<AccountAuditEntry> {
+        let entry = AccountAuditEntry {
+            id: Uuid::new(),
+            account,
+            action,
+            issued_by,
+            reason,
+            until,
+            timestamp: Utc::now(),
+        };
+
+        Self::collection(db).insert_one(&entry).await?;
+
+        Ok(entry)
+    }
+
+    pub async fn list_for_account(db: &Database, account: &Uuid) -> DBResult<Vec<AccountAuditEntry>> {
+        Ok(Self::collection(db)
+            .find(doc!("account": *account))
+            .sort(doc!("timestamp": -1))
+            .await?
+            .try_collect()
+            .await?)
+    }
+}
+
+impl DatabaseRecord for AccountAuditEntry {
+    type PrimaryKey = Uuid;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "id"
+    }
+    
+    fn collection_name() -> &'static str {
+        "account_audit_log"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc!("id": 1))
+            .options(IndexOptions::builder().unique(true).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc!("account": 1, "timestamp": -1))
+            .build()).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bson::doc;
+use chrono::{DateTime, Duration, Utc};
+use database::{DBResult, DatabaseRecord};
+use futures::TryStreamExt;
+use mongodb::{Database, IndexModel, options::{IndexOptions, ReturnDocument}};
+use serde::{Deserialize, Serialize};
+use toolkit::{anyhow, config::CLUSTER_CONF, types::Uuid};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LoginThrottleKind {
+    Account,
+    Address,
+}
+
+/// Failed login attempts for an account or a client address.
+/// After too many failures, further attempts are locked out for
+/// a time that doubles with every additional failure.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LoginThrottle {
+    pub id: Uuid,
+    pub kind: LoginThrottleKind,
+    /// Account id or client address, depending on `kind`.
+    pub key: String,
+    pub failures: u32,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub last_failure: DateTime<Utc>,
+    #[serde(default, with = "database::chrono_datetime_as_bson_datetime_optional")]
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginThrottle {
+    pub async fn get_for(db: &Database, kind: LoginThrottleKind, key: &str) -> DBResult<Option<LoginThrottle>> {
+        Ok(Self::collection(db)
+            .find_one(doc!("kind": bson::to_bson(&kind).map_err(anyhow::Error::from)?, "key": key))
+            .await?)
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked_until.is_some_and(|until| until > Utc::now())
+    }
+
+    /// Counts a failed attempt and locks the key once the threshold is reached.
+    pub async fn record_failure(db: &Database, kind: LoginThrottleKind, key: &str) -> DBResult<LoginThrottle> {
+        let conf = &CLUSTER_CONF.login;
+        let threshold = conf.failed_login_threshold.unwrap_or(5);
+        let reset_after = Duration::seconds(conf.failed_login_reset.unwrap_or(900));
+        let base_lockout = conf.failed_login_lockout.unwrap_or(60);
+        let max_lockout = conf.failed_login_max_lockout.unwrap_or(3600);
+
+        let now = Utc::now();
+        let kind_bson = bson::to_bson(&kind).map_err(anyhow::Error::from)?;
+        let now_bson = bson::DateTime::from_chrono(now);
+        let reset_before = bson::DateTime::from_chrono(now - reset_after);
+
+        // Forget old failures, unless they are the reason for an active lockout
+        Self::collection(db)
+            .update_one(
+                doc!(
+                    "kind": kind_bson.clone(), 
+                    "key": key, 
+                    "last_failure": { "$lt": reset_before },
+                    "locked_until": { "$not": { "$gt": now_bson } },
+                ),
+                doc!("$set": { "failures": 0 })
+            )
+            .await?;
+
+        let mut throttle = Self::collection(db)
+            .find_one_and_update(
+                doc!("kind": kind_bson.clone(), "key": key),
+                doc!(
+                    "$inc": { "failures": 1 },
+                    "$set": { "last_failure": now_bson },
+                    "$setOnInsert": { "id": Uuid::new(), "locked_until": null },
+                )
+            )
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await?
+            .ok_or(anyhow::anyhow!("login throttle upsert returned no document"))?;
+
+        if let Some(lockout) = lockout_duration(throttle.failures, threshold, base_lockout, max_lockout) {
+            let locked_until = now + lockout;
+
+            // Concurrent failures must not shorten a longer lockout
+            Self::collection(db)
+                .update_one(
+                    doc!("kind": kind_bson, "key": key),
+                    doc!("$max": { "locked_until": bson::DateTime::from_chrono(locked_until) })
+                )
+                .await?;
+
+            throttle.locked_until = throttle.locked_until.max(Some(locked_until));
+        }
+
+        Ok(throttle)
+    }
+
+    pub async fn clear(db: &Database, kind: LoginThrottleKind, key: &str) -> DBResult<bool> {
+        Ok(Self::collection(db)
+            .delete_one(doc!("kind": bson::to_bson(&kind).map_err(anyhow::Error::from)?, "key": key))
+            .await?
+            .deleted_count > 0)
+    }
+
+    pub async fn list_locked(db: &Database) -> DBResult<Vec<LoginThrottle>> {
+        Ok(Self::collection(db)
+            .find(doc!("locked_until": { "$gt": bson::DateTime::now() }))
+            .sort(doc!("locked_until": -1))
+            .await?
+            .try_collect()
+            .await?)
+    }
+}
+
+/// Returns how long a key is locked out after the given number of failures.
+/// The first lockout happens at the threshold and doubles with every further
+/// failure, up to `max_lockout` seconds.
+fn lockout_duration(failures: u32, threshold: u32, base_lockout: i64, max_lockout: i64) -> Option<Duration> {
+    if failures < threshold {
+        return None;
+    }
+
+    let exponent = (failures - threshold).min(16);
+    Some(Duration::seconds(base_lockout.saturating_mul(1 << exponent).min(max_lockout)))
+}
+
+impl DatabaseRecord for LoginThrottle {
+    type PrimaryKey = Uuid;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "id"
+    }
+    
+    fn collection_name() -> &'static str {
+        "login_throttles"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc!("id": 1))
+            .options(IndexOptions::builder().unique(true).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc!("kind": 1, "key": 1))
+            .options(IndexOptions::builder().unique(true).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc!("locked_until": 1))
+            .build()).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::lockout_duration;
+
+    #[test]
+    fn no_lockout_below_threshold() {
+        assert_eq!(lockout_duration(0, 5, 60, 3600), None);
+        assert_eq!(lockout_duration(4, 5, 60, 3600), None);
+    }
+
+    #[test]
+    fn lockout_doubles_per_failure() {
+        assert_eq!(lockout_duration(5, 5, 60, 3600), Some(Duration::seconds(60)));
+        assert_eq!(lockout_duration(6, 5, 60, 3600), Some(Duration::seconds(120)));
+        assert_eq!(lockout_duration(7, 5, 60, 3600), Some(Duration::seconds(240)));
+    }
+
+    #[test]
+    fn lockout_is_capped() {
+        assert_eq!(lockout_duration(11, 5, 60, 3600), Some(Duration::seconds(3600)));
+        assert_eq!(lockout_duration(u32::MAX, 5, 60, 3600), Some(Duration::seconds(3600)));
+    }
+}
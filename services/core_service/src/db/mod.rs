@@ -14,11 +14,15 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 mod account;
+mod account_audit;
 mod session;
 mod status;
 mod realm;
+mod login_throttle;
 
 pub use account::*;
+pub use account_audit::*;
 pub use session::*;
 pub use status::*;
-pub use realm::*;
\ No newline at end of file
+pub use realm::*;
+pub use login_throttle::*;
\ No newline at end of file
@@ -19,10 +19,9 @@ use async_graphql::{EmptySubscription, Schema};
 use async_graphql_poem::GraphQL;
 use clap::Parser;
 use core_server_runner::run_core_server;
-use database::DatabaseExt;
-use db::{Account, Realm, Session, Status};
+use database::{connect_instrumented, DatabaseExt};
+use db::{Account, AccountAuditEntry, LoginThrottle, Realm, Session, Status};
 use log::info;
-use mongodb::Client;
 use poem::{listener::TcpListener, post, Route, Server};
 use proto::CoreServer;
 use realm_status_registry::RealmStatusRegistry;
@@ -58,15 +57,17 @@ async fn main() {
     print_banner();
 
     // Init database
-    let client = Client::with_uri_str(&args.mongo_uri).await
+    let client = connect_instrumented(&args.mongo_uri).await
         .expect("Database connection failed");
     let db = client.database(&args.mongo_db);
 
     // Init collections
     db.init_collection::<Account>().await;
+    db.init_collection::<AccountAuditEntry>().await;
     db.init_collection::<Session>().await;
     db.init_collection::<Status>().await;
     db.init_collection::<Realm>().await;
+    db.init_collection::<LoginThrottle>().await;
 
     // Cluster server
     let server = Arc::new(
@@ -23,9 +23,14 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
 pub enum CoreRequest {
-    ConnectRealm(i32, SocketAddr),
-    DisconnectRealm(i32, SocketAddr),
-    UpdateRealmPopulation(f32)
+    /// Realm id, channel and the frontend endpoint serving it.
+    ConnectRealm(i32, u32, SocketAddr),
+    DisconnectRealm(i32, u32, SocketAddr),
+    UpdateRealmPopulation {
+        channel: u32,
+        players: u32,
+        max_players: Option<u32>,
+    },
 }
 
 impl Request for CoreRequest {}
@@ -13,15 +13,56 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{collections::{BTreeMap, HashMap}, net::SocketAddr, sync::Arc};
 use tokio::sync::RwLock;
 
 use crate::proto::{CoreNotification, CoreServer};
 
+/// Channel used when a realm doesn't specify one.
+pub const DEFAULT_CHANNEL: u32 = 1;
+
 #[derive(Default, Clone)]
-pub struct RealmStatus {
+pub struct ChannelStatus {
     pub endpoints: Vec<SocketAddr>,
     pub population: f32,
+    pub players: u32,
+    /// Reported by the realm, `None` if the channel has no player limit.
+    pub max_players: Option<u32>,
+}
+
+#[derive(Default, Clone)]
+pub struct RealmStatus {
+    pub channels: BTreeMap<u32, ChannelStatus>,
+}
+
+impl RealmStatus {
+    pub fn players(&self) -> u32 {
+        self.channels.values()
+            .map(|channel| channel.players)
+            .sum()
+    }
+
+    /// Combined capacity of all channels, `None` if any channel is unlimited.
+    pub fn max_players(&self) -> Option<u32> {
+        self.channels.values()
+            .map(|channel| channel.max_players)
+            .sum()
+    }
+
+    pub fn population(&self) -> f32 {
+        if self.channels.is_empty() {
+            0.0
+        } else {
+            self.channels.values()
+                .map(|channel| channel.population)
+                .sum::<f32>() / self.channels.len() as f32
+        }
+    }
+
+    pub fn endpoint(&self) -> Option<SocketAddr> {
+        self.channels.values()
+            .find_map(|channel| channel.endpoints.first().copied())
+    }
 }
 
 pub struct RealmStatusRegistry {
@@ -38,21 +79,32 @@ impl RealmStatusRegistry {
         }
     }
 
-    pub async fn register_endpoint(&self, id: i32, endpoint: SocketAddr) {
+    pub async fn register_endpoint(&self, id: i32, channel: u32, endpoint: SocketAddr) {
         let mut registry = self.registry.write().await;
         let entry = registry.entry(id)
-            .or_insert(RealmStatus::default());
+            .or_insert(RealmStatus::default())
+            .channels.entry(channel)
+            .or_insert(ChannelStatus::default());
         entry.endpoints.push(endpoint);
 
         let _ = self.server.notify(CoreNotification::RealmListUpdated).await;
     }
 
+    /// Removes an endpoint from a channel. Channels without endpoints are
+    /// removed, returns whether the realm still has any channel online.
     #[allow(dead_code)]
-    pub async fn unregister_endpoint(&self, id: i32, endpoint: SocketAddr) -> bool {
+    pub async fn unregister_endpoint(&self, id: i32, channel: u32, endpoint: SocketAddr) -> bool {
         let mut registry = self.registry.write().await;
         let online = if let Some(entry) = registry.get_mut(&id) {
-            entry.endpoints.retain_mut(|addr| addr != &endpoint);
-            !entry.endpoints.is_empty()
+            if let Some(status) = entry.channels.get_mut(&channel) {
+                status.endpoints.retain_mut(|addr| addr != &endpoint);
+
+                if status.endpoints.is_empty() {
+                    entry.channels.remove(&channel);
+                }
+            }
+
+            !entry.channels.is_empty()
         } else {
             false
         };
@@ -65,17 +117,52 @@ impl RealmStatusRegistry {
         online
     }
 
-    pub async fn update_population(&self, id: &i32, population: f32) {
+    pub async fn update_population(&self, id: &i32, channel: u32, population: f32) {
         let mut registry = self.registry.write().await;
-        if let Some(status) = registry.get_mut(id) {
+        if 
+            let Some(status) = registry.get_mut(id) &&
+            let Some(status) = status.channels.get_mut(&channel)
+        {
             status.population = population;
         }
 
         let _ = self.server.notify(CoreNotification::RealmListUpdated).await;
     }
 
+    /// Updates the player count and capacity of a realm channel.
+    /// The population is derived as the fraction of occupied slots.
+    /// Listeners are only notified if anything changed.
+    pub async fn update_capacity(&self, id: &i32, channel: u32, players: u32, max_players: Option<u32>) {
+        let mut registry = self.registry.write().await;
+        if 
+            let Some(status) = registry.get_mut(id) &&
+            let Some(status) = status.channels.get_mut(&channel)
+        {
+            let population = match max_players {
+                Some(max_players) if max_players > 0 => (players as f32 / max_players as f32).min(1.0),
+                _ => 0.0,
+            };
+
+            if 
+                status.players == players &&
+                status.max_players == max_players &&
+                status.population == population
+            {
+                return;
+            }
+
+            status.players = players;
+            status.max_players = max_players;
+            status.population = population;
+        } else {
+            return;
+        }
+
+        let _ = self.server.notify(CoreNotification::RealmListUpdated).await;
+    }
+
     pub async fn status(&self, id: &i32) -> Option<RealmStatus> {
         let registry = self.registry.read().await;
         registry.get(id).cloned()
     }
-}
\ No newline at end of file
+}
@@ -13,13 +13,17 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use async_graphql::{Context, Error, InputObject, Object, OneofObject, SimpleObject, Union};
+use std::sync::Arc;
+
+use async_graphql::{Context, Enum, Error, InputObject, Object, OneofObject, SimpleObject, Union};
 use chrono::{DateTime, Utc};
 use database::DatabaseRecord;
 use mongodb::Database;
 use toolkit::types::Uuid;
 
-use crate::db;
+use crate::{db, proto::CoreServer};
+
+use super::session::terminate_account_sessions;
 
 #[derive(Default)]
 pub struct AccountRoot;
@@ -46,6 +50,14 @@ impl AccountRoot {
 
         Ok(res.map(Account::from_db))
     }
+
+    async fn account_audit_log(&self, ctx: &Context<'_>, account_id: Uuid) -> Result<Vec<AccountAuditEntry>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        Ok(db::AccountAuditEntry::list_for_account(&db, &account_id).await?
+            .into_iter()
+            .map(AccountAuditEntry::from_db)
+            .collect())
+    }
 }
 
 #[Object]
@@ -103,50 +115,68 @@ impl AccountMutationRoot {
         }
     }
 
-    async fn ban_account(&self, ctx: &Context<'_>, id: Uuid, reason: String) -> Result<Option<Account>, Error> {
+    async fn ban_account(&self, ctx: &Context<'_>, id: Uuid, reason: String, until: Option<DateTime<Utc>>, #[graphql(validator(min_length = 1))] issued_by: String) -> Result<Option<Account>, Error> {
         let db = ctx.data::<Database>()?.clone();
+
+        if until.is_some_and(|until| until <= Utc::now()) {
+            return Err(Error::new("ban expiry must be in the future"));
+        }
+
         if let Some(mut account) = db::Account::get(&db, &id).await? {
             account.banned = true;
-            account.ban_reason = Some(reason);
+            account.ban_reason = Some(reason.clone());
+            account.banned_until = until;
             account.save(&db).await?;
 
+            db::AccountAuditEntry::record(&db, id, db::AccountAction::Ban, issued_by, Some(reason), until).await?;
+
+            let socket = ctx.data::<Arc<CoreServer>>()?.clone();
+            terminate_account_sessions(&db, &socket, id).await?;
+
             Ok(Some(Account::from_db(account)))
         } else {
             Ok(None)
         }
     }
 
-    async fn unban_account(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Account>, Error> {
+    async fn unban_account(&self, ctx: &Context<'_>, id: Uuid, #[graphql(validator(min_length = 1))] issued_by: String) -> Result<Option<Account>, Error> {
         let db = ctx.data::<Database>()?.clone();
         if let Some(mut account) = db::Account::get(&db, &id).await? {
             account.banned = false;
             account.ban_reason = None;
+            account.banned_until = None;
             account.save(&db).await?;
 
+            db::AccountAuditEntry::record(&db, id, db::AccountAction::Unban, issued_by, None, None).await?;
+
             Ok(Some(Account::from_db(account)))
         } else {
             Ok(None)
         }
     }
 
-    async fn promote_account(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Account>, Error> {
+    async fn promote_account(&self, ctx: &Context<'_>, id: Uuid, #[graphql(validator(min_length = 1))] issued_by: String) -> Result<Option<Account>, Error> {
         let db = ctx.data::<Database>()?.clone();
         if let Some(mut account) = db::Account::get(&db, &id).await? {
             account.is_gm = true;
             account.save(&db).await?;
 
+            db::AccountAuditEntry::record(&db, id, db::AccountAction::Promote, issued_by, None, None).await?;
+
             Ok(Some(Account::from_db(account)))
         } else {
             Ok(None)
         }
     }
 
-    async fn demote_account(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Account>, Error> {
+    async fn demote_account(&self, ctx: &Context<'_>, id: Uuid, #[graphql(validator(min_length = 1))] issued_by: String) -> Result<Option<Account>, Error> {
         let db = ctx.data::<Database>()?.clone();
         if let Some(mut account) = db::Account::get(&db, &id).await? {
             account.is_gm = false;
             account.save(&db).await?;
 
+            db::AccountAuditEntry::record(&db, id, db::AccountAction::Demote, issued_by, None, None).await?;
+
             Ok(Some(Account::from_db(account)))
         } else {
             Ok(None)
@@ -185,6 +215,7 @@ pub struct Account {
     last_login: Option<DateTime<Utc>>,
     banned: bool,
     ban_reason: Option<String>,
+    banned_until: Option<DateTime<Utc>>,
     is_gm: bool,
 }
 
@@ -203,8 +234,9 @@ impl Account {
             },
             created: account.created,
             last_login: account.last_login,
-            banned: account.banned,
+            banned: account.is_banned(),
             ban_reason: account.ban_reason,
+            banned_until: account.banned_until,
             is_gm: account.is_gm,
         }
     }
@@ -226,3 +258,47 @@ pub enum Identifier {
     Steam(SteamIdentifier),
     Username(UsernameIdentifier),
 }
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum AccountAction {
+    Ban,
+    Unban,
+    Promote,
+    Demote,
+}
+
+impl From<db::AccountAction> for AccountAction {
+    fn from(value: db::AccountAction) -> Self {
+        match value {
+            db::AccountAction::Ban => Self::Ban,
+            db::AccountAction::Unban => Self::Unban,
+            db::AccountAction::Promote => Self::Promote,
+            db::AccountAction::Demote => Self::Demote,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct AccountAuditEntry {
+    id: Uuid,
+    account: Uuid,
+    action: AccountAction,
+    issued_by: String,
+    reason: Option<String>,
+    until: Option<DateTime<Utc>>,
+    timestamp: DateTime<Utc>,
+}
+
+impl AccountAuditEntry {
+    pub fn from_db(entry: db::AccountAuditEntry) -> Self {
+        Self {
+            id: entry.id,
+            account: entry.account,
+            action: entry.action.into(),
+            issued_by: entry.issued_by,
+            reason: entry.reason,
+            until: entry.until,
+            timestamp: entry.timestamp,
+        }
+    }
+}
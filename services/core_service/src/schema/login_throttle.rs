@@ -0,0 +1,102 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::{Context, Enum, Error, Object, SimpleObject};
+use chrono::{DateTime, Utc};
+use mongodb::Database;
+use toolkit::types::Uuid;
+
+use crate::db;
+
+#[derive(Default)]
+pub struct LoginThrottleRoot;
+
+#[derive(Default)]
+pub struct LoginThrottleMutationRoot;
+
+#[Object]
+impl LoginThrottleRoot {
+    /// Lists all accounts and addresses that are currently locked out.
+    async fn login_lockouts(&self, ctx: &Context<'_>) -> Result<Vec<LoginThrottle>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        Ok(db::LoginThrottle::list_locked(&db).await?
+            .into_iter()
+            .map(LoginThrottle::from_db)
+            .collect())
+    }
+
+    async fn account_login_throttle(&self, ctx: &Context<'_>, account_id: Uuid) -> Result<Option<LoginThrottle>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        Ok(db::LoginThrottle::get_for(&db, db::LoginThrottleKind::Account, &account_id.to_string()).await?
+            .map(LoginThrottle::from_db))
+    }
+
+    async fn address_login_throttle(&self, ctx: &Context<'_>, client_address: String) -> Result<Option<LoginThrottle>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        Ok(db::LoginThrottle::get_for(&db, db::LoginThrottleKind::Address, &client_address).await?
+            .map(LoginThrottle::from_db))
+    }
+}
+
+#[Object]
+impl LoginThrottleMutationRoot {
+    /// Resets failed attempts of an account, lifting its lockout.
+    async fn clear_account_lockout(&self, ctx: &Context<'_>, account_id: Uuid) -> Result<bool, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        Ok(db::LoginThrottle::clear(&db, db::LoginThrottleKind::Account, &account_id.to_string()).await?)
+    }
+
+    /// Resets failed attempts of a client address, lifting its lockout.
+    async fn clear_address_lockout(&self, ctx: &Context<'_>, client_address: String) -> Result<bool, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        Ok(db::LoginThrottle::clear(&db, db::LoginThrottleKind::Address, &client_address).await?)
+    }
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum LoginThrottleKind {
+    Account,
+    Address,
+}
+
+impl From<db::LoginThrottleKind> for LoginThrottleKind {
+    fn from(value: db::LoginThrottleKind) -> Self {
+        match value {
+            db::LoginThrottleKind::Account => Self::Account,
+            db::LoginThrottleKind::Address => Self::Address,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct LoginThrottle {
+    kind: LoginThrottleKind,
+    key: String,
+    failures: u32,
+    last_failure: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginThrottle {
+    pub fn from_db(throttle: db::LoginThrottle) -> Self {
+        Self {
+            kind: throttle.kind.into(),
+            key: throttle.key,
+            failures: throttle.failures,
+            last_failure: throttle.last_failure,
+            locked_until: throttle.locked_until,
+        }
+    }
+}
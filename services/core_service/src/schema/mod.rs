@@ -18,11 +18,13 @@ use async_graphql::MergedObject;
 use realm::{RealmMutationRoot, RealmRoot};
 use session::{SessionMutationRoot, SessionRoot};
 use state::{StateMutationRoot, StateRoot};
+use login_throttle::{LoginThrottleMutationRoot, LoginThrottleRoot};
 
 mod account;
 mod session;
 mod state;
 mod realm;
+mod login_throttle;
 
 #[derive(MergedObject, Default)]
 pub struct QueryRoot(
@@ -30,6 +32,7 @@ pub struct QueryRoot(
     pub SessionRoot,
     pub StateRoot,
     pub RealmRoot,
+    pub LoginThrottleRoot,
 );
 
 #[derive(MergedObject, Default)]
@@ -38,4 +41,5 @@ pub struct MutationRoot(
     pub SessionMutationRoot,
     pub StateMutationRoot,
     pub RealmMutationRoot,
+    pub LoginThrottleMutationRoot,
 );
@@ -20,7 +20,7 @@ use database::DatabaseRecord;
 use futures::TryStreamExt;
 use mongodb::Database;
 
-use crate::{db, realm_status_registry::{RealmStatus, RealmStatusRegistry}};
+use crate::{db, realm_status_registry::{ChannelStatus, RealmStatus, RealmStatusRegistry, DEFAULT_CHANNEL}};
 
 #[derive(Default)]
 pub struct RealmRoot;
@@ -85,12 +85,14 @@ impl RealmMutationRoot {
                 realm.name = name;
             }
 
+            let channel = update.channel.map(|channel| channel as u32).unwrap_or(DEFAULT_CHANNEL);
+
             if let Some(endpoint) = update.endpoint {
-                status_registry.register_endpoint(id, endpoint.parse()?).await;
+                status_registry.register_endpoint(id, channel, endpoint.parse()?).await;
             }
 
             if let Some(population) = update.population {
-                status_registry.update_population(&id, population).await;
+                status_registry.update_population(&id, channel, population).await;
             }
 
             Ok(Some(Realm::from_db(realm, status_registry.status(&id).await)))
@@ -109,33 +111,68 @@ struct RealmCreationInput {
 #[derive(InputObject)]
 struct RealmUpdateInput {
     pub name: Option<String>,
+    /// Channel the endpoint and population apply to, defaults to the first channel.
+    #[graphql(validator(minimum = 1, maximum = 255))]
+    pub channel: Option<i32>,
     pub population: Option<f32>,
     pub endpoint: Option<String>,
 }
 
+#[derive(SimpleObject, Clone, Debug)]
+struct RealmChannel {
+    pub id: i32,
+    pub population: f32,
+    pub players: i32,
+    pub max_players: Option<i32>,
+    pub endpoint: Option<String>,
+}
+
+impl RealmChannel {
+    fn from_status(id: u32, status: &ChannelStatus) -> Self {
+        Self {
+            id: id as i32,
+            population: status.population,
+            players: status.players as i32,
+            max_players: status.max_players.map(|max_players| max_players as i32),
+            endpoint: status.endpoints.first().map(|addr| addr.to_string()),
+        }
+    }
+}
+
 #[derive(SimpleObject, Clone, Debug)]
 struct Realm {
     pub id: i32,
     pub name: String,
     pub population: Option<f32>,
+    pub players: Option<i32>,
+    pub max_players: Option<i32>,
     pub endpoint: Option<String>,
+    pub channels: Vec<RealmChannel>,
 }
 
 impl Realm {
     fn from_db(realm: db::Realm, status: Option<RealmStatus>) -> Self {
-        if let Some(RealmStatus { endpoints, population }) = status {
+        if let Some(status) = status {
             Self {
                 id: realm.id,
                 name: realm.name.clone(),
-                population: Some(population),
-                endpoint: endpoints.first().map(|addr| addr.to_string()),
+                population: Some(status.population()),
+                players: Some(status.players() as i32),
+                max_players: status.max_players().map(|max_players| max_players as i32),
+                endpoint: status.endpoint().map(|addr| addr.to_string()),
+                channels: status.channels.iter()
+                    .map(|(id, channel)| RealmChannel::from_status(*id, channel))
+                    .collect(),
             }
         } else {
             Self {
                 id: realm.id,
                 name: realm.name.clone(),
                 population: None,
+                players: None,
+                max_players: None,
                 endpoint: None,
+                channels: vec![],
             }
         }
     }
@@ -56,48 +56,65 @@ impl SessionMutationRoot {
 
         match auth {
             AuthInfo::EMailAuth(auth) => {
-                if let Some(mut account) = db::Account::get_by_username_or_mail(&db, &auth.username_or_mail).await? {
-                    if let db::Credentials::Username{password, ..} = &account.credentials {
-                        if !password.check_password(auth.password) {
-                            return Ok(AuthResult {
-                                session: None,
-                                error: Some(AuthError::WrongCredentials)
-                            });
-                        }
-
-                        if account.banned {
-                            return Ok(AuthResult {
-                                session: None,
-                                error: Some(AuthError::Banned)
-                            });
-                        }
-
-                        if status.cluster_locked.unwrap_or_default() && !account.is_gm {
-                            return Ok(AuthResult {
-                                session: None,
-                                error: Some(AuthError::ServerLocked)
-                            });
-                        }
-
-                        // Force logout any existing sessions for this account, as there
-                        // can only be one active session per account.
-                        let _ = self.force_logout_account(ctx, account.id).await?;
-
-                        account.record_login();
-                        account.save(&db).await?;
-
-                        let session = db::Session::create(&db, &account).await?;
-                        Ok(AuthResult {
-                            session: Some(Session::from_db(session, account)),
-                            error: None,
-                        })
-                    } else{
-                        Ok(AuthResult {
+                let account = db::Account::get_by_username_or_mail(&db, &auth.username_or_mail).await?;
+
+                // Don't even check passwords while the account or client is locked out
+                if 
+                    throttled(&db, db::LoginThrottleKind::Address, auth.client_address.as_deref()).await? ||
+                    throttled(&db, db::LoginThrottleKind::Account, account.as_ref().map(|account| account.id.to_string()).as_deref()).await?
+                {
+                    return Ok(AuthResult {
+                        session: None,
+                        error: Some(AuthError::TooManyAttempts)
+                    });
+                }
+
+                let password_valid = match &account {
+                    Some(db::Account { credentials: db::Credentials::Username{password, ..}, .. }) => password.check_password(auth.password),
+                    _ => false,
+                };
+
+                if 
+                    password_valid &&
+                    let Some(mut account) = account
+                {
+                    db::LoginThrottle::clear(&db, db::LoginThrottleKind::Account, &account.id.to_string()).await?;
+
+                    if account.is_banned() {
+                        return Ok(AuthResult {
                             session: None,
-                            error: Some(AuthError::WrongCredentials)
-                        })
+                            error: Some(AuthError::Banned)
+                        });
                     }
+
+                    if status.cluster_locked.unwrap_or_default() && !account.is_gm {
+                        return Ok(AuthResult {
+                            session: None,
+                            error: Some(AuthError::ServerLocked)
+                        });
+                    }
+
+                    // Force logout any existing sessions for this account, as there
+                    // can only be one active session per account.
+                    let _ = self.force_logout_account(ctx, account.id).await?;
+
+                    account.record_login();
+                    account.save(&db).await?;
+
+                    let session = db::Session::create(&db, &account).await?;
+                    Ok(AuthResult {
+                        session: Some(Session::from_db(session, account)),
+                        error: None,
+                    })
                 } else {
+                    if let Some(account) = &account {
+                        db::LoginThrottle::record_failure(&db, db::LoginThrottleKind::Account, &account.id.to_string()).await?;
+                    }
+
+                    if let Some(client_address) = &auth.client_address {
+                        db::LoginThrottle::record_failure(&db, db::LoginThrottleKind::Address, client_address).await?;
+                    }
+
                     Ok(AuthResult {
                         session: None,
                         error: Some(AuthError::WrongCredentials)
@@ -106,7 +123,7 @@ impl SessionMutationRoot {
             },
             AuthInfo::SteamAuth(auth) => {
                 if let Some(mut account) = db::Account::get_by_steam_id(&db, &auth.steam_id).await? {
-                    if account.banned {
+                    if account.is_banned() {
                         return Ok(AuthResult {
                             session: None,
                             error: Some(AuthError::Banned)
@@ -182,9 +199,7 @@ impl SessionMutationRoot {
         let db = ctx.data::<Database>()?.clone();
         let socket = ctx.data::<Arc<CoreServer>>()?.clone();
         
-        if let Some(session) = db::Session::collection(&db).find_one_and_delete(doc!{"account": account_id}).await? {
-            socket.notify(CoreNotification::SessionTerminated(session.id)).await?;
-
+        if let Some(session) = terminate_account_sessions(&db, &socket, account_id).await?.into_iter().next() {
             if let Some(account) = db::Account::get(&db, &session.account).await? {
                 Ok(Some(Session::from_db(session, account)))
             } else {
@@ -196,6 +211,30 @@ impl SessionMutationRoot {
     }
 }
 
+/// Deletes all sessions of an account and kicks the player
+/// from login server and realm. Returns the terminated sessions.
+pub(super) async fn terminate_account_sessions(db: &Database, socket: &CoreServer, account_id: Uuid) -> Result<Vec<db::Session>, Error> {
+    let mut cursor = db::Session::collection(db).find(doc!{"account": account_id}).await?;
+    let mut sessions = vec![];
+
+    while let Some(session) = cursor.try_next().await? {
+        session.delete(db).await?;
+        socket.notify(CoreNotification::SessionTerminated(session.id)).await?;
+        sessions.push(session);
+    }
+
+    Ok(sessions)
+}
+
+async fn throttled(db: &Database, kind: db::LoginThrottleKind, key: Option<&str>) -> Result<bool, Error> {
+    if let Some(key) = key {
+        Ok(db::LoginThrottle::get_for(db, kind, key).await?
+            .is_some_and(|throttle| throttle.is_locked()))
+    } else {
+        Ok(false)
+    }
+}
+
 #[derive(SimpleObject)]
 pub struct Session {
     id: Uuid,
@@ -219,6 +258,8 @@ impl Session {
 pub struct EMailAuthInfo {
     pub username_or_mail: String,
     pub password: String,
+    /// Address of the client, used to throttle failed attempts.
+    pub client_address: Option<String>,
 }
 
 #[derive(InputObject)]
@@ -238,6 +279,7 @@ pub enum AuthError {
     Banned,
     ServerLocked,
     WrongCredentials,
+    TooManyAttempts,
 }
 
 #[derive(SimpleObject)]
@@ -21,7 +21,7 @@ use log::{error, warn};
 use obj_params::ParamWriter;
 use protocol::{oaCharacter, oaPktCharacterDeleteSuccess, oaPktCharacterFailure, oaPktCharacterSelectSuccess, oaPktResponseSelectWorld, CPkt, CPktStream_126_1, CPktStream_126_5, OaPktCharacterFailureErrorCode, OaPktResponseSelectWorldErrorCode, OtherlandPacket};
 use raknet::{RakNetSocket, Reliability};
-use realm_api::{ClusterAddress, NodeType, RealmApi};
+use realm_api::{proto::DEFAULT_CHANNEL, ClusterAddress, NodeType, RealmApi};
 use toolkit::anyhow;
 
 use crate::error::FrontendError;
@@ -29,6 +29,7 @@ use crate::error::FrontendError;
 pub struct FrontendSessionContext {
     core_api: CoreApi,
     realm_api: RealmApi,
+    channel: u32,
     socket: RakNetSocket,
     cluster_addr: Option<SocketAddrV4>,
 }
@@ -37,11 +38,13 @@ impl FrontendSessionContext {
     pub fn start_frontend_session(
         core_api: CoreApi, 
         realm_api: RealmApi, 
+        channel: u32,
         socket: RakNetSocket
     ) {
         let mut context = Self {
             core_api,
             realm_api,
+            channel,
             socket,
             cluster_addr: None,
         };
@@ -145,7 +148,7 @@ impl FrontendSessionContext {
             },
             CPkt::oaPktCharacterDelete(pkt) => {
                 if let Some(character) = self.realm_api.get_character_for_account(session.account().id(), pkt.character_id).await? {
-                    match character.delete().await {
+                    match character.schedule_deletion().await {
                         Ok(_) => {
                             self.socket.send(
                                 &oaPktCharacterDeleteSuccess {
@@ -174,11 +177,14 @@ impl FrontendSessionContext {
                 }
             },
             CPkt::oaPktRequestSelectWorld(_) => {
-                // Check if we can get a valid cluster node
+                // Check if we can get a valid cluster node serving our channel
                 let cluster_node = 
                 if let Some(node) = self.realm_api.get_cluster_nodes().await?
                     .into_iter()
-                    .find(|node| matches!(node.ty, NodeType::Cluster))
+                    .find(|node| 
+                        matches!(node.ty, NodeType::Cluster) &&
+                        node.channel.unwrap_or(DEFAULT_CHANNEL) == self.channel
+                    )
                 {
                     node
                 } else {
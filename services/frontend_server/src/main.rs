@@ -22,7 +22,7 @@ use frontend_session_context::FrontendSessionContext;
 use log::info;
 use once_cell::sync::Lazy;
 use raknet::RakNetListener;
-use realm_api::{proto::{NodeAddress, NodeType, RealmClient, RealmRequest}, RealmApi};
+use realm_api::{proto::{NodeAddress, NodeType, RealmClient, RealmRequest, DEFAULT_CHANNEL}, RealmApi};
 use reqwest::Url;
 use tokio::select;
 use toolkit::print_banner;
@@ -50,6 +50,11 @@ struct Cli {
 
     #[arg(long, default_value_t = false)]
     insecure: bool,
+
+    /// Realm channel served by this frontend, clients are handed
+    /// to cluster nodes of the same channel. Between 1 and 255.
+    #[arg(long, env = "CHANNEL", default_value_t = DEFAULT_CHANNEL, value_parser = clap::value_parser!(u32).range(1..=255))]
+    channel: u32,
 }
 
 static ARGS: Lazy<Cli> = Lazy::new(Cli::parse);
@@ -82,7 +87,7 @@ async fn main() -> FrontendResult<()> {
         info!("Server started...");
 
         // notify realm server we're online
-        realm_client.send(RealmRequest::RegisterNode(NodeType::Frontend, NodeAddress::Public(ARGS.public_addr))).await?;
+        realm_client.send(RealmRequest::RegisterNode(NodeType::Frontend, NodeAddress::Public(ARGS.public_addr), Some(ARGS.channel))).await?;
     
         loop {
             select! {
@@ -90,6 +95,7 @@ async fn main() -> FrontendResult<()> {
                     FrontendSessionContext::start_frontend_session(
                         core_api.clone(), 
                         realm_api.clone(), 
+                        ARGS.channel,
                         socket
                     );
                 },
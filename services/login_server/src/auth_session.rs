@@ -21,30 +21,52 @@ use steamworks::SteamId;
 use tokio::{select, sync::broadcast};
 use toolkit::types::Uuid;
 
-use crate::error::AppError;
+use crate::{error::AppError, queue_server::LoginQueue};
 
 pub struct AuthSessionContext {
     auth_api: CoreApi,
+    login_queue: LoginQueue,
     socket: RakNetSocket,
     session_id: Option<Uuid>,
 }
 
+/*
+    The client returns an index of the client-internal realm array as realm id,
+    which does not necessarily matches with the real realm id.
+    Therefore channel ids have to be unique across all realms, so we pack
+    the realm id and the realm-local channel into a single id.
+*/
+const CHANNEL_BITS: u32 = 8;
+
+fn encode_channel_id(realm_id: i32, channel: i32) -> u32 {
+    ((realm_id as u32) << CHANNEL_BITS) | (channel as u32 & ((1 << CHANNEL_BITS) - 1))
+}
+
+fn decode_channel_id(channel_id: i32) -> (i32, i32) {
+    let channel_id = channel_id as u32;
+    ((channel_id >> CHANNEL_BITS) as i32, (channel_id & ((1 << CHANNEL_BITS) - 1)) as i32)
+}
+
 fn serialize_realm(realm: &Realm) -> RealmStatus {
-    if let Some(population) = realm.population() {
+    if !realm.channels().is_empty() {
         RealmStatus {
             id: realm.id(),
             name: realm.name().to_string(),
-            channel_count: 1,
-            channel_id: vec![realm.id() as u32], // Due to a client bug, we have to identify realms based on channel ids. 
-            channel_population_count: 1,
-            channel_population: vec![population as f32],
+            channel_count: realm.channels().len() as u32,
+            channel_id: realm.channels().iter()
+                .map(|channel| encode_channel_id(realm.id(), channel.id()))
+                .collect(),
+            channel_population_count: realm.channels().len() as u32,
+            channel_population: realm.channels().iter()
+                .map(|channel| channel.population() as f32)
+                .collect(),
         }
     } else {
         RealmStatus {
             id: realm.id(),
             name: format!("[OFFLINE] {}", realm.name()),
             channel_count: 1,
-            channel_id: vec![realm.id() as u32], // Due to a client bug, we have to identify realms based on channel ids. 
+            channel_id: vec![encode_channel_id(realm.id(), 1)],
             channel_population_count: 1,
             channel_population: vec![0.0],
         }
@@ -52,9 +74,10 @@ fn serialize_realm(realm: &Realm) -> RealmStatus {
 }
 
 impl AuthSessionContext {
-    pub fn start_auth_session(auth_api: CoreApi, socket: RakNetSocket, mut realm_update: broadcast::Receiver<()>) {
+    pub fn start_auth_session(auth_api: CoreApi, login_queue: LoginQueue, socket: RakNetSocket, mut realm_update: broadcast::Receiver<()>) {
         let mut context = Self {
             auth_api,
+            login_queue,
             socket,
             session_id: None,
         };
@@ -112,13 +135,31 @@ impl AuthSessionContext {
             CPkt::CPktLogin(pkt) => {
                 let result = match pkt.login_type {
                     CpktLoginLoginType::Normal => {
-                        self.auth_api.login_username(&pkt.username, &pkt.password).await?
+                        let client_address = self.socket.peer_addr().ip().to_string();
+                        self.auth_api.login_username(&pkt.username, &pkt.password, Some(&client_address)).await?
                     },
                     CpktLoginLoginType::Steam => {
                         self.auth_api.login_steam(SteamId::from_raw(pkt.steam_id), &pkt.steam_auth_session_ticket).await?
                     }
                 };
 
+                // Players have to wait in the login queue while the realms are full,
+                // game master accounts can always log in.
+                let result = match result {
+                    Ok(session) => {
+                        if 
+                            session.account().is_gm() ||
+                            self.login_queue.may_login(self.socket.peer_addr().ip(), *session.account().id()).await
+                        {
+                            Ok(session)
+                        } else {
+                            session.destroy().await?;
+                            Err(LoginError::ServerLocked)
+                        }
+                    },
+                    Err(e) => Err(e),
+                };
+
                 match result {
                     Ok(session) => {
                         self.session_id = Some(*session.id());
@@ -127,10 +168,12 @@ impl AuthSessionContext {
                         // Now get the realm list.
                         let realms = self.auth_api.get_realms().await?;
                         
-                        // Send login result
+                        // Send login result, skipping the realm selection
+                        // if there is nothing to choose from.
                         if 
                             let Some(realm) = realms.first() && realms.len() == 1 &&
-                            let Some(endpoint) = realm.endpoint()
+                            let [channel] = realm.channels() &&
+                            let Some(endpoint) = channel.endpoint()
                         {
                             // Login result
                             self.socket.send(&CPktLoginResult {
@@ -174,6 +217,7 @@ impl AuthSessionContext {
                             LoginError::WrongCredentials => "#Login.ERROR_USERNOTFOUND#",
                             LoginError::Banned => "#Login.ERROR_BANNED#",
                             LoginError::ServerLocked => "#Login.ERROR_SERVERSLOCKED#",
+                            LoginError::TooManyAttempts => "#Login.ERROR_ACCOUNTLOCKED#",
                         };
 
                         self.socket.send(&CPktLoginResult {
@@ -189,15 +233,14 @@ impl AuthSessionContext {
                 Ok(())
             },
             CPkt::oaPktRealmSelect(pkt) => {
-                /*
-                    The client returns an index of the client-internal realm array as realm id,
-                    which does not necessarily matches with the real realm id.
-                    Therefore we use the channel_id to identify the realm.
-                    We can do this, because we currently always assume only a single channel per realm.
-                */
+                // The realm id sent by the client is unreliable, so identify
+                // both realm and channel by the channel id.
+                let (realm_id, channel_id) = decode_channel_id(pkt.channel_id);
+
                 if 
-                    let Some(realm) = self.auth_api.get_realm(pkt.channel_id).await? &&
-                    let Some(endpoint) = realm.endpoint()
+                    let Some(realm) = self.auth_api.get_realm(realm_id).await? &&
+                    let Some(channel) = realm.channel(channel_id) &&
+                    let Some(endpoint) = channel.endpoint()
                 {
                     // Login result
                     self.socket.send(&CPktLoginResult {
@@ -234,3 +277,22 @@ impl AuthSessionContext {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_channel_id, encode_channel_id};
+
+    #[test]
+    fn channel_id_round_trip() {
+        for (realm_id, channel) in [(0, 1), (1, 1), (1, 255), (42, 7), (0x7f_ffff, 255)] {
+            let channel_id = encode_channel_id(realm_id, channel);
+            assert_eq!(decode_channel_id(channel_id as i32), (realm_id, channel));
+        }
+    }
+
+    #[test]
+    fn channel_ids_are_unique_across_realms() {
+        assert_ne!(encode_channel_id(1, 2), encode_channel_id(2, 1));
+        assert_ne!(encode_channel_id(1, 255), encode_channel_id(2, 0));
+    }
+}
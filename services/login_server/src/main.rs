@@ -13,14 +13,14 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 
 use auth_session::AuthSessionContext;
 use clap::Parser;
 use core_api::{proto::{CoreClient, CoreNotification}, CoreApi};
 use error::AppResult;
 use log::info;
-use queue_server::start_queue_server;
+use queue_server::{start_queue_server, LoginQueue};
 use raknet::RakNetListener;
 use reqwest::Url;
 use tokio::sync::{broadcast, mpsc::Receiver};
@@ -47,6 +47,10 @@ pub struct LoginServerOptions {
     #[arg(long, env = "QUEUE_BIND_ADDR", default_value = "0.0.0.0:53292")]
     queue_bind_addr: SocketAddr,
 
+    /// Seconds a client that dropped out of the login queue keeps its spot.
+    #[arg(long, env = "QUEUE_TIMEOUT", default_value_t = 60)]
+    queue_timeout: u64,
+
     #[arg(long, env = "VERIFICATION_BIND_ADDR", default_value = "0.0.0.0:7998")]
     verification_bind_addr: SocketAddr,
 
@@ -66,11 +70,13 @@ async fn main() -> AppResult<()> {
 
     core_client.subscribe("core.realms.").await?;
 
-    start_queue_server(opts.queue_bind_addr).await?;
-    start_verification_server(core_api.clone(), opts.verification_bind_addr).await?;
-
     let (realm_update_sender, _) = broadcast::channel(100);
 
+    let login_queue = LoginQueue::new(Duration::from_secs(opts.queue_timeout));
+
+    start_queue_server(login_queue.clone(), core_api.clone(), realm_update_sender.subscribe(), opts.queue_bind_addr).await?;
+    start_verification_server(core_api.clone(), opts.verification_bind_addr).await?;
+
     // listen for realm updates
     fn forward_realm_updates(realm_update_sender: broadcast::Sender<()>, mut notifications: Receiver<CoreNotification>) {
         tokio::spawn(async move {
@@ -98,7 +104,7 @@ async fn main() -> AppResult<()> {
     
         loop {
             let socket = listener.accept().await.unwrap();
-            AuthSessionContext::start_auth_session(core_api.clone(), socket, realm_update_sender.subscribe());
+            AuthSessionContext::start_auth_session(core_api.clone(), login_queue.clone(), socket, realm_update_sender.subscribe());
         }
     }).await?
 }
@@ -13,21 +13,404 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use tokio::{io::AsyncWriteExt, net::{TcpListener, ToSocketAddrs}};
+use std::{collections::VecDeque, net::IpAddr, sync::Arc, time::Duration};
+
+use core_api::{CoreApi, Realm};
+use log::{debug, error, info};
+use toolkit::types::Uuid;
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::{TcpListener, ToSocketAddrs}, select, sync::{broadcast, mpsc, Mutex}, time::{self, Instant}};
 
 use crate::error::AppResult;
 
-pub async fn start_queue_server(bind_addr: impl ToSocketAddrs) -> AppResult<()> {
+// Queue protocol, server to client:
+//   0x01                         - Admitted, the client may log in. The connection is closed afterwards.
+//   0x02 <u32 position, le>      - Still waiting, at the given 1-based position.
+//
+// Only 0x01 has been observed from the original servers. The position message is
+// our own extension, clients that don't understand it keep waiting for 0x01.
+const QUEUE_ADMITTED: u8 = 0x1;
+const QUEUE_POSITION: u8 = 0x2;
+
+/// How often admissions are processed and positions are sent.
+const QUEUE_TICK: Duration = Duration::from_secs(5);
+
+/// Admitted players count against the realm capacity for this long,
+/// until they show up in the population reported by the realm.
+const ADMISSION_GRACE: Duration = Duration::from_secs(120);
+
+enum QueueUpdate {
+    Position(u32),
+    Admitted,
+}
+
+struct Ticket {
+    id: u64,
+    addr: IpAddr,
+    updates: mpsc::UnboundedSender<QueueUpdate>,
+    // Set when the client disconnected, it keeps its spot until the timeout
+    dropped: Option<Instant>,
+}
+
+struct Admission {
+    addr: IpAddr,
+    // Set once an account logged in with this admission
+    account: Option<Uuid>,
+    admitted: Instant,
+}
+
+#[derive(Default)]
+struct LoginQueueData {
+    waiting: VecDeque<Ticket>,
+    admissions: Vec<Admission>,
+    // Free slots across all online realms, `None` if there is no limit
+    free_slots: Option<usize>,
+    next_id: u64,
+}
+
+/// FIFO queue in front of the login. Clients wait in line on the queue
+/// connection until the realms have room for them. Game masters bypass
+/// the queue by account, once they authenticated with the login server.
+/// 
+/// Clients can't identify themselves on the queue connection, so admissions
+/// are handed out by address. The first account logging in from that address
+/// claims it, so players sharing an address can't use each other's admission.
+#[derive(Clone)]
+pub struct LoginQueue {
+    data: Arc<Mutex<LoginQueueData>>,
+    timeout: Duration,
+}
+
+impl LoginQueue {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            data: Arc::default(),
+            timeout,
+        }
+    }
+
+    async fn join(&self, addr: IpAddr) -> (u64, mpsc::UnboundedReceiver<QueueUpdate>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut data = self.data.lock().await;
+
+        // Clients reconnecting before their timeout get their old spot back
+        let id = if let Some(ticket) = data.waiting.iter_mut().find(|ticket| ticket.addr == addr && ticket.dropped.is_some()) {
+            ticket.updates = sender;
+            ticket.dropped = None;
+            ticket.id
+        } else {
+            let id = data.next_id;
+            data.next_id += 1;
+
+            data.waiting.push_back(Ticket {
+                id,
+                addr,
+                updates: sender,
+                dropped: None,
+            });
+
+            id
+        };
+
+        self.process(&mut data);
+
+        (id, receiver)
+    }
+
+    async fn leave(&self, id: u64) {
+        let mut data = self.data.lock().await;
+        if let Some(ticket) = data.waiting.iter_mut().find(|ticket| ticket.id == id) {
+            ticket.dropped = Some(Instant::now());
+        }
+    }
+
+    async fn tick(&self) {
+        let mut data = self.data.lock().await;
+        self.process(&mut data);
+    }
+
+    /// Recalculates the free slots from the realm list.
+    pub async fn update_realms(&self, realms: &[Realm]) {
+        let channels = realms.iter()
+            .flat_map(|realm| realm.channels())
+            .collect::<Vec<_>>();
+
+        // Without any reported limit there's nothing to queue for.
+        // Slots are counted per channel, as a full channel can't take
+        // players from another one.
+        let free_slots = if channels.is_empty() || channels.iter().any(|channel| channel.max_players().is_none()) {
+            None
+        } else {
+            Some(channels.iter()
+                .map(|channel| (channel.max_players().unwrap_or_default() - channel.players()).max(0) as usize)
+                .sum())
+        };
+
+        let mut data = self.data.lock().await;
+        data.free_slots = free_slots;
+        self.process(&mut data);
+    }
+
+    /// Returns whether an account, logging in from the given address, may log in.
+    /// That's the case if it already holds an admission, went through the queue, 
+    /// or if nobody is waiting and there is room.
+    pub async fn may_login(&self, addr: IpAddr, account: Uuid) -> bool {
+        let mut data = self.data.lock().await;
+
+        if data.admissions.iter().any(|admission| admission.account == Some(account)) {
+            return true;
+        }
+
+        if let Some(admission) = data.admissions.iter_mut().find(|admission| admission.addr == addr && admission.account.is_none()) {
+            admission.account = Some(account);
+            return true;
+        }
+
+        let available = data.free_slots.map(|free_slots| free_slots.saturating_sub(data.admissions.len()));
+        if data.waiting.is_empty() && available.is_none_or(|available| available > 0) {
+            data.admissions.push(Admission {
+                addr,
+                account: Some(account),
+                admitted: Instant::now(),
+            });
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn process(&self, data: &mut LoginQueueData) {
+        data.admissions.retain(|admission| admission.admitted.elapsed() < ADMISSION_GRACE);
+
+        let timeout = self.timeout;
+        data.waiting.retain(|ticket| ticket.dropped.is_none_or(|dropped| dropped.elapsed() < timeout));
+
+        let mut available = data.free_slots.map(|free_slots| free_slots.saturating_sub(data.admissions.len()));
+        let mut index = 0;
+
+        while index < data.waiting.len() {
+            let ticket = &data.waiting[index];
+
+            if 
+                ticket.dropped.is_none() &&
+                available.is_none_or(|available| available > 0)
+            {
+                let ticket = data.waiting.remove(index).unwrap();
+                let _ = ticket.updates.send(QueueUpdate::Admitted);
+
+                data.admissions.push(Admission {
+                    addr: ticket.addr,
+                    account: None,
+                    admitted: Instant::now(),
+                });
+
+                available = available.map(|available| available.saturating_sub(1));
+            } else {
+                index += 1;
+            }
+        }
+
+        for (position, ticket) in data.waiting.iter().enumerate() {
+            if ticket.dropped.is_none() {
+                let _ = ticket.updates.send(QueueUpdate::Position(position as u32 + 1));
+            }
+        }
+    }
+}
+
+pub async fn start_queue_server(queue: LoginQueue, core_api: CoreApi, mut realm_update: broadcast::Receiver<()>, bind_addr: impl ToSocketAddrs) -> AppResult<()> {
     let listener = TcpListener::bind(bind_addr).await?;
 
+    queue.update_realms(&core_api.get_realms().await?).await;
+
+    // Keep free slots in sync with the realm population
+    {
+        let queue = queue.clone();
+
+        tokio::spawn(async move {
+            let mut interval = time::interval(QUEUE_TICK);
+
+            loop {
+                select! {
+                    _ = interval.tick() => queue.tick().await,
+                    Ok(_) = realm_update.recv() => {
+                        match core_api.get_realms().await {
+                            Ok(realms) => queue.update_realms(&realms).await,
+                            Err(e) => error!("Failed to update realm capacity: {e:?}"),
+                        }
+                    }
+                }
+            }
+        });
+    }
+
     tokio::spawn(async move {
         loop {
-            let (mut client, _) = listener.accept().await.unwrap();
+            let (mut client, peer_addr) = listener.accept().await.unwrap();
+            let queue = queue.clone();
+
+            tokio::spawn(async move {
+                let (id, mut updates) = queue.join(peer_addr.ip()).await;
+                let mut buf = [0; 64];
 
-            let _ = client.write(&[0x1]).await;
-            let _ = client.shutdown().await;
+                loop {
+                    select! {
+                        update = updates.recv() => {
+                            match update {
+                                Some(QueueUpdate::Position(position)) => {
+                                    let mut msg = vec![QUEUE_POSITION];
+                                    msg.extend_from_slice(&position.to_le_bytes());
+
+                                    if client.write_all(&msg).await.is_err() {
+                                        break;
+                                    }
+                                },
+                                Some(QueueUpdate::Admitted) => {
+                                    debug!("Admitted {peer_addr} from login queue");
+
+                                    let _ = client.write_all(&[QUEUE_ADMITTED]).await;
+                                    let _ = client.shutdown().await;
+                                    return;
+                                },
+                                None => break,
+                            }
+                        },
+                        res = client.read(&mut buf) => {
+                            // The client doesn't send anything, so this is a disconnect
+                            if matches!(res, Ok(0) | Err(_)) {
+                                break;
+                            }
+                        }
+                    }
+                }
+
+                info!("{peer_addr} left the login queue");
+                queue.leave(id).await;
+            });
         }
     });
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use super::*;
+
+    fn addr(last: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, last))
+    }
+
+    fn account(id: u8) -> Uuid {
+        format!("00000000-0000-0000-0000-{id:012}").parse().unwrap()
+    }
+
+    fn last_update(updates: &mut mpsc::UnboundedReceiver<QueueUpdate>) -> Option<QueueUpdate> {
+        let mut last = None;
+        while let Ok(update) = updates.try_recv() {
+            last = Some(update);
+        }
+
+        last
+    }
+
+    async fn set_free_slots(queue: &LoginQueue, free_slots: Option<usize>) {
+        let mut data = queue.data.lock().await;
+        data.free_slots = free_slots;
+        queue.process(&mut data);
+    }
+
+    #[tokio::test]
+    async fn admits_in_joining_order() {
+        let queue = LoginQueue::new(Duration::from_secs(60));
+        set_free_slots(&queue, Some(0)).await;
+
+        let (_, mut first) = queue.join(addr(1)).await;
+        let (_, mut second) = queue.join(addr(2)).await;
+        let (_, mut third) = queue.join(addr(3)).await;
+
+        assert!(matches!(last_update(&mut first), Some(QueueUpdate::Position(1))));
+        assert!(matches!(last_update(&mut second), Some(QueueUpdate::Position(2))));
+        assert!(matches!(last_update(&mut third), Some(QueueUpdate::Position(3))));
+
+        set_free_slots(&queue, Some(1)).await;
+
+        assert!(matches!(last_update(&mut first), Some(QueueUpdate::Admitted)));
+        assert!(matches!(last_update(&mut second), Some(QueueUpdate::Position(1))));
+        assert!(matches!(last_update(&mut third), Some(QueueUpdate::Position(2))));
+
+        assert!(queue.may_login(addr(1), account(1)).await);
+        assert!(!queue.may_login(addr(2), account(2)).await);
+    }
+
+    #[tokio::test]
+    async fn unlimited_realms_admit_everyone() {
+        let queue = LoginQueue::new(Duration::from_secs(60));
+
+        let (_, mut updates) = queue.join(addr(1)).await;
+
+        assert!(matches!(last_update(&mut updates), Some(QueueUpdate::Admitted)));
+        assert!(queue.may_login(addr(2), account(2)).await);
+    }
+
+    #[tokio::test]
+    async fn dropped_clients_keep_their_spot_until_timeout() {
+        let queue = LoginQueue::new(Duration::from_millis(50));
+        set_free_slots(&queue, Some(0)).await;
+
+        let (id, _) = queue.join(addr(1)).await;
+        let (_, mut second) = queue.join(addr(2)).await;
+
+        // Reconnecting within the timeout returns the old ticket
+        queue.leave(id).await;
+        let (rejoined, _) = queue.join(addr(1)).await;
+        assert_eq!(id, rejoined);
+        assert!(matches!(last_update(&mut second), Some(QueueUpdate::Position(2))));
+
+        queue.leave(id).await;
+        time::sleep(Duration::from_millis(100)).await;
+        queue.tick().await;
+
+        assert!(matches!(last_update(&mut second), Some(QueueUpdate::Position(1))));
+
+        let (rejoined, _) = queue.join(addr(1)).await;
+        assert_ne!(id, rejoined);
+    }
+
+    #[tokio::test]
+    async fn dropped_clients_are_skipped_on_admission() {
+        let queue = LoginQueue::new(Duration::from_secs(60));
+        set_free_slots(&queue, Some(0)).await;
+
+        let (id, _) = queue.join(addr(1)).await;
+        let (_, mut second) = queue.join(addr(2)).await;
+        queue.leave(id).await;
+
+        set_free_slots(&queue, Some(1)).await;
+
+        assert!(matches!(last_update(&mut second), Some(QueueUpdate::Admitted)));
+        assert!(!queue.may_login(addr(1), account(1)).await);
+    }
+
+    #[tokio::test]
+    async fn admissions_are_claimed_by_account() {
+        let queue = LoginQueue::new(Duration::from_secs(60));
+        set_free_slots(&queue, Some(0)).await;
+
+        // Two players behind the same address
+        let (_, mut first) = queue.join(addr(1)).await;
+        let (_, mut second) = queue.join(addr(1)).await;
+
+        set_free_slots(&queue, Some(1)).await;
+        assert!(matches!(last_update(&mut first), Some(QueueUpdate::Admitted)));
+        assert!(matches!(last_update(&mut second), Some(QueueUpdate::Position(1))));
+
+        assert!(queue.may_login(addr(1), account(1)).await);
+        assert!(!queue.may_login(addr(1), account(2)).await);
+
+        // The admission stays with the account, e.g. when logging in again
+        assert!(queue.may_login(addr(1), account(1)).await);
+    }
+}
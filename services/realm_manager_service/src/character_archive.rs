@@ -0,0 +1,502 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet};
+
+use async_graphql::{Enum, SimpleObject};
+use chrono::{DateTime, Utc};
+use database::{DatabaseError, DatabaseRecord};
+use log::{info, warn};
+use mongodb::{bson::doc, error::{ErrorKind, InsertManyError}, Database};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use toolkit::{transaction_with_retry, types::Uuid, GetMongoError, ObjectId};
+
+use crate::db::{AbilityBar, Character, ItemStorage, ObjectTemplate, QuestState, Skillbook, StorageOwner, BUYBACK_STORAGE, MAIL_STORAGE_PREFIX};
+
+/// Bumped whenever the archive layout changes in an incompatible way.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Highest number appended to a name when resolving a collision.
+const MAX_NAME_SUFFIX: u32 = 99;
+
+/// Error code MongoDB reports for unique index violations.
+const DUPLICATE_KEY: i32 = 11000;
+
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("Character not found.")]
+    CharacterNotFound,
+
+    #[error("Unsupported archive version {0}, expected {ARCHIVE_VERSION}.")]
+    UnsupportedVersion(u32),
+
+    #[error("The name {0} is already taken.")]
+    NameTaken(String),
+
+    #[error("Invalid archive: {0}")]
+    InvalidArchive(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    DatabaseError(#[from] DatabaseError),
+
+    #[error(transparent)]
+    MongodbError(#[from] mongodb::error::Error),
+}
+
+impl GetMongoError for ArchiveError {
+    fn get_mongo_error(&self) -> Option<&mongodb::error::Error> {
+        match self {
+            Self::MongodbError(e) => Some(e),
+            Self::DatabaseError(e) => e.get_mongo_error(),
+            _ => None,
+        }
+    }
+}
+
+pub type ArchiveResult<T> = Result<T, ArchiveError>;
+
+/// How to deal with imported characters whose name is already taken in the realm.
+#[derive(Enum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameConflict {
+    /// Abort the whole import.
+    #[default]
+    Fail,
+    /// Append the lowest free number to the name.
+    Rename,
+}
+
+/// A character together with everything stored for it.
+#[derive(Serialize, Deserialize)]
+pub struct ArchivedCharacter {
+    pub character: Character,
+    pub item_storages: Vec<ItemStorage>,
+    pub quest_states: Vec<QuestState>,
+    pub skillbook: Option<Skillbook>,
+    pub ability_bar: Option<AbilityBar>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CharacterArchive {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub characters: Vec<ArchivedCharacter>,
+}
+
+impl CharacterArchive {
+    /// Parses an archive, checking the version before the content
+    /// so older tools get a meaningful error for newer archives.
+    pub fn from_json(value: serde_json::Value) -> ArchiveResult<Self> {
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+
+        let header = Header::deserialize(&value)?;
+        if header.version != ARCHIVE_VERSION {
+            return Err(ArchiveError::UnsupportedVersion(header.version));
+        }
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub fn to_json(&self) -> ArchiveResult<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ImportedCharacter {
+    pub original_id: Uuid,
+    pub id: Uuid,
+    pub name: String,
+    pub renamed: bool,
+    /// Items whose template doesn't exist in this realm.
+    pub dropped_items: i32,
+}
+
+async fn archive_character(db: &Database, character: Character) -> ArchiveResult<ArchivedCharacter> {
+    let mut item_storages = vec![];
+    let mut cursor = ItemStorage::collection(db)
+        .find(doc! { "owner.Character": character.id })
+        .await?;
+
+    while cursor.advance().await? {
+        let storage = cursor.deserialize_current()?;
+
+        // Buyback and mail attachments are bound to this realm
+        if storage.name != BUYBACK_STORAGE && !storage.name.starts_with(MAIL_STORAGE_PREFIX) {
+            item_storages.push(storage);
+        }
+    }
+
+    let mut quest_states = vec![];
+    let mut cursor = QuestState::collection(db)
+        .find(doc! { "character_id": character.id })
+        .await?;
+
+    while cursor.advance().await? {
+        quest_states.push(cursor.deserialize_current()?);
+    }
+
+    Ok(ArchivedCharacter {
+        skillbook: Skillbook::get(db, &character.id).await?,
+        ability_bar: AbilityBar::get(db, &character.id).await?,
+        character,
+        item_storages,
+        quest_states,
+    })
+}
+
+pub async fn export_character(db: &Database, id: Uuid) -> ArchiveResult<CharacterArchive> {
+    let character = Character::get(db, &id).await?
+        .ok_or(ArchiveError::CharacterNotFound)?;
+
+    Ok(CharacterArchive {
+        version: ARCHIVE_VERSION,
+        exported_at: Utc::now(),
+        characters: vec![archive_character(db, character).await?],
+    })
+}
+
+/// Exports all characters of the account, except those pending deletion.
+pub async fn export_account(db: &Database, account_id: Uuid) -> ArchiveResult<CharacterArchive> {
+    let mut characters = vec![];
+    let mut cursor = Character::collection(db)
+        .find(doc! { "account": account_id, "purge_at": null })
+        .sort(doc! { "index": 1 })
+        .await?;
+
+    while cursor.advance().await? {
+        characters.push(archive_character(db, cursor.deserialize_current()?).await?);
+    }
+
+    Ok(CharacterArchive {
+        version: ARCHIVE_VERSION,
+        exported_at: Utc::now(),
+        characters,
+    })
+}
+
+async fn name_taken(db: &Database, name: &str) -> ArchiveResult<bool> {
+    // Names of characters pending deletion are still reserved.
+    Ok(Character::find_by_name(db, name, true).await?.is_some())
+}
+
+/// Finds a free name for an imported character. `reserved` holds the
+/// lowercase names already picked for other characters of the same import.
+async fn resolve_name(
+    name: &str, 
+    conflict: NameConflict, 
+    reserved: &HashSet<String>, 
+    is_taken: impl AsyncFn(&str) -> ArchiveResult<bool>
+) -> ArchiveResult<String> {
+    let is_free = async |candidate: &str| -> ArchiveResult<bool> {
+        Ok(!reserved.contains(&candidate.to_lowercase()) && !is_taken(candidate).await?)
+    };
+
+    if is_free(name).await? {
+        return Ok(name.to_string());
+    }
+
+    if conflict == NameConflict::Rename {
+        for suffix in 2..=MAX_NAME_SUFFIX {
+            let candidate = format!("{name}{suffix}");
+            if is_free(&candidate).await? {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    Err(ArchiveError::NameTaken(name.to_string()))
+}
+
+/// Returns the first character whose name has been taken since it was
+/// resolved, e.g. by a character created while the import was running.
+async fn find_name_collision<'a>(db: &Database, characters: impl IntoIterator<Item = &'a Character>) -> ArchiveResult<Option<String>> {
+    for character in characters {
+        if name_taken(db, &character.name).await? {
+            return Ok(Some(character.name.clone()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the positions of inserted characters rejected by a unique index.
+/// The driver doesn't report which index was violated, so callers have to
+/// check the name of those characters themselves.
+fn duplicate_keys(err: &mongodb::error::Error) -> Vec<usize> {
+    if let ErrorKind::InsertMany(InsertManyError { write_errors: Some(errors), .. }) = err.kind.as_ref() {
+        errors.iter()
+            .filter(|e| e.code == DUPLICATE_KEY)
+            .map(|e| e.index)
+            .collect()
+    } else {
+        vec![]
+    }
+}
+
+/// Imports all characters of the archive into the account. Every record gets
+/// a fresh id and characters are appended to the account's character list.
+/// Either all characters are imported or none.
+pub async fn import_archive(db: &Database, archive: CharacterArchive, account_id: Uuid, conflict: NameConflict) -> ArchiveResult<Vec<ImportedCharacter>> {
+    let mut cursor = Character::collection(db)
+        .find(doc! { "account": account_id })
+        .sort(doc! { "index": -1 })
+        .limit(1)
+        .await?;
+
+    let mut next_index = if cursor.advance().await? {
+        cursor.deserialize_current()?.index + 1
+    } else {
+        1
+    };
+
+    let mut reserved = HashSet::new();
+    let mut imported = vec![];
+    let mut characters = vec![];
+    let mut item_storages = vec![];
+    let mut quest_states = vec![];
+    let mut skillbooks = vec![];
+    let mut ability_bars = vec![];
+
+    for archived in archive.characters {
+        let mut character = archived.character;
+        let original_id = character.id;
+        let name = resolve_name(&character.name, conflict, &reserved, async |candidate: &str| name_taken(db, candidate).await).await?;
+        reserved.insert(name.to_lowercase());
+
+        let renamed = name != character.name;
+        character.id = Uuid::new();
+        character.account = account_id;
+        character.index = next_index;
+        character.name = name;
+        character.purge_at = None;
+        next_index += 1;
+
+        // Items can only be carried over if the realm knows their templates
+        let mut dropped_items = 0;
+        for mut storage in archived.item_storages {
+            let mut items = Vec::with_capacity(storage.items.len());
+
+            for mut item in storage.items {
+                if ObjectTemplate::get(db, &item.template_id).await?.is_some() {
+                    item.id = Uuid::new();
+                    items.push(item);
+                } else {
+                    warn!("Dropping item {} of character {}: unknown template {}", item.id, original_id, item.template_id);
+                    dropped_items += 1;
+                }
+            }
+
+            storage.id = Uuid::new();
+            storage.owner = StorageOwner::Character(character.id);
+            storage.items = items;
+            item_storages.push(storage);
+        }
+
+        for mut state in archived.quest_states {
+            state.id = ObjectId::new();
+            state.character_id = character.id;
+            quest_states.push(state);
+        }
+
+        // Ability bar slots refer to skillbook entries, so they have to follow the new entry ids
+        let mut entry_ids = HashMap::new();
+        if let Some(mut skillbook) = archived.skillbook {
+            skillbook.character_id = character.id;
+
+            for entry in skillbook.skills.iter_mut() {
+                let id = Uuid::new();
+                entry_ids.insert(entry.id.to_string(), id.to_string());
+                entry.id = id;
+            }
+
+            skillbooks.push(skillbook);
+        }
+
+        if let Some(mut ability_bar) = archived.ability_bar {
+            ability_bar.character_id = character.id;
+
+            for slot in std::iter::once(&mut ability_bar.single_slot).chain(ability_bar.slots.iter_mut()) {
+                if let Some(id) = entry_ids.get(&slot.ability) {
+                    slot.ability = id.clone();
+                }
+            }
+
+            ability_bars.push(ability_bar);
+        }
+
+        imported.push(ImportedCharacter {
+            original_id,
+            id: character.id,
+            name: character.name.clone(),
+            renamed,
+            dropped_items,
+        });
+        characters.push(character);
+    }
+
+    if characters.is_empty() {
+        return Ok(imported);
+    }
+
+    if let Some(name) = find_name_collision(db, &characters).await? {
+        return Err(ArchiveError::NameTaken(name));
+    }
+
+    transaction_with_retry(db.clone(), async |mut session| -> ArchiveResult<_> {
+        // A name can still be claimed between the check and the insert, 
+        // in which case the unique name index rejects the character.
+        if let Err(e) = Character::collection(db)
+            .insert_many(&characters)
+            .session(&mut session)
+            .await
+        {
+            let rejected = duplicate_keys(&e).into_iter()
+                .filter_map(|idx| characters.get(idx));
+
+            if let Some(name) = find_name_collision(db, rejected).await? {
+                return Err(ArchiveError::NameTaken(name));
+            }
+
+            return Err(e.into());
+        }
+
+        if !item_storages.is_empty() {
+            ItemStorage::collection(db)
+                .insert_many(&item_storages)
+                .session(&mut session)
+                .await?;
+        }
+
+        if !quest_states.is_empty() {
+            QuestState::collection(db)
+                .insert_many(&quest_states)
+                .session(&mut session)
+                .await?;
+        }
+
+        if !skillbooks.is_empty() {
+            Skillbook::collection(db)
+                .insert_many(&skillbooks)
+                .session(&mut session)
+                .await?;
+        }
+
+        if !ability_bars.is_empty() {
+            AbilityBar::collection(db)
+                .insert_many(&ability_bars)
+                .session(&mut session)
+                .await?;
+        }
+
+        Ok((session, ()))
+    }).await?;
+
+    for character in &imported {
+        info!("Imported character {} as {} ({})", character.original_id, character.name, character.id);
+    }
+
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn resolve(name: &str, conflict: NameConflict, reserved: &[&str], taken: &[&str]) -> ArchiveResult<String> {
+        let reserved = reserved.iter().map(|name| name.to_lowercase()).collect();
+        resolve_name(name, conflict, &reserved, async |candidate: &str| -> ArchiveResult<bool> {
+            Ok(taken.iter().any(|name| *name == candidate))
+        }).await
+    }
+
+    #[test]
+    fn from_json_accepts_current_version() {
+        let archive = CharacterArchive {
+            version: ARCHIVE_VERSION,
+            exported_at: Utc::now(),
+            characters: vec![],
+        };
+
+        let parsed = CharacterArchive::from_json(archive.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.version, ARCHIVE_VERSION);
+        assert_eq!(parsed.exported_at, archive.exported_at);
+        assert!(parsed.characters.is_empty());
+    }
+
+    #[test]
+    fn from_json_rejects_other_versions() {
+        // The version is checked before the content, even if the rest doesn't parse
+        let res = CharacterArchive::from_json(serde_json::json!({ "version": ARCHIVE_VERSION + 1 }));
+        assert!(matches!(res, Err(ArchiveError::UnsupportedVersion(v)) if v == ARCHIVE_VERSION + 1));
+    }
+
+    #[test]
+    fn from_json_rejects_malformed_archives() {
+        assert!(matches!(
+            CharacterArchive::from_json(serde_json::json!({ "characters": [] })),
+            Err(ArchiveError::InvalidArchive(_))
+        ));
+
+        assert!(matches!(
+            CharacterArchive::from_json(serde_json::json!({ "version": ARCHIVE_VERSION, "characters": [] })),
+            Err(ArchiveError::InvalidArchive(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn resolve_name_keeps_free_names() {
+        assert_eq!(resolve("Alice", NameConflict::Fail, &[], &["Bob"]).await.unwrap(), "Alice");
+    }
+
+    #[tokio::test]
+    async fn resolve_name_fails_on_conflict() {
+        assert!(matches!(
+            resolve("Alice", NameConflict::Fail, &[], &["Alice"]).await,
+            Err(ArchiveError::NameTaken(name)) if name == "Alice"
+        ));
+    }
+
+    #[tokio::test]
+    async fn resolve_name_appends_lowest_free_suffix() {
+        assert_eq!(resolve("Alice", NameConflict::Rename, &[], &["Alice"]).await.unwrap(), "Alice2");
+        assert_eq!(resolve("Alice", NameConflict::Rename, &[], &["Alice", "Alice2"]).await.unwrap(), "Alice3");
+    }
+
+    #[tokio::test]
+    async fn resolve_name_respects_reserved_names() {
+        assert_eq!(resolve("Alice", NameConflict::Rename, &["ALICE"], &[]).await.unwrap(), "Alice2");
+        assert!(matches!(
+            resolve("Alice", NameConflict::Fail, &["alice"], &[]).await,
+            Err(ArchiveError::NameTaken(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn resolve_name_gives_up_after_max_suffix() {
+        let taken: Vec<String> = std::iter::once("Alice".to_string())
+            .chain((2..=MAX_NAME_SUFFIX).map(|suffix| format!("Alice{suffix}")))
+            .collect();
+        let taken: Vec<&str> = taken.iter().map(String::as_str).collect();
+
+        assert!(matches!(
+            resolve("Alice", NameConflict::Rename, &[], &taken).await,
+            Err(ArchiveError::NameTaken(_))
+        ));
+    }
+}
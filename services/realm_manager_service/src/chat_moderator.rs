@@ -0,0 +1,340 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::{HashMap, HashSet, VecDeque}, fs, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use chrono::{DateTime, Utc};
+use database::{DatabaseError, DatabaseRecord};
+use log::{error, info, warn};
+use mongodb::Database;
+use thiserror::Error;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use toolkit::{types::Uuid, ObjectId};
+
+use crate::{db::{Character, ChatChannel, ChatLogEntry, ChatMute, ChatVerdict}, player_requests::impl_request_error, proto::Destination, CHAT_ROUTER, SESSION_MANAGER};
+
+/// Number of messages a session may send within `FLOOD_WINDOW`.
+const FLOOD_MESSAGES: usize = 5;
+const FLOOD_WINDOW: Duration = Duration::from_secs(5);
+
+#[derive(Error, Debug)]
+pub enum ModerationError {
+    #[error("session not found")]
+    SessionNotFound,
+
+    #[error("Character not found.")]
+    CharacterNotFound,
+
+    #[error("You are muted for another {0} minute(s).")]
+    Muted(u64),
+
+    #[error("You are sending messages too quickly.")]
+    Flooding,
+
+    #[error(transparent)]
+    DatabaseError(#[from] DatabaseError),
+
+    #[error(transparent)]
+    MongodbError(#[from] mongodb::error::Error),
+}
+
+impl_request_error!(ModerationError);
+
+/// Replaces blocked words with asterisks. Only whole words are matched,
+/// so harmless words containing a blocked one pass unchanged.
+#[derive(Default)]
+pub struct WordFilter {
+    words: HashSet<String>,
+}
+
+impl WordFilter {
+    /// Reads one word per line. Empty lines and lines starting with `#` are skipped.
+    pub fn load(path: &str) -> Self {
+        let path = match content::get_content_path(path) {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("Chat filter unavailable: {e:?}");
+                return Self::default();
+            }
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(words) => {
+                let filter = Self {
+                    words: words.lines()
+                        .map(str::trim)
+                        .filter(|word| !word.is_empty() && !word.starts_with('#'))
+                        .map(str::to_lowercase)
+                        .collect(),
+                };
+
+                info!("Loaded {} chat filter words", filter.words.len());
+                filter
+            },
+            Err(e) => {
+                warn!("Failed to read chat filter {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Returns the censored message, or `None` if nothing was filtered.
+    pub fn apply(&self, message: &str) -> Option<String> {
+        if self.words.is_empty() {
+            return None;
+        }
+
+        let mut filtered = String::with_capacity(message.len());
+        let mut word = String::new();
+        let mut matched = false;
+
+        let mut flush = |word: &mut String, filtered: &mut String| {
+            if self.words.contains(&word.to_lowercase()) {
+                filtered.extend(word.chars().map(|_| '*'));
+                matched = true;
+            } else {
+                filtered.push_str(word);
+            }
+
+            word.clear();
+        };
+
+        for c in message.chars() {
+            if c.is_alphanumeric() {
+                word.push(c);
+            } else {
+                flush(&mut word, &mut filtered);
+                filtered.push(c);
+            }
+        }
+
+        flush(&mut word, &mut filtered);
+
+        if matched {
+            Some(filtered)
+        } else {
+            None
+        }
+    }
+}
+
+struct ModeratedSession {
+    account_id: Uuid,
+    character_id: Uuid,
+    character_name: String,
+    muted_until: Option<DateTime<Utc>>,
+    // Send times of the most recent messages
+    history: VecDeque<Instant>,
+}
+
+#[derive(Default)]
+struct ChatModeratorData {
+    sessions: HashMap<Uuid, ModeratedSession>,
+}
+
+/// Checks every player sent chat message before it is delivered.
+/// Muted accounts and flooding sessions are rejected, blocked words
+/// are censored and every message is written to the chat log.
+/// Account and mute state is cached per session, so moderating a
+/// message never waits for the database.
+#[derive(Clone)]
+pub struct ChatModerator {
+    db: Database,
+    filter: Arc<WordFilter>,
+    data: Arc<Mutex<ChatModeratorData>>,
+    log: UnboundedSender<ChatLogEntry>,
+}
+
+impl ChatModerator {
+    pub fn new(db: Database, filter: WordFilter) -> Self {
+        let (log, mut entries) = mpsc::unbounded_channel();
+
+        // Chat log entries are written in the background
+        {
+            let db = db.clone();
+
+            tokio::spawn(async move {
+                while let Some(entry) = entries.recv().await {
+                    if let Err(e) = ChatLogEntry::create(&db, entry).await {
+                        error!("Failed to write chat log: {e:?}");
+                    }
+                }
+            });
+        }
+
+        Self {
+            db,
+            filter: Arc::new(filter),
+            data: Arc::default(),
+            log,
+        }
+    }
+
+    /// Starts moderating a session. The accounts mutes are loaded in the
+    /// background and apply as soon as they are known.
+    pub fn connect_session(&self, session_id: Uuid, character: &Character) {
+        let account_id = character.account;
+
+        self.data.lock().unwrap()
+            .sessions.insert(session_id, ModeratedSession {
+                account_id,
+                character_id: character.id,
+                character_name: character.name.clone(),
+                muted_until: None,
+                history: VecDeque::new(),
+            });
+
+        let moderator = self.clone();
+
+        tokio::spawn(async move {
+            let expires = match ChatMute::active_for_account(&moderator.db, &account_id).await {
+                Ok(Some(mute)) => mute.expires,
+                Ok(None) => return,
+                Err(e) => {
+                    error!("Failed to load mutes of account {account_id}: {e:?}");
+                    return;
+                }
+            };
+
+            // The session might have been disconnected or muted in the meantime
+            if let Some(session) = moderator.data.lock().unwrap().sessions.get_mut(&session_id) {
+                session.muted_until = Some(session.muted_until.map_or(expires, |until| until.max(expires)));
+            }
+        });
+    }
+
+    pub fn disconnect_session(&self, session_id: Uuid) {
+        self.data.lock().unwrap().sessions.remove(&session_id);
+    }
+
+    /// Returns the message as it should be delivered.
+    pub fn moderate(&self, session_id: Uuid, zone_id: Option<Uuid>, channel: ChatChannel, target: Option<String>, message: String) -> Result<String, ModerationError> {
+        let mut data = self.data.lock().unwrap();
+        let session = data.sessions.get_mut(&session_id)
+            .ok_or(ModerationError::SessionNotFound)?;
+
+        let now = Utc::now();
+
+        let res = if let Some(muted_until) = session.muted_until.filter(|expires| *expires > now) {
+            let remaining = (muted_until - now).num_seconds().max(0) as u64;
+            Err(ModerationError::Muted(remaining.div_ceil(60)))
+        } else if is_flooding(&mut session.history) {
+            Err(ModerationError::Flooding)
+        } else {
+            Ok(self.filter.apply(&message))
+        };
+
+        let verdict = match &res {
+            Ok(Some(_)) => ChatVerdict::Filtered,
+            Ok(None) => ChatVerdict::Delivered,
+            Err(ModerationError::Muted(_)) => ChatVerdict::Muted,
+            Err(_) => ChatVerdict::Flooding,
+        };
+
+        // Always log the original message, reports are about what was typed
+        let _ = self.log.send(ChatLogEntry {
+            id: ObjectId::default(),
+            account_id: session.account_id,
+            character_id: session.character_id,
+            character_name: session.character_name.clone(),
+            channel,
+            target,
+            zone_id,
+            message: message.clone(),
+            verdict,
+            timestamp: now,
+        });
+
+        res.map(|filtered| filtered.unwrap_or(message))
+    }
+
+    /// Mutes the account owning the named character.
+    pub async fn mute(&self, character_name: &str, issued_by: Uuid, minutes: u32, reason: String) -> Result<String, ModerationError> {
+        let character = self.find_character(character_name).await?;
+        let now = Utc::now();
+        let expires = now + chrono::Duration::minutes(minutes as i64);
+
+        ChatMute::create(&self.db, ChatMute {
+            id: ObjectId::default(),
+            account_id: character.account,
+            character_id: character.id,
+            issued_by,
+            reason: reason.clone(),
+            created: now,
+            expires,
+        }).await?;
+
+        self.update_account_sessions(character.account, |muted_until| {
+            *muted_until = Some(muted_until.map_or(expires, |until| until.max(expires)));
+        });
+
+        self.notify_character(character.id, if reason.is_empty() {
+            format!("You have been muted for {minutes} minute(s).")
+        } else {
+            format!("You have been muted for {minutes} minute(s): {reason}")
+        }).await;
+
+        Ok(format!("{} has been muted for {minutes} minute(s).", character.name))
+    }
+
+    /// Lifts all active mutes of the account owning the named character.
+    pub async fn unmute(&self, character_name: &str) -> Result<String, ModerationError> {
+        let character = self.find_character(character_name).await?;
+
+        if ChatMute::lift_for_account(&self.db, &character.account).await? > 0 {
+            self.update_account_sessions(character.account, |muted_until| *muted_until = None);
+            self.notify_character(character.id, "You are no longer muted.".to_string()).await;
+            Ok(format!("{} is no longer muted.", character.name))
+        } else {
+            Ok(format!("{} is not muted.", character.name))
+        }
+    }
+
+    fn update_account_sessions(&self, account_id: Uuid, update: impl Fn(&mut Option<DateTime<Utc>>)) {
+        self.data.lock().unwrap()
+            .sessions.values_mut()
+            .filter(|session| session.account_id == account_id)
+            .for_each(|session| update(&mut session.muted_until));
+    }
+
+    async fn notify_character(&self, character_id: Uuid, message: String) {
+        if let Some(state) = SESSION_MANAGER.get().unwrap().get_state_for_character(character_id).await {
+            CHAT_ROUTER.get().unwrap()
+                .system_message(vec![state.id], Destination::Whisper(String::default()), message).await;
+        }
+    }
+
+    async fn find_character(&self, name: &str) -> Result<Character, ModerationError> {
+        Character::find_by_name(&self.db, name, false).await?
+            .ok_or(ModerationError::CharacterNotFound)
+    }
+}
+
+fn is_flooding(history: &mut VecDeque<Instant>) -> bool {
+    let now = Instant::now();
+
+    while history.front().is_some_and(|sent| now.duration_since(*sent) > FLOOD_WINDOW) {
+        history.pop_front();
+    }
+
+    // Rejected messages don't count, so players can't lock
+    // themselves out for longer than the window.
+    if history.len() >= FLOOD_MESSAGES {
+        true
+    } else {
+        history.push_back(now);
+        false
+    }
+}
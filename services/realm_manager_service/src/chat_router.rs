@@ -16,13 +16,13 @@
 use std::{collections::HashMap, sync::Arc};
 
 use database::DatabaseRecord;
-use log::debug;
+use log::{debug, error};
 use mongodb::Database;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::{self, Sender};
 use toolkit::types::Uuid;
 
-use crate::{db::Character, proto::{NodeType, RealmResponse, RealmServer}, NODE_REGISTRY, SESSION_MANAGER};
+use crate::{db::{Character, ChatChannel, Clan}, player_requests::RequestError, proto::{NodeType, RealmResponse, RealmServer}, CHAT_MODERATOR, CLAN_MANAGER, NODE_REGISTRY, PARTY_REGISTRY, SESSION_MANAGER, SOCIAL_MANAGER};
 
 #[derive(Clone)]
 pub struct ChatRouter(Sender<Message>);
@@ -44,6 +44,11 @@ enum Message {
         destination: Destination,
         message: String,
     },
+    System {
+        sessions: Vec<Uuid>,
+        destination: Destination,
+        message: String,
+    },
 }
 
 impl ChatRouter {
@@ -61,6 +66,8 @@ impl ChatRouter {
                             let Some(state) = SESSION_MANAGER.get().unwrap().get_state(id).await &&
                             let Ok(Some(character)) = Character::get(&db, &state.character_id).await
                         {
+                            CHAT_MODERATOR.get().unwrap().connect_session(state.id, &character);
+
                             name_lookup.insert(character.name.clone(), state.clone());
                             sessions.insert(state.id, (character.name, state));
                         }
@@ -69,21 +76,49 @@ impl ChatRouter {
                         if let Some((name, _)) = sessions.remove(&id) {
                             name_lookup.remove(&name);
                         }
+
+                        CHAT_MODERATOR.get().unwrap().disconnect_session(id);
                     },
                     Some(Message::Forward { session_id, destination, message }) => {
-                        let (sender_id, sender_name) = if let Some(id) = session_id {
+                        let (sender_id, sender_character, sender_name, sender_zone) = if let Some(id) = session_id {
                             // Prepend character name if sent from a valid session
                             if 
                                 let Some(state) = SESSION_MANAGER.get().unwrap().get_state(id).await &&
-                                let Ok(Some(character)) = Character::get(&db, &state.character_id).await
+                                let Some((name, _)) = sessions.get(&id)
                             {
-                                (Some(state.avatar_id), character.name.clone())
+                                (Some(state.avatar_id), Some(state.character_id), name.clone(), state.zone)
                             } else {
                                 // Session or character not found, drop message
-                                return;
+                                continue;
+                            }
+                        } else {
+                            (None, None, "System".to_string(), None)
+                        };
+
+                        // Player messages have to pass moderation before being routed
+                        let message = if let Some(id) = session_id {
+                            let (channel, target) = moderation_channel(&destination);
+
+                            match CHAT_MODERATOR.get().unwrap().moderate(id, sender_zone, channel, target, message) {
+                                Ok(message) => message,
+                                Err(e) => {
+                                    if e.is_client_error() {
+                                        deliver(&server, vec![id], RealmResponse::ChatMessage {
+                                            recipients: vec![],
+                                            sender_id: None,
+                                            sender_name: "System".to_string(),
+                                            destination: Destination::Whisper(String::default()),
+                                            message: e.to_string(),
+                                        }).await;
+                                    } else {
+                                        error!("Chat moderation failed: {e:?}");
+                                    }
+
+                                    continue;
+                                }
                             }
                         } else {
-                            (None, "System".to_string())
+                            message
                         };
                 
                         let msg = match &destination {
@@ -113,6 +148,15 @@ impl ChatRouter {
                                 if 
                                     let Some(state) = name_lookup.get(character_name)
                                 {
+                                    // Silently drop whispers from ignored characters
+                                    if 
+                                        let Some(sender_character) = sender_character &&
+                                        SOCIAL_MANAGER.get().unwrap().is_ignoring(state.character_id, sender_character).await
+                                    {
+                                        debug!("Dropping whisper to {character_name}, sender is ignored");
+                                        continue;
+                                    }
+
                                     sessions.push(state.id);
 
                                     Some((sessions, RealmResponse::ChatMessage {
@@ -126,54 +170,65 @@ impl ChatRouter {
                                     None
                                 }
                             },
-                            Destination::Clan(_) => { 
-                                debug!("Clan chat not implemented!"); 
-                                None
-                            },
-                            Destination::ClanOfficer(_) => { 
-                                debug!("Clan officer chat not implemented!"); 
-                                None
-                            },
-                            Destination::Party(_) => { 
-                                debug!("Party chat not implemented!"); 
-                                None
-                            },
-                        };
-
-                        if let Some((mut sessions, msg)) = msg {
-                            sessions.dedup();
+                            Destination::Clan(clan_id) | Destination::ClanOfficer(clan_id) => {
+                                let officers_only = matches!(destination, Destination::ClanOfficer(_));
 
-                            let mut messages = HashMap::new();
-
-                            // Group messages before sending to cluster nodes
-                            for session in sessions {
+                                // Only members are allowed to talk to their clan,
+                                // and only officers to other officers.
                                 if 
-                                    let Some(state) = SESSION_MANAGER.get().unwrap().get_state(session).await &&
-                                    let Some(cluster_node) = state.cluster_node
+                                    let Ok(Some(clan)) = Clan::get(&db, clan_id).await &&
+                                    sender_character.is_none_or(|id| {
+                                        clan.member(id)
+                                            .is_some_and(|member| !officers_only || member.rank.is_officer())
+                                    })
                                 {
-                                    if let Some(RealmResponse::ChatMessage { recipients, .. }) = messages.get_mut(&cluster_node) {
-                                        recipients.push(state.id);
-                                    } else {
-                                        let mut msg = msg.clone();
-                                        if let RealmResponse::ChatMessage { recipients, .. } = &mut msg {
-                                            recipients.push(session);
-                                        } else {
-                                            unreachable!()
-                                        }
-
-                                        messages.insert(cluster_node, msg);
-                                    }
+                                    Some((CLAN_MANAGER.get().unwrap().member_sessions(&clan, officers_only).await, RealmResponse::ChatMessage {
+                                        recipients: vec![],
+                                        sender_id,
+                                        sender_name,
+                                        destination,
+                                        message
+                                    }))
+                                } else {
+                                    debug!("Dropping message for clan {clan_id}");
+                                    None
                                 }
-                            }
+                            },
+                            Destination::Party(party_id) => {
+                                let party = PARTY_REGISTRY.get().unwrap().party(*party_id).await;
 
-                            // Forward chat messages to cluster nodes
-                            for (node, msg) in messages {
-                                if let Some((peer, _)) = NODE_REGISTRY.get().unwrap().node(node).await {
-                                    let _ = server.send(&peer, msg).await;
+                                // Only members are allowed to talk to their party
+                                if 
+                                    let Some(party) = party &&
+                                    sender_character.is_none_or(|id| party.members.contains(&id))
+                                {
+                                    Some((PARTY_REGISTRY.get().unwrap().member_sessions(party.id).await, RealmResponse::ChatMessage {
+                                        recipients: vec![],
+                                        sender_id,
+                                        sender_name,
+                                        destination,
+                                        message
+                                    }))
+                                } else {
+                                    debug!("Dropping message for unknown party {party_id}");
+                                    None
                                 }
-                            }
+                            },
+                        };
+
+                        if let Some((sessions, msg)) = msg {
+                            deliver(&server, sessions, msg).await;
                         }
                     },
+                    Some(Message::System { sessions, destination, message }) => {
+                        deliver(&server, sessions, RealmResponse::ChatMessage {
+                            recipients: vec![],
+                            sender_id: None,
+                            sender_name: "System".to_string(),
+                            destination,
+                            message
+                        }).await;
+                    },
                     None => break,
                 }
             }
@@ -194,5 +249,56 @@ impl ChatRouter {
         let _ = self.0.send(Message::Forward { session_id, destination, message }).await;
     }
 
+    /// Sends a system message to the given sessions.
+    pub async fn system_message(&self, sessions: Vec<Uuid>, destination: Destination, message: String) {
+        let _ = self.0.send(Message::System { sessions, destination, message }).await;
+    }
+
     
-}
\ No newline at end of file
+}
+
+fn moderation_channel(destination: &Destination) -> (ChatChannel, Option<String>) {
+    match destination {
+        Destination::Broadcast => (ChatChannel::Broadcast, None),
+        Destination::Whisper(character_name) => (ChatChannel::Whisper, Some(character_name.clone())),
+        Destination::Clan(clan_id) => (ChatChannel::Clan, Some(clan_id.to_string())),
+        Destination::ClanOfficer(clan_id) => (ChatChannel::ClanOfficer, Some(clan_id.to_string())),
+        Destination::Party(party_id) => (ChatChannel::Party, Some(party_id.to_string())),
+    }
+}
+
+/// Groups the message by the cluster node each session is connected to
+/// and forwards it to those nodes.
+async fn deliver(server: &RealmServer, mut sessions: Vec<Uuid>, msg: RealmResponse) {
+    sessions.dedup();
+
+    let mut messages = HashMap::new();
+
+    // Group messages before sending to cluster nodes
+    for session in sessions {
+        if 
+            let Some(state) = SESSION_MANAGER.get().unwrap().get_state(session).await &&
+            let Some(cluster_node) = state.cluster_node
+        {
+            if let Some(RealmResponse::ChatMessage { recipients, .. }) = messages.get_mut(&cluster_node) {
+                recipients.push(state.id);
+            } else {
+                let mut msg = msg.clone();
+                if let RealmResponse::ChatMessage { recipients, .. } = &mut msg {
+                    recipients.push(session);
+                } else {
+                    unreachable!()
+                }
+
+                messages.insert(cluster_node, msg);
+            }
+        }
+    }
+
+    // Forward chat messages to cluster nodes
+    for (node, msg) in messages {
+        if let Some((peer, _)) = NODE_REGISTRY.get().unwrap().node(node).await {
+            let _ = server.send(&peer, msg).await;
+        }
+    }
+}
@@ -0,0 +1,673 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use database::{DatabaseError, DatabaseRecord};
+use mongodb::Database;
+use thiserror::Error;
+use tokio::{sync::Mutex, time::Instant};
+use toolkit::types::{Uuid, UUID_NIL};
+
+use crate::{db::{Character, Clan, ClanMember, ClanRank}, player_requests::{impl_request_error, report_error, MessageOutbox}, proto::{ClanMembership, Destination, RealmNotification, RealmServer}, SESSION_MANAGER};
+
+const INVITE_TIMEOUT: Duration = Duration::from_secs(60);
+const CLAN_NAME_LENGTH: std::ops::RangeInclusive<usize> = 3..=32;
+
+#[derive(Error, Debug)]
+pub enum ClanError {
+    #[error("session not found")]
+    SessionNotFound,
+
+    #[error("Clan not found.")]
+    ClanNotFound,
+
+    #[error("Character not found.")]
+    CharacterNotFound,
+
+    #[error("{0} is not online.")]
+    NotOnline(String),
+
+    #[error("A clan named {0} already exists.")]
+    NameTaken(String),
+
+    #[error("Clan names must be between 3 and 32 letters, digits or spaces.")]
+    InvalidName,
+
+    #[error("{0} is already in a clan.")]
+    AlreadyInClan(String),
+
+    #[error("You are not in a clan.")]
+    NotInClan,
+
+    #[error("{0} is not a member of your clan.")]
+    NotAMember(String),
+
+    #[error("You don't have permission to do that.")]
+    InsufficientRank,
+
+    #[error("The clan leader can't leave the clan. Promote another member or disband the clan.")]
+    LeaderCannotLeave,
+
+    #[error("You have no pending clan invite.")]
+    NoInvite,
+
+    #[error("You can't target yourself.")]
+    SelfTarget,
+
+    #[error(transparent)]
+    DatabaseError(#[from] DatabaseError),
+
+    #[error(transparent)]
+    MongodbError(#[from] mongodb::error::Error),
+}
+
+impl_request_error!(ClanError);
+
+struct ClanInvite {
+    clan_id: Uuid,
+    inviter: Uuid,
+    valid_until: Instant,
+}
+
+#[derive(Default)]
+struct Outbox {
+    updates: Vec<(Uuid, Option<ClanMembership>)>,
+    messages: MessageOutbox,
+}
+
+impl Outbox {
+    fn joined(&mut self, clan: &Clan, member: &ClanMember) {
+        self.updates.push((member.character_id, Some(ClanMembership {
+            id: clan.id,
+            name: clan.name.clone(),
+            rank: member.rank,
+        })));
+    }
+
+    fn left(&mut self, character_id: Uuid) {
+        self.updates.push((character_id, None));
+    }
+
+    fn message(&mut self, recipients: Vec<Uuid>, clan_id: Uuid, message: String) {
+        self.messages.push(recipients, Destination::Clan(clan_id), message);
+    }
+
+    fn broadcast(&mut self, clan: &Clan, message: String) {
+        let recipients = clan.members.iter()
+            .map(|member| member.character_id)
+            .collect();
+
+        self.message(recipients, clan.id, message);
+    }
+}
+
+#[derive(Default)]
+struct ClanManagerState {
+    invites: HashMap<Uuid, ClanInvite>,
+}
+
+#[derive(Clone)]
+pub struct ClanManager {
+    db: Database,
+    server: Arc<RealmServer>,
+
+    // Serializes all clan mutations, so permission checks
+    // and updates can't interleave.
+    state: Arc<Mutex<ClanManagerState>>,
+}
+
+impl ClanManager {
+    pub fn new(db: Database, server: Arc<RealmServer>) -> Self {
+        Self {
+            db,
+            server,
+            state: Arc::new(Mutex::new(ClanManagerState::default())),
+        }
+    }
+
+    pub async fn membership(&self, character_id: Uuid) -> Result<Option<ClanMembership>, ClanError> {
+        Ok(Clan::get_for_character(&self.db, &character_id).await?
+            .and_then(|clan| {
+                clan.member(character_id)
+                    .map(|member| ClanMembership {
+                        id: clan.id,
+                        name: clan.name.clone(),
+                        rank: member.rank,
+                    })
+            }))
+    }
+
+    /// Returns the session ids of all online clan members,
+    /// optionally limited to officers.
+    pub async fn member_sessions(&self, clan: &Clan, officers_only: bool) -> Vec<Uuid> {
+        let mut sessions = Vec::with_capacity(clan.members.len());
+        for member in &clan.members {
+            if 
+                (!officers_only || member.rank.is_officer()) &&
+                let Some(state) = SESSION_MANAGER.get().unwrap().get_state_for_character(member.character_id).await
+            {
+                sessions.push(state.id);
+            }
+        }
+
+        sessions
+    }
+
+    pub async fn create_clan(&self, name: String, leader: Uuid) -> Result<Clan, ClanError> {
+        let guard = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let clan = self.insert_clan(name, leader, &mut outbox).await?;
+
+        drop(guard);
+        self.dispatch(outbox).await;
+
+        Ok(clan)
+    }
+
+    pub async fn add_member(&self, clan_id: Uuid, character_id: Uuid, rank: ClanRank) -> Result<Clan, ClanError> {
+        let guard = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let mut clan = Clan::get(&self.db, &clan_id).await?
+            .ok_or(ClanError::ClanNotFound)?;
+
+        let character = Character::get(&self.db, &character_id).await?
+            .ok_or(ClanError::CharacterNotFound)?;
+
+        self.insert_member(&mut clan, &character, rank, &mut outbox).await?;
+
+        drop(guard);
+        self.dispatch(outbox).await;
+
+        Ok(clan)
+    }
+
+    pub async fn remove_member(&self, clan_id: Uuid, character_id: Uuid) -> Result<Option<Clan>, ClanError> {
+        let guard = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let mut clan = Clan::get(&self.db, &clan_id).await?
+            .ok_or(ClanError::ClanNotFound)?;
+
+        let clan = self.delete_member(&mut clan, character_id, &mut outbox).await?
+            .then_some(clan);
+
+        drop(guard);
+        self.dispatch(outbox).await;
+
+        Ok(clan)
+    }
+
+    pub async fn set_rank(&self, clan_id: Uuid, character_id: Uuid, rank: ClanRank) -> Result<Clan, ClanError> {
+        let guard = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let mut clan = Clan::get(&self.db, &clan_id).await?
+            .ok_or(ClanError::ClanNotFound)?;
+
+        self.update_rank(&mut clan, character_id, rank, &mut outbox).await?;
+
+        drop(guard);
+        self.dispatch(outbox).await;
+
+        Ok(clan)
+    }
+
+    pub async fn disband_clan(&self, clan_id: Uuid) -> Result<(), ClanError> {
+        let guard = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let clan = Clan::get(&self.db, &clan_id).await?
+            .ok_or(ClanError::ClanNotFound)?;
+
+        self.delete_clan(clan, &mut outbox).await?;
+
+        drop(guard);
+        self.dispatch(outbox).await;
+
+        Ok(())
+    }
+
+    pub async fn create(&self, session_id: Uuid, name: String) {
+        let res = self.try_create(session_id, name).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn invite(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_invite(session_id, character_name).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn accept(&self, session_id: Uuid) {
+        let res = self.try_accept(session_id).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn decline(&self, session_id: Uuid) {
+        let res = self.try_decline(session_id).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn kick(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_kick(session_id, character_name).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn leave(&self, session_id: Uuid) {
+        let res = self.try_leave(session_id).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn promote(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_promote(session_id, character_name).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn demote(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_demote(session_id, character_name).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn disband(&self, session_id: Uuid) {
+        let res = self.try_disband(session_id).await;
+        self.finish(session_id, res).await;
+    }
+
+    async fn try_create(&self, session_id: Uuid, name: String) -> Result<Outbox, ClanError> {
+        let character = self.session_character(session_id).await?;
+
+        let _guard = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let clan = self.insert_clan(name, character.id, &mut outbox).await?;
+        outbox.broadcast(&clan, format!("You have founded the clan {}.", clan.name));
+
+        Ok(outbox)
+    }
+
+    async fn try_invite(&self, session_id: Uuid, character_name: String) -> Result<Outbox, ClanError> {
+        let inviter = self.session_character(session_id).await?;
+        let target = self.find_character(&character_name).await?;
+
+        if target.id == inviter.id {
+            return Err(ClanError::SelfTarget);
+        }
+
+        if SESSION_MANAGER.get().unwrap().get_state_for_character(target.id).await.is_none() {
+            return Err(ClanError::NotOnline(target.name));
+        }
+
+        let mut s = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let clan = Clan::get_for_character(&self.db, &inviter.id).await?
+            .ok_or(ClanError::NotInClan)?;
+
+        if !clan.member(inviter.id).is_some_and(|member| member.rank.is_officer()) {
+            return Err(ClanError::InsufficientRank);
+        }
+
+        if Clan::get_for_character(&self.db, &target.id).await?.is_some() {
+            return Err(ClanError::AlreadyInClan(target.name));
+        }
+
+        let now = Instant::now();
+        s.invites.retain(|_, invite| invite.valid_until > now);
+        s.invites.insert(target.id, ClanInvite {
+            clan_id: clan.id,
+            inviter: inviter.id,
+            valid_until: now + INVITE_TIMEOUT,
+        });
+
+        outbox.message(vec![target.id], clan.id, format!("{} has invited you to join the clan {}.", inviter.name, clan.name));
+        outbox.message(vec![inviter.id], clan.id, format!("You have invited {} to join your clan.", target.name));
+
+        Ok(outbox)
+    }
+
+    async fn try_accept(&self, session_id: Uuid) -> Result<Outbox, ClanError> {
+        let character = self.session_character(session_id).await?;
+
+        let mut s = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let now = Instant::now();
+        s.invites.retain(|_, invite| invite.valid_until > now);
+
+        let invite = s.invites.remove(&character.id)
+            .ok_or(ClanError::NoInvite)?;
+
+        let mut clan = Clan::get(&self.db, &invite.clan_id).await?
+            .ok_or(ClanError::ClanNotFound)?;
+
+        // The inviter might have been demoted or left in the meantime
+        if !clan.member(invite.inviter).is_some_and(|member| member.rank.is_officer()) {
+            return Err(ClanError::NoInvite);
+        }
+
+        self.insert_member(&mut clan, &character, ClanRank::Member, &mut outbox).await?;
+        outbox.broadcast(&clan, format!("{} has joined the clan.", character.name));
+
+        Ok(outbox)
+    }
+
+    async fn try_decline(&self, session_id: Uuid) -> Result<Outbox, ClanError> {
+        let character = self.session_character(session_id).await?;
+
+        let mut s = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let invite = s.invites.remove(&character.id)
+            .filter(|invite| invite.valid_until > Instant::now())
+            .ok_or(ClanError::NoInvite)?;
+
+        outbox.message(vec![invite.inviter], invite.clan_id, format!("{} has declined your clan invite.", character.name));
+
+        Ok(outbox)
+    }
+
+    async fn try_kick(&self, session_id: Uuid, character_name: String) -> Result<Outbox, ClanError> {
+        let character = self.session_character(session_id).await?;
+        let target = self.find_character(&character_name).await?;
+
+        if target.id == character.id {
+            return Err(ClanError::SelfTarget);
+        }
+
+        let _guard = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let mut clan = Clan::get_for_character(&self.db, &character.id).await?
+            .ok_or(ClanError::NotInClan)?;
+
+        let rank = clan.member(character.id).map(|member| member.rank)
+            .ok_or(ClanError::NotInClan)?;
+        let target_rank = clan.member(target.id).map(|member| member.rank)
+            .ok_or_else(|| ClanError::NotAMember(target.name.clone()))?;
+
+        // Officers may only kick regular members
+        if 
+            !rank.is_officer() ||
+            (rank == ClanRank::Officer && target_rank != ClanRank::Member)
+        {
+            return Err(ClanError::InsufficientRank);
+        }
+
+        outbox.message(vec![target.id], clan.id, format!("You have been removed from the clan {}.", clan.name));
+
+        if self.delete_member(&mut clan, target.id, &mut outbox).await? {
+            outbox.broadcast(&clan, format!("{} has been removed from the clan.", target.name));
+        }
+
+        Ok(outbox)
+    }
+
+    async fn try_leave(&self, session_id: Uuid) -> Result<Outbox, ClanError> {
+        let character = self.session_character(session_id).await?;
+
+        let _guard = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let mut clan = Clan::get_for_character(&self.db, &character.id).await?
+            .ok_or(ClanError::NotInClan)?;
+
+        let rank = clan.member(character.id).map(|member| member.rank)
+            .ok_or(ClanError::NotInClan)?;
+
+        if rank == ClanRank::Leader && clan.members.len() > 1 {
+            return Err(ClanError::LeaderCannotLeave);
+        }
+
+        outbox.message(vec![character.id], clan.id, format!("You have left the clan {}.", clan.name));
+
+        if self.delete_member(&mut clan, character.id, &mut outbox).await? {
+            outbox.broadcast(&clan, format!("{} has left the clan.", character.name));
+        }
+
+        Ok(outbox)
+    }
+
+    async fn try_promote(&self, session_id: Uuid, character_name: String) -> Result<Outbox, ClanError> {
+        let _guard = self.state.lock().await;
+        let (mut clan, target, target_rank, mut outbox) = self.prepare_rank_change(session_id, character_name).await?;
+
+        let rank = match target_rank {
+            ClanRank::Member => ClanRank::Officer,
+            // Promoting an officer hands over clan leadership
+            ClanRank::Officer | ClanRank::Leader => ClanRank::Leader,
+        };
+
+        self.update_rank(&mut clan, target.id, rank, &mut outbox).await?;
+
+        if rank == ClanRank::Leader {
+            outbox.broadcast(&clan, format!("{} is now the leader of the clan.", target.name));
+        } else {
+            outbox.broadcast(&clan, format!("{} has been promoted to officer.", target.name));
+        }
+
+        Ok(outbox)
+    }
+
+    async fn try_demote(&self, session_id: Uuid, character_name: String) -> Result<Outbox, ClanError> {
+        let _guard = self.state.lock().await;
+        let (mut clan, target, _, mut outbox) = self.prepare_rank_change(session_id, character_name).await?;
+
+        self.update_rank(&mut clan, target.id, ClanRank::Member, &mut outbox).await?;
+        outbox.broadcast(&clan, format!("{} has been demoted to member.", target.name));
+
+        Ok(outbox)
+    }
+
+    async fn try_disband(&self, session_id: Uuid) -> Result<Outbox, ClanError> {
+        let character = self.session_character(session_id).await?;
+
+        let _guard = self.state.lock().await;
+        let mut outbox = Outbox::default();
+
+        let clan = Clan::get_for_character(&self.db, &character.id).await?
+            .ok_or(ClanError::NotInClan)?;
+
+        if !clan.member(character.id).is_some_and(|member| member.rank == ClanRank::Leader) {
+            return Err(ClanError::InsufficientRank);
+        }
+
+        self.delete_clan(clan, &mut outbox).await?;
+
+        Ok(outbox)
+    }
+
+    // Rank changes can only be performed by the clan leader.
+    // Expects the state lock to be held by the caller.
+    async fn prepare_rank_change(&self, session_id: Uuid, character_name: String) -> Result<(Clan, Character, ClanRank, Outbox), ClanError> {
+        let character = self.session_character(session_id).await?;
+        let target = self.find_character(&character_name).await?;
+
+        if target.id == character.id {
+            return Err(ClanError::SelfTarget);
+        }
+
+        let clan = Clan::get_for_character(&self.db, &character.id).await?
+            .ok_or(ClanError::NotInClan)?;
+
+        if !clan.member(character.id).is_some_and(|member| member.rank == ClanRank::Leader) {
+            return Err(ClanError::InsufficientRank);
+        }
+
+        let target_rank = clan.member(target.id).map(|member| member.rank)
+            .ok_or_else(|| ClanError::NotAMember(target.name.clone()))?;
+
+        Ok((clan, target, target_rank, Outbox::default()))
+    }
+
+    async fn insert_clan(&self, name: String, leader: Uuid, outbox: &mut Outbox) -> Result<Clan, ClanError> {
+        let name = name.trim().to_string();
+
+        if 
+            !CLAN_NAME_LENGTH.contains(&name.chars().count()) ||
+            !name.chars().all(|c| c.is_alphanumeric() || c == ' ')
+        {
+            return Err(ClanError::InvalidName);
+        }
+
+        let character = Character::get(&self.db, &leader).await?
+            .ok_or(ClanError::CharacterNotFound)?;
+
+        if Clan::get_for_character(&self.db, &leader).await?.is_some() {
+            return Err(ClanError::AlreadyInClan(character.name));
+        }
+
+        if Clan::get_by_name(&self.db, &name).await?.is_some() {
+            return Err(ClanError::NameTaken(name));
+        }
+
+        let clan = Clan::create(&self.db, Clan {
+            id: Uuid::new(),
+            name,
+            motd: String::default(),
+            created: Utc::now(),
+            members: vec![
+                ClanMember {
+                    character_id: leader,
+                    rank: ClanRank::Leader,
+                    joined: Utc::now(),
+                }
+            ],
+        }).await?;
+
+        outbox.joined(&clan, &clan.members[0]);
+
+        Ok(clan)
+    }
+
+    async fn insert_member(&self, clan: &mut Clan, character: &Character, rank: ClanRank, outbox: &mut Outbox) -> Result<(), ClanError> {
+        if Clan::get_for_character(&self.db, &character.id).await?.is_some() {
+            return Err(ClanError::AlreadyInClan(character.name.clone()));
+        }
+
+        let member = ClanMember {
+            character_id: character.id,
+            rank,
+            joined: Utc::now(),
+        };
+
+        outbox.joined(clan, &member);
+
+        clan.members.push(member);
+        clan.save(&self.db).await?;
+
+        Ok(())
+    }
+
+    /// Returns false, if the clan was disbanded
+    /// because the last member left.
+    async fn delete_member(&self, clan: &mut Clan, character_id: Uuid, outbox: &mut Outbox) -> Result<bool, ClanError> {
+        let Some((_, successor)) = clan.remove_member(character_id) else {
+            return Err(ClanError::CharacterNotFound);
+        };
+
+        outbox.left(character_id);
+
+        if clan.members.is_empty() {
+            clan.delete(&self.db).await?;
+            return Ok(false);
+        }
+
+        if let Some(successor) = successor {
+            outbox.joined(clan, &successor);
+        }
+
+        clan.save(&self.db).await?;
+
+        Ok(true)
+    }
+
+    async fn update_rank(&self, clan: &mut Clan, character_id: Uuid, rank: ClanRank, outbox: &mut Outbox) -> Result<(), ClanError> {
+        if clan.member(character_id).is_none() {
+            return Err(ClanError::CharacterNotFound);
+        }
+
+        let mut changed = vec![character_id];
+
+        // There can only be one leader
+        if rank == ClanRank::Leader {
+            for member in clan.members.iter_mut() {
+                if member.rank == ClanRank::Leader && member.character_id != character_id {
+                    member.rank = ClanRank::Officer;
+                    changed.push(member.character_id);
+                }
+            }
+        }
+
+        for member in clan.members.iter_mut() {
+            if member.character_id == character_id {
+                member.rank = rank;
+            }
+        }
+
+        for member in clan.members.iter().filter(|member| changed.contains(&member.character_id)) {
+            outbox.joined(clan, member);
+        }
+
+        clan.save(&self.db).await?;
+
+        Ok(())
+    }
+
+    async fn delete_clan(&self, clan: Clan, outbox: &mut Outbox) -> Result<(), ClanError> {
+        clan.delete(&self.db).await?;
+
+        outbox.broadcast(&clan, format!("The clan {} has been disbanded.", clan.name));
+
+        for member in &clan.members {
+            outbox.left(member.character_id);
+        }
+
+        Ok(())
+    }
+
+    async fn session_character(&self, session_id: Uuid) -> Result<Character, ClanError> {
+        let state = SESSION_MANAGER.get().unwrap().get_state(session_id).await
+            .ok_or(ClanError::SessionNotFound)?;
+
+        Character::get(&self.db, &state.character_id).await?
+            .ok_or(ClanError::CharacterNotFound)
+    }
+
+    async fn find_character(&self, name: &str) -> Result<Character, ClanError> {
+        Character::find_by_name(&self.db, name, false).await?
+            .ok_or(ClanError::CharacterNotFound)
+    }
+
+    async fn finish(&self, session_id: Uuid, res: Result<Outbox, ClanError>) {
+        match res {
+            Ok(outbox) => self.dispatch(outbox).await,
+            Err(e) => report_error(session_id, Destination::Clan(UUID_NIL), "Clan", e).await,
+        }
+    }
+
+    async fn dispatch(&self, outbox: Outbox) {
+        for (character_id, clan) in outbox.updates {
+            let _ = self.server.notify(RealmNotification::ClanMembershipUpdated {
+                character_id,
+                clan,
+            }).await;
+        }
+
+        outbox.messages.deliver().await;
+    }
+}
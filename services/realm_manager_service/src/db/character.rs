@@ -16,17 +16,19 @@
 use std::{collections::{HashMap, HashSet}, sync::Arc};
 
 use async_graphql::Enum;
-use database::DatabaseRecord;
+use chrono::{DateTime, Duration, Utc};
+use database::{DBResult, DatabaseRecord};
+use futures_util::TryStreamExt;
 use log::debug;
 use mongodb::{action::Update, bson::{self, doc}, options::{Collation, CollationStrength, IndexOptions}, ClientSession, Collection, IndexModel};
 use obj_params::{GameObjectData, GenericParamSet, ItemBase, ItemEdna, ParamFlag, ParamSet, Player};
 use serde::{Deserialize, Serialize};
-use toolkit::{types::Uuid, GraphqlCrud};
+use toolkit::{config::REALM_CONF, transaction_with_retry, types::Uuid, GraphqlCrud};
 use anyhow::anyhow;
 
 use crate::equipment_slots::EQUIPMENT_SLOTS;
 
-use super::{ItemStorage, ObjectTemplate};
+use super::{Clan, ItemStorage, Mail, ObjectTemplate, SocialRelation};
 
 #[derive(Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum CombatStyle {
@@ -94,6 +96,11 @@ pub struct Character {
     pub name: String,
     #[graphql_crud(serialize_as = serde_json::Value)]
     pub data: GameObjectData,
+    /// Set while the character is pending deletion. The character can
+    /// be restored until then and keeps its name reserved.
+    #[serde(default, with = "database::chrono_datetime_as_bson_datetime_optional")]
+    #[graphql_crud(readonly)]
+    pub purge_at: Option<DateTime<Utc>>,
 }
 
 impl DatabaseRecord for Character {
@@ -149,11 +156,153 @@ impl DatabaseRecord for Character {
             .options(IndexOptions::builder().unique(true).build())
             .build()).await?;
 
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "purge_at": 1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
         Ok(())
     }
 }
 
 impl Character {
+    /// How long a deleted character can be restored.
+    pub fn deletion_grace() -> Duration {
+        Duration::seconds(REALM_CONF.characters.deletion_grace.unwrap_or(7 * 24 * 60 * 60))
+    }
+
+    pub fn is_pending_delete(&self) -> bool {
+        self.purge_at.is_some()
+    }
+
+    /// Looks up a character by name, ignoring case like the name index does.
+    /// Characters pending deletion keep their name, but are only returned
+    /// if `include_pending_delete` is set.
+    pub async fn find_by_name(db: &mongodb::Database, name: &str, include_pending_delete: bool) -> DBResult<Option<Self>> {
+        let filter = if include_pending_delete {
+            doc! { "name": name }
+        } else {
+            doc! { "name": name, "purge_at": null }
+        };
+
+        Ok(
+            Self::collection(db)
+                .find_one(filter)
+                .collation(
+                    Collation::builder()
+                        .locale("en")
+                        .strength(CollationStrength::Secondary)
+                        .build()
+                )
+                .await?
+        )
+    }
+
+    /// Marks the character for deletion after the grace period.
+    pub async fn schedule_deletion(db: &mongodb::Database, id: Uuid) -> DBResult<Option<Self>> {
+        let purge_at = bson::DateTime::from_chrono(Utc::now() + Self::deletion_grace());
+
+        Self::collection(db)
+            .update_one(
+                doc! { "id": id, "purge_at": null }, 
+                doc! { "$set": { "purge_at": purge_at } }
+            )
+            .await?;
+
+        Self::get(db, &id).await
+    }
+
+    /// Takes back a pending deletion, returns false if
+    /// the character wasn't pending deletion anymore.
+    pub async fn restore(db: &mongodb::Database, id: Uuid) -> DBResult<bool> {
+        Ok(
+            Self::collection(db)
+                .update_one(
+                    doc! { "id": id, "purge_at": { "$gt": bson::DateTime::now() } }, 
+                    doc! { "$set": { "purge_at": null } }
+                )
+                .await?
+                .modified_count > 0
+        )
+    }
+
+    /// Lists all characters whose grace period ran out.
+    pub async fn list_expired_deletions(db: &mongodb::Database) -> DBResult<Vec<Uuid>> {
+        Ok(
+            Self::collection(db)
+                .find(doc! { "purge_at": { "$lte": bson::DateTime::now() } })
+                .await?
+                .try_collect::<Vec<_>>()
+                .await?
+                .into_iter()
+                .map(|character| character.id)
+                .collect()
+        )
+    }
+
+    /// Permanently removes a character and every record referencing it in a 
+    /// single transaction. Mail still in the characters mailbox is deleted
+    /// along with its attachments, so return it to the senders beforehand.
+    pub async fn purge(db: &mongodb::Database, id: Uuid) -> DBResult<()> {
+        transaction_with_retry(db.clone(), async |mut session| -> DBResult<_> {
+            Self::collection(db)
+                .delete_one(doc! { "id": id })
+                .session(&mut session)
+                .await?;
+
+            // Covers the inventory, bank, buyback and mail attachment storages
+            for (collection, field) in Self::relations() {
+                db.collection::<bson::Document>(collection)
+                    .delete_many(doc! { *field: id })
+                    .session(&mut session)
+                    .await?;
+            }
+
+            // Same succession rules as leaving the clan
+            if 
+                let Some(mut clan) = Clan::collection(db)
+                    .find_one(doc! { "members.character_id": id })
+                    .session(&mut session)
+                    .await? &&
+                clan.remove_member(id).is_some()
+            {
+                if clan.members.is_empty() {
+                    Clan::collection(db)
+                        .delete_one(doc! { "id": clan.id })
+                        .session(&mut session)
+                        .await?;
+                } else {
+                    Clan::collection(db)
+                        .update_one(
+                            doc! { "id": clan.id },
+                            doc! { "$set": { "members": bson::to_bson(&clan.members).map_err(anyhow::Error::from)? } }
+                        )
+                        .session(&mut session)
+                        .await?;
+                }
+            }
+
+            SocialRelation::collection(db)
+                .delete_many(doc! { "$or": [ { "character_id": id }, { "target_id": id } ] })
+                .session(&mut session)
+                .await?;
+
+            Mail::collection(db)
+                .delete_many(doc! { "recipient": id })
+                .session(&mut session)
+                .await?;
+
+            // Mail sent by the character can't be returned to it anymore
+            Mail::collection(db)
+                .update_many(doc! { "sender": id }, doc! { "$set": { "sender": null } })
+                .session(&mut session)
+                .await?;
+
+            Ok((session, ()))
+        }).await
+    }
+
     pub async fn update_equipment(db: &mongodb::Database, session: &mut ClientSession, character_id: Uuid, storage_id: Uuid) -> database::DBResult<Box<dyn GenericParamSet>> {
         #[derive(Debug)]
         struct Item {
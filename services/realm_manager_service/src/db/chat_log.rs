@@ -0,0 +1,186 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::Enum;
+use chrono::{DateTime, Utc};
+use database::{DBResult, DatabaseRecord};
+use futures_util::TryStreamExt;
+use mongodb::{bson::{self, doc, Document}, options::IndexOptions, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use toolkit::{types::Uuid, GraphqlCrud, ObjectId};
+
+#[derive(Debug, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ChatChannel {
+    Local,
+    Yell,
+    Shout,
+    Whisper,
+    Party,
+    Clan,
+    ClanOfficer,
+    Broadcast,
+}
+
+/// What the moderation stage did with a message.
+#[derive(Debug, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ChatVerdict {
+    Delivered,
+    Filtered,
+    Muted,
+    Flooding,
+}
+
+/// A chat message sent by a player, as it was typed.
+/// Entries are only ever appended, so they can serve as evidence for reports.
+#[derive(Debug, Serialize, Deserialize, GraphqlCrud)]
+#[graphql_crud(name = "chat_log_entry", primary_key_type = "async_graphql::types::ID")]
+pub struct ChatLogEntry {
+    #[serde(
+        rename = "_id",
+        default,
+    )]
+    #[graphql_crud(serialize_as = "async_graphql::types::ID", readonly)]
+    pub id: ObjectId,
+
+    #[graphql_crud(filter)]
+    pub account_id: Uuid,
+
+    #[graphql_crud(filter)]
+    pub character_id: Uuid,
+
+    pub character_name: String,
+
+    #[graphql_crud(filter)]
+    pub channel: ChatChannel,
+
+    /// Whisper recipient, or the party or clan the message was sent to.
+    pub target: Option<String>,
+
+    pub zone_id: Option<Uuid>,
+    pub message: String,
+    pub verdict: ChatVerdict,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ChatLogEntry {
+    /// Returns the newest entries matching all given criteria.
+    /// `text` is matched case insensitive anywhere in the message.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn search(
+        db: &Database, 
+        account_id: Option<Uuid>, 
+        character_id: Option<Uuid>, 
+        channel: Option<ChatChannel>, 
+        text: Option<&str>, 
+        since: Option<DateTime<Utc>>, 
+        until: Option<DateTime<Utc>>, 
+        limit: i64
+    ) -> DBResult<Vec<Self>> {
+        let mut filter = doc! {};
+
+        if let Some(account_id) = account_id {
+            filter.insert("account_id", account_id);
+        }
+
+        if let Some(character_id) = character_id {
+            filter.insert("character_id", character_id);
+        }
+
+        if let Some(channel) = channel {
+            filter.insert("channel", bson::to_bson(&channel).map_err(anyhow::Error::from)?);
+        }
+
+        if let Some(text) = text {
+            filter.insert("message", doc! { "$regex": escape_regex(text), "$options": "i" });
+        }
+
+        let mut timestamp = Document::new();
+        if let Some(since) = since {
+            timestamp.insert("$gte", bson::DateTime::from_chrono(since));
+        }
+
+        if let Some(until) = until {
+            timestamp.insert("$lte", bson::DateTime::from_chrono(until));
+        }
+
+        if !timestamp.is_empty() {
+            filter.insert("timestamp", timestamp);
+        }
+
+        Ok(
+            Self::collection(db)
+                .find(filter)
+                .sort(doc! { "timestamp": -1 })
+                .limit(limit)
+                .await?
+                .try_collect()
+                .await?
+        )
+    }
+}
+
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+impl DatabaseRecord for ChatLogEntry {
+    type PrimaryKey = ObjectId;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "_id"
+    }
+
+    fn collection_name() -> &'static str {
+        "chat_log"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "account_id": 1, "timestamp": -1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "character_id": 1, "timestamp": -1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "timestamp": -1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        Ok(())
+    }
+}
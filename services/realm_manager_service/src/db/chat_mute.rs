@@ -0,0 +1,103 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Utc};
+use database::{DBResult, DatabaseRecord};
+use mongodb::{bson::{self, doc}, options::IndexOptions, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use toolkit::{types::Uuid, GraphqlCrud, ObjectId};
+
+/// A game master issued mute. Mutes apply to the whole account,
+/// so switching characters doesn't lift them.
+#[derive(Debug, Serialize, Deserialize, GraphqlCrud)]
+#[graphql_crud(name = "chat_mute", primary_key_type = "async_graphql::types::ID")]
+pub struct ChatMute {
+    #[serde(
+        rename = "_id",
+        default,
+    )]
+    #[graphql_crud(serialize_as = "async_graphql::types::ID", readonly)]
+    pub id: ObjectId,
+
+    #[graphql_crud(filter)]
+    pub account_id: Uuid,
+
+    /// The character that was reported, for reference.
+    pub character_id: Uuid,
+
+    #[graphql_crud(filter)]
+    pub issued_by: Uuid,
+
+    pub reason: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires: DateTime<Utc>,
+}
+
+impl ChatMute {
+    /// Returns the mute that expires last, if the account is currently muted.
+    pub async fn active_for_account(db: &Database, account_id: &Uuid) -> DBResult<Option<Self>> {
+        Ok(
+            Self::collection(db)
+                .find_one(doc! { "account_id": account_id, "expires": { "$gt": bson::DateTime::now() } })
+                .sort(doc! { "expires": -1 })
+                .await?
+        )
+    }
+
+    /// Lifts all active mutes of the account by letting them expire now.
+    /// Returns the number of mutes lifted.
+    pub async fn lift_for_account(db: &Database, account_id: &Uuid) -> DBResult<u64> {
+        let now = bson::DateTime::now();
+
+        Ok(
+            Self::collection(db)
+                .update_many(
+                    doc! { "account_id": account_id, "expires": { "$gt": now } }, 
+                    doc! { "$set": { "expires": now } }
+                )
+                .await?
+                .modified_count
+        )
+    }
+}
+
+impl DatabaseRecord for ChatMute {
+    type PrimaryKey = ObjectId;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "_id"
+    }
+
+    fn collection_name() -> &'static str {
+        "chat_mutes"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "account_id": 1, "expires": -1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        Ok(())
+    }
+}
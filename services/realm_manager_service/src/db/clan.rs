@@ -0,0 +1,168 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::{DateTime, Utc};
+use database::{DBResult, DatabaseRecord};
+use mongodb::{bson::{self, doc}, options::{Collation, CollationStrength, IndexOptions}, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use toolkit::{types::Uuid, GraphqlCrud};
+
+#[derive(Debug, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ClanRank {
+    Leader,
+    Officer,
+    Member,
+}
+
+impl ClanRank {
+    pub fn is_officer(&self) -> bool {
+        matches!(self, ClanRank::Leader | ClanRank::Officer)
+    }
+}
+
+// Rank index as expected by Player::ClanRank
+impl From<ClanRank> for i32 {
+    fn from(rank: ClanRank) -> Self {
+        match rank {
+            ClanRank::Leader => 0,
+            ClanRank::Officer => 1,
+            ClanRank::Member => 2,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, InputObject, SimpleObject, Clone)]
+#[graphql(input_name = "ClanMemberInput", name = "ClanMember")]
+pub struct ClanMember {
+    pub character_id: Uuid,
+    pub rank: ClanRank,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub joined: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, GraphqlCrud)]
+#[graphql_crud(name = "clan")]
+pub struct Clan {
+    pub id: Uuid,
+    #[graphql_crud(filter)]
+    pub name: String,
+    pub motd: String,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created: DateTime<Utc>,
+    pub members: Vec<ClanMember>,
+}
+
+impl Clan {
+    pub fn member(&self, character_id: Uuid) -> Option<&ClanMember> {
+        self.members.iter()
+            .find(|member| member.character_id == character_id)
+    }
+
+    pub fn leader(&self) -> Option<&ClanMember> {
+        self.members.iter()
+            .find(|member| member.rank == ClanRank::Leader)
+    }
+
+    /// Removes a member. If the leader left, the longest serving officer,
+    /// or member if there are no officers, takes over. Returns the removed
+    /// member and the successor, if one was promoted.
+    pub fn remove_member(&mut self, character_id: Uuid) -> Option<(ClanMember, Option<ClanMember>)> {
+        let idx = self.members.iter()
+            .position(|member| member.character_id == character_id)?;
+        let member = self.members.remove(idx);
+
+        // Never leave a clan without a leader
+        let successor = if member.rank == ClanRank::Leader {
+            self.members.iter_mut()
+                .max_by_key(|member| (member.rank.is_officer(), std::cmp::Reverse(member.joined)))
+                .map(|successor| {
+                    successor.rank = ClanRank::Leader;
+                    successor.clone()
+                })
+        } else {
+            None
+        };
+
+        Some((member, successor))
+    }
+
+    pub async fn get_by_name(db: &Database, name: &str) -> DBResult<Option<Self>> {
+        Ok(Self::collection(db)
+            .find_one(doc! { "name": name })
+            .collation(
+                Collation::builder()
+                    .locale("en")
+                    .strength(CollationStrength::Secondary)
+                    .build()
+            )
+            .await?)
+    }
+
+    pub async fn get_for_character(db: &Database, character_id: &Uuid) -> DBResult<Option<Self>> {
+        Ok(Self::collection(db)
+            .find_one(doc! { "members.character_id": *character_id })
+            .await?)
+    }
+}
+
+impl DatabaseRecord for Clan {
+    type PrimaryKey = Uuid;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "id"
+    }
+
+    fn collection_name() -> &'static str {
+        "clans"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "name": 1 })
+            .options(
+                IndexOptions::builder()
+                    .unique(true)
+                    .collation(
+                        Collation::builder()
+                        .locale("en")
+                        .strength(CollationStrength::Secondary)
+                        .build()
+                    )
+                    .build())
+            .build()).await?;
+
+        // A character can only be a member of a single clan
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "members.character_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build()).await?;
+
+        Ok(())
+    }
+}
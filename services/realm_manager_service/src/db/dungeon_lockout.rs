@@ -0,0 +1,91 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Utc};
+use database::{DBResult, DatabaseRecord};
+use mongodb::{bson::{self, doc}, options::IndexOptions, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use toolkit::{types::Uuid, ObjectId};
+
+/// Binds a character to a dungeon instance, so leaving and re-entering
+/// a dungeon doesn't reset it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DungeonLockout {
+    #[serde(
+        rename = "_id",
+        default,
+    )]
+    pub id: ObjectId,
+    pub character_id: Uuid,
+    pub zone_id: Uuid,
+    pub instance_id: Uuid,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires: DateTime<Utc>,
+}
+
+impl DungeonLockout {
+    /// Returns the characters lockout for a dungeon zone, unless it has expired.
+    pub async fn active(db: &Database, character_id: &Uuid, zone_id: &Uuid) -> DBResult<Option<Self>> {
+        Ok(
+            Self::collection(db)
+                .find_one(doc! { 
+                    "character_id": character_id, 
+                    "zone_id": zone_id, 
+                    "expires": { "$gt": bson::DateTime::now() } 
+                })
+                .await?
+        )
+    }
+
+    /// Binds the character to a dungeon instance, replacing an expired lockout.
+    pub async fn bind(db: &Database, character_id: &Uuid, zone_id: &Uuid, instance_id: &Uuid, expires: DateTime<Utc>) -> DBResult<()> {
+        Self::collection(db)
+            .update_one(
+                doc! { "character_id": character_id, "zone_id": zone_id },
+                doc! { "$set": { "instance_id": instance_id, "expires": bson::DateTime::from_chrono(expires) } }
+            )
+            .upsert(true)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl DatabaseRecord for DungeonLockout {
+    type PrimaryKey = ObjectId;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "_id"
+    }
+
+    fn collection_name() -> &'static str {
+        "dungeon_lockouts"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "character_id": 1, "zone_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build()).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Utc};
+use database::{DBResult, DatabaseRecord};
+use mongodb::{bson::{self, doc}, options::IndexOptions, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use toolkit::{types::Uuid, GraphqlCrud, ObjectId};
+
+/// A privileged command executed by a game master.
+/// Entries are only ever appended, never updated.
+#[derive(Debug, Serialize, Deserialize, GraphqlCrud)]
+#[graphql_crud(name = "gm_audit_record", primary_key_type = "async_graphql::types::ID")]
+pub struct GmAuditRecord {
+    #[serde(
+        rename = "_id",
+        default,
+    )]
+    #[graphql_crud(serialize_as = "async_graphql::types::ID", readonly)]
+    pub id: ObjectId,
+
+    #[graphql_crud(filter)]
+    pub account_id: Uuid,
+
+    #[graphql_crud(filter)]
+    pub character_id: Uuid,
+
+    pub zone_id: Uuid,
+
+    #[graphql_crud(filter)]
+    pub command: String,
+
+    pub arguments: Vec<String>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub timestamp: DateTime<Utc>,
+}
+
+impl DatabaseRecord for GmAuditRecord {
+    type PrimaryKey = ObjectId;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "_id"
+    }
+
+    fn collection_name() -> &'static str {
+        "gm_audit_log"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "account_id": 1, "timestamp": -1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "character_id": 1, "timestamp": -1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        Ok(())
+    }
+}
@@ -96,6 +96,15 @@ pub struct Item {
     pub instance: async_graphql::Json<GameObjectData>,
 }
 
+/// Number of soma colors a character can carry.
+pub const SOMA_TYPES: usize = 8;
+
+/// Name of the storage keeping sold items for buyback.
+pub const BUYBACK_STORAGE: &str = "buyback";
+
+/// Prefix of the storages holding mail attachments, followed by the mail id.
+pub const MAIL_STORAGE_PREFIX: &str = "mail:";
+
 #[derive(Serialize, Deserialize, GraphqlCrud)]
 #[graphql_crud(name = "item_storage")]
 pub struct ItemStorage {
@@ -107,6 +116,7 @@ pub struct ItemStorage {
     pub capacity: i32,
     pub bling: Option<i32>,
     pub game_cash: Option<i32>,
+    pub soma: Option<Vec<i32>>,
     pub items: Vec<Item>,
 }
 
@@ -158,6 +168,7 @@ impl ItemStorage {
                 capacity: 30,
                 bling: Some(0),
                 game_cash: Some(0),
+                soma: None,
                 items: vec![],
             },
             StorageOwner::Character(_) => ItemStorage {
@@ -167,6 +178,7 @@ impl ItemStorage {
                 capacity: 30,
                 bling: Some(0),
                 game_cash: Some(0),
+                soma: Some(vec![0; SOMA_TYPES]),
                 items: vec![],
             },
             StorageOwner::Guild(_) => ItemStorage {
@@ -176,6 +188,7 @@ impl ItemStorage {
                 capacity: 30,
                 bling: None,
                 game_cash: Some(0),
+                soma: None,
                 items: vec![],
             },
         }).unwrap();
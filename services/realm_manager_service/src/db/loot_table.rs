@@ -0,0 +1,91 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use database::{DBResult, DatabaseRecord};
+use mongodb::{bson::doc, options::IndexOptions, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use toolkit::{types::Uuid, GraphqlCrud};
+
+#[derive(Debug, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LootKind {
+    Item,
+    Soma,
+    Bits,
+}
+
+#[derive(Debug, Serialize, Deserialize, InputObject, SimpleObject, Clone)]
+#[graphql(input_name = "LootTableEntryInput", name = "LootTableEntry")]
+pub struct LootTableEntry {
+    pub kind: LootKind,
+    pub item_name: Option<String>,
+    pub soma_type: Option<i32>,
+    pub min_amount: i32,
+    pub max_amount: i32,
+    /// Relative chance of this entry being picked by a roll.
+    pub weight: i32,
+    /// Only drops for killers currently on this quest.
+    pub quest_id: Option<i32>,
+}
+
+/// Weighted drops rolled when an NPC is killed. Tables without
+/// NPCs apply to every NPC within the level range.
+#[derive(Debug, Serialize, Deserialize, GraphqlCrud)]
+#[graphql_crud(name = "loot_table")]
+pub struct LootTable {
+    pub id: Uuid,
+    #[graphql_crud(filter)]
+    pub name: String,
+    pub npcs: Vec<Uuid>,
+    pub min_level: Option<i32>,
+    pub max_level: Option<i32>,
+    /// Chance between 0 and 1 for the table to drop anything at all.
+    pub drop_chance: f32,
+    pub rolls: i32,
+    pub entries: Vec<LootTableEntry>,
+}
+
+impl DatabaseRecord for LootTable {
+    type PrimaryKey = Uuid;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "id"
+    }
+
+    fn collection_name() -> &'static str {
+        "loot_tables"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "name": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build()).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Utc};
+use database::{DBResult, DatabaseRecord};
+use futures_util::TryStreamExt;
+use mongodb::{bson::{self, doc}, options::IndexOptions, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use toolkit::{types::Uuid, GraphqlCrud};
+
+/// A letter in a character's mailbox. Attached items and bits are kept
+/// in a separate item storage until the recipient takes them.
+#[derive(Debug, Clone, Serialize, Deserialize, GraphqlCrud)]
+#[graphql_crud(name = "mail")]
+pub struct Mail {
+    pub id: Uuid,
+
+    #[graphql_crud(filter)]
+    pub recipient: Uuid,
+
+    pub sender: Option<Uuid>,
+    pub sender_name: String,
+
+    pub subject: String,
+    pub body: String,
+
+    pub attachments: Option<Uuid>,
+    pub attached_items: i32,
+    pub attached_bits: i32,
+
+    pub read: bool,
+    pub returned: bool,
+
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub sent: DateTime<Utc>,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub expires: DateTime<Utc>,
+}
+
+impl Mail {
+    pub async fn list_for_recipient(db: &Database, recipient: &Uuid) -> DBResult<Vec<Mail>> {
+        Ok(
+            Self::collection(db)
+                .find(doc! { "recipient": recipient })
+                .sort(doc! { "sent": -1 })
+                .await?
+                .try_collect()
+                .await?
+        )
+    }
+
+    pub async fn list_expired(db: &Database) -> DBResult<Vec<Mail>> {
+        Ok(
+            Self::collection(db)
+                .find(doc! { "expires": { "$lt": bson::DateTime::now() } })
+                .await?
+                .try_collect()
+                .await?
+        )
+    }
+}
+
+impl DatabaseRecord for Mail {
+    type PrimaryKey = Uuid;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "id"
+    }
+
+    fn collection_name() -> &'static str {
+        "mail"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "recipient": 1, "sent": -1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "expires": 1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        Ok(())
+    }
+}
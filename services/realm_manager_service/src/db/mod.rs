@@ -31,6 +31,15 @@ mod navmesh_tile;
 mod quest_state;
 mod quest_template;
 mod quest_dialogue;
+mod clan;
+mod social_relation;
+mod gm_audit_record;
+mod trade_record;
+mod mail;
+mod loot_table;
+mod chat_log;
+mod chat_mute;
+mod dungeon_lockout;
 
 pub use character::*;
 pub use premium_currency::*;
@@ -49,4 +58,13 @@ pub use navmesh::*;
 pub use navmesh_tile::*;
 pub use quest_state::*;
 pub use quest_template::*;
-pub use quest_dialogue::*;
\ No newline at end of file
+pub use quest_dialogue::*;
+pub use clan::*;
+pub use social_relation::*;
+pub use gm_audit_record::*;
+pub use trade_record::*;
+pub use mail::*;
+pub use loot_table::*;
+pub use chat_log::*;
+pub use chat_mute::*;
+pub use dungeon_lockout::*;
\ No newline at end of file
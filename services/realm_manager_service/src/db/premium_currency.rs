@@ -14,7 +14,8 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use database::{DBResult, DatabaseRecord};
-use mongodb::{bson::doc, options::IndexOptions, ClientSession, Database, IndexModel};
+use anyhow::anyhow;
+use mongodb::{bson::doc, options::{IndexOptions, ReturnDocument}, ClientSession, Database, IndexModel};
 use serde::{Deserialize, Serialize};
 use toolkit::{transaction_with_retry, types::Uuid};
 
@@ -31,27 +32,52 @@ impl PremiumCurrency {
         let comment = comment.as_ref();
 
         transaction_with_retry(db.clone(), async |mut session| -> DBResult<(ClientSession, PremiumCurrencyTransaction)> {
-            let record = PremiumCurrency::collection(db).find_one_and_update(doc! {
+            let transaction = Self::transfer_currency_with_session(db, &mut session, account_id, amount, comment.cloned()).await?
+                .ok_or_else(|| anyhow!("insufficient balance"))?;
+
+            Ok((session, transaction))
+        }).await
+    }
+
+    /// Transfers currency as part of an already running transaction.
+    /// Returns None, if a debit would bring the balance below zero.
+    pub async fn transfer_currency_with_session(db: &Database, session: &mut ClientSession, account_id: &Uuid, amount: i32, comment: Option<String>) -> DBResult<Option<PremiumCurrencyTransaction>> {
+        let record = if amount < 0 {
+            PremiumCurrency::collection(db).find_one_and_update(doc! {
+                "account_id": account_id,
+                "balance": { "$gte": -amount },
+            }, doc! {
+                "$inc": { "balance": amount }
+            })
+            .return_document(ReturnDocument::After)
+            .session(&mut *session)
+            .await?
+        } else {
+            PremiumCurrency::collection(db).find_one_and_update(doc! {
                 "account_id": account_id,
             }, doc! {
                 "$inc": { "balance": amount }
             })
             .upsert(true)
-            .session(&mut session)
+            .return_document(ReturnDocument::After)
+            .session(&mut *session)
             .await?
-            .unwrap();
+        };
 
-            let transaction = PremiumCurrencyTransaction::write(db, 
-                &mut session, 
-                *account_id, 
-                amount, 
-                record.balance, 
-                comment.cloned(), 
-                true
-            ).await?;
+        let Some(record) = record else {
+            return Ok(None);
+        };
 
-            Ok((session, transaction))
-        }).await
+        let transaction = PremiumCurrencyTransaction::write(db, 
+            session, 
+            *account_id, 
+            amount, 
+            record.balance, 
+            comment, 
+            true
+        ).await?;
+
+        Ok(Some(transaction))
     }
 
     pub async fn get_balance(db: &Database, account_id: &Uuid) -> DBResult<i32> {
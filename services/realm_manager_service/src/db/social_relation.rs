@@ -0,0 +1,116 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::Enum;
+use chrono::{DateTime, Utc};
+use database::{DBResult, DatabaseRecord};
+use futures_util::TryStreamExt;
+use mongodb::{bson::{self, doc}, options::IndexOptions, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use toolkit::{types::Uuid, GraphqlCrud, ObjectId};
+
+#[derive(Debug, Enum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SocialRelationKind {
+    Friend,
+    Ignore,
+}
+
+/// A one-sided relation from `character_id` to `target_id`.
+/// A character can either befriend or ignore another character, but not both.
+#[derive(Debug, Serialize, Deserialize, GraphqlCrud)]
+#[graphql_crud(name = "SocialRelation", primary_key_type = "async_graphql::types::ID")]
+pub struct SocialRelation {
+    #[serde(
+        rename = "_id",
+        default,
+    )]
+    #[graphql_crud(serialize_as = "async_graphql::types::ID", readonly)]
+    pub id: ObjectId,
+
+    #[graphql_crud(filter)]
+    pub character_id: Uuid,
+
+    #[graphql_crud(filter)]
+    pub target_id: Uuid,
+
+    pub kind: SocialRelationKind,
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created: DateTime<Utc>,
+}
+
+impl SocialRelation {
+    pub async fn get_relation(db: &Database, character_id: &Uuid, target_id: &Uuid) -> DBResult<Option<Self>> {
+        Ok(Self::collection(db)
+            .find_one(doc! { "character_id": *character_id, "target_id": *target_id })
+            .await?)
+    }
+
+    /// Returns all relations of the given kind owned by `character_id`.
+    pub async fn list_for_character(db: &Database, character_id: &Uuid, kind: SocialRelationKind) -> DBResult<Vec<Self>> {
+        Ok(Self::collection(db)
+            .find(doc! { "character_id": *character_id, "kind": bson::to_bson(&kind).unwrap() })
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    /// Returns all relations of the given kind pointing at `target_id`.
+    pub async fn list_for_target(db: &Database, target_id: &Uuid, kind: SocialRelationKind) -> DBResult<Vec<Self>> {
+        Ok(Self::collection(db)
+            .find(doc! { "target_id": *target_id, "kind": bson::to_bson(&kind).unwrap() })
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    pub async fn is_ignoring(db: &Database, character_id: &Uuid, target_id: &Uuid) -> DBResult<bool> {
+        Ok(Self::get_relation(db, character_id, target_id).await?
+            .is_some_and(|relation| relation.kind == SocialRelationKind::Ignore))
+    }
+}
+
+impl DatabaseRecord for SocialRelation {
+    type PrimaryKey = ObjectId;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "_id"
+    }
+
+    fn collection_name() -> &'static str {
+        "social_relations"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "character_id": 1, "target_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build()).await?;
+
+        // Used to find everyone who befriended or ignores a character
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "target_id": 1, "kind": 1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        Ok(())
+    }
+}
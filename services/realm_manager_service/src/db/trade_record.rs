@@ -0,0 +1,103 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Utc};
+use database::{DBResult, DatabaseRecord};
+use mongodb::{bson::{self, doc}, options::IndexOptions, ClientSession, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use toolkit::{types::Uuid, GraphqlCrud, ObjectId};
+
+/// A completed trade between two characters.
+/// Kept to investigate item duplication reports.
+#[derive(Debug, Serialize, Deserialize, GraphqlCrud)]
+#[graphql_crud(name = "trade_record", primary_key_type = "async_graphql::types::ID")]
+pub struct TradeRecord {
+    #[serde(
+        rename = "_id",
+        default,
+    )]
+    #[graphql_crud(serialize_as = "async_graphql::types::ID", readonly)]
+    pub id: ObjectId,
+
+    #[graphql_crud(filter)]
+    pub left_character: Uuid,
+    pub left_items: Vec<Uuid>,
+    pub left_bits: i32,
+
+    #[graphql_crud(filter)]
+    pub right_character: Uuid,
+    pub right_items: Vec<Uuid>,
+    pub right_bits: i32,
+
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub timestamp: DateTime<Utc>,
+}
+
+impl TradeRecord {
+    /// Logs the trade as part of the transaction exchanging the items.
+    pub async fn write(db: &Database, session: &mut ClientSession, record: TradeRecord) -> DBResult<()> {
+        Self::collection(db)
+            .insert_one(record)
+            .session(&mut *session)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl DatabaseRecord for TradeRecord {
+    type PrimaryKey = ObjectId;
+
+    fn key(&self) -> &Self::PrimaryKey {
+        &self.id
+    }
+
+    fn key_name() -> &'static str {
+        "_id"
+    }
+
+    fn collection_name() -> &'static str {
+        "trade_log"
+    }
+
+    async fn build_index(db: &Database) -> DBResult<()> {
+        let collection = Self::collection(db);
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "left_character": 1, "timestamp": -1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "right_character": 1, "timestamp": -1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "left_items": 1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        collection.create_index(
+            IndexModel::builder()
+            .keys(doc! { "right_items": 1 })
+            .options(IndexOptions::builder().unique(false).build())
+            .build()).await?;
+
+        Ok(())
+    }
+}
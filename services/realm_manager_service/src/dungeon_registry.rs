@@ -0,0 +1,138 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::Enum;
+use chrono::{TimeDelta, Utc};
+use database::DatabaseError;
+use log::debug;
+use mongodb::Database;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use toolkit::types::Uuid;
+
+use crate::{db::DungeonLockout, proto::InstanceKey, INSTANCE_REGISTRY, PARTY_REGISTRY};
+
+/// How long a character stays bound to a dungeon instance after entering it.
+const DUNGEON_LOCKOUT: TimeDelta = TimeDelta::minutes(30);
+
+#[derive(Error, Debug)]
+pub enum DungeonError {
+    #[error("You are locked out of this dungeon for another {0} minute(s).")]
+    LockedOut(i64),
+
+    #[error(transparent)]
+    DatabaseError(#[from] DatabaseError),
+}
+
+impl DungeonError {
+    fn locked_out(lockout: &DungeonLockout) -> Self {
+        let remaining = (lockout.expires - Utc::now()).num_seconds().max(0);
+        Self::LockedOut((remaining + 59) / 60)
+    }
+}
+
+/// Who shares a dungeon instance, taken from the zone configs instance scope.
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq)]
+pub enum DungeonScope {
+    /// Party members share an instance, characters without a party get their own.
+    Party,
+    /// Every character gets its own instance.
+    Character,
+}
+
+#[derive(Default)]
+struct DungeonRegistryData {
+    // Instance currently used by a party or solo character, keyed by (owner, zone)
+    instances: HashMap<(Uuid, Uuid), Uuid>,
+}
+
+/// Hands out private dungeon instances. Depending on the dungeons scope, parties
+/// share one instance per dungeon or every character gets its own. Everyone entering
+/// an instance is bound to it until the lockout expires, so resetting a dungeon by
+/// re-entering is not possible. Lockouts are persisted, to survive realm restarts.
+#[derive(Clone)]
+pub struct DungeonRegistry {
+    db: Database,
+    data: Arc<Mutex<DungeonRegistryData>>,
+}
+
+impl DungeonRegistry {
+    pub fn new(db: Database) -> Self {
+        Self {
+            db,
+            data: Arc::default(),
+        }
+    }
+
+    /// Returns the instance id the character has to travel to, to enter the given dungeon zone.
+    pub async fn enter(&self, character_id: Uuid, zone_id: Uuid, scope: DungeonScope) -> Result<Uuid, DungeonError> {
+        let owner = match scope {
+            DungeonScope::Party => PARTY_REGISTRY.get().unwrap()
+                .party_for_character(character_id).await
+                .map(|party| party.id)
+                .unwrap_or(character_id),
+            DungeonScope::Character => character_id,
+        };
+
+        // Resolved up front, so entering doesn't hold up the whole registry
+        let locked = DungeonLockout::active(&self.db, &character_id, &zone_id).await?;
+        let bound = locked.is_some();
+        let bound_running = match &locked {
+            Some(lockout) => INSTANCE_REGISTRY.get().unwrap()
+                .get_instance(InstanceKey::new(zone_id, Some(lockout.instance_id))).await
+                .is_some(),
+            None => false,
+        };
+
+        let instance_id = {
+            let mut s = self.data.lock().await;
+
+            match (s.instances.get(&(owner, zone_id)).copied(), locked) {
+                (None, None) => {
+                    let instance_id = Uuid::new();
+                    s.instances.insert((owner, zone_id), instance_id);
+                    instance_id
+                },
+                (Some(instance_id), None) => instance_id,
+                (Some(instance_id), Some(lockout)) if instance_id == lockout.instance_id => instance_id,
+                // Rejoin the bound instance while it is still running. The instance
+                // might belong to another party, so it's not handed out to the owner.
+                (None, Some(lockout)) if bound_running => lockout.instance_id,
+                (_, Some(lockout)) => {
+                    return Err(DungeonError::locked_out(&lockout));
+                },
+            }
+        };
+
+        debug!("Character {character_id} enters dungeon instance ({zone_id}, {instance_id})");
+
+        // Existing lockouts keep their expiry
+        if !bound {
+            DungeonLockout::bind(&self.db, &character_id, &zone_id, &instance_id, Utc::now() + DUNGEON_LOCKOUT).await?;
+        }
+
+        Ok(instance_id)
+    }
+
+    /// Forgets a dungeon instance that has been shut down. Lockouts stay in place.
+    pub async fn instance_closed(&self, key: &InstanceKey) {
+        if let Some(instance_id) = key.instance() {
+            self.data.lock().await
+                .instances.retain(|(_, zone_id), id| *zone_id != key.zone() || *id != instance_id);
+        }
+    }
+}
@@ -16,7 +16,7 @@
 use core_api::CoreApiError;
 use cynic::{http::CynicReqwestError, GraphQlError};
 use thiserror::Error;
-use toolkit::{GetMongoError, anyhow};
+use toolkit::{GetMongoError, anyhow, types::Uuid};
 
 use crate::item_storage_session::ItemStorageSessionError;
 
@@ -55,6 +55,9 @@ pub enum RealmError {
     #[error(transparent)]
     ItemStorageSessionError(#[from] ItemStorageSessionError),
 
+    #[error("no eligible node available for zone {0}")]
+    NoEligibleNode(Uuid),
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
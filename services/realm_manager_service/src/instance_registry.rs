@@ -16,13 +16,17 @@
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use chrono::{DateTime, TimeDelta, Utc};
-use cluster::PeerIdentity;
-use log::{error, info, warn};
-use mongodb::Database;
+use cluster::{PeerIdentity, RequestId};
+use database::DBResult;
+use log::{debug, error, info, warn};
+use mongodb::{bson::doc, Database};
 use tokio::sync::{broadcast, RwLock};
-use toolkit::{anyhow, types::Uuid};
+use toolkit::{anyhow, config::REALM_CONF, types::Uuid};
 
-use crate::{error::RealmResult, proto::{InstanceKey, RealmNotification, RealmResponse, RealmServer}, NODE_REGISTRY, SESSION_MANAGER};
+use crate::{db::Zone, error::{RealmError, RealmResult}, metrics::{INSTANCES, INSTANCE_REQUESTS_FAILED, INSTANCE_REQUESTS_PENDING}, proto::{InstanceKey, NodeLoad, RealmNotification, RealmResponse, RealmServer}, NODE_REGISTRY, SESSION_MANAGER};
+
+// How long to wait for further offers after the first one arrived
+const OFFER_WINDOW: TimeDelta = TimeDelta::milliseconds(250);
 
 struct InstanceRequest {
     key: InstanceKey,
@@ -30,18 +34,85 @@ struct InstanceRequest {
     valid_until: DateTime<Utc>,
     wait_state: broadcast::Sender<Option<Arc<Instance>>>,
     count: i32,
+    node_tags: Option<Vec<String>>, // Placement restriction, any of these tags is required
+}
+
+struct InstanceOffer {
+    peer: PeerIdentity,
+    request_id: Option<RequestId>,
+    key: InstanceKey,
+    score: f32,
 }
 
 enum InstanceRequestState {
-    Inquiried,
+    Inquiried { 
+        offers: Vec<InstanceOffer>,
+        decide_at: Option<DateTime<Utc>>,
+    },
     Offered { peer: PeerIdentity },
 }
 
+impl InstanceRequestState {
+    fn inquiried() -> Self {
+        Self::Inquiried { offers: vec![], decide_at: None }
+    }
+}
+
+/// Lower is better. Nodes which haven't reported their load yet
+/// are considered fully loaded, so they are only picked as last resort.
+fn load_score(load: Option<&NodeLoad>) -> f32 {
+    if let Some(load) = load {
+        let instances = load.instances as f32 / load.instance_limit.max(1) as f32;
+
+        // Use the player count as tie breaker between otherwise idle nodes
+        instances.max(load.tick_budget) + load.players as f32 * 0.0001
+    } else {
+        1.0
+    }
+}
+
+fn is_eligible(load: Option<&NodeLoad>, node_tags: Option<&[String]>) -> bool {
+    match node_tags {
+        Some(tags) => load.is_some_and(|load| load.tags.iter().any(|tag| tags.contains(tag))),
+        None => true,
+    }
+}
+
+/// Checks if any node, which isn't draining, satisfies the placement restriction.
+async fn eligible_node_available(node_tags: Option<&[String]>) -> bool {
+    node_tags.is_none() || 
+        NODE_REGISTRY.get().unwrap()
+            .available_loads().await
+            .iter()
+            .any(|load| is_eligible(Some(load), node_tags))
+}
+
+/// Looks up the first placement rule matching the zone.
+async fn placement_tags(db: &Database, zone: Uuid) -> DBResult<Option<Vec<String>>> {
+    let rules = &REALM_CONF.placement.rules;
+    if rules.is_empty() {
+        return Ok(None);
+    }
+
+    let Some(zone) = Zone::collection(db).find_one(doc! { "guid": zone }).await? else {
+        return Ok(None);
+    };
+
+    Ok(rules.iter()
+        .find(|rule| {
+            rule.zone.as_ref().is_none_or(|name| name.eq_ignore_ascii_case(&zone.zone)) &&
+            rule.zone_type.as_ref().is_none_or(|ty| *ty == zone.realu_zone_type) &&
+            rule.instanced.is_none_or(|instanced| instanced == zone.is_instance)
+        })
+        .map(|rule| rule.node_tags.clone()))
+}
+
 struct InstanceRegistryData {
-    _db: Database,
+    db: Database,
     server: Arc<RealmServer>,
     requests: HashMap<Uuid, InstanceRequest>,
     instances: HashMap<InstanceKey, Arc<Instance>>,
+    placements: HashMap<Uuid, Option<Vec<String>>>, // Placement tags by zone, rules don't change at runtime
 }
 
 #[derive(Clone)]
@@ -50,10 +121,11 @@ pub struct InstanceRegistry(Arc<RwLock<InstanceRegistryData>>);
 impl InstanceRegistry {
     pub fn new(db: Database, server: Arc<RealmServer>) -> Self {
         let data = Arc::new(RwLock::new(InstanceRegistryData {
-            _db: db,
+            db,
             server,
             requests: HashMap::new(),
             instances: HashMap::new(),
+            placements: HashMap::new(),
         }));
 
         fn start_tick(data: Arc<RwLock<InstanceRegistryData>>) {
@@ -75,12 +147,13 @@ impl InstanceRegistry {
                                         .entry(Uuid::new())
                                         .insert_entry(InstanceRequest {
                                             key: req.key,
-                                            state: InstanceRequestState::Inquiried,
+                                            state: InstanceRequestState::inquiried(),
                                             valid_until: Utc::now()
                                                 .checked_add_signed(TimeDelta::seconds(2))
                                                 .expect("valid time"),
                                             wait_state: req.wait_state,
                                             count: req.count + 1,
+                                            node_tags: req.node_tags,
                                         });
                         
                                     RealmNotification::InstanceRequested {
@@ -96,8 +169,36 @@ impl InstanceRegistry {
                                     .expect("failed to send notification");
                             } else {
                                 error!("Instance request {} timed out! No node available to handle zone {}...", key, req.key.zone());
+                                INSTANCE_REQUESTS_FAILED.inc();
                             }
                         }
+
+                        // Accept the best offer, once the offer window closed
+                        let mut accepted = vec![];
+                        for (transaction_id, req) in s.requests.iter_mut() {
+                            if 
+                                let InstanceRequestState::Inquiried { offers, decide_at } = &mut req.state &&
+                                decide_at.is_some_and(|decide_at| decide_at <= Utc::now()) &&
+                                let Some(offer) = offers.iter()
+                                    .min_by(|a, b| a.score.total_cmp(&b.score))
+                            {
+                                let (peer, request_id, key) = (offer.peer.clone(), offer.request_id, offer.key.clone());
+
+                                req.key = key.clone();
+                                req.state = InstanceRequestState::Offered { peer: peer.clone() };
+                                accepted.push((peer, request_id, *transaction_id, key));
+                            }
+                        }
+
+                        for (peer, request_id, transaction_id, key) in accepted {
+                            let _ = s.server.reply(&peer, request_id, RealmResponse::InstanceOfferingAccepted { 
+                                transaction_id, 
+                                key,
+                            }).await;
+                        }
+
+                        INSTANCE_REQUESTS_PENDING.set(s.requests.len() as i64);
+                        INSTANCES.set(s.instances.len() as i64);
                     }
 
                     tokio::time::sleep(Duration::from_millis(100)).await;
@@ -111,6 +212,38 @@ impl InstanceRegistry {
     }
 
     pub async fn request_instance(&self, session_id: Uuid, key: InstanceKey) -> RealmResult<Arc<Instance>> {
+        let instance = self.provision(key).await?;
+
+        SESSION_MANAGER.get().unwrap()
+            .update_instance(session_id, instance.key.zone(), instance.key.instance()).await;
+
+        Ok(instance)
+    }
+
+    async fn placement_tags(&self, zone: Uuid) -> RealmResult<Option<Vec<String>>> {
+        let db = {
+            let s = self.0.read().await;
+            if let Some(tags) = s.placements.get(&zone) {
+                return Ok(tags.clone());
+            }
+
+            s.db.clone()
+        };
+
+        let tags = placement_tags(&db, zone).await?;
+        self.0.write().await.placements.insert(zone, tags.clone());
+
+        Ok(tags)
+    }
+
+    async fn provision(&self, key: InstanceKey) -> RealmResult<Arc<Instance>> {
+        let node_tags = self.placement_tags(key.zone()).await?;
+
+        // Offers of ineligible nodes are ignored, so the request would just time out
+        if !eligible_node_available(node_tags.as_deref()).await {
+            return Err(RealmError::NoEligibleNode(key.zone()));
+        }
+
         let mut s = self.0.write().await;
         
         // Check if there is already a running instance we could connect to
@@ -137,12 +270,13 @@ impl InstanceRegistry {
         let request = {
             let entry = entry.insert_entry(InstanceRequest {
                 key,
-                state: InstanceRequestState::Inquiried,
+                state: InstanceRequestState::inquiried(),
                 valid_until: Utc::now()
                     .checked_add_signed(TimeDelta::seconds(2))
                     .expect("valid time"),
                 wait_state: sender,
                 count: 0,
+                node_tags,
             });
 
             RealmNotification::InstanceRequested {
@@ -158,29 +292,46 @@ impl InstanceRegistry {
         drop(s);
 
         if let Ok(Some(instance)) = receiver.recv().await {
-            SESSION_MANAGER.get().unwrap()
-                .update_instance(session_id, instance.key.zone(), instance.key.instance()).await;
-
             Ok(instance)
+        } else if !eligible_node_available(node_tags.as_deref()).await {
+            // All eligible nodes went away while we were waiting
+            Err(RealmError::NoEligibleNode(key.zone()))
         } else {
             Err(anyhow::Error::msg("failed to join instance").into())
         }
     }
 
-    pub async fn process_instance_offer(&self, peer: PeerIdentity, transaction_id: Uuid, key: InstanceKey) {
+    pub async fn process_instance_offer(&self, peer: PeerIdentity, request_id: Option<RequestId>, transaction_id: Uuid, key: InstanceKey) {
+        let Some(node) = NODE_REGISTRY.get().unwrap().node_for_peer(&peer).await else {
+            return;
+        };
+
+        // Draining nodes are about to go away, don't place anything new on them
+        if NODE_REGISTRY.get().unwrap().is_draining(node.id).await {
+            return;
+        }
+
+        let load = NODE_REGISTRY.get().unwrap().load(node.id).await;
+
         let mut s = self.0.write().await;
         if 
             let Some(req) = s.requests.get_mut(&transaction_id) &&
-            matches!(req.state, InstanceRequestState::Inquiried)
+            let InstanceRequestState::Inquiried { offers, decide_at } = &mut req.state
         {
-            req.key = key.clone();
-            req.state = InstanceRequestState::Offered { peer: peer.clone() };
-            
-            // We accept the first offer we receive
-            let _ = s.server.send(&peer, RealmResponse::InstanceOfferingAccepted { 
-                transaction_id, 
+            if !is_eligible(load.as_ref(), req.node_tags.as_deref()) {
+                debug!("Node {} is not eligible for zone {}", node.id, key.zone());
+                return;
+            }
+
+            // Collect offers for a short while, so we can pick the least loaded node
+            offers.push(InstanceOffer {
+                peer,
+                request_id,
                 key,
-            }).await;
+                score: load_score(load.as_ref()),
+            });
+
+            decide_at.get_or_insert_with(|| Utc::now() + OFFER_WINDOW);
         }
     }
 
@@ -210,9 +361,19 @@ impl InstanceRegistry {
         }
     }
 
-    pub async fn remove_instance(&self, key: InstanceKey) {
+    /// Removes an instance registered for the given node.
+    /// Returns false if the instance is not registered for that node.
+    pub async fn remove_instance(&self, node: Uuid, key: InstanceKey) -> bool {
         let mut s = self.0.write().await;
-        s.instances.remove(&key);
+
+        // A drained instance might already run on a different node,
+        // so only remove it if it is still registered for the sender.
+        if s.instances.get(&key).is_some_and(|instance| instance.node == node) {
+            s.instances.remove(&key);
+            true
+        } else {
+            false
+        }
     }
 
     pub async fn get_instance(&self, key: InstanceKey) -> Option<Arc<Instance>> {
@@ -220,6 +381,40 @@ impl InstanceRegistry {
         s.instances.get(&key).cloned()
     }
 
+    /// Unregisters all instances of a draining node, so players travelling away
+    /// from it get routed to a new instance. Persistent zones are re-provisioned
+    /// right away, instanced zones are only recreated once somebody joins them.
+    pub async fn drain_node(&self, node: Uuid) {
+        let (db, drained) = {
+            let mut s = self.0.write().await;
+            let drained: Vec<InstanceKey> = s.instances
+                .extract_if(|_, instance| instance.node == node)
+                .map(|(key, _)| key)
+                .collect();
+
+            (s.db.clone(), drained)
+        };
+
+        info!("Draining {} instances of node {}", drained.len(), node);
+
+        for key in drained {
+            let registry = self.clone();
+            let db = db.clone();
+
+            tokio::spawn(async move {
+                match Zone::collection(&db).find_one(doc! { "guid": key.zone() }).await {
+                    Ok(Some(zone)) if !zone.is_instance => {
+                        if let Err(e) = registry.provision(key.clone()).await {
+                            error!("Failed to re-provision zone {}: {e:?}", key.zone());
+                        }
+                    },
+                    Ok(_) => (),
+                    Err(e) => error!("Failed to query zone {}: {e:?}", key.zone()),
+                }
+            });
+        }
+    }
+
     pub async fn purge_node(&self, node: Uuid) {
         let mut s = self.0.write().await;
         s.instances.retain(|_, instance| instance.node != node);
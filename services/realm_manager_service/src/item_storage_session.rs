@@ -24,7 +24,7 @@ use thiserror::Error;
 use tokio::sync::Mutex;
 use toolkit::{GetMongoError, NativeParam, anyhow::anyhow, types::Uuid};
 
-use crate::{db::{ItemStorage, ObjectTemplate, StorageOwner}, equipment_slots::{EquipmentType, SlotType, EQUIPMENT_SLOTS}};
+use crate::{db::{ItemStorage, ObjectTemplate, PremiumCurrency, StorageOwner, SOMA_TYPES}, equipment_slots::{EquipmentType, SlotType, EQUIPMENT_SLOTS}};
 
 #[derive(Error, Debug)]
 pub enum ItemStorageSessionError {
@@ -54,6 +54,22 @@ impl GetMongoError for ItemStorageSessionError {
     }
 }
 
+pub const PURCHASE_FAILED: &str = "#Shop.false_buymultiple#";
+pub const SELL_FAILED: &str = "#Shop.false_sell#";
+pub const TRADE_FAILED: &str = "#Trade.failed#";
+
+/// Returns the price a vendor pays for an item, if it can be sold at all.
+pub fn sell_price(item: &GameObjectData) -> Option<i32> {
+    if !*item.get::<_, bool>(ItemBase::AllowSell).unwrap_or(&false) {
+        return None;
+    }
+
+    let price = (*item.get::<_, i32>(ItemBase::SellPriceBling).unwrap_or(&0))
+        .max(*item.get::<_, i32>(ItemBase::BlingSellingPrice).unwrap_or(&0));
+
+    (price > 0).then_some(price)
+}
+
 #[derive(Debug, Clone)]
 struct Item {
     id: Uuid,
@@ -162,6 +178,7 @@ pub struct ItemStorageSession {
 
     bling: Option<i32>,
     game_cash: Option<i32>,
+    soma: Option<Vec<i32>>,
     capacity: i32,
 
     removed_items: Vec<Uuid>,
@@ -211,6 +228,7 @@ impl ItemStorageSession {
 
             bling: storage.bling,
             game_cash: storage.game_cash,
+            soma: storage.soma,
             capacity: storage.capacity,
 
             removed_items: Vec::new(),
@@ -440,6 +458,11 @@ impl ItemStorageSession {
             item.instance.apply(overrides.as_mut());
         }
 
+        self.place_item(item).await
+    }
+
+    /// Places an item into the first free inventory slot.
+    async fn place_item(&mut self, mut item: Item) -> Result<Uuid, ItemStorageSessionError> {
         let tab = self.get_item_tab(&item.instance);
         let id = item.id;
 
@@ -474,6 +497,96 @@ impl ItemStorageSession {
         Err(ItemStorageSessionError::ClientError("#ItemAction.NotEnoughInventorySlots#", None))
     }
 
+    /// Places an item at the given slot if it's free, otherwise into the first free slot.
+    async fn place_item_at(&mut self, mut item: Item, slot: i32) -> Result<Uuid, ItemStorageSessionError> {
+        let id = item.id;
+        let tab = self.get_item_tab(&item.instance);
+
+        if
+            let Some(entry) = usize::try_from(slot).ok().and_then(|idx| tab.slots.get_mut(idx)) &&
+            entry.is_none()
+        {
+            item.instance.set(ItemBase::ContainerId, 0);
+            item.instance.set(ItemBase::InventorySlotIndex, slot);
+            item.instance.set(ItemBase::SlotId, -1);
+
+            let item = Arc::new(Mutex::new(item));
+            *entry = Some(item.clone());
+            self.items.insert(id, item);
+
+            Ok(id)
+        } else {
+            self.place_item(item).await
+        }
+    }
+
+    /// Appends an item after all other items of its tab, keeping the items 
+    /// ordered by age. If the tab is full, the oldest item is dropped.
+    async fn push_item(&mut self, mut item: Item) -> Result<(), ItemStorageSessionError> {
+        let id = item.id;
+        let tab = self.get_item_tab(&item.instance);
+        let capacity = tab.slots.len();
+        let mut items = tab.slots.iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let evicted = if !tab.can_grow && items.len() >= capacity {
+            if items.is_empty() {
+                return Err(ItemStorageSessionError::ClientError("#ItemAction.NotEnoughInventorySlots#", None));
+            }
+
+            Some(items.remove(0))
+        } else {
+            None
+        };
+
+        item.instance.set(ItemBase::ContainerId, 0);
+        item.instance.set(ItemBase::SlotId, -1);
+
+        let item = Arc::new(Mutex::new(item));
+        items.push(item.clone());
+
+        let len = capacity.max(items.len());
+        tab.slots = items.into_iter()
+            .map(Some)
+            .chain(repeat_n(None, len))
+            .take(len)
+            .collect();
+
+        for (idx, slot) in tab.slots.iter().enumerate() {
+            if let Some(item) = slot {
+                item.set_inventory_slot_index(idx as i32).await;
+            }
+        }
+
+        self.items.insert(id, item);
+
+        if let Some(evicted) = evicted {
+            let evicted_id = evicted.id().await;
+            self.items.remove(&evicted_id);
+            self.removed_items.push(evicted_id);
+        }
+
+        Ok(())
+    }
+
+    /// Removes an unequipped item from the storage, 
+    /// so it can be placed into another storage.
+    async fn take_item(&mut self, item_id: Uuid) -> Result<Item, ItemStorageSessionError> {
+        let item = self.items.get(&item_id).cloned()
+            .ok_or(ItemStorageSessionError::Other(anyhow!("item {} not found", item_id)))?;
+
+        if item.container_id().await != 0 {
+            return Err(ItemStorageSessionError::Other(anyhow!("item {} is equipped", item_id)));
+        }
+
+        self.destroy_item(item_id).await?;
+
+        let item = item.lock().await.clone();
+        Ok(item)
+    }
+
     pub async fn destroy_item(&mut self, item_id: Uuid) -> Result<(), ItemStorageSessionError> {
         if let Some(item) = self.items.remove(&item_id) {
             self.removed_items.push(item_id);
@@ -639,9 +752,134 @@ impl ItemStorageSession {
         }
     }
 
-    #[allow(unused)]
-    pub async fn transfer_item(&mut self, _item_id: Uuid, _new_storage_id: Uuid, _new_slot: i32) -> Result<(), ItemStorageSessionError> {
-        todo!()
+    /// Moves an item into another storage. The item is placed at `new_slot` 
+    /// if that slot is free, otherwise into the first free slot.
+    pub async fn transfer_item(&mut self, item_id: Uuid, new_storage_id: Uuid, new_slot: i32) -> Result<(), ItemStorageSessionError> {
+        if new_storage_id == self.id {
+            return self.move_item(item_id, new_slot).await;
+        }
+
+        let item = self.take_item(item_id).await?;
+
+        self.open_sub_session(new_storage_id).await?
+            .place_item_at(item, new_slot).await?;
+
+        Ok(())
+    }
+
+    /// Swaps items and bits between this storage and another one.
+    /// Both sides are taken out before anything is placed, so
+    /// slots freed by one side can be used by the other.
+    pub async fn exchange(&mut self, items: &[Uuid], bits: i32, other_id: Uuid, other_items: &[Uuid], other_bits: i32) -> Result<(), ItemStorageSessionError> {
+        if self.game_cash.unwrap_or(0) < bits {
+            return Err(ItemStorageSessionError::ClientError(TRADE_FAILED, None));
+        }
+
+        let mut taken = Vec::new();
+        for &item_id in items {
+            taken.push(self.take_item(item_id).await?);
+        }
+
+        let other = self.open_sub_session(other_id).await?;
+        if other.game_cash.unwrap_or(0) < other_bits {
+            return Err(ItemStorageSessionError::ClientError(TRADE_FAILED, None));
+        }
+
+        let mut received = Vec::new();
+        for &item_id in other_items {
+            received.push(other.take_item(item_id).await?);
+        }
+
+        for item in taken {
+            other.place_item(item).await?;
+        }
+
+        if bits > 0 {
+            other.add_bits(bits).await?;
+        }
+
+        if other_bits > 0 {
+            other.take_bits(other_bits).await?;
+        }
+
+        for item in received {
+            self.place_item(item).await?;
+        }
+
+        if bits > 0 {
+            self.take_bits(bits).await?;
+        }
+
+        if other_bits > 0 {
+            self.add_bits(other_bits).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Opens another storage as part of the same transaction.
+    /// Sub sessions are written together with this session.
+    pub async fn open_sub_session(&mut self, id: Uuid) -> Result<&mut ItemStorageSession, ItemStorageSessionError> {
+        if let Some(idx) = self.sub_sessions.iter().position(|session| session.id == id) {
+            return Ok(&mut self.sub_sessions[idx]);
+        }
+
+        let sub_session = Self::init(&self.db, self.session.clone(), id).await?;
+        self.sub_sessions.push(sub_session);
+
+        Ok(self.sub_sessions.last_mut().unwrap())
+    }
+
+    /// Sells an item for its bling selling price. The item is kept in the
+    /// buyback storage, so it can be bought back for the same price.
+    /// The world service only sells items while a vendor is targeted.
+    pub async fn sell_item(&mut self, item_id: Uuid, buyback_id: Uuid) -> Result<i32, ItemStorageSessionError> {
+        let item = self.items.get(&item_id)
+            .ok_or(ItemStorageSessionError::Other(anyhow!("item {} not found", item_id)))?;
+
+        // Equipped items have to be taken off first
+        if item.container_id().await != 0 {
+            return Err(ItemStorageSessionError::ClientError(SELL_FAILED, None));
+        }
+
+        let price = sell_price(&item.lock().await.instance)
+            .ok_or(ItemStorageSessionError::ClientError(SELL_FAILED, None))?;
+
+        let item = self.take_item(item_id).await?;
+        self.add_cash(price).await?;
+
+        self.open_sub_session(buyback_id).await?
+            .push_item(item).await?;
+
+        Ok(price)
+    }
+
+    /// Moves a previously sold item from the buyback storage back into this storage.
+    pub async fn buyback_item(&mut self, item_id: Uuid, buyback_id: Uuid) -> Result<i32, ItemStorageSessionError> {
+        let item = self.open_sub_session(buyback_id).await?
+            .take_item(item_id).await?;
+
+        let price = sell_price(&item.instance)
+            .ok_or(ItemStorageSessionError::ClientError(PURCHASE_FAILED, None))?;
+
+        self.take_cash(price).await?;
+        self.place_item(item).await?;
+
+        Ok(price)
+    }
+
+    /// Charges premium currency from an account as part of this transaction.
+    pub async fn take_premium_currency(&mut self, account_id: &Uuid, amount: i32, comment: String) -> Result<(), ItemStorageSessionError> {
+        if amount <= 0 {
+            return Err(ItemStorageSessionError::Other(anyhow!("amount must be positive")));
+        }
+
+        let mut session = self.session.lock().await;
+
+        PremiumCurrency::transfer_currency_with_session(&self.db, session.deref_mut(), account_id, -amount, Some(comment)).await?
+            .ok_or(ItemStorageSessionError::ClientError(PURCHASE_FAILED, None))?;
+
+        Ok(())
     }
 
     pub async fn clear_items(&mut self) -> Result<(), ItemStorageSessionError> {
@@ -680,7 +918,26 @@ impl ItemStorageSession {
         }
     }
 
-    #[allow(dead_code)]
+    /// Adds looted soma of the given type to the balance of a character storage.
+    pub async fn add_soma(&mut self, soma_type: i32, amount: i32) -> Result<i32, ItemStorageSessionError> {
+        if amount <= 0 {
+            return Err(ItemStorageSessionError::Other(anyhow!("amount must be positive")));
+        }
+
+        let Some(idx) = usize::try_from(soma_type).ok().filter(|idx| *idx < SOMA_TYPES) else {
+            return Err(ItemStorageSessionError::Other(anyhow!("invalid soma type {soma_type}")));
+        };
+
+        // Storages created before soma was tracked don't have a balance yet
+        if matches!(self.owner, StorageOwner::Character(_)) {
+            let soma = &mut self.soma.get_or_insert_with(|| vec![0; SOMA_TYPES])[idx];
+            *soma = soma.saturating_add(amount);
+            Ok(*soma)
+        } else {
+            Err(ItemStorageSessionError::Other(anyhow!("storage is no soma container")))?
+        }
+    }
+
     pub async fn take_bits(&mut self, amount: i32) -> Result<i32, ItemStorageSessionError> {
         if amount <= 0 {
             return Err(ItemStorageSessionError::Other(anyhow!("amount must be positive")));
@@ -689,7 +946,7 @@ impl ItemStorageSession {
         if let Some(game_cash) = &mut self.game_cash {
             let new_amount = game_cash.saturating_sub(amount);
             if new_amount < 0 {
-                return Err(ItemStorageSessionError::Other(anyhow!("not enough game cash in storage")));
+                return Err(ItemStorageSessionError::ClientError(PURCHASE_FAILED, None));
             }
 
             *game_cash = new_amount;
@@ -699,7 +956,6 @@ impl ItemStorageSession {
         }
     }
 
-    #[allow(dead_code)]
     pub async fn take_cash(&mut self, amount: i32) -> Result<i32, ItemStorageSessionError> {
         if amount <= 0 {
             return Err(ItemStorageSessionError::Other(anyhow!("amount must be positive")));
@@ -708,7 +964,7 @@ impl ItemStorageSession {
         if let Some(bling) = &mut self.bling {
             let new_amount = bling.saturating_sub(amount);
             if new_amount < 0 {
-                return Err(ItemStorageSessionError::Other(anyhow!("not enough bling in storage")));
+                return Err(ItemStorageSessionError::ClientError(PURCHASE_FAILED, None));
             }
 
             *bling = new_amount;
@@ -723,6 +979,7 @@ impl ItemStorageSession {
             id: self.id,
             bling: self.bling,
             game_cash: self.game_cash,
+            soma: self.soma.clone(),
             _capacity: self.capacity,
             removed_items: self.removed_items.clone(),
             changed_items: Vec::new(),
@@ -734,6 +991,7 @@ impl ItemStorageSession {
             owner: self.owner,
             bling: self.bling,
             game_cash: self.game_cash,
+            soma: self.soma.clone(),
             capacity: self.capacity,
             items: Vec::new(),
         };
@@ -789,7 +1047,10 @@ impl ItemStorageSession {
         Ok((Arc::into_inner(self.session).unwrap().into_inner(), results))
     }
 
-    pub fn abort(self) -> ClientSession {
+    pub fn abort(mut self) -> ClientSession {
+        // Sub sessions share our client session
+        self.sub_sessions.clear();
+
         Arc::into_inner(self.session).unwrap().into_inner()
     }
 }
@@ -800,5 +1061,6 @@ pub struct ItemStorageSessionResult {
     pub changed_items: Vec<crate::db::Item>,
     pub bling: Option<i32>,
     pub game_cash: Option<i32>,
+    pub soma: Option<Vec<i32>>,
     pub _capacity: i32,
 }
\ No newline at end of file
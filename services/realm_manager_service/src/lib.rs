@@ -20,6 +20,12 @@ use std::sync::OnceLock;
 use async_graphql::{EmptySubscription, Schema};
 use chat_router::ChatRouter;
 use instance_registry::InstanceRegistry;
+use party_registry::PartyRegistry;
+use clan_manager::ClanManager;
+use social_manager::SocialManager;
+use mail_manager::MailManager;
+use dungeon_registry::DungeonRegistry;
+use chat_moderator::ChatModerator;
 use node_registry::NodeRegistry;
 use schema::{MutationRoot, QueryRoot};
 
@@ -29,8 +35,17 @@ mod node_registry;
 mod instance_registry;
 mod session_manager;
 mod chat_router;
+mod party_registry;
+mod clan_manager;
+mod social_manager;
+mod mail_manager;
+mod dungeon_registry;
+mod chat_moderator;
+mod player_requests;
+mod character_archive;
 mod item_storage_session;
 mod equipment_slots;
+mod metrics;
 
 pub mod error;
 pub mod proto;
@@ -43,6 +58,12 @@ pub static NODE_REGISTRY: OnceLock<NodeRegistry> = OnceLock::new();
 pub static INSTANCE_REGISTRY: OnceLock<InstanceRegistry> = OnceLock::new();
 pub static SESSION_MANAGER: OnceLock<SessionManager> = OnceLock::new();
 pub static CHAT_ROUTER: OnceLock<ChatRouter> = OnceLock::new();
+pub static PARTY_REGISTRY: OnceLock<PartyRegistry> = OnceLock::new();
+pub static CLAN_MANAGER: OnceLock<ClanManager> = OnceLock::new();
+pub static SOCIAL_MANAGER: OnceLock<SocialManager> = OnceLock::new();
+pub static MAIL_MANAGER: OnceLock<MailManager> = OnceLock::new();
+pub static DUNGEON_REGISTRY: OnceLock<DungeonRegistry> = OnceLock::new();
+pub static CHAT_MODERATOR: OnceLock<ChatModerator> = OnceLock::new();
 
 pub fn get_schema_sdl() -> String {
     Schema::build(QueryRoot::default(), MutationRoot::default(), EmptySubscription)
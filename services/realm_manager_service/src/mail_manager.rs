@@ -0,0 +1,471 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use anyhow::anyhow;
+use chrono::{TimeDelta, Utc};
+use database::{DatabaseError, DatabaseRecord};
+use log::{debug, error};
+use mongodb::{bson::doc, Database};
+use thiserror::Error;
+use toolkit::{transaction_with_retry, types::Uuid};
+
+use crate::{db::{Character, ItemStorage, Mail, ObjectTemplate, StorageOwner, MAIL_STORAGE_PREFIX}, item_storage_session::{ItemStorageSession, ItemStorageSessionError, ItemStorageSessionResult}, proto::Destination, CHAT_ROUTER, SESSION_MANAGER};
+
+const MAIL_LIFETIME: TimeDelta = TimeDelta::days(30);
+const MAX_ATTACHMENTS: usize = 10;
+
+#[derive(Error, Debug)]
+pub enum MailError {
+    #[error("Character not found.")]
+    CharacterNotFound,
+
+    #[error("Mail not found.")]
+    MailNotFound,
+
+    #[error("You can't send mail to yourself.")]
+    SelfTarget,
+
+    #[error("You can attach at most 10 items.")]
+    TooManyAttachments,
+
+    #[error("Invalid amount of bits.")]
+    InvalidBits,
+
+    #[error("This mail has no attachments.")]
+    NoAttachments,
+
+    #[error("Take the attachments before deleting this mail.")]
+    HasAttachments,
+
+    #[error("This mail can't be returned.")]
+    CantReturn,
+
+    #[error("{0}")]
+    Storage(&'static str),
+
+    #[error(transparent)]
+    StorageError(ItemStorageSessionError),
+
+    #[error(transparent)]
+    DatabaseError(#[from] DatabaseError),
+
+    #[error(transparent)]
+    MongodbError(#[from] mongodb::error::Error),
+}
+
+impl From<ItemStorageSessionError> for MailError {
+    fn from(value: ItemStorageSessionError) -> Self {
+        match value {
+            ItemStorageSessionError::ClientError(msg, _) => Self::Storage(msg),
+            e => Self::StorageError(e),
+        }
+    }
+}
+
+/// Delivers mail to characters, whether they are online or not.
+/// 
+/// Attachments are moved into an item storage of their own when the mail is
+/// sent, and moved into the recipients inventory when taken. Mail that isn't
+/// collected in time is returned to the sender, or deleted along with its 
+/// attachments if there is nobody to return it to.
+#[derive(Clone)]
+pub struct MailManager {
+    db: Database,
+}
+
+impl MailManager {
+    pub fn new(db: Database) -> Self {
+        Self {
+            db,
+        }
+    }
+
+    pub fn start_expiry(&self, interval: Duration) {
+        let manager = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+
+            loop {
+                interval.tick().await;
+                manager.expire_mail().await;
+            }
+        });
+    }
+
+    pub async fn mailbox(&self, character_id: Uuid) -> Result<Vec<Mail>, MailError> {
+        Ok(Mail::list_for_recipient(&self.db, &character_id).await?)
+    }
+
+    /// Sends mail from a character. Attached items and bits are taken from 
+    /// `storage_id`, which has to belong to the sender.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send(&self, sender_id: Uuid, storage_id: Uuid, recipient_name: &str, subject: String, body: String, items: Vec<Uuid>, bits: i32) -> Result<(Mail, Vec<ItemStorageSessionResult>), MailError> {
+        let sender = Character::get(&self.db, &sender_id).await?
+            .ok_or(MailError::CharacterNotFound)?;
+        let recipient = self.find_character(recipient_name).await?;
+
+        if sender.id == recipient.id {
+            return Err(MailError::SelfTarget);
+        }
+
+        if items.len() > MAX_ATTACHMENTS {
+            return Err(MailError::TooManyAttachments);
+        }
+
+        if bits < 0 {
+            return Err(MailError::InvalidBits);
+        }
+
+        let mut mail = new_mail(recipient.id, Some(sender.id), sender.name, subject, body);
+
+        if items.is_empty() && bits == 0 {
+            Mail::create(&self.db, mail.clone()).await?;
+            self.notify_recipient(&mail).await;
+
+            return Ok((mail, vec![]));
+        }
+
+        let attachments = self.create_attachment_storage(&mail).await?;
+        mail.attachments = Some(attachments);
+        mail.attached_items = items.len() as i32;
+        mail.attached_bits = bits;
+
+        let (items, mail_ref) = (&items, &mail);
+        let res = transaction_with_retry(self.db.clone(), async |session| -> Result<_, ItemStorageSessionError> {
+            let mut session = ItemStorageSession::with_session(&self.db, session, storage_id).await?;
+
+            if !matches!(session.owner(), StorageOwner::Character(id) if *id == sender_id) {
+                return Err(anyhow!("storage {} doesn't belong to the sender", storage_id).into());
+            }
+
+            session.exchange(items, bits, attachments, &[], 0).await?;
+
+            let (mut session, results) = session.write_uncommitted().await?;
+
+            Mail::collection(&self.db)
+                .insert_one(mail_ref)
+                .session(&mut session)
+                .await?;
+
+            Ok((session, results))
+        }).await;
+
+        match res {
+            Ok(results) => {
+                self.notify_recipient(&mail).await;
+                Ok((mail, results))
+            },
+            Err(e) => {
+                self.delete_attachment_storage(attachments).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Sends mail on behalf of the realm, e.g. rewards granted by a GM or event.
+    /// Attached items are created from the given templates.
+    pub async fn send_system(&self, sender_name: String, recipient_name: &str, subject: String, body: String, templates: Vec<Uuid>, bits: i32) -> Result<Mail, MailError> {
+        let recipient = self.find_character(recipient_name).await?;
+
+        if templates.len() > MAX_ATTACHMENTS {
+            return Err(MailError::TooManyAttachments);
+        }
+
+        if bits < 0 {
+            return Err(MailError::InvalidBits);
+        }
+
+        let mut mail = new_mail(recipient.id, None, sender_name, subject, body);
+
+        if templates.is_empty() && bits == 0 {
+            Mail::create(&self.db, mail.clone()).await?;
+            self.notify_recipient(&mail).await;
+
+            return Ok(mail);
+        }
+
+        let attachments = self.create_attachment_storage(&mail).await?;
+        mail.attachments = Some(attachments);
+        mail.attached_items = templates.len() as i32;
+        mail.attached_bits = bits;
+
+        let (templates, mail_ref) = (&templates, &mail);
+        let res = transaction_with_retry(self.db.clone(), async |session| -> Result<_, ItemStorageSessionError> {
+            let mut session = ItemStorageSession::with_session(&self.db, session, attachments).await?;
+
+            for id in templates {
+                let template = ObjectTemplate::get(&self.db, id).await?
+                    .ok_or(anyhow!("item {} not found", id))?;
+
+                session.insert_item(template, None, None).await?;
+            }
+
+            if bits > 0 {
+                session.add_bits(bits).await?;
+            }
+
+            let (mut session, _) = session.write_uncommitted().await?;
+
+            Mail::collection(&self.db)
+                .insert_one(mail_ref)
+                .session(&mut session)
+                .await?;
+
+            Ok((session, ()))
+        }).await;
+
+        match res {
+            Ok(_) => {
+                self.notify_recipient(&mail).await;
+                Ok(mail)
+            },
+            Err(e) => {
+                self.delete_attachment_storage(attachments).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    pub async fn read(&self, character_id: Uuid, mail_id: Uuid) -> Result<Mail, MailError> {
+        let mut mail = self.recipient_mail(character_id, mail_id).await?;
+
+        if !mail.read {
+            Mail::collection(&self.db)
+                .update_one(doc! { "id": mail_id }, doc! { "$set": { "read": true } })
+                .await?;
+
+            mail.read = true;
+        }
+
+        Ok(mail)
+    }
+
+    /// Moves all attachments into the recipients storage.
+    pub async fn take_attachments(&self, character_id: Uuid, mail_id: Uuid, storage_id: Uuid) -> Result<Vec<ItemStorageSessionResult>, MailError> {
+        let mail = self.recipient_mail(character_id, mail_id).await?;
+        let attachments = mail.attachments.ok_or(MailError::NoAttachments)?;
+        let storage = ItemStorage::get(&self.db, &attachments).await?
+            .ok_or(MailError::NoAttachments)?;
+
+        let item_ids = storage.items.iter()
+            .map(|item| item.id)
+            .collect::<Vec<_>>();
+        let bits = storage.game_cash.unwrap_or(0);
+        let item_ids = &item_ids;
+
+        Ok(transaction_with_retry(self.db.clone(), async |session| -> Result<_, ItemStorageSessionError> {
+            let mut session = ItemStorageSession::with_session(&self.db, session, storage_id).await?;
+
+            if !matches!(session.owner(), StorageOwner::Character(id) if *id == character_id) {
+                return Err(anyhow!("storage {} doesn't belong to the recipient", storage_id).into());
+            }
+
+            session.exchange(&[], 0, attachments, item_ids, bits).await?;
+
+            let (mut session, results) = session.write_uncommitted().await?;
+
+            ItemStorage::collection(&self.db)
+                .delete_one(doc! { "id": attachments })
+                .session(&mut session)
+                .await?;
+
+            Mail::collection(&self.db)
+                .update_one(doc! { "id": mail_id }, doc! { "$set": { 
+                    "attachments": null,
+                    "attached_items": 0,
+                    "attached_bits": 0,
+                    "read": true,
+                } })
+                .session(&mut session)
+                .await?;
+
+            Ok((session, results))
+        }).await?)
+    }
+
+    pub async fn return_to_sender(&self, character_id: Uuid, mail_id: Uuid) -> Result<(), MailError> {
+        let mail = self.recipient_mail(character_id, mail_id).await?;
+        self.bounce(mail).await
+    }
+
+    pub async fn delete(&self, character_id: Uuid, mail_id: Uuid) -> Result<(), MailError> {
+        let mail = self.recipient_mail(character_id, mail_id).await?;
+
+        if mail.attachments.is_some() {
+            return Err(MailError::HasAttachments);
+        }
+
+        mail.delete(&self.db).await?;
+
+        Ok(())
+    }
+
+    /// Returns all mail with attachments in a characters mailbox to the 
+    /// senders, so nothing is lost when the character is purged.
+    pub async fn return_undelivered(&self, character_id: Uuid) -> Result<(), MailError> {
+        for mail in Mail::list_for_recipient(&self.db, &character_id).await? {
+            if is_returnable(&mail) {
+                self.bounce(mail).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn expire_mail(&self) {
+        let expired = match Mail::list_expired(&self.db).await {
+            Ok(expired) => expired,
+            Err(e) => {
+                error!("Failed to query expired mail: {e:?}");
+                return;
+            }
+        };
+
+        for mail in expired {
+            debug!("Mail {} expired", mail.id);
+
+            let res = if is_returnable(&mail) {
+                self.bounce(mail).await
+            } else {
+                if let Some(attachments) = mail.attachments {
+                    self.delete_attachment_storage(attachments).await;
+                }
+
+                mail.delete(&self.db).await.map_err(Into::into)
+            };
+
+            if let Err(e) = res {
+                error!("Failed to expire mail: {e:?}");
+            }
+        }
+    }
+
+    // Sends the mail back to its sender, attachments included.
+    // Returned mail can't be returned again.
+    async fn bounce(&self, mail: Mail) -> Result<(), MailError> {
+        let Some(sender) = mail.sender else {
+            return Err(MailError::CantReturn);
+        };
+
+        if mail.returned {
+            return Err(MailError::CantReturn);
+        }
+
+        let recipient_name = Character::get(&self.db, &mail.recipient).await?
+            .map(|character| character.name)
+            .unwrap_or_default();
+
+        let mut returned = new_mail(sender, Some(mail.recipient), recipient_name, format!("Returned: {}", mail.subject), mail.body.clone());
+        returned.attachments = mail.attachments;
+        returned.attached_items = mail.attached_items;
+        returned.attached_bits = mail.attached_bits;
+        returned.returned = true;
+
+        let (mail_ref, returned_ref) = (&mail, &returned);
+        transaction_with_retry(self.db.clone(), async |mut session| -> Result<_, ItemStorageSessionError> {
+            // Attachments travel with the mail, so they must not
+            // be purged together with the original recipient.
+            if let Some(attachments) = returned_ref.attachments {
+                ItemStorage::collection(&self.db)
+                    .update_one(
+                        doc! { "id": attachments }, 
+                        doc! { "$set": { "owner": StorageOwner::Character(sender) } }
+                    )
+                    .session(&mut session)
+                    .await?;
+            }
+
+            Mail::collection(&self.db)
+                .insert_one(returned_ref)
+                .session(&mut session)
+                .await?;
+
+            Mail::collection(&self.db)
+                .delete_one(doc! { "id": mail_ref.id })
+                .session(&mut session)
+                .await?;
+
+            Ok((session, ()))
+        }).await?;
+
+        self.notify_recipient(&returned).await;
+
+        Ok(())
+    }
+
+    async fn create_attachment_storage(&self, mail: &Mail) -> Result<Uuid, MailError> {
+        Ok(
+            ItemStorage::get_or_create_for_owner(
+                &self.db, 
+                &format!("{MAIL_STORAGE_PREFIX}{}", mail.id), 
+                StorageOwner::Character(mail.recipient)
+            ).await?.id
+        )
+    }
+
+    async fn delete_attachment_storage(&self, id: Uuid) {
+        if let Err(e) = ItemStorage::collection(&self.db)
+            .delete_one(doc! { "id": id })
+            .await 
+        {
+            error!("Failed to delete mail attachments {id}: {e:?}");
+        }
+    }
+
+    async fn recipient_mail(&self, character_id: Uuid, mail_id: Uuid) -> Result<Mail, MailError> {
+        Mail::get(&self.db, &mail_id).await?
+            .filter(|mail| mail.recipient == character_id)
+            .ok_or(MailError::MailNotFound)
+    }
+
+    async fn notify_recipient(&self, mail: &Mail) {
+        if let Some(state) = SESSION_MANAGER.get().unwrap().get_state_for_character(mail.recipient).await {
+            CHAT_ROUTER.get().unwrap()
+                .system_message(vec![state.id], Destination::Whisper(String::default()), format!("You have new mail from {}.", mail.sender_name)).await;
+        }
+    }
+
+    async fn find_character(&self, name: &str) -> Result<Character, MailError> {
+        Character::find_by_name(&self.db, name, false).await?
+            .ok_or(MailError::CharacterNotFound)
+    }
+}
+
+fn is_returnable(mail: &Mail) -> bool {
+    mail.attachments.is_some() && mail.sender.is_some() && !mail.returned
+}
+
+fn new_mail(recipient: Uuid, sender: Option<Uuid>, sender_name: String, subject: String, body: String) -> Mail {
+    let now = Utc::now();
+
+    Mail {
+        id: Uuid::new(),
+        recipient,
+        sender,
+        sender_name,
+        subject,
+        body,
+        attachments: None,
+        attached_items: 0,
+        attached_bits: 0,
+        read: false,
+        returned: false,
+        sent: now,
+        expires: now + MAIL_LIFETIME,
+    }
+}
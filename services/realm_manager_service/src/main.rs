@@ -30,16 +30,21 @@ use cluster::{ClusterEvent, Endpoint, Host, PeerIdentity};
 use content::set_content_path;
 use core_api::CoreApi;
 use core_api::proto::{CoreRequest, CoreClient, CoreNotification};
-use database::DatabaseExt;
+use database::{connect_instrumented, DatabaseExt};
 use db::{CashShopItem, CashShopItemBundle, CashShopVendor, Character, ItemStorage, ObjectPlacement, ObjectTemplate, PremiumCurrency, PremiumCurrencyTransaction, WorldDef, Zone};
 use equipment_slots::EQUIPMENT_SLOTS;
 use error::RealmResult;
 use instance_registry::InstanceRegistry;
 use log::{debug, info, error};
-use mongodb::Client;
 use node_registry::{NodeRegistry, NodeSocketAddress};
+use party_registry::PartyRegistry;
+use clan_manager::ClanManager;
+use social_manager::SocialManager;
+use mail_manager::MailManager;
+use dungeon_registry::DungeonRegistry;
+use chat_moderator::{ChatModerator, WordFilter};
 use poem::{listener::TcpListener, post, Route, Server};
-use proto::{NodeAddress, NodeType, RealmNotification, RealmResponse, RealmServer};
+use proto::{NodeAddress, NodeType, RealmNotification, RealmResponse, RealmServer, DEFAULT_CHANNEL};
 use reqwest::Url;
 use schema::{MutationRoot, QueryRoot};
 use session_manager::SessionManager;
@@ -47,7 +52,7 @@ use tokio::sync::{mpsc::Receiver, Mutex};
 use tokio::time;
 use toolkit::print_banner;
 
-use crate::db::{Navmesh, NavmeshTile, QuestDialogue, QuestState, QuestTemplate};
+use crate::db::{Clan, LootTable, Mail, Navmesh, NavmeshTile, QuestDialogue, QuestState, QuestTemplate, SocialRelation, GmAuditRecord, TradeRecord, ChatLogEntry, ChatMute, DungeonLockout};
 
 mod schema;
 mod db;
@@ -57,8 +62,17 @@ mod node_registry;
 mod instance_registry;
 mod session_manager;
 mod chat_router;
+mod party_registry;
+mod clan_manager;
+mod social_manager;
+mod mail_manager;
+mod dungeon_registry;
+mod chat_moderator;
+mod player_requests;
+mod character_archive;
 mod item_storage_session;
 mod equipment_slots;
+mod metrics;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -83,12 +97,22 @@ pub struct Args {
 
     #[arg(long, env = "REALM_ID")]
     realm_id: i32,
+
+    /// Players per channel beyond this limit have to wait in the login queue.
+    #[arg(long, env = "MAX_PLAYERS")]
+    max_players: Option<u32>,
 }
 
 pub static NODE_REGISTRY: OnceLock<NodeRegistry> = OnceLock::new();
 pub static INSTANCE_REGISTRY: OnceLock<InstanceRegistry> = OnceLock::new();
 pub static SESSION_MANAGER: OnceLock<SessionManager> = OnceLock::new();
 pub static CHAT_ROUTER: OnceLock<ChatRouter> = OnceLock::new();
+pub static PARTY_REGISTRY: OnceLock<PartyRegistry> = OnceLock::new();
+pub static CLAN_MANAGER: OnceLock<ClanManager> = OnceLock::new();
+pub static SOCIAL_MANAGER: OnceLock<SocialManager> = OnceLock::new();
+pub static MAIL_MANAGER: OnceLock<MailManager> = OnceLock::new();
+pub static DUNGEON_REGISTRY: OnceLock<DungeonRegistry> = OnceLock::new();
+pub static CHAT_MODERATOR: OnceLock<ChatModerator> = OnceLock::new();
 
 #[toolkit::service_main(realm)]
 async fn main() -> RealmResult<()> {
@@ -114,7 +138,7 @@ async fn main() -> RealmResult<()> {
     info!("Starting up realm {}...", realm.name());
 
     // Init database
-    let client = Client::with_uri_str(&args.mongo_uri).await
+    let client = connect_instrumented(&args.mongo_uri).await
         .expect("Database connection failed");
 
     let db = client.database(&args.mongo_db);
@@ -135,6 +159,15 @@ async fn main() -> RealmResult<()> {
     db.init_collection::<QuestState>().await;
     db.init_collection::<QuestTemplate>().await;
     db.init_collection::<QuestDialogue>().await;
+    db.init_collection::<Clan>().await;
+    db.init_collection::<SocialRelation>().await;
+    db.init_collection::<GmAuditRecord>().await;
+    db.init_collection::<TradeRecord>().await;
+    db.init_collection::<Mail>().await;
+    db.init_collection::<LootTable>().await;
+    db.init_collection::<ChatLogEntry>().await;
+    db.init_collection::<ChatMute>().await;
+    db.init_collection::<DungeonLockout>().await;
 
     // Read content
     LazyLock::force(&EQUIPMENT_SLOTS);
@@ -153,6 +186,14 @@ async fn main() -> RealmResult<()> {
     let _ = INSTANCE_REGISTRY.set(InstanceRegistry::new(db.clone(), server.clone()));
     let _ = SESSION_MANAGER.set(SessionManager::new(core_api.clone(), server.clone()).await?);
     let _ = CHAT_ROUTER.set(ChatRouter::new(db.clone(), server.clone()));
+    let _ = PARTY_REGISTRY.set(PartyRegistry::new(db.clone(), server.clone()));
+    let _ = CLAN_MANAGER.set(ClanManager::new(db.clone(), server.clone()));
+    let _ = SOCIAL_MANAGER.set(SocialManager::new(db.clone(), server.clone()));
+    let _ = MAIL_MANAGER.set(MailManager::new(db.clone()));
+    let _ = DUNGEON_REGISTRY.set(DungeonRegistry::new(db.clone()));
+    let _ = CHAT_MODERATOR.set(ChatModerator::new(db.clone(), WordFilter::load("misc/chat_filter.txt")));
+
+    MAIL_MANAGER.get().unwrap().start_expiry(Duration::from_secs(3600));
 
     let peer_endpoints = Arc::new(Mutex::new(HashMap::new()));
 
@@ -170,7 +211,8 @@ async fn main() -> RealmResult<()> {
                             let NodeSocketAddress::Public(endpoint) = node.addr
                         {
                             // Register new frontend node with core server, so clients can connect to it
-                            let _ = core_client.send(CoreRequest::ConnectRealm(realm_id, endpoint)).await;
+                            let channel = node.channel.unwrap_or(DEFAULT_CHANNEL);
+                            let _ = core_client.send(CoreRequest::ConnectRealm(realm_id, channel, endpoint)).await;
                         }
                     },
                     node_registry::NodeRegistryEvent::NodeRemoved(node) => {
@@ -178,7 +220,8 @@ async fn main() -> RealmResult<()> {
                             proto::NodeType::Frontend => {
                                 if let NodeSocketAddress::Public(endpoint) = node.addr {
                                     // Unregister frontend node from core server
-                                    let _ = core_client.send(CoreRequest::DisconnectRealm(realm_id, endpoint)).await;
+                                    let channel = node.channel.unwrap_or(DEFAULT_CHANNEL);
+                                    let _ = core_client.send(CoreRequest::DisconnectRealm(realm_id, channel, endpoint)).await;
                                 }
                             },
                             proto::NodeType::World => {
@@ -192,6 +235,82 @@ async fn main() -> RealmResult<()> {
         });
     }
 
+    // Report population to the core server, so the login queue
+    // can hold back players while the realm is full.
+    {
+        let core_client = core_client.clone();
+        let max_players = args.max_players;
+
+        tokio::spawn(async move {
+            let mut interval = time::interval(Duration::from_secs(10));
+
+            loop {
+                interval.tick().await;
+
+                // Count players per channel, based on the cluster node they're connected to
+                let sessions = SESSION_MANAGER.get().unwrap().sessions_per_cluster_node().await;
+                let mut channels = HashMap::<u32, u32>::new();
+
+                for (_, node) in NODE_REGISTRY.get().unwrap().nodes().await {
+                    let channel = node.channel.unwrap_or(DEFAULT_CHANNEL);
+
+                    match node.ty {
+                        NodeType::Frontend => {
+                            channels.entry(channel).or_default();
+                        },
+                        NodeType::Cluster => {
+                            *channels.entry(channel).or_default() += sessions.get(&node.id).copied().unwrap_or_default() as u32;
+                        },
+                        NodeType::World => (),
+                    }
+                }
+
+                for (channel, players) in channels {
+                    let _ = core_client.send(CoreRequest::UpdateRealmPopulation { channel, players, max_players }).await;
+                }
+            }
+        });
+    }
+
+    // Purge deleted characters once their restore window has passed
+    {
+        let db = db.clone();
+
+        tokio::spawn(async move {
+            let mut interval = time::interval(Duration::from_secs(600));
+
+            loop {
+                interval.tick().await;
+
+                let characters = match Character::list_expired_deletions(&db).await {
+                    Ok(characters) => characters,
+                    Err(e) => {
+                        error!("Failed to query deleted characters: {e:?}");
+                        continue;
+                    }
+                };
+
+                // Characters failing to purge are retried on the next run
+                let mut purged = 0;
+                for id in characters {
+                    if let Err(e) = MAIL_MANAGER.get().unwrap().return_undelivered(id).await {
+                        error!("Failed to return mail of character {id}: {e:?}");
+                        continue;
+                    }
+
+                    match Character::purge(&db, id).await {
+                        Ok(_) => purged += 1,
+                        Err(e) => error!("Failed to purge character {id}: {e:?}"),
+                    }
+                }
+
+                if purged > 0 {
+                    info!("Purged {purged} deleted characters");
+                }
+            }
+        });
+    }
+
     // Start graphql api
     let schema = Schema::build(QueryRoot::default(), MutationRoot::default(), EmptySubscription)
         .data(db.clone())
@@ -208,6 +327,7 @@ async fn main() -> RealmResult<()> {
             while let Some(notification) = notifications.recv().await {
                 if let CoreNotification::SessionTerminated(id) = &notification {
                     CHAT_ROUTER.get().unwrap().disconnect_session(*id).await;
+                    PARTY_REGISTRY.get().unwrap().disconnect_session(*id).await;
                     SESSION_MANAGER.get().unwrap().terminate_session(*id).await;
                 }
 
@@ -223,13 +343,13 @@ async fn main() -> RealmResult<()> {
         endpoints: Arc<Mutex<HashMap<PeerIdentity, Endpoint>>>
     ) {
         tokio::spawn(async move {
-            while let Ok((peer, req)) = server.recv().await {
+            while let Ok((peer, request_id, req)) = server.recv().await {
                 match req {
-                    proto::RealmRequest::RegisterNode(node_type, address) => {
+                    proto::RealmRequest::RegisterNode(node_type, address, channel) => {
                         match address {
                             NodeAddress::Public(addr) => {
                                 NODE_REGISTRY.get().unwrap()
-                                    .register_node(peer, node_type, NodeSocketAddress::Public(addr)).await
+                                    .register_node(peer, node_type, NodeSocketAddress::Public(addr), channel).await
                             },
                             NodeAddress::Internal(port) => {
                                 let endpoints = endpoints.clone();
@@ -252,7 +372,7 @@ async fn main() -> RealmResult<()> {
                                                 };
 
                                                 NODE_REGISTRY.get().unwrap()
-                                                    .register_node(peer, node_type, NodeSocketAddress::Internal(SocketAddr::new(ip, port))).await
+                                                    .register_node(peer, node_type, NodeSocketAddress::Internal(SocketAddr::new(ip, port)), channel).await
                                             } else {
                                                 error!("Unsupported node endpoint: {endpoint}");
                                             }
@@ -267,25 +387,129 @@ async fn main() -> RealmResult<()> {
                             }
                         };
                     },
+                    proto::RealmRequest::NodeLoadReport(load) => {
+                        NODE_REGISTRY.get().unwrap()
+                            .update_load(&peer, load).await;
+                    },
                     proto::RealmRequest::InstanceOffering { transaction_id, key } => {
                         INSTANCE_REGISTRY.get().unwrap()
-                            .process_instance_offer(peer, transaction_id, key).await;
+                            .process_instance_offer(peer, request_id, transaction_id, key).await;
                     },
                     proto::RealmRequest::InstanceProvisioned { transaction_id } => {
                         INSTANCE_REGISTRY.get().unwrap()
                             .complete_instance_provisioning(peer, transaction_id).await;
                     },
+                    proto::RealmRequest::NodeDraining => {
+                        if let Some(node) = NODE_REGISTRY.get().unwrap().node_for_peer(&peer).await {
+                            NODE_REGISTRY.get().unwrap()
+                                .set_draining(node.id).await;
+                            INSTANCE_REGISTRY.get().unwrap()
+                                .drain_node(node.id).await;
+
+                            let _ = server.reply(&peer, request_id, RealmResponse::NodeDrainAck).await;
+                        }
+                    },
                     proto::RealmRequest::InstanceShutdownNotification(key) => {
                         debug!("Instance {key:?} shutting down...");
-                        INSTANCE_REGISTRY.get().unwrap()
-                            .remove_instance(key.clone()).await;
+                        if 
+                            let Some(node) = NODE_REGISTRY.get().unwrap().node_for_peer(&peer).await &&
+                            INSTANCE_REGISTRY.get().unwrap()
+                                .remove_instance(node.id, key.clone()).await
+                        {
+                            DUNGEON_REGISTRY.get().unwrap()
+                                .instance_closed(&key).await;
+                        }
 
-                        let _ = server.send(&peer, RealmResponse::InstanceShutdownAck(key)).await;
+                        let _ = server.reply(&peer, request_id, RealmResponse::InstanceShutdownAck(key)).await;
+                    },
+                    proto::RealmRequest::MovementViolation { session_id, kind } => {
+                        SESSION_MANAGER.get().unwrap()
+                            .record_movement_violation(session_id, kind).await;
                     },
                     proto::RealmRequest::ChatMessage { sender_id, destination, message } => {
                         CHAT_ROUTER.get().unwrap()
                             .forward_message(sender_id, destination, message).await;
                     },
+                    proto::RealmRequest::PartyInvite { session_id, character_name } => {
+                        PARTY_REGISTRY.get().unwrap()
+                            .invite(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::PartyAccept { session_id } => {
+                        PARTY_REGISTRY.get().unwrap()
+                            .accept(session_id).await;
+                    },
+                    proto::RealmRequest::PartyDecline { session_id } => {
+                        PARTY_REGISTRY.get().unwrap()
+                            .decline(session_id).await;
+                    },
+                    proto::RealmRequest::PartyKick { session_id, character_name } => {
+                        PARTY_REGISTRY.get().unwrap()
+                            .kick(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::PartyLeave { session_id } => {
+                        PARTY_REGISTRY.get().unwrap()
+                            .leave(session_id).await;
+                    },
+                    proto::RealmRequest::PartyPromote { session_id, character_name } => {
+                        PARTY_REGISTRY.get().unwrap()
+                            .promote(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::ClanCreate { session_id, name } => {
+                        CLAN_MANAGER.get().unwrap()
+                            .create(session_id, name).await;
+                    },
+                    proto::RealmRequest::ClanInvite { session_id, character_name } => {
+                        CLAN_MANAGER.get().unwrap()
+                            .invite(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::ClanAccept { session_id } => {
+                        CLAN_MANAGER.get().unwrap()
+                            .accept(session_id).await;
+                    },
+                    proto::RealmRequest::ClanDecline { session_id } => {
+                        CLAN_MANAGER.get().unwrap()
+                            .decline(session_id).await;
+                    },
+                    proto::RealmRequest::ClanKick { session_id, character_name } => {
+                        CLAN_MANAGER.get().unwrap()
+                            .kick(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::ClanLeave { session_id } => {
+                        CLAN_MANAGER.get().unwrap()
+                            .leave(session_id).await;
+                    },
+                    proto::RealmRequest::ClanPromote { session_id, character_name } => {
+                        CLAN_MANAGER.get().unwrap()
+                            .promote(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::ClanDemote { session_id, character_name } => {
+                        CLAN_MANAGER.get().unwrap()
+                            .demote(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::ClanDisband { session_id } => {
+                        CLAN_MANAGER.get().unwrap()
+                            .disband(session_id).await;
+                    },
+                    proto::RealmRequest::FriendAdd { session_id, character_name } => {
+                        SOCIAL_MANAGER.get().unwrap()
+                            .add_friend(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::FriendRemove { session_id, character_name } => {
+                        SOCIAL_MANAGER.get().unwrap()
+                            .remove_friend(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::IgnoreAdd { session_id, character_name } => {
+                        SOCIAL_MANAGER.get().unwrap()
+                            .ignore(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::IgnoreRemove { session_id, character_name } => {
+                        SOCIAL_MANAGER.get().unwrap()
+                            .unignore(session_id, character_name).await;
+                    },
+                    proto::RealmRequest::SocialListRequest { session_id } => {
+                        SOCIAL_MANAGER.get().unwrap()
+                            .list(session_id).await;
+                    },
                     proto::RealmRequest::ClientConnected { session_id } => {
                         if let Some(node) = NODE_REGISTRY.get().unwrap().node_for_peer(&peer).await {
                             SESSION_MANAGER.get().unwrap()
@@ -0,0 +1,34 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::sync::LazyLock;
+
+use prometheus::{register_int_counter, register_int_gauge, IntCounter, IntGauge};
+
+pub static INSTANCE_REQUESTS_PENDING: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("realm_instance_requests_pending", "Number of instance requests waiting for a world node")
+        .expect("metric can be registered")
+});
+
+pub static INSTANCE_REQUESTS_FAILED: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("realm_instance_requests_failed_total", "Number of instance requests no world node answered")
+        .expect("metric can be registered")
+});
+
+pub static INSTANCES: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("realm_instances", "Number of provisioned zone instances")
+        .expect("metric can be registered")
+});
@@ -13,7 +13,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, fmt::Display, net::SocketAddr, sync::Arc};
+use std::{collections::{HashMap, HashSet}, fmt::Display, net::SocketAddr, sync::Arc};
 
 use cluster::{ClusterEvent, PeerIdentity};
 use log::info;
@@ -21,7 +21,7 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::{broadcast::{self, Receiver}, RwLock};
 use toolkit::types::Uuid;
 
-use crate::proto::{NodeType, RealmServer};
+use crate::proto::{NodeLoad, NodeType, RealmServer};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum NodeSocketAddress {
@@ -49,10 +49,13 @@ pub struct Node {
     pub id: Uuid,
     pub ty: NodeType,
     pub addr: NodeSocketAddress,
+    pub channel: Option<u32>,
 }
 
 struct NodeRegistryData {
     nodes: HashMap<PeerIdentity, Node>,
+    loads: HashMap<Uuid, NodeLoad>,
+    draining: HashSet<Uuid>,
 }
 
 #[derive(Clone)]
@@ -67,6 +70,8 @@ impl NodeRegistry {
         let (events, _) = broadcast::channel(10);
         let data = Arc::new(RwLock::new(NodeRegistryData {  
             nodes: HashMap::new(),
+            loads: HashMap::new(),
+            draining: HashSet::new(),
         }));
 
         NodeRegistry::start_monitor(data.clone(), events.clone(), server_events);
@@ -77,18 +82,23 @@ impl NodeRegistry {
         }
     }
 
-    pub async fn register_node(&self, peer_identity: PeerIdentity, node_type: NodeType, address: NodeSocketAddress) {
+    pub async fn register_node(&self, peer_identity: PeerIdentity, node_type: NodeType, address: NodeSocketAddress, channel: Option<u32>) {
         let mut state = self.data.write().await;
 
         let entry = state.nodes.entry(peer_identity)
             .or_insert(Node {
                 id: Uuid::new(),
                 ty: node_type, 
-                addr: address
+                addr: address,
+                channel,
             })
             .clone();
 
-        info!("Registered {node_type} at {address}");
+        if let Some(channel) = channel {
+            info!("Registered {node_type} at {address} for channel {channel}");
+        } else {
+            info!("Registered {node_type} at {address}");
+        }
 
         let _ = self.events.send(NodeRegistryEvent::NodeAdded(entry));
     }
@@ -100,6 +110,9 @@ impl NodeRegistry {
                     let mut state = state.write().await;
 
                     if let Some(node) = state.nodes.remove(&peer_identity) {
+                        state.loads.remove(&node.id);
+                        state.draining.remove(&node.id);
+
                         info!("Unregistered {} at {}", node.ty, node.addr);
                         
                         let _ = events.send(NodeRegistryEvent::NodeRemoved(node));
@@ -109,6 +122,39 @@ impl NodeRegistry {
         });
     }
 
+    pub async fn update_load(&self, peer: &PeerIdentity, load: NodeLoad) {
+        let mut s = self.data.write().await;
+        if let Some(id) = s.nodes.get(peer).map(|node| node.id) {
+            s.loads.insert(id, load);
+        }
+    }
+
+    pub async fn load(&self, id: Uuid) -> Option<NodeLoad> {
+        let s = self.data.read().await;
+        s.loads.get(&id).cloned()
+    }
+
+    /// Loads of all nodes, which accept new instances.
+    pub async fn available_loads(&self) -> Vec<NodeLoad> {
+        let s = self.data.read().await;
+        s.loads.iter()
+            .filter(|(id, _)| !s.draining.contains(id))
+            .map(|(_, load)| load.clone())
+            .collect()
+    }
+
+    pub async fn set_draining(&self, id: Uuid) {
+        let mut s = self.data.write().await;
+        if s.draining.insert(id) {
+            info!("Node {id} is draining");
+        }
+    }
+
+    pub async fn is_draining(&self, id: Uuid) -> bool {
+        let s = self.data.read().await;
+        s.draining.contains(&id)
+    }
+
     pub async fn node_for_peer(&self, peer: &PeerIdentity) -> Option<Node> {
         let s = self.data.read().await;
         s.nodes.get(peer)
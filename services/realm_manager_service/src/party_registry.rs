@@ -0,0 +1,497 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use database::{DatabaseError, DatabaseRecord};
+use mongodb::Database;
+use thiserror::Error;
+use tokio::{sync::Mutex, time::Instant};
+use toolkit::types::{Uuid, UUID_NIL};
+
+use crate::{db::Character, player_requests::{impl_request_error, report_error, MessageOutbox}, proto::{Destination, RealmNotification, RealmServer}, SESSION_MANAGER};
+
+const MAX_PARTY_SIZE: usize = 5;
+const INVITE_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Error, Debug)]
+pub enum PartyError {
+    #[error("session not found")]
+    SessionNotFound,
+
+    #[error("Character not found.")]
+    CharacterNotFound,
+
+    #[error("{0} is not online.")]
+    NotOnline(String),
+
+    #[error("{0} is already in a party.")]
+    AlreadyInParty(String),
+
+    #[error("You are not in a party.")]
+    NotInParty,
+
+    #[error("{0} is not a member of your party.")]
+    NotAMember(String),
+
+    #[error("Only the party leader can do that.")]
+    NotLeader,
+
+    #[error("The party is full.")]
+    PartyFull,
+
+    #[error("You have no pending party invite.")]
+    NoInvite,
+
+    #[error("You can't target yourself.")]
+    SelfTarget,
+
+    #[error(transparent)]
+    DatabaseError(#[from] DatabaseError),
+
+    #[error(transparent)]
+    MongodbError(#[from] mongodb::error::Error),
+}
+
+impl_request_error!(PartyError);
+
+#[derive(Clone)]
+pub struct Party {
+    pub id: Uuid,
+    pub leader: Uuid,
+    pub members: Vec<Uuid>, // Character ids
+}
+
+struct PartyInvite {
+    inviter: Uuid,
+    inviter_name: String,
+    valid_until: Instant,
+}
+
+#[derive(Default)]
+struct Outbox {
+    updates: Vec<Party>,
+    messages: MessageOutbox,
+}
+
+impl Outbox {
+    fn update(&mut self, party: &Party) {
+        self.updates.push(party.clone());
+    }
+
+    fn message(&mut self, recipients: Vec<Uuid>, party_id: Uuid, message: String) {
+        self.messages.push(recipients, Destination::Party(party_id), message);
+    }
+}
+
+#[derive(Default)]
+struct PartyRegistryData {
+    parties: HashMap<Uuid, Party>,
+    memberships: HashMap<Uuid, Uuid>,
+    invites: HashMap<Uuid, PartyInvite>,
+    names: HashMap<Uuid, String>,
+}
+
+impl PartyRegistryData {
+    fn purge_invites(&mut self) {
+        let now = Instant::now();
+        self.invites.retain(|_, invite| invite.valid_until > now);
+    }
+
+    fn name(&self, character_id: &Uuid) -> String {
+        self.names.get(character_id).cloned().unwrap_or_default()
+    }
+
+    fn remove_member(&mut self, party_id: Uuid, character_id: Uuid, outbox: &mut Outbox) {
+        self.memberships.remove(&character_id);
+        self.names.remove(&character_id);
+
+        let Some(party) = self.parties.get_mut(&party_id) else {
+            return;
+        };
+
+        party.members.retain(|member| *member != character_id);
+
+        if party.members.len() < 2 {
+            // A party of one is no party at all
+            let mut party = self.parties.remove(&party_id).unwrap();
+
+            for member in party.members.drain(..) {
+                self.memberships.remove(&member);
+                self.names.remove(&member);
+
+                outbox.message(vec![member], party_id, "Your party has been disbanded.".to_string());
+            }
+
+            outbox.update(&party);
+        } else {
+            if party.leader == character_id {
+                party.leader = party.members[0];
+
+                let leader = party.leader;
+                let members = party.members.clone();
+                let name = self.name(&leader);
+
+                outbox.message(members, party_id, format!("{name} is now the party leader."));
+            }
+
+            outbox.update(&self.parties[&party_id]);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PartyRegistry {
+    db: Database,
+    server: Arc<RealmServer>,
+    data: Arc<Mutex<PartyRegistryData>>,
+}
+
+impl PartyRegistry {
+    pub fn new(db: Database, server: Arc<RealmServer>) -> Self {
+        Self {
+            db,
+            server,
+            data: Arc::new(Mutex::new(PartyRegistryData::default())),
+        }
+    }
+
+    pub async fn party(&self, party_id: Uuid) -> Option<Party> {
+        self.data.lock().await
+            .parties.get(&party_id).cloned()
+    }
+
+    pub async fn party_for_character(&self, character_id: Uuid) -> Option<Party> {
+        let s = self.data.lock().await;
+        s.memberships.get(&character_id)
+            .and_then(|party_id| s.parties.get(party_id))
+            .cloned()
+    }
+
+    /// Returns the session ids of all online party members.
+    pub async fn member_sessions(&self, party_id: Uuid) -> Vec<Uuid> {
+        let Some(party) = self.party(party_id).await else {
+            return vec![];
+        };
+
+        let mut sessions = Vec::with_capacity(party.members.len());
+        for member in party.members {
+            if let Some(state) = SESSION_MANAGER.get().unwrap().get_state_for_character(member).await {
+                sessions.push(state.id);
+            }
+        }
+
+        sessions
+    }
+
+    pub async fn invite(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_invite(session_id, character_name).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn accept(&self, session_id: Uuid) {
+        let res = self.try_accept(session_id).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn decline(&self, session_id: Uuid) {
+        let res = self.try_decline(session_id).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn kick(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_kick(session_id, character_name).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn leave(&self, session_id: Uuid) {
+        let res = self.try_leave(session_id).await;
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn promote(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_promote(session_id, character_name).await;
+        self.finish(session_id, res).await;
+    }
+
+    /// Removes the sessions character from its party and drops
+    /// all pending invites. Must be called before the session
+    /// state is removed from the session manager.
+    pub async fn disconnect_session(&self, session_id: Uuid) {
+        let Some(state) = SESSION_MANAGER.get().unwrap().get_state(session_id).await else {
+            return;
+        };
+
+        let mut outbox = Outbox::default();
+
+        {
+            let mut s = self.data.lock().await;
+            let character_id = state.character_id;
+
+            s.invites.retain(|invitee, invite| *invitee != character_id && invite.inviter != character_id);
+
+            if let Some(party_id) = s.memberships.get(&character_id).copied() {
+                let name = s.name(&character_id);
+                let others = s.parties[&party_id].members.iter()
+                    .filter(|member| **member != character_id)
+                    .copied()
+                    .collect();
+
+                outbox.message(others, party_id, format!("{name} has left the party."));
+                s.remove_member(party_id, character_id, &mut outbox);
+            }
+        }
+
+        self.dispatch(outbox).await;
+    }
+
+    async fn try_invite(&self, session_id: Uuid, character_name: String) -> Result<Outbox, PartyError> {
+        let inviter = self.session_character(session_id).await?;
+        let target = self.find_character(&character_name).await?;
+
+        if target.id == inviter.id {
+            return Err(PartyError::SelfTarget);
+        }
+
+        if SESSION_MANAGER.get().unwrap().get_state_for_character(target.id).await.is_none() {
+            return Err(PartyError::NotOnline(target.name));
+        }
+
+        let mut s = self.data.lock().await;
+        let mut outbox = Outbox::default();
+
+        s.purge_invites();
+
+        if s.memberships.contains_key(&target.id) {
+            return Err(PartyError::AlreadyInParty(target.name));
+        }
+
+        let party_id = if let Some(party_id) = s.memberships.get(&inviter.id) {
+            let party = &s.parties[party_id];
+
+            if party.leader != inviter.id {
+                return Err(PartyError::NotLeader);
+            } else if party.members.len() >= MAX_PARTY_SIZE {
+                return Err(PartyError::PartyFull);
+            }
+
+            party.id
+        } else {
+            UUID_NIL
+        };
+
+        s.invites.insert(target.id, PartyInvite {
+            inviter: inviter.id,
+            inviter_name: inviter.name.clone(),
+            valid_until: Instant::now() + INVITE_TIMEOUT,
+        });
+
+        outbox.message(vec![target.id], party_id, format!("{} has invited you to join a party.", inviter.name));
+        outbox.message(vec![inviter.id], party_id, format!("You have invited {} to join your party.", target.name));
+
+        Ok(outbox)
+    }
+
+    async fn try_accept(&self, session_id: Uuid) -> Result<Outbox, PartyError> {
+        let character = self.session_character(session_id).await?;
+
+        let mut s = self.data.lock().await;
+        let mut outbox = Outbox::default();
+
+        s.purge_invites();
+
+        let invite = s.invites.remove(&character.id)
+            .ok_or(PartyError::NoInvite)?;
+
+        if s.memberships.contains_key(&character.id) {
+            return Err(PartyError::AlreadyInParty(character.name));
+        }
+
+        let party_id = if let Some(party_id) = s.memberships.get(&invite.inviter).copied() {
+            let party = &s.parties[&party_id];
+
+            // The invite is void if the inviter lost leadership in the meantime
+            if party.leader != invite.inviter {
+                return Err(PartyError::NoInvite);
+            } else if party.members.len() >= MAX_PARTY_SIZE {
+                return Err(PartyError::PartyFull);
+            }
+
+            party_id
+        } else {
+            let party = Party {
+                id: Uuid::new(),
+                leader: invite.inviter,
+                members: vec![invite.inviter],
+            };
+
+            s.memberships.insert(invite.inviter, party.id);
+            s.names.insert(invite.inviter, invite.inviter_name);
+            s.parties.insert(party.id, party.clone());
+
+            party.id
+        };
+
+        s.memberships.insert(character.id, party_id);
+        s.names.insert(character.id, character.name.clone());
+
+        let party = s.parties.get_mut(&party_id).unwrap();
+        party.members.push(character.id);
+
+        outbox.update(party);
+        outbox.message(party.members.clone(), party_id, format!("{} has joined the party.", character.name));
+
+        Ok(outbox)
+    }
+
+    async fn try_decline(&self, session_id: Uuid) -> Result<Outbox, PartyError> {
+        let character = self.session_character(session_id).await?;
+
+        let mut s = self.data.lock().await;
+        let mut outbox = Outbox::default();
+
+        s.purge_invites();
+
+        let invite = s.invites.remove(&character.id)
+            .ok_or(PartyError::NoInvite)?;
+
+        outbox.message(vec![invite.inviter], UUID_NIL, format!("{} has declined your party invite.", character.name));
+
+        Ok(outbox)
+    }
+
+    async fn try_kick(&self, session_id: Uuid, character_name: String) -> Result<Outbox, PartyError> {
+        let leader = self.session_character(session_id).await?;
+        let target = self.find_character(&character_name).await?;
+
+        if target.id == leader.id {
+            return Err(PartyError::SelfTarget);
+        }
+
+        let mut s = self.data.lock().await;
+        let mut outbox = Outbox::default();
+
+        let party = s.memberships.get(&leader.id)
+            .and_then(|party_id| s.parties.get(party_id))
+            .ok_or(PartyError::NotInParty)?;
+
+        if party.leader != leader.id {
+            return Err(PartyError::NotLeader);
+        } else if !party.members.contains(&target.id) {
+            return Err(PartyError::NotAMember(target.name));
+        }
+
+        let party_id = party.id;
+        let others = party.members.iter()
+            .filter(|member| **member != target.id)
+            .copied()
+            .collect();
+
+        outbox.message(vec![target.id], party_id, "You have been removed from the party.".to_string());
+        outbox.message(others, party_id, format!("{} has been removed from the party.", target.name));
+
+        s.remove_member(party_id, target.id, &mut outbox);
+
+        Ok(outbox)
+    }
+
+    async fn try_leave(&self, session_id: Uuid) -> Result<Outbox, PartyError> {
+        let character = self.session_character(session_id).await?;
+
+        let mut s = self.data.lock().await;
+        let mut outbox = Outbox::default();
+
+        let party = s.memberships.get(&character.id)
+            .and_then(|party_id| s.parties.get(party_id))
+            .ok_or(PartyError::NotInParty)?;
+
+        let party_id = party.id;
+        let others = party.members.iter()
+            .filter(|member| **member != character.id)
+            .copied()
+            .collect();
+
+        outbox.message(vec![character.id], party_id, "You have left the party.".to_string());
+        outbox.message(others, party_id, format!("{} has left the party.", character.name));
+
+        s.remove_member(party_id, character.id, &mut outbox);
+
+        Ok(outbox)
+    }
+
+    async fn try_promote(&self, session_id: Uuid, character_name: String) -> Result<Outbox, PartyError> {
+        let leader = self.session_character(session_id).await?;
+        let target = self.find_character(&character_name).await?;
+
+        if target.id == leader.id {
+            return Err(PartyError::SelfTarget);
+        }
+
+        let mut s = self.data.lock().await;
+        let mut outbox = Outbox::default();
+
+        let party = s.memberships.get(&leader.id).copied()
+            .and_then(|party_id| s.parties.get_mut(&party_id))
+            .ok_or(PartyError::NotInParty)?;
+
+        if party.leader != leader.id {
+            return Err(PartyError::NotLeader);
+        } else if !party.members.contains(&target.id) {
+            return Err(PartyError::NotAMember(target.name));
+        }
+
+        party.leader = target.id;
+
+        outbox.update(party);
+        outbox.message(party.members.clone(), party.id, format!("{} is now the party leader.", target.name));
+
+        Ok(outbox)
+    }
+
+    async fn session_character(&self, session_id: Uuid) -> Result<Character, PartyError> {
+        let state = SESSION_MANAGER.get().unwrap().get_state(session_id).await
+            .ok_or(PartyError::SessionNotFound)?;
+
+        Character::get(&self.db, &state.character_id).await?
+            .ok_or(PartyError::CharacterNotFound)
+    }
+
+    async fn find_character(&self, name: &str) -> Result<Character, PartyError> {
+        Character::find_by_name(&self.db, name, false).await?
+            .ok_or(PartyError::CharacterNotFound)
+    }
+
+    async fn finish(&self, session_id: Uuid, res: Result<Outbox, PartyError>) {
+        match res {
+            Ok(outbox) => self.dispatch(outbox).await,
+            Err(e) => report_error(session_id, Destination::Party(UUID_NIL), "Party", e).await,
+        }
+    }
+
+    // Messages and notifications are sent after the registry lock
+    // has been released, as the chat router queries the registry
+    // while routing party messages.
+    async fn dispatch(&self, outbox: Outbox) {
+        for party in outbox.updates {
+            let _ = self.server.notify(RealmNotification::PartyUpdated {
+                id: party.id,
+                leader: party.leader,
+                members: party.members,
+            }).await;
+        }
+
+        outbox.messages.deliver().await;
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use log::error;
+use toolkit::types::Uuid;
+
+use crate::{proto::Destination, CHAT_ROUTER, SESSION_MANAGER};
+
+/// Errors of requests issued by players. Client errors are
+/// reported back to the player, everything else is logged.
+pub trait RequestError: std::error::Error {
+    fn is_client_error(&self) -> bool;
+}
+
+/// Implements `RequestError` for errors which share the `SessionNotFound`,
+/// `DatabaseError` and `MongodbError` variants, which are not the player's fault.
+macro_rules! impl_request_error {
+    ($error:ty) => {
+        impl $crate::player_requests::RequestError for $error {
+            fn is_client_error(&self) -> bool {
+                !matches!(self, Self::SessionNotFound | Self::DatabaseError(_) | Self::MongodbError(_))
+            }
+        }
+    };
+}
+
+pub(crate) use impl_request_error;
+
+/// System messages for characters, which are collected while a manager
+/// holds its locks and delivered once they have been released.
+#[derive(Default)]
+pub struct MessageOutbox(Vec<(Vec<Uuid>, Destination, String)>);
+
+impl MessageOutbox {
+    pub fn push(&mut self, recipients: Vec<Uuid>, destination: Destination, message: String) {
+        self.0.push((recipients, destination, message));
+    }
+
+    /// Sends the messages to every recipient that's currently online.
+    pub async fn deliver(self) {
+        for (recipients, destination, message) in self.0 {
+            let mut sessions = Vec::with_capacity(recipients.len());
+            for character_id in recipients {
+                if let Some(state) = SESSION_MANAGER.get().unwrap().get_state_for_character(character_id).await {
+                    sessions.push(state.id);
+                }
+            }
+
+            CHAT_ROUTER.get().unwrap()
+                .system_message(sessions, destination, message).await;
+        }
+    }
+}
+
+/// Tells the player why their request failed, or logs the error
+/// if it isn't something the player can do anything about.
+pub async fn report_error<E: RequestError>(session_id: Uuid, destination: Destination, request: &str, err: E) {
+    if err.is_client_error() {
+        CHAT_ROUTER.get().unwrap()
+            .system_message(vec![session_id], destination, err.to_string()).await;
+    } else {
+        error!("{request} request failed: {err:?}");
+    }
+}
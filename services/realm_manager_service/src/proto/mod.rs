@@ -24,6 +24,7 @@ use serde::{Deserialize, Serialize};
 use toolkit::types::{AvatarId, Uuid};
 
 pub use crate::chat_router::Destination;
+pub use crate::db::ClanRank;
 
 #[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
 pub struct InstanceKey(Uuid, Option<Uuid>);
@@ -47,6 +48,23 @@ impl Display for InstanceKey {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClanMembership {
+    pub id: Uuid,
+    pub name: String,
+    pub rank: ClanRank,
+}
+
+/// Periodically reported by world nodes, used for instance placement.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct NodeLoad {
+    pub instances: u32,
+    pub instance_limit: u32,
+    pub players: u32,
+    pub tick_budget: f32, // Share of the tick interval spent updating instances
+    pub tags: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum NodeAddress {
     Public(SocketAddr),
@@ -59,9 +77,22 @@ impl Display for NodeAddress {
     }
 }
 
+/// Channel assumed for nodes that don't serve a specific one.
+pub const DEFAULT_CHANNEL: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum MovementViolationKind {
+    Speed,
+    Height,
+}
+
 #[derive(Serialize, Deserialize)]
 pub enum RealmRequest {
-    RegisterNode(NodeType, NodeAddress),
+    /// Frontend and cluster nodes serve a single channel of the realm,
+    /// world nodes are shared by all channels.
+    RegisterNode(NodeType, NodeAddress, Option<u32>),
+    NodeLoadReport(NodeLoad),
+    NodeDraining,
     ClientConnected { session_id: Uuid },
     ClientDisconnected { session_id: Uuid },
     InstanceOffering {
@@ -76,10 +107,78 @@ pub enum RealmRequest {
         sender_id: Option<Uuid>,
         destination: Destination,
         message: String,
-    }
+    },
+    PartyInvite {
+        session_id: Uuid,
+        character_name: String,
+    },
+    PartyAccept { session_id: Uuid },
+    PartyDecline { session_id: Uuid },
+    PartyKick {
+        session_id: Uuid,
+        character_name: String,
+    },
+    PartyLeave { session_id: Uuid },
+    PartyPromote {
+        session_id: Uuid,
+        character_name: String,
+    },
+    ClanCreate {
+        session_id: Uuid,
+        name: String,
+    },
+    ClanInvite {
+        session_id: Uuid,
+        character_name: String,
+    },
+    ClanAccept { session_id: Uuid },
+    ClanDecline { session_id: Uuid },
+    ClanKick {
+        session_id: Uuid,
+        character_name: String,
+    },
+    ClanLeave { session_id: Uuid },
+    ClanPromote {
+        session_id: Uuid,
+        character_name: String,
+    },
+    ClanDemote {
+        session_id: Uuid,
+        character_name: String,
+    },
+    ClanDisband { session_id: Uuid },
+    FriendAdd {
+        session_id: Uuid,
+        character_name: String,
+    },
+    FriendRemove {
+        session_id: Uuid,
+        character_name: String,
+    },
+    IgnoreAdd {
+        session_id: Uuid,
+        character_name: String,
+    },
+    IgnoreRemove {
+        session_id: Uuid,
+        character_name: String,
+    },
+    SocialListRequest { session_id: Uuid },
+    MovementViolation {
+        session_id: Uuid,
+        kind: MovementViolationKind,
+    },
 }
 
-impl Request for RealmRequest {}
+impl Request for RealmRequest {
+    fn expects_reply(&self) -> bool {
+        matches!(self, 
+            RealmRequest::NodeDraining | 
+            RealmRequest::InstanceOffering { .. } |
+            RealmRequest::InstanceShutdownNotification(_)
+        )
+    }
+}
 
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -89,6 +188,7 @@ pub enum RealmResponse {
         key: InstanceKey 
     },
     InstanceShutdownAck(InstanceKey),
+    NodeDrainAck,
     ChatMessage {
         recipients: Vec<Uuid>, // Session ids
         sender_id: Option<AvatarId>,
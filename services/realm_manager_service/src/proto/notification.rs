@@ -19,7 +19,7 @@ use core_api::proto::CoreNotification;
 use serde::{Deserialize, Serialize};
 use toolkit::types::Uuid;
 
-use crate::node_registry::Node;
+use crate::{node_registry::Node, proto::ClanMembership};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum RealmNotification {
@@ -35,7 +35,20 @@ pub enum RealmNotification {
     ItemStorageUpdated { 
         id: Uuid,
         tag: Option<String>, 
-    }
+    },
+    PartyUpdated {
+        id: Uuid,
+        leader: Uuid,
+        members: Vec<Uuid>, // Character ids, empty if the party was disbanded
+    },
+    ClanMembershipUpdated {
+        character_id: Uuid,
+        clan: Option<ClanMembership>,
+    },
+    IgnoreListUpdated {
+        character_id: Uuid,
+        ignored: Vec<Uuid>, // Character ids
+    },
 }
 
 impl Notification for RealmNotification {
@@ -46,6 +59,9 @@ impl Notification for RealmNotification {
             RealmNotification::NodeRemoved(_) => "cluster.node.removed",
             RealmNotification::InstanceRequested { .. } => "realm.instance.request",
             RealmNotification::ItemStorageUpdated { .. } => "realm.item_storage.updated",
+            RealmNotification::PartyUpdated { .. } => "realm.party.updated",
+            RealmNotification::ClanMembershipUpdated { .. } => "realm.clan.membership_updated",
+            RealmNotification::IgnoreListUpdated { .. } => "realm.social.ignore_list_updated",
         }
     }
 }
\ No newline at end of file
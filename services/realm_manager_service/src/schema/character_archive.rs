@@ -0,0 +1,58 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::{Context, Error, Object};
+use mongodb::Database;
+use toolkit::types::Uuid;
+
+use crate::character_archive::{self, CharacterArchive, ImportedCharacter, NameConflict};
+
+#[derive(Default)]
+pub struct CharacterArchiveRoot;
+
+#[derive(Default)]
+pub struct CharacterArchiveMutationRoot;
+
+#[Object]
+impl CharacterArchiveRoot {
+    /// Serializes the character and everything stored for it into a versioned archive.
+    async fn export_character(&self, ctx: &Context<'_>, id: Uuid) -> Result<serde_json::Value, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        Ok(character_archive::export_character(&db, id).await?.to_json()?)
+    }
+
+    /// Archives all characters of the account, except those pending deletion.
+    async fn export_account(&self, ctx: &Context<'_>, account_id: Uuid) -> Result<serde_json::Value, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        Ok(character_archive::export_account(&db, account_id).await?.to_json()?)
+    }
+}
+
+#[Object]
+impl CharacterArchiveMutationRoot {
+    /// Imports the characters of an archive into the account, with fresh ids.
+    async fn import_characters(
+        &self, 
+        ctx: &Context<'_>, 
+        account_id: Uuid, 
+        archive: serde_json::Value, 
+        #[graphql(default)] name_conflict: NameConflict
+    ) -> Result<Vec<ImportedCharacter>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        let archive = CharacterArchive::from_json(archive)?;
+
+        Ok(character_archive::import_archive(&db, archive, account_id, name_conflict).await?)
+    }
+}
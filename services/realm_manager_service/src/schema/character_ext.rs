@@ -39,7 +39,8 @@ impl CharacterExtRoot {
         let res = db::Character::collection(&db).find_one(doc! {
             "$and": [
                 { "account": account_id },
-                { "index": index }
+                { "index": index },
+                { "purge_at": null }
             ]
         }).await?;
 
@@ -53,7 +54,7 @@ impl CharacterExtRoot {
     async fn characters_for_account(&self, ctx: &Context<'_>, account_id: Uuid) -> Result<Vec<CharacterOutput>, Error> {
         let db = ctx.data::<Database>()?.clone();
         let mut res = db::Character::collection(&db)
-            .find(doc! {"account": account_id})
+            .find(doc! {"account": account_id, "purge_at": null})
             .sort(doc! {"index": 1})
             .await?;
         let mut characters = Vec::new();
@@ -64,6 +65,22 @@ impl CharacterExtRoot {
 
         Ok(characters)
     }
+
+    /// Characters of the account that are pending deletion and can still be restored.
+    async fn deleted_characters_for_account(&self, ctx: &Context<'_>, account_id: Uuid) -> Result<Vec<CharacterOutput>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        let mut res = db::Character::collection(&db)
+            .find(doc! {"account": account_id, "purge_at": { "$ne": null }})
+            .sort(doc! {"purge_at": 1})
+            .await?;
+        let mut characters = Vec::new();
+
+        while let Some(character) = res.try_next().await? {
+            characters.push(character.try_into()?);
+        }
+
+        Ok(characters)
+    }
 }
 
 #[Object]
@@ -132,11 +149,46 @@ impl CharacterExtMutationRoot {
             index: next_index,
             name: input.name,
             data,
+            purge_at: None,
         }).await?;
 
         Ok(character.try_into()?)
     }
 
+    /// Marks the character for deletion. It's purged once the grace period
+    /// has passed, until then it can be restored with `restoreCharacter`.
+    pub async fn schedule_character_deletion(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<CharacterOutput>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+
+        if let Some(character) = Character::schedule_deletion(&db, id).await? {
+            Ok(Some(character.try_into()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn restore_character(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<CharacterOutput>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+
+        let Some(character) = Character::get(&db, &id).await? else {
+            return Ok(None);
+        };
+
+        if !character.is_pending_delete() {
+            return Err(anyhow!("character is not pending deletion").into());
+        }
+
+        if !Character::restore(&db, id).await? {
+            return Err(anyhow!("restore window has expired").into());
+        }
+
+        if let Some(character) = Character::get(&db, &id).await? {
+            Ok(Some(character.try_into()?))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub async fn update_character_data_diff(&self, ctx: &Context<'_>, id: Uuid, params: serde_json::Value) -> Result<Option<CharacterOutput>, Error> {
         let db = ctx.data::<Database>()?.clone();
 
@@ -0,0 +1,78 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::{Context, Error, Object};
+use chrono::{DateTime, Utc};
+use mongodb::Database;
+use toolkit::types::Uuid;
+
+use crate::{db::{ChatChannel, ChatLogEntry, ChatLogEntryOutput}, CHAT_MODERATOR, SESSION_MANAGER};
+
+/// Upper bound for chat log searches, to keep responses reasonably sized.
+const MAX_CHAT_LOG_RESULTS: i64 = 500;
+
+#[derive(Default)]
+pub struct ChatModerationRoot;
+
+#[derive(Default)]
+pub struct ChatModerationMutationRoot;
+
+#[Object]
+impl ChatModerationRoot {
+    /// Searches the chat log, newest entries first.
+    #[allow(clippy::too_many_arguments)]
+    async fn search_chat_log(
+        &self, 
+        ctx: &Context<'_>, 
+        account_id: Option<Uuid>, 
+        character_id: Option<Uuid>, 
+        channel: Option<ChatChannel>, 
+        text: Option<String>, 
+        since: Option<DateTime<Utc>>, 
+        until: Option<DateTime<Utc>>, 
+        #[graphql(default = 100)] limit: i64
+    ) -> Result<Vec<ChatLogEntryOutput>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+
+        Ok(ChatLogEntry::search(&db, account_id, character_id, channel, text.as_deref(), since, until, limit.clamp(1, MAX_CHAT_LOG_RESULTS)).await?
+            .into_iter()
+            .map(ChatLogEntryOutput::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+#[Object]
+impl ChatModerationMutationRoot {
+    /// Runs a message sent by a player through moderation and returns
+    /// the text to deliver. Used by world nodes for local chat.
+    async fn moderate_chat(&self, _ctx: &Context<'_>, session_id: Uuid, channel: ChatChannel, message: String) -> Result<String, Error> {
+        let zone_id = SESSION_MANAGER.get().unwrap().get_state(session_id).await
+            .and_then(|state| state.zone);
+
+        Ok(CHAT_MODERATOR.get().unwrap().moderate(session_id, zone_id, channel, None, message)?)
+    }
+
+    async fn mute_character(&self, _ctx: &Context<'_>, character_name: String, issued_by: Uuid, minutes: i32, reason: String) -> Result<String, Error> {
+        let minutes = u32::try_from(minutes).ok()
+            .filter(|minutes| *minutes > 0)
+            .ok_or(Error::new("Mute duration must be positive."))?;
+
+        Ok(CHAT_MODERATOR.get().unwrap().mute(&character_name, issued_by, minutes, reason).await?)
+    }
+
+    async fn unmute_character(&self, _ctx: &Context<'_>, character_name: String) -> Result<String, Error> {
+        Ok(CHAT_MODERATOR.get().unwrap().unmute(&character_name).await?)
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::{Context, Error, Object, SimpleObject};
+use toolkit::types::Uuid;
+
+use crate::{db::{ClanOutput, ClanRank}, proto, CLAN_MANAGER};
+
+#[derive(Default)]
+pub struct ClanExtRoot;
+
+#[derive(Default)]
+pub struct ClanExtMutationRoot;
+
+#[Object]
+impl ClanExtRoot {
+    async fn clan_membership(&self, _ctx: &Context<'_>, character_id: Uuid) -> Result<Option<ClanMembership>, Error> {
+        Ok(CLAN_MANAGER.get().unwrap().membership(character_id).await?
+            .map(|membership| membership.into()))
+    }
+}
+
+#[Object]
+impl ClanExtMutationRoot {
+    async fn found_clan(&self, _ctx: &Context<'_>, name: String, leader: Uuid) -> Result<ClanOutput, Error> {
+        Ok(CLAN_MANAGER.get().unwrap().create_clan(name, leader).await?.try_into()?)
+    }
+
+    async fn disband_clan(&self, _ctx: &Context<'_>, id: Uuid) -> Result<bool, Error> {
+        CLAN_MANAGER.get().unwrap().disband_clan(id).await?;
+        Ok(true)
+    }
+
+    async fn add_clan_member(&self, _ctx: &Context<'_>, id: Uuid, character_id: Uuid, rank: ClanRank) -> Result<ClanOutput, Error> {
+        Ok(CLAN_MANAGER.get().unwrap().add_member(id, character_id, rank).await?.try_into()?)
+    }
+
+    async fn remove_clan_member(&self, _ctx: &Context<'_>, id: Uuid, character_id: Uuid) -> Result<Option<ClanOutput>, Error> {
+        Ok(CLAN_MANAGER.get().unwrap().remove_member(id, character_id).await?
+            .map(ClanOutput::try_from)
+            .transpose()?)
+    }
+
+    async fn set_clan_member_rank(&self, _ctx: &Context<'_>, id: Uuid, character_id: Uuid, rank: ClanRank) -> Result<ClanOutput, Error> {
+        Ok(CLAN_MANAGER.get().unwrap().set_rank(id, character_id, rank).await?.try_into()?)
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ClanMembership {
+    id: Uuid,
+    name: String,
+    rank: ClanRank,
+}
+
+impl From<proto::ClanMembership> for ClanMembership {
+    fn from(value: proto::ClanMembership) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            rank: value.rank,
+        }
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::{Context, Error, Object};
+use chrono::Utc;
+use database::DatabaseRecord;
+use mongodb::Database;
+use toolkit::{types::Uuid, ObjectId};
+
+use crate::db::GmAuditRecord;
+
+#[derive(Default)]
+pub struct GmAuditMutationRoot;
+
+#[Object]
+impl GmAuditMutationRoot {
+    async fn log_gm_command(&self, ctx: &Context<'_>, account_id: Uuid, character_id: Uuid, zone_id: Uuid, command: String, arguments: Vec<String>) -> Result<bool, Error> {
+        let db = ctx.data::<Database>()?.clone();
+
+        GmAuditRecord::create(&db, GmAuditRecord {
+            id: ObjectId::default(),
+            account_id,
+            character_id,
+            zone_id,
+            command,
+            arguments,
+            timestamp: Utc::now(),
+        }).await?;
+
+        Ok(true)
+    }
+}
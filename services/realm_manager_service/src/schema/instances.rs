@@ -16,7 +16,7 @@
 use async_graphql::{Context, Error, Object, SimpleObject};
 use toolkit::types::Uuid;
 
-use crate::{proto::InstanceKey, INSTANCE_REGISTRY};
+use crate::{dungeon_registry::DungeonScope, proto::InstanceKey, DUNGEON_REGISTRY, INSTANCE_REGISTRY};
 
 use super::nodes::{Node, NodesRoot};
 
@@ -57,6 +57,13 @@ impl InstancesMutationRoot {
                 .ok_or(Error::new("node not found"))?
         })
     }
+
+    /// Picks the private instance of a dungeon zone for the character,
+    /// based on the dungeons scope, its party and dungeon lockouts.
+    pub async fn enter_dungeon(&self, character_id: Uuid, zone_id: Uuid, scope: DungeonScope) -> Result<Uuid, Error> {
+        Ok(DUNGEON_REGISTRY.get().unwrap()
+            .enter(character_id, zone_id, scope).await?)
+    }
 }
 
 #[derive(SimpleObject)]
@@ -19,10 +19,10 @@ use anyhow::anyhow;
 use async_graphql::{Context, Error, Json, Object, OneofObject, SimpleObject};
 use database::DatabaseRecord;
 use mongodb::{Database, bson::doc};
-use obj_params::GenericParamSet;
+use obj_params::{GenericParamSet, ItemBase};
 use toolkit::{NativeParam, transaction_with_retry, types::Uuid};
 
-use crate::{db::{self, Character, FlatennedStorageOwner, Item, ItemStorageOutput, ObjectTemplate, SkillbookOutput, StorageOwner}, error::RealmResult, item_storage_session::{ItemStorageSession, ItemStorageSessionError, ItemStorageSessionResult}, proto::{RealmNotification, RealmServer}};
+use crate::{db::{self, BUYBACK_STORAGE, CashShopItem, CashShopVendor, Character, FlatennedStorageOwner, Item, ItemStorageOutput, ObjectTemplate, SkillbookOutput, StorageOwner}, error::RealmResult, item_storage_session::{ItemStorageSession, ItemStorageSessionError, ItemStorageSessionResult, PURCHASE_FAILED}, proto::{RealmNotification, RealmServer}};
 
 #[derive(Default)]
 pub struct ItemStorageExtMutationRoot;
@@ -36,12 +36,23 @@ pub enum ItemRef {
 
 #[derive(SimpleObject, Default)]
 pub struct StorageResult {
-    storage_id: Uuid,
-    error: Option<async_graphql::Json<(String, Option<NativeParam>)>>,
+    pub(super) storage_id: Uuid,
+    pub(super) error: Option<async_graphql::Json<(String, Option<NativeParam>)>>,
     changed_items: Option<Vec<Item>>,
     removed_items: Option<Vec<Uuid>>,
     bling: Option<i32>,
     game_cash: Option<i32>,
+    soma: Option<Vec<i32>>,
+}
+
+impl StorageResult {
+    pub fn client_error(storage_id: Uuid, str: &str, e: Option<NativeParam>) -> Self {
+        Self {
+            storage_id,
+            error: Some(async_graphql::Json((str.to_string(), e))),
+            ..Default::default()
+        }
+    }
 }
 
 impl From<ItemStorageSessionResult> for StorageResult {
@@ -61,6 +72,7 @@ impl From<ItemStorageSessionResult> for StorageResult {
             removed_items: if result.removed_items.is_empty() { None } else { Some(result.removed_items.clone()) },
             bling: result.bling,
             game_cash: result.game_cash,
+            soma: result.soma,
         }
     }
 }
@@ -79,6 +91,116 @@ pub enum Price {
     GameCash(i32),
 }
 
+/// Returns the vendor price of an item template, preferring game cash over bling.
+fn vendor_price(template: &ObjectTemplate) -> Option<Price> {
+    let data = &template.data;
+
+    let game_cash = (*data.get::<_, i32>(ItemBase::BuyPriceGameCash).unwrap_or(&0))
+        .max(*data.get::<_, i32>(ItemBase::GameCashPrice).unwrap_or(&0));
+    let bling = (*data.get::<_, i32>(ItemBase::BuyPriceBling).unwrap_or(&0))
+        .max(*data.get::<_, i32>(ItemBase::BlingPrice).unwrap_or(&0));
+
+    if game_cash > 0 {
+        Some(Price::GameCash(game_cash))
+    } else if bling > 0 {
+        Some(Price::Bling(bling))
+    } else {
+        None
+    }
+}
+
+async fn charge_price(session: &mut ItemStorageSession, price: &Price) -> Result<(), ItemStorageSessionError> {
+    match *price {
+        Price::Bling(amount) if amount > 0 => session.take_cash(amount).await,
+        Price::GameCash(amount) if amount > 0 => session.take_bits(amount).await,
+        _ => Ok(()),
+    }
+}
+
+async fn storage_account(db: &Database, owner: &StorageOwner) -> Result<Uuid, ItemStorageSessionError> {
+    match owner {
+        StorageOwner::Account(account) => Ok(*account),
+        StorageOwner::Character(id) => Ok(
+            Character::get(db, id).await?
+                .ok_or(anyhow!("character {} not found", id))?
+                .account
+        ),
+        StorageOwner::Guild(_) => Err(anyhow!("guild storages can't purchase cash shop items").into()),
+    }
+}
+
+/// A validated shopping cart entry.
+enum CartEntry {
+    CashShop(CashShopItem, ObjectTemplate),
+    Vendor(ObjectTemplate, Price),
+}
+
+/// Looks up a shopping cart entry and makes sure it is actually for sale. Cash shop
+/// items must be in stock and listed by a cash shop vendor, item templates must be
+/// sold by the vendor the cart is purchased from.
+async fn resolve_cart_entry(db: &Database, vendor_items: &[Uuid], id: &Uuid) -> Result<CartEntry, ItemStorageSessionError> {
+    if let Some(offer) = CashShopItem::get(db, id).await? {
+        let listed = CashShopVendor::collection(db)
+            .find_one(doc! { "sku_list": id })
+            .await?
+            .is_some();
+
+        if !offer.is_in_stock || !listed {
+            return Err(ItemStorageSessionError::ClientError(PURCHASE_FAILED, None));
+        }
+
+        let template = ObjectTemplate::get(db, &offer.reference_item_guid).await?
+            .ok_or(anyhow!("item {} not found", offer.reference_item_guid))?;
+
+        Ok(CartEntry::CashShop(offer, template))
+    } else if let Some(template) = ObjectTemplate::get(db, id).await? {
+        if !vendor_items.contains(id) {
+            return Err(ItemStorageSessionError::ClientError(PURCHASE_FAILED, None));
+        }
+
+        let price = vendor_price(&template)
+            .ok_or(ItemStorageSessionError::ClientError(PURCHASE_FAILED, None))?;
+
+        Ok(CartEntry::Vendor(template, price))
+    } else {
+        Err(anyhow!("item {} not found", id).into())
+    }
+}
+
+/// Buys every entry of a shopping cart. Entries are either cash shop items, 
+/// paid with premium currency, or item templates sold by vendors.
+/// No currency is taken unless every entry is for sale.
+async fn purchase_cart(db: &Database, session: &mut ItemStorageSession, vendor_items: &[Uuid], items: &[Uuid]) -> Result<(), ItemStorageSessionError> {
+    let mut entries = Vec::with_capacity(items.len());
+    for id in items {
+        entries.push(resolve_cart_entry(db, vendor_items, id).await?);
+    }
+
+    for entry in entries {
+        match entry {
+            CartEntry::CashShop(offer, template) => {
+                let account = storage_account(db, session.owner()).await?;
+
+                session.take_premium_currency(&account, offer.cash_price, format!("purchase {}", offer.sku_code)).await?;
+                session.insert_item(template, None, None).await?;
+            },
+            CartEntry::Vendor(template, price) => {
+                charge_price(session, &price).await?;
+                session.insert_item(template, None, None).await?;
+            },
+        }
+    }
+
+    Ok(())
+}
+
+async fn buyback_storage_id(db: &Database, id: Uuid) -> Result<Uuid, Error> {
+    let storage = db::ItemStorage::get(db, &id).await?
+        .ok_or(anyhow!("storage {} not found", id))?;
+
+    Ok(db::ItemStorage::get_or_create_for_owner(db, BUYBACK_STORAGE, storage.owner).await?.id)
+}
+
 pub async fn find_item(db: &Database, item_ref: ItemRef) -> RealmResult<Option<ObjectTemplate>> {
     match item_ref {
         ItemRef::Name(name) => {
@@ -175,6 +297,7 @@ impl ItemStorageExtMutationRoot {
                                 removed_items: None, 
                                 bling: None,
                                 game_cash: None,
+                                soma: None,
                             }
                         ))
                     },
@@ -211,6 +334,7 @@ impl ItemStorageExtMutationRoot {
                                     removed_items: None, 
                                     bling: None,
                                     game_cash: None,
+                                    soma: None,
                                 }
                             ));
                         },
@@ -258,6 +382,7 @@ impl ItemStorageExtMutationRoot {
                             removed_items: None, 
                             bling: None,
                             game_cash: None,
+                            soma: None,
                         }
                     ))
                 },
@@ -290,6 +415,7 @@ impl ItemStorageExtMutationRoot {
                                 removed_items: None, 
                                 bling: None,
                                 game_cash: None,
+                                soma: None,
                             }
                         ))
                     },
@@ -332,6 +458,7 @@ impl ItemStorageExtMutationRoot {
                             removed_items: None, 
                             bling: None,
                             game_cash: None,
+                            soma: None,
                         }
                     ))
                 },
@@ -343,8 +470,32 @@ impl ItemStorageExtMutationRoot {
         Ok(res)
     }
 
-    pub async fn storage_transfer_item(&self, _ctx: &Context<'_>, _tag: Option<String>, _id: Uuid, _item_id: Uuid, _new_storage: Uuid, _new_slot: i32) -> Result<Vec<StorageResult>, Error> {
-        unimplemented!()
+    pub async fn storage_transfer_item(&self, ctx: &Context<'_>, tag: Option<String>, id: Uuid, item_id: Uuid, new_storage: Uuid, new_slot: i32) -> Result<Vec<StorageResult>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+
+        let res = transaction_with_retry(db.clone(), async |session| -> RealmResult<_> {
+            let mut session = ItemStorageSession::with_session(&db, session, id).await?;
+
+            match session.transfer_item(item_id, new_storage, new_slot).await {
+                Ok(_) => {
+                    let (session, results) = session.write_uncommitted().await?;
+                    Ok((session, results.into_iter().map(StorageResult::from).collect::<Vec<_>>()))
+                },
+                Err(ItemStorageSessionError::ClientError(str, e)) => {
+                    Ok((
+                        session.abort(),
+                        vec![StorageResult::client_error(id, str, e)]
+                    ))
+                },
+                Err(e) => Err(e.into())
+            }
+        }).await?;
+
+        for res in &res {
+            send_inventory_update_notifications(ctx, tag.clone(), res).await?;
+        }
+
+        Ok(res)
     }
 
     pub async fn storage_equip_item(&self, ctx: &Context<'_>, tag: Option<String>, id: Uuid, item_id: Uuid, idx: Option<i32>) -> Result<EquipmentResult, Error> {
@@ -443,15 +594,55 @@ impl ItemStorageExtMutationRoot {
         unimplemented!()
     }
 
-    pub async fn storage_purchase_item(&self, ctx: &Context<'_>, tag: Option<String>, id: Uuid, base_item: ItemRef, _price: Price) -> Result<StorageResult, Error> {
+    /// Credits looted bits to this storage.
+    pub async fn storage_add_bits(&self, ctx: &Context<'_>, tag: Option<String>, id: Uuid, amount: i32) -> Result<StorageResult, Error> {
+        let db = ctx.data::<Database>()?.clone();
+
+        let res = transaction_with_retry(db.clone(), async |session| -> RealmResult<_> {
+            let mut session = ItemStorageSession::with_session(&db, session, id).await?;
+            session.add_bits(amount).await?;
+
+            let (session, results) = session.write_uncommitted().await?;
+            Ok((session, results.into_iter().next().unwrap().into()))
+        }).await?;
+
+        send_inventory_update_notifications(ctx, tag, &res).await?;
+
+        Ok(res)
+    }
+
+    /// Credits looted soma to the character owning this storage.
+    pub async fn storage_add_soma(&self, ctx: &Context<'_>, tag: Option<String>, id: Uuid, soma_type: i32, amount: i32) -> Result<StorageResult, Error> {
+        let db = ctx.data::<Database>()?.clone();
+
+        let res = transaction_with_retry(db.clone(), async |session| -> RealmResult<_> {
+            let mut session = ItemStorageSession::with_session(&db, session, id).await?;
+            session.add_soma(soma_type, amount).await?;
+
+            let (session, results) = session.write_uncommitted().await?;
+            Ok((session, results.into_iter().next().unwrap().into()))
+        }).await?;
+
+        send_inventory_update_notifications(ctx, tag, &res).await?;
+
+        Ok(res)
+    }
+
+    pub async fn storage_purchase_item(&self, ctx: &Context<'_>, tag: Option<String>, id: Uuid, base_item: ItemRef, price: Price) -> Result<StorageResult, Error> {
         let db = ctx.data::<Database>()?.clone();
         let base_item = &base_item;
+        let price = &price;
 
         let res = transaction_with_retry(db.clone(), async |session| -> RealmResult<_> {
             let mut session = ItemStorageSession::with_session(&db, session, id).await?;
 
             if let Some(item) = find_item(&db, base_item.clone()).await? {
-                match session.insert_item(item, None, None).await {
+                let res = match charge_price(&mut session, price).await {
+                    Ok(_) => session.insert_item(item, None, None).await,
+                    Err(e) => Err(e),
+                };
+
+                match res {
                     Ok(_) => {
                         let (session, results) = session.write_uncommitted().await?;
                         let res = results.into_iter().next().unwrap();
@@ -460,15 +651,8 @@ impl ItemStorageExtMutationRoot {
                     },
                     Err(ItemStorageSessionError::ClientError(str, e)) => {
                         Ok((
-                            session.abort(),
-                            StorageResult { 
-                                storage_id: id, 
-                                error: Some(async_graphql::Json((str.to_string(), e))),
-                                changed_items: None, 
-                                removed_items: None, 
-                                bling: None,
-                                game_cash: None,
-                            }
+                            session.abort(), 
+                            StorageResult::client_error(id, str, e)
                         ))
                     },
                     Err(e) => Err(e.into())
@@ -483,7 +667,94 @@ impl ItemStorageExtMutationRoot {
         Ok(res)
     }
 
-    pub async fn storage_sell_item(&self, _ctx: &Context<'_>, _tag: Option<String>, _id: Uuid, _item_id: Uuid) -> Result<StorageResult, Error> {
-        unimplemented!()
+    /// Purchases all items of a shopping cart in a single transaction. 
+    /// Either every item is bought, or none.
+    pub async fn storage_purchase_cart(&self, ctx: &Context<'_>, tag: Option<String>, id: Uuid, items: Vec<Uuid>, vendor_items: Vec<Uuid>) -> Result<StorageResult, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        let items = &items;
+        let vendor_items = &vendor_items;
+
+        let res = transaction_with_retry(db.clone(), async |session| -> RealmResult<_> {
+            let mut session = ItemStorageSession::with_session(&db, session, id).await?;
+
+            match purchase_cart(&db, &mut session, vendor_items, items).await {
+                Ok(_) => {
+                    let (session, results) = session.write_uncommitted().await?;
+                    let res = results.into_iter().next().unwrap();
+
+                    Ok((session, res.into()))
+                },
+                Err(ItemStorageSessionError::ClientError(str, e)) => {
+                    Ok((
+                        session.abort(), 
+                        StorageResult::client_error(id, str, e)
+                    ))
+                },
+                Err(e) => Err(e.into())
+            }
+        }).await?;
+
+        send_inventory_update_notifications(ctx, tag, &res).await?;
+
+        Ok(res)
     }
-}
\ No newline at end of file
+
+    pub async fn storage_sell_item(&self, ctx: &Context<'_>, tag: Option<String>, id: Uuid, item_id: Uuid) -> Result<StorageResult, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        let buyback_id = buyback_storage_id(&db, id).await?;
+
+        let res = transaction_with_retry(db.clone(), async |session| -> RealmResult<_> {
+            let mut session = ItemStorageSession::with_session(&db, session, id).await?;
+
+            match session.sell_item(item_id, buyback_id).await {
+                Ok(_) => {
+                    let (session, results) = session.write_uncommitted().await?;
+                    Ok((session, results.into_iter().map(StorageResult::from).collect::<Vec<_>>()))
+                },
+                Err(ItemStorageSessionError::ClientError(str, e)) => {
+                    Ok((
+                        session.abort(), 
+                        vec![StorageResult::client_error(id, str, e)]
+                    ))
+                },
+                Err(e) => Err(e.into())
+            }
+        }).await?;
+
+        for res in &res {
+            send_inventory_update_notifications(ctx, tag.clone(), res).await?;
+        }
+
+        Ok(res.into_iter().next().unwrap())
+    }
+
+    /// Buys back an item previously sold from this storage.
+    pub async fn storage_buyback_item(&self, ctx: &Context<'_>, tag: Option<String>, id: Uuid, item_id: Uuid) -> Result<StorageResult, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        let buyback_id = buyback_storage_id(&db, id).await?;
+
+        let res = transaction_with_retry(db.clone(), async |session| -> RealmResult<_> {
+            let mut session = ItemStorageSession::with_session(&db, session, id).await?;
+
+            match session.buyback_item(item_id, buyback_id).await {
+                Ok(_) => {
+                    let (session, results) = session.write_uncommitted().await?;
+                    Ok((session, results.into_iter().map(StorageResult::from).collect::<Vec<_>>()))
+                },
+                Err(ItemStorageSessionError::ClientError(str, e)) => {
+                    Ok((
+                        session.abort(), 
+                        vec![StorageResult::client_error(id, str, e)]
+                    ))
+                },
+                Err(e) => Err(e.into())
+            }
+        }).await?;
+
+        for res in &res {
+            send_inventory_update_notifications(ctx, tag.clone(), res).await?;
+        }
+
+        Ok(res.into_iter().next().unwrap())
+    }
+}
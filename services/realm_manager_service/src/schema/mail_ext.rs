@@ -0,0 +1,102 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use async_graphql::{Context, Error, Object};
+use mongodb::Database;
+use toolkit::types::Uuid;
+
+use crate::{db::MailOutput, item_storage_session::ItemStorageSessionResult, proto::{RealmNotification, RealmServer}, schema::item_storage_ext::{find_item, ItemRef, StorageResult}, MAIL_MANAGER};
+
+#[derive(Default)]
+pub struct MailExtRoot;
+
+#[derive(Default)]
+pub struct MailExtMutationRoot;
+
+#[Object]
+impl MailExtRoot {
+    async fn mailbox(&self, _ctx: &Context<'_>, character_id: Uuid) -> Result<Vec<MailOutput>, Error> {
+        Ok(MAIL_MANAGER.get().unwrap().mailbox(character_id).await?
+            .into_iter()
+            .map(MailOutput::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+#[Object]
+impl MailExtMutationRoot {
+    /// Sends mail from a character, taking attachments from its storage.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_mail(&self, ctx: &Context<'_>, tag: Option<String>, sender_id: Uuid, storage_id: Uuid, recipient_name: String, subject: String, body: String, items: Vec<Uuid>, bits: i32) -> Result<Vec<StorageResult>, Error> {
+        let (_, results) = MAIL_MANAGER.get().unwrap()
+            .send(sender_id, storage_id, &recipient_name, subject, body, items, bits).await?;
+
+        notify_storages(ctx, tag, results).await
+    }
+
+    /// Sends mail on behalf of the realm, creating the attached items.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_system_mail(&self, ctx: &Context<'_>, sender_name: String, recipient_name: String, subject: String, body: String, items: Vec<ItemRef>, bits: i32) -> Result<MailOutput, Error> {
+        let db = ctx.data::<Database>()?;
+
+        let mut templates = Vec::new();
+        for item in items {
+            templates.push(
+                find_item(db, item).await?
+                    .ok_or(Error::new("Item not found"))?
+                    .id
+            );
+        }
+
+        Ok(MAIL_MANAGER.get().unwrap()
+            .send_system(sender_name, &recipient_name, subject, body, templates, bits).await?
+            .try_into()?)
+    }
+
+    async fn read_mail(&self, _ctx: &Context<'_>, character_id: Uuid, mail_id: Uuid) -> Result<MailOutput, Error> {
+        Ok(MAIL_MANAGER.get().unwrap().read(character_id, mail_id).await?.try_into()?)
+    }
+
+    async fn take_mail_attachments(&self, ctx: &Context<'_>, tag: Option<String>, character_id: Uuid, mail_id: Uuid, storage_id: Uuid) -> Result<Vec<StorageResult>, Error> {
+        let results = MAIL_MANAGER.get().unwrap()
+            .take_attachments(character_id, mail_id, storage_id).await?;
+
+        notify_storages(ctx, tag, results).await
+    }
+
+    async fn return_mail(&self, _ctx: &Context<'_>, character_id: Uuid, mail_id: Uuid) -> Result<bool, Error> {
+        MAIL_MANAGER.get().unwrap().return_to_sender(character_id, mail_id).await?;
+        Ok(true)
+    }
+
+    async fn delete_mail(&self, _ctx: &Context<'_>, character_id: Uuid, mail_id: Uuid) -> Result<bool, Error> {
+        MAIL_MANAGER.get().unwrap().delete(character_id, mail_id).await?;
+        Ok(true)
+    }
+}
+
+async fn notify_storages(ctx: &Context<'_>, tag: Option<String>, results: Vec<ItemStorageSessionResult>) -> Result<Vec<StorageResult>, Error> {
+    let server = ctx.data::<Arc<RealmServer>>()?;
+
+    for res in &results {
+        server.notify(RealmNotification::ItemStorageUpdated { id: res.id, tag: tag.clone() }).await?;
+    }
+
+    Ok(results.into_iter()
+        .map(StorageResult::from)
+        .collect())
+}
@@ -15,13 +15,21 @@
 
 use abilitybar_ext::AbilityBarExtMutationRoot;
 use async_graphql::MergedObject;
+use character_archive::{CharacterArchiveMutationRoot, CharacterArchiveRoot};
 use character_ext::{CharacterExtMutationRoot, CharacterExtRoot};
+use clan_ext::{ClanExtMutationRoot, ClanExtRoot};
+use gm_audit::GmAuditMutationRoot;
+use trade::TradeMutationRoot;
+use mail_ext::{MailExtMutationRoot, MailExtRoot};
 use instances::{InstancesMutationRoot, InstancesRoot};
 use item_storage_ext::ItemStorageExtMutationRoot;
 use nodes::NodesRoot;
+use party::PartyRoot;
 use premium_currency::{PremiumCurrencyMutationRoot, PremiumCurrencyRoot};
 use session_state::{SessionStateMutationRoot, SessionStateRoot};
 use skillbook_ext::SkillbookExtMutationRoot;
+use social::SocialRoot;
+use chat_moderation::{ChatModerationMutationRoot, ChatModerationRoot};
 
 use crate::{db, schema::{object_placements_ext::ObjectPlacementsExtRoot, queststate_ext::QuestStateExtMutationRoot}};
 
@@ -36,6 +44,14 @@ mod skillbook_ext;
 mod abilitybar_ext;
 mod queststate_ext;
 mod object_placements_ext;
+mod party;
+mod clan_ext;
+mod social;
+mod gm_audit;
+mod trade;
+mod mail_ext;
+mod chat_moderation;
+mod character_archive;
 
 pub use types::*;
 
@@ -46,6 +62,9 @@ pub struct QueryRoot(
     pub NodesRoot,
     pub InstancesRoot,
     pub SessionStateRoot,
+    pub PartyRoot,
+    pub ClanExtRoot,
+    pub SocialRoot,
     pub db::WorldDefQueryRoot,
     pub db::ZoneQueryRoot,
     pub db::ObjectPlacementQueryRoot,
@@ -62,6 +81,17 @@ pub struct QueryRoot(
     pub db::QuestStateQueryRoot,
     pub db::QuestTemplateQueryRoot,
     pub db::QuestDialogueQueryRoot,
+    pub db::ClanQueryRoot,
+    pub db::SocialRelationQueryRoot,
+    pub db::GmAuditRecordQueryRoot,
+    pub db::TradeRecordQueryRoot,
+    pub db::MailQueryRoot,
+    pub db::LootTableQueryRoot,
+    pub db::ChatLogEntryQueryRoot,
+    pub db::ChatMuteQueryRoot,
+    pub ChatModerationRoot,
+    pub CharacterArchiveRoot,
+    pub MailExtRoot,
     pub ObjectPlacementsExtRoot,
 );
 
@@ -91,4 +121,14 @@ pub struct MutationRoot(
     pub QuestStateExtMutationRoot,
     pub db::QuestTemplateMutationRoot,
     pub db::QuestDialogueMutationRoot,
+    pub db::ClanMutationRoot,
+    pub ClanExtMutationRoot,
+    pub db::SocialRelationMutationRoot,
+    pub GmAuditMutationRoot,
+    pub TradeMutationRoot,
+    pub MailExtMutationRoot,
+    pub db::LootTableMutationRoot,
+    pub db::ChatMuteMutationRoot,
+    pub ChatModerationMutationRoot,
+    pub CharacterArchiveMutationRoot,
 );
\ No newline at end of file
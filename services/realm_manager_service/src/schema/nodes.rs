@@ -61,6 +61,7 @@ pub struct Node {
     id: Uuid,
     ty: NodeType,
     addr: NodeAddress,
+    channel: Option<u32>,
 }
 
 impl From<node_registry::Node> for Node {
@@ -78,6 +79,7 @@ impl From<node_registry::Node> for Node {
                     port: addr.port(), 
                 }),
             },
+            channel: value.channel,
         }
     }
 }
@@ -0,0 +1,52 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::{Context, Error, Object, SimpleObject};
+use toolkit::types::Uuid;
+
+use crate::{party_registry, PARTY_REGISTRY};
+
+#[derive(Default)]
+pub struct PartyRoot;
+
+#[Object]
+impl PartyRoot {
+    async fn party(&self, _ctx: &Context<'_>, id: Uuid) -> Result<Option<Party>, Error> {
+        Ok(PARTY_REGISTRY.get().unwrap().party(id).await
+            .map(|party| party.into()))
+    }
+
+    async fn party_for_character(&self, _ctx: &Context<'_>, character_id: Uuid) -> Result<Option<Party>, Error> {
+        Ok(PARTY_REGISTRY.get().unwrap().party_for_character(character_id).await
+            .map(|party| party.into()))
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Party {
+    id: Uuid,
+    leader: Uuid,
+    members: Vec<Uuid>,
+}
+
+impl From<party_registry::Party> for Party {
+    fn from(value: party_registry::Party) -> Self {
+        Self {
+            id: value.id,
+            leader: value.leader,
+            members: value.members,
+        }
+    }
+}
@@ -48,6 +48,8 @@ pub struct SessionState {
     character: Uuid,
     zone: Option<Uuid>,
     instance: Option<Uuid>,
+    speed_violations: u32,
+    height_violations: u32,
 }
 
 impl From<Arc<session_manager::SessionState>> for SessionState {
@@ -58,6 +60,8 @@ impl From<Arc<session_manager::SessionState>> for SessionState {
             character: value.character_id,
             zone: value.zone,
             instance: value.instance,
+            speed_violations: value.speed_violations,
+            height_violations: value.height_violations,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,29 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use async_graphql::{Context, Error, Object};
+use toolkit::types::Uuid;
+
+use crate::SOCIAL_MANAGER;
+
+#[derive(Default)]
+pub struct SocialRoot;
+
+#[Object]
+impl SocialRoot {
+    async fn ignored_characters(&self, _ctx: &Context<'_>, character_id: Uuid) -> Result<Vec<Uuid>, Error> {
+        Ok(SOCIAL_MANAGER.get().unwrap().ignored_characters(character_id).await?)
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use async_graphql::{Context, Error, InputObject, Object};
+use chrono::Utc;
+use mongodb::Database;
+use toolkit::{transaction_with_retry, types::Uuid, ObjectId};
+
+use crate::{db::TradeRecord, error::RealmResult, item_storage_session::{ItemStorageSession, ItemStorageSessionError}, proto::{RealmNotification, RealmServer}, schema::item_storage_ext::StorageResult};
+
+#[derive(Default)]
+pub struct TradeMutationRoot;
+
+/// What one side of a trade hands over.
+#[derive(InputObject)]
+pub struct TradeOffer {
+    pub character_id: Uuid,
+    pub storage_id: Uuid,
+    pub items: Vec<Uuid>,
+    pub bits: i32,
+}
+
+#[Object]
+impl TradeMutationRoot {
+    /// Exchanges both offers in a single transaction and logs the trade.
+    /// Returns the results of both storages, or a single error result.
+    async fn execute_trade(&self, ctx: &Context<'_>, tag: Option<String>, left: TradeOffer, right: TradeOffer) -> Result<Vec<StorageResult>, Error> {
+        let db = ctx.data::<Database>()?.clone();
+        let (left, right) = (&left, &right);
+
+        if left.storage_id == right.storage_id || left.bits < 0 || right.bits < 0 {
+            return Err(Error::new("invalid trade"));
+        }
+
+        let res = transaction_with_retry(db.clone(), async |session| -> RealmResult<_> {
+            let mut session = ItemStorageSession::with_session(&db, session, left.storage_id).await?;
+
+            match session.exchange(&left.items, left.bits, right.storage_id, &right.items, right.bits).await {
+                Ok(_) => {
+                    let (mut session, results) = session.write_uncommitted().await?;
+
+                    TradeRecord::write(&db, &mut session, TradeRecord {
+                        id: ObjectId::default(),
+                        left_character: left.character_id,
+                        left_items: left.items.clone(),
+                        left_bits: left.bits,
+                        right_character: right.character_id,
+                        right_items: right.items.clone(),
+                        right_bits: right.bits,
+                        timestamp: Utc::now(),
+                    }).await?;
+
+                    Ok((session, results.into_iter().map(StorageResult::from).collect::<Vec<_>>()))
+                },
+                Err(ItemStorageSessionError::ClientError(str, e)) => {
+                    Ok((
+                        session.abort(),
+                        vec![StorageResult::client_error(left.storage_id, str, e)]
+                    ))
+                },
+                Err(e) => Err(e.into())
+            }
+        }).await?;
+
+        let server = ctx.data::<Arc<RealmServer>>()?;
+        for res in &res {
+            if res.error.is_none() {
+                server.notify(RealmNotification::ItemStorageUpdated { id: res.storage_id, tag: tag.clone() }).await?;
+            }
+        }
+
+        Ok(res)
+    }
+}
@@ -20,7 +20,7 @@ use rand::random;
 use tokio::sync::Mutex;
 use toolkit::types::{AvatarId, AvatarType, Uuid};
 
-use crate::{error::RealmResult, proto::RealmServer, CHAT_ROUTER};
+use crate::{error::RealmResult, proto::{MovementViolationKind, RealmServer}, CHAT_ROUTER, SOCIAL_MANAGER};
 
 struct SessionManagerData {
     _core_api: CoreApi,
@@ -38,6 +38,8 @@ pub struct SessionState {
     pub zone: Option<Uuid>,
     pub instance: Option<Uuid>,
     pub cluster_node: Option<Uuid>,
+    pub speed_violations: u32,
+    pub height_violations: u32,
 }
 
 #[derive(Clone)]
@@ -61,11 +63,30 @@ impl SessionManager {
         s.states.get(&session).cloned()
     }
 
+    /// Number of sessions connected to each cluster node.
+    pub async fn sessions_per_cluster_node(&self) -> HashMap<Uuid, usize> {
+        let s = self.0.lock().await;
+        let mut counts = HashMap::new();
+
+        for node in s.states.values().filter_map(|state| state.cluster_node) {
+            *counts.entry(node).or_default() += 1;
+        }
+
+        counts
+    }
+
     pub async fn get_state_for_avatar(&self, avatar_id: AvatarId) -> Option<Arc<SessionState>> {
         let s = self.0.lock().await;
         s.avatars.get(&avatar_id).cloned()
     }
 
+    pub async fn get_state_for_character(&self, character_id: Uuid) -> Option<Arc<SessionState>> {
+        let s = self.0.lock().await;
+        s.states.values()
+            .find(|state| state.character_id == character_id)
+            .cloned()
+    }
+
     pub async fn join_game(&self, session: Uuid, character_id: Uuid) -> RealmResult<Arc<SessionState>> {
         let mut s = self.0.lock().await;
         
@@ -89,6 +110,8 @@ impl SessionManager {
             zone: None,
             instance: None,
             cluster_node: None,
+            speed_violations: 0,
+            height_violations: 0,
         });
 
         s.states.insert(session, state.clone());
@@ -96,6 +119,10 @@ impl SessionManager {
 
         CHAT_ROUTER.get().unwrap().connect_session(session).await;
 
+        // The social manager looks up sessions itself, so release the lock first.
+        drop(s);
+        SOCIAL_MANAGER.get().unwrap().character_online(character_id).await;
+
         Ok(state)
     }
 
@@ -122,6 +149,22 @@ impl SessionManager {
         }
     }
 
+    /// Counts a rejected movement update. Counters live as long as the session,
+    /// so they survive zone changes for GM review.
+    pub async fn record_movement_violation(&self, session: Uuid, kind: MovementViolationKind) {
+        let mut s = self.0.lock().await;
+        
+        if let Some(state) = s.states.get(&session).cloned() {
+            let mut state = state.deref().clone();
+            match kind {
+                MovementViolationKind::Speed => state.speed_violations += 1,
+                MovementViolationKind::Height => state.height_violations += 1,
+            }
+
+            s.states.insert(session, Arc::new(state));
+        }
+    }
+
     pub async fn terminate_session(&self, session: Uuid) {
         let mut s = self.0.lock().await;
         if let Some(state) = s.states.remove(&session) {
@@ -129,6 +172,9 @@ impl SessionManager {
 
             // Free avatar id
             s.avatar_ids.remove(&state.avatar_id);
+
+            drop(s);
+            SOCIAL_MANAGER.get().unwrap().character_offline(state.character_id).await;
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,277 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use database::{DatabaseError, DatabaseRecord};
+use log::error;
+use mongodb::Database;
+use thiserror::Error;
+use toolkit::{types::Uuid, ObjectId};
+
+use crate::{db::{Character, SocialRelation, SocialRelationKind}, player_requests::{impl_request_error, report_error}, proto::{Destination, RealmNotification, RealmServer}, CHAT_ROUTER, SESSION_MANAGER};
+
+#[derive(Error, Debug)]
+pub enum SocialError {
+    #[error("session not found")]
+    SessionNotFound,
+
+    #[error("Character not found.")]
+    CharacterNotFound,
+
+    #[error("You can't target yourself.")]
+    SelfTarget,
+
+    #[error("{0} is already on your friends list.")]
+    AlreadyFriend(String),
+
+    #[error("{0} is not on your friends list.")]
+    NotAFriend(String),
+
+    #[error("You are already ignoring {0}.")]
+    AlreadyIgnored(String),
+
+    #[error("You are not ignoring {0}.")]
+    NotIgnored(String),
+
+    #[error(transparent)]
+    DatabaseError(#[from] DatabaseError),
+
+    #[error(transparent)]
+    MongodbError(#[from] mongodb::error::Error),
+}
+
+impl_request_error!(SocialError);
+
+/// Maintains friend and ignore lists. Relations are stored per character
+/// in the realm database, so they are available across all cluster nodes.
+#[derive(Clone)]
+pub struct SocialManager {
+    db: Database,
+    server: Arc<RealmServer>,
+}
+
+impl SocialManager {
+    pub fn new(db: Database, server: Arc<RealmServer>) -> Self {
+        Self {
+            db,
+            server,
+        }
+    }
+
+    pub async fn ignored_characters(&self, character_id: Uuid) -> Result<Vec<Uuid>, SocialError> {
+        Ok(SocialRelation::list_for_character(&self.db, &character_id, SocialRelationKind::Ignore).await?
+            .into_iter()
+            .map(|relation| relation.target_id)
+            .collect())
+    }
+
+    pub async fn is_ignoring(&self, character_id: Uuid, target_id: Uuid) -> bool {
+        match SocialRelation::is_ignoring(&self.db, &character_id, &target_id).await {
+            Ok(ignoring) => ignoring,
+            Err(e) => {
+                error!("Failed to query ignore list: {e:?}");
+                false
+            }
+        }
+    }
+
+    pub async fn add_friend(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_set_relation(session_id, &character_name, SocialRelationKind::Friend).await
+            .map(|_| format!("{character_name} has been added to your friends list."));
+
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn remove_friend(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_remove_relation(session_id, &character_name, SocialRelationKind::Friend).await
+            .map(|_| format!("{character_name} has been removed from your friends list."));
+
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn ignore(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_set_relation(session_id, &character_name, SocialRelationKind::Ignore).await
+            .map(|_| format!("You are now ignoring {character_name}."));
+
+        self.finish(session_id, res).await;
+    }
+
+    pub async fn unignore(&self, session_id: Uuid, character_name: String) {
+        let res = self.try_remove_relation(session_id, &character_name, SocialRelationKind::Ignore).await
+            .map(|_| format!("You are no longer ignoring {character_name}."));
+
+        self.finish(session_id, res).await;
+    }
+
+    /// Sends the friend and ignore list of the sessions character as system messages.
+    pub async fn list(&self, session_id: Uuid) {
+        let res = self.try_list(session_id).await;
+        self.finish(session_id, res).await;
+    }
+
+    /// Tells everyone who befriended the character that it came online.
+    pub async fn character_online(&self, character_id: Uuid) {
+        self.notify_watchers(character_id, "is now online").await;
+    }
+
+    /// Tells everyone who befriended the character that it went offline.
+    pub async fn character_offline(&self, character_id: Uuid) {
+        self.notify_watchers(character_id, "has gone offline").await;
+    }
+
+    async fn try_set_relation(&self, session_id: Uuid, character_name: &str, kind: SocialRelationKind) -> Result<(), SocialError> {
+        let character = self.session_character(session_id).await?;
+        let target = self.find_character(character_name).await?;
+
+        if character.id == target.id {
+            return Err(SocialError::SelfTarget);
+        }
+
+        // Befriending a character lifts an ignore and vice versa
+        if let Some(mut relation) = SocialRelation::get_relation(&self.db, &character.id, &target.id).await? {
+            if relation.kind == kind {
+                return Err(match kind {
+                    SocialRelationKind::Friend => SocialError::AlreadyFriend(target.name),
+                    SocialRelationKind::Ignore => SocialError::AlreadyIgnored(target.name),
+                });
+            }
+
+            relation.kind = kind;
+            relation.created = Utc::now();
+            relation.save(&self.db).await?;
+        } else {
+            SocialRelation::create(&self.db, SocialRelation {
+                id: ObjectId::default(),
+                character_id: character.id,
+                target_id: target.id,
+                kind,
+                created: Utc::now(),
+            }).await?;
+        }
+
+        self.notify_ignore_list(character.id).await?;
+
+        Ok(())
+    }
+
+    async fn try_remove_relation(&self, session_id: Uuid, character_name: &str, kind: SocialRelationKind) -> Result<(), SocialError> {
+        let character = self.session_character(session_id).await?;
+        let target = self.find_character(character_name).await?;
+
+        if 
+            let Some(relation) = SocialRelation::get_relation(&self.db, &character.id, &target.id).await? &&
+            relation.kind == kind
+        {
+            relation.delete(&self.db).await?;
+        } else {
+            return Err(match kind {
+                SocialRelationKind::Friend => SocialError::NotAFriend(target.name),
+                SocialRelationKind::Ignore => SocialError::NotIgnored(target.name),
+            });
+        }
+
+        self.notify_ignore_list(character.id).await?;
+
+        Ok(())
+    }
+
+    async fn try_list(&self, session_id: Uuid) -> Result<String, SocialError> {
+        let character = self.session_character(session_id).await?;
+
+        let mut friends = vec![];
+        for relation in SocialRelation::list_for_character(&self.db, &character.id, SocialRelationKind::Friend).await? {
+            if let Some(friend) = Character::get(&self.db, &relation.target_id).await? {
+                let online = SESSION_MANAGER.get().unwrap().get_state_for_character(friend.id).await.is_some();
+                friends.push(format!("{} ({})", friend.name, if online { "online" } else { "offline" }));
+            }
+        }
+
+        let mut ignored = vec![];
+        for relation in SocialRelation::list_for_character(&self.db, &character.id, SocialRelationKind::Ignore).await? {
+            if let Some(target) = Character::get(&self.db, &relation.target_id).await? {
+                ignored.push(target.name);
+            }
+        }
+
+        Ok(format!(
+            "Friends: {}. Ignored: {}.", 
+            if friends.is_empty() { "none".to_string() } else { friends.join(", ") },
+            if ignored.is_empty() { "none".to_string() } else { ignored.join(", ") },
+        ))
+    }
+
+    // World nodes filter local chat by the ignore list,
+    // so keep them up to date.
+    async fn notify_ignore_list(&self, character_id: Uuid) -> Result<(), SocialError> {
+        let ignored = self.ignored_characters(character_id).await?;
+
+        let _ = self.server.notify(RealmNotification::IgnoreListUpdated {
+            character_id,
+            ignored,
+        }).await;
+
+        Ok(())
+    }
+
+    async fn notify_watchers(&self, character_id: Uuid, status: &str) {
+        let res: Result<(), SocialError> = async {
+            let character = Character::get(&self.db, &character_id).await?
+                .ok_or(SocialError::CharacterNotFound)?;
+
+            let mut sessions = vec![];
+            for relation in SocialRelation::list_for_target(&self.db, &character_id, SocialRelationKind::Friend).await? {
+                if let Some(state) = SESSION_MANAGER.get().unwrap().get_state_for_character(relation.character_id).await {
+                    sessions.push(state.id);
+                }
+            }
+
+            if !sessions.is_empty() {
+                CHAT_ROUTER.get().unwrap()
+                    .system_message(sessions, Destination::Whisper(String::default()), format!("{} {status}.", character.name)).await;
+            }
+
+            Ok(())
+        }.await;
+
+        if let Err(e) = res {
+            error!("Failed to notify friends of {character_id}: {e:?}");
+        }
+    }
+
+    async fn session_character(&self, session_id: Uuid) -> Result<Character, SocialError> {
+        let state = SESSION_MANAGER.get().unwrap().get_state(session_id).await
+            .ok_or(SocialError::SessionNotFound)?;
+
+        Character::get(&self.db, &state.character_id).await?
+            .ok_or(SocialError::CharacterNotFound)
+    }
+
+    async fn find_character(&self, name: &str) -> Result<Character, SocialError> {
+        Character::find_by_name(&self.db, name, false).await?
+            .ok_or(SocialError::CharacterNotFound)
+    }
+
+    async fn finish(&self, session_id: Uuid, res: Result<String, SocialError>) {
+        match res {
+            Ok(message) => {
+                CHAT_ROUTER.get().unwrap()
+                    .system_message(vec![session_id], Destination::Whisper(String::default()), message).await;
+            },
+            Err(e) => report_error(session_id, Destination::Whisper(String::default()), "Social", e).await,
+        }
+    }
+}
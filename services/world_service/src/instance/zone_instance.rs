@@ -21,12 +21,12 @@ use derive_builder::Builder;
 use log::{debug, trace, error};
 use mlua::LuaSerdeExt;
 use obj_params::{Class, OaZoneConfig};
-use realm_api::{proto::RealmClient, Category, RealmApi, WorldDef, Zone};
+use realm_api::{proto::RealmClient, Category, DungeonScope, RealmApi, WorldDef, Zone};
 use scripting::{LuaRuntime, LuaRuntimeBuilder, ScriptObject, ScriptingPlugin};
 use serde_json::Value;
 use toolkit::types::Uuid;
 
-use crate::{ARGS, error::{WorldError, WorldResult}, instance::InstanceLabel, manager::InstanceManager, plugins::{AbilitiesPlugin, AsyncOperationCommandsExt, AsyncOperationPlugin, AttributesPlugin, AvatarPlugin, BehaviorPlugin, BuffsPlugin, CashShopPlugin, ChatPlugin, ClientSyncPlugin, CombatPlugin, CombatStylesPlugin, CommandsPlugin, DialoguePlugin, FactionsPlugin, InterestsPlugin, InventoryPlugin, LifetimePlugin, LoaderPlugin, MovementPlugin, NavigationPlugin, Navmesh, NetworkPlugin, NonPlayerPlugin, PartitioningPlugin, PlayerController, PlayerPlugin, QuestsPlugin, ScriptObjectInfoPlugin, ServerActionPlugin, SocialPlugin, SpecialEventsPlugin, TravelPlugin, WorldSpace}};
+use crate::{ARGS, error::{WorldError, WorldResult}, instance::InstanceLabel, manager::InstanceManager, plugins::{AbilitiesPlugin, AsyncOperationCommandsExt, AsyncOperationPlugin, AttributesPlugin, AvatarPlugin, BehaviorPlugin, BuffsPlugin, CashShopPlugin, ChatPlugin, ClanPlugin, ClientSyncPlugin, CombatPlugin, CombatStylesPlugin, CommandsPlugin, DialoguePlugin, FactionsPlugin, InterestsPlugin, InventoryPlugin, LifetimePlugin, LoaderPlugin, LootTablesPlugin, MailPlugin, MetricsPlugin, MovementPlugin, NavigationPlugin, Navmesh, NetworkPlugin, NonPlayerPlugin, PartitioningPlugin, PartyPlugin, PlayerController, PlayerPlugin, QuestsPlugin, ScriptObjectInfoPlugin, ServerActionPlugin, SocialPlugin, SpecialEventsPlugin, TradePlugin, TravelPlugin, WorldSpace}};
 
 #[derive(ScheduleLabel, Hash, Debug, PartialEq, Eq, Clone, Copy)]
 pub struct InstanceShutdown;
@@ -99,6 +99,57 @@ pub struct ZoneConfig {
     pub json_config: serde_json::Value,
 }
 
+impl ZoneConfig {
+    /// Loads the zone config referenced by the zones realu zone type, if there is one.
+    pub async fn load(zone: &Zone) -> WorldResult<Option<Self>> {
+        if let Some(config) = RealmApi::get()
+            .query_object_templates()
+            .category(Category::Misc)
+            .class(Class::OaZoneConfig)
+            .name(zone.realu_zone_type().to_owned())
+            .query().await?
+            .try_next().await?
+        {
+            Ok(Some(ZoneConfig { 
+                force_generate_guid_key: *config.data
+                    .get(OaZoneConfig::ForceGenerateGuidKey)?, 
+                allow_summon_portal: *config.data
+                    .get(OaZoneConfig::AllowSummonPortal)?,
+                spawn_to_the_last_save_position: *config.data
+                    .get(OaZoneConfig::SpawnToTheLastSavePosition)?,  
+                instance_type: (*config.data
+                    .get::<_, i32>(OaZoneConfig::InstanceType)?)
+                    .try_into()?,  
+                instance_scope: *config.data
+                    .get(OaZoneConfig::InstanceScope)?, 
+                zone_type: config.data
+                    .get::<_, String>(OaZoneConfig::ZoneType)?
+                    .parse()?, 
+                json_config: config.data
+                    .get::<_, Value>(OaZoneConfig::JsonConfig)?
+                    .clone()
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Dungeons get private instances, just like zones configured to be instanced.
+    pub fn is_private(&self) -> bool {
+        matches!(self.instance_type, InstanceType::Instanced) ||
+        matches!(self.zone_type, ZoneType::Dungeon)
+    }
+
+    /// Who shares a private instance of this zone. An instance scope of 1 gives
+    /// every character its own instance, otherwise parties share one.
+    pub fn dungeon_scope(&self) -> DungeonScope {
+        match self.instance_scope {
+            1 => DungeonScope::Character,
+            _ => DungeonScope::Party,
+        }
+    }
+}
+
 #[derive(Builder, Resource)]
 #[builder(pattern = "owned", build_fn(private, error = "WorldError"))]
 pub struct ZoneInstance {
@@ -137,33 +188,8 @@ impl ZoneInstanceBuilder {
 
         let world_def = instance.world_def.clone();
 
-        if let Some(config) = RealmApi::get()
-            .query_object_templates()
-            .category(Category::Misc)
-            .class(Class::OaZoneConfig)
-            .name(instance.zone.realu_zone_type().to_owned())
-            .query().await?
-            .try_next().await?
-        {
-            instance.config = Arc::new(ZoneConfig { 
-                force_generate_guid_key: *config.data
-                    .get(OaZoneConfig::ForceGenerateGuidKey)?, 
-                allow_summon_portal: *config.data
-                    .get(OaZoneConfig::AllowSummonPortal)?,
-                spawn_to_the_last_save_position: *config.data
-                    .get(OaZoneConfig::SpawnToTheLastSavePosition)?,  
-                instance_type: (*config.data
-                    .get::<_, i32>(OaZoneConfig::InstanceType)?)
-                    .try_into()?,  
-                instance_scope: *config.data
-                    .get(OaZoneConfig::InstanceScope)?, 
-                zone_type: config.data
-                    .get::<_, String>(OaZoneConfig::ZoneType)?
-                    .parse()?, 
-                json_config: config.data
-                    .get::<_, Value>(OaZoneConfig::JsonConfig)?
-                    .clone()
-            });
+        if let Some(config) = ZoneConfig::load(&instance.zone).await? {
+            instance.config = Arc::new(config);
         }
 
         // Low level setup
@@ -189,6 +215,7 @@ impl ZoneInstanceBuilder {
             NetworkPlugin,
             ScriptingPlugin,
             CommandsPlugin,
+            MetricsPlugin,
         ));
 
         app.insert_resource(
@@ -233,6 +260,14 @@ impl ZoneInstanceBuilder {
             PartitioningPlugin,
             LifetimePlugin,
             AttributesPlugin,
+            PartyPlugin,
+        ));
+
+        app.add_plugins((
+            ClanPlugin,
+            TradePlugin,
+            MailPlugin,
+            LootTablesPlugin,
         ));
 
         let navmesh = Navmesh::load(world_def.as_ref()).await?;
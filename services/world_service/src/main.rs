@@ -17,12 +17,12 @@
 #![feature(specialization)]
 #![feature(associated_type_defaults)]
 
-use instance::{InstanceLabel, ZoneSubApp};
-use plugins::{ControllerEvent, PlayerControllerSubAppExt};
+use instance::{InstanceLabel, ZoneInstance, ZoneSubApp};
+use plugins::{ClanMembershipUpdated, ControllerEvent, Draining, IgnoreListUpdated, PartyUpdated, PlayerController, PlayerControllerSubAppExt};
 use protocol::CPkt;
 use tokio_util::sync::CancellationToken;
 
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::{Duration, Instant}};
 
 use bevy::{MinimalPlugins, app::App};
 use clap::Parser;
@@ -65,12 +65,23 @@ pub struct Cli {
     #[arg(long, env = "ZONE_GROUPS")]
     pub zone_groups: Option<String>,
 
+    /// Comma separated tags, matched against the realms placement rules
+    #[arg(long, env = "NODE_TAGS")]
+    pub node_tags: Option<String>,
+
+    /// Seconds to wait for players to be moved to other nodes,
+    /// before a draining node shuts down regardless.
+    #[arg(long, env = "DRAIN_TIMEOUT", default_value_t = 300)]
+    pub drain_timeout: u64,
+
     #[arg(long, default_value_t = false)]
     pub hot_reload: bool,
 }
 
 pub static ARGS: Lazy<Cli> = Lazy::new(Cli::parse);
 
+const TICK_INTERVAL: Duration = Duration::from_millis(20);
+
 fn handle_realm_events(manager: InstanceManager, mut notifications: mpsc::Receiver<RealmNotification>) {
     tokio::spawn(async move {
         while let Some(event) = notifications.recv().await {
@@ -81,6 +92,15 @@ fn handle_realm_events(manager: InstanceManager, mut notifications: mpsc::Receiv
                 },
                 RealmNotification::ClusterNotification(_) => {
 
+                },
+                RealmNotification::PartyUpdated { id, members, .. } => {
+                    manager.update_party(id, members).await;
+                },
+                RealmNotification::ClanMembershipUpdated { character_id, clan } => {
+                    manager.update_clan_membership(character_id, clan).await;
+                },
+                RealmNotification::IgnoreListUpdated { character_id, ignored } => {
+                    manager.update_ignore_list(character_id, ignored).await;
                 },
                 _ => unimplemented!(),
             }
@@ -96,6 +116,8 @@ fn handle_realm_msgs(realm_client: Arc<RealmClient>, manager: InstanceManager, c
                     manager.provision_instance(transaction_id).await,
                 RealmResponse::InstanceShutdownAck(label) => 
                     manager.shutdown_instance(label).await,
+                RealmResponse::NodeDrainAck =>
+                    manager.migrate_instances().await,
                 _ => (),
             }
         }
@@ -114,7 +136,7 @@ fn handle_world_msgs(server: Arc<WorldServer>, _realm_api: RealmApi, event_sende
 
         loop {
             select! {
-                Ok((router_id, msg)) = server.recv() => {
+                Ok((router_id, _, msg)) = server.recv() => {
                     match msg {
                         WorldRequest::RouterChannel { id, msg} => {
                             match msg {
@@ -236,6 +258,9 @@ async fn main() -> WorldResult<()> {
     // subscribe to events
     realm_client.subscribe("core.session.").await?;
     realm_client.subscribe("realm.instance.").await?;
+    realm_client.subscribe("realm.party.").await?;
+    realm_client.subscribe("realm.clan.").await?;
+    realm_client.subscribe("realm.social.").await?;
 
     let cancel_token = CancellationToken::new();
 
@@ -258,7 +283,7 @@ async fn main() -> WorldResult<()> {
 
     // register node
     if let Endpoint::Tcp(_, port) = server.endpoint() {
-        realm_client.send(RealmRequest::RegisterNode(NodeType::World, NodeAddress::Internal(*port))).await?;
+        realm_client.send(RealmRequest::RegisterNode(NodeType::World, NodeAddress::Internal(*port), None)).await?;
     } else {
         unreachable!()
     }
@@ -269,12 +294,22 @@ async fn main() -> WorldResult<()> {
 
     info!("Starting world server!");
 
+    let node_tags = ARGS.node_tags
+        .as_ref()
+        .map(|tags| tags.split(",").map(|tag| tag.trim().to_string()).collect::<Vec<_>>())
+        .unwrap_or_default();
+
     // Create bevy app
     let mut app = App::new();
     app.add_plugins(MinimalPlugins);
     
     // Aim for 50 cycles/sec
-    let mut update_interval = time::interval(Duration::from_millis(20));   
+    let mut update_interval = time::interval(TICK_INTERVAL);   
+
+    // Report node load to the realm for instance placement
+    let mut load_report_interval = time::interval(Duration::from_secs(5));
+    let mut tick_time = Duration::ZERO;
+    let mut ticks = 0u32;
 
     {
         let manager = manager.clone();
@@ -289,12 +324,61 @@ async fn main() -> WorldResult<()> {
         });
     }
 
+    // Rolling restarts send SIGTERM, move players elsewhere before going down
+    #[cfg(unix)]
+    {
+        let manager = manager.clone();
+        tokio::spawn(async move {
+            match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+                Ok(mut sigterm) => {
+                    sigterm.recv().await;
+
+                    warn!("Draining world server...");
+                    manager.drain_world().await;
+
+                    time::sleep(Duration::from_secs(ARGS.drain_timeout)).await;
+
+                    warn!("Drain timed out, shutting down world server...");
+                    manager.shutdown_world().await;
+                },
+                Err(e) => {
+                    warn!("Error while listening for SIGTERM signal: {e:?}");
+                }
+            }
+        });
+    }
+
     loop {
         select! {
             _ = update_interval.tick() => {
+                let started = Instant::now();
+
                 tokio::task::block_in_place(|| {
                     app.update();
                 });
+
+                tick_time += started.elapsed();
+                ticks += 1;
+            },
+            _ = load_report_interval.tick() => {
+                let mut players = 0;
+                for subapp in app.sub_apps_mut().iter_mut() {
+                    if subapp.world().contains_resource::<ZoneInstance>() {
+                        let world = subapp.world_mut();
+                        players += world.query::<&PlayerController>().iter(world).count();
+                    }
+                }
+
+                let tick_budget = if ticks > 0 {
+                    tick_time.as_secs_f32() / ticks as f32 / TICK_INTERVAL.as_secs_f32()
+                } else {
+                    0.0
+                };
+
+                tick_time = Duration::ZERO;
+                ticks = 0;
+
+                manager.report_load(players, tick_budget, node_tags.clone()).await;
             },
             event = instance_events.recv() => {
                 match event {
@@ -309,6 +393,11 @@ async fn main() -> WorldResult<()> {
                                 subapp.shutdown();
                             }
                         },
+                        InstanceEvent::InstanceDraining(label) => {
+                            if let Some(subapp) = app.get_sub_app_mut(label) {
+                                subapp.world_mut().init_resource::<Draining>();
+                            }
+                        },
                         InstanceEvent::InstanceStopped(label) => {
                             manager.unregister_instance(label).await;
                         },
@@ -319,6 +408,37 @@ async fn main() -> WorldResult<()> {
                                 let _ = controller.send(Err(anyhow::Error::msg("instance not found").into()));
                             }
                         },
+                        InstanceEvent::PartyUpdated { id, members } => {
+                            // Party members might be spread across any zone of this node
+                            for subapp in app.sub_apps_mut().iter_mut() {
+                                if subapp.world().contains_resource::<ZoneInstance>() {
+                                    subapp.world_mut().write_message(PartyUpdated {
+                                        id,
+                                        members: members.clone(),
+                                    });
+                                }
+                            }
+                        },
+                        InstanceEvent::ClanMembershipUpdated { character_id, clan } => {
+                            for subapp in app.sub_apps_mut().iter_mut() {
+                                if subapp.world().contains_resource::<ZoneInstance>() {
+                                    subapp.world_mut().write_message(ClanMembershipUpdated {
+                                        character_id,
+                                        clan: clan.clone(),
+                                    });
+                                }
+                            }
+                        },
+                        InstanceEvent::IgnoreListUpdated { character_id, ignored } => {
+                            for subapp in app.sub_apps_mut().iter_mut() {
+                                if subapp.world().contains_resource::<ZoneInstance>() {
+                                    subapp.world_mut().write_message(IgnoreListUpdated {
+                                        character_id,
+                                        ignored: ignored.clone(),
+                                    });
+                                }
+                            }
+                        },
                         InstanceEvent::WorldShutdown => {
                             info!("World server shutdown completed!");
                             break;
@@ -21,7 +21,7 @@ use core_api::CoreApi;
 use futures_util::TryStreamExt;
 use log::{debug, error, info, trace};
 use obj_params::OaZoneConfig;
-use realm_api::{proto::{InstanceKey, RealmClient, RealmRequest}, RealmApi, WorldDef, Zone};
+use realm_api::{proto::{ClanMembership, InstanceKey, NodeLoad, RealmClient, RealmRequest}, RealmApi, WorldDef, Zone};
 use tokio::{sync::{mpsc::{self, Sender, UnboundedSender}, oneshot, Mutex}};
 use toolkit::types::Uuid;
 
@@ -44,6 +44,7 @@ struct InstanceManagerData {
     instances: Vec<InstanceLabel>,
     event_sender: mpsc::UnboundedSender<InstanceEvent>,
     limit: usize,
+    draining: bool,
 }
 
 pub enum InstanceEvent {
@@ -51,6 +52,7 @@ pub enum InstanceEvent {
     InstanceStopping(InstanceLabel),
     InstanceStopped(InstanceLabel),
     InstanceRemoved(InstanceLabel),
+    InstanceDraining(InstanceLabel),
     ControllerSpawnRequested {
         peer: Uuid,
         instance: InstanceLabel,
@@ -60,6 +62,18 @@ pub enum InstanceEvent {
         travel_mode: TravelMode,
         movie: Option<String>,
     },
+    PartyUpdated {
+        id: Uuid,
+        members: Vec<Uuid>,
+    },
+    ClanMembershipUpdated {
+        character_id: Uuid,
+        clan: Option<ClanMembership>,
+    },
+    IgnoreListUpdated {
+        character_id: Uuid,
+        ignored: Vec<Uuid>,
+    },
     WorldShutdown,
 }
 
@@ -121,6 +135,7 @@ impl InstanceManager {
             instances: Vec::new(),
             event_sender,
             limit,
+            draining: false,
         }))))
     }
 
@@ -247,6 +262,32 @@ impl InstanceManager {
         let _ = s.event_sender.send(InstanceEvent::InstanceStopping(InstanceLabel::new(key.zone(), key.instance())));
     }
 
+    pub async fn report_load(&self, players: usize, tick_budget: f32, tags: Vec<String>) {
+        let s = self.0.lock().await;
+        let _ = s.realm_client.send(RealmRequest::NodeLoadReport(NodeLoad {
+            instances: s.instances.len() as u32,
+            instance_limit: s.limit as u32,
+            players: players as u32,
+            tick_budget,
+            tags,
+        })).await;
+    }
+
+    pub async fn update_party(&self, id: Uuid, members: Vec<Uuid>) {
+        let s = self.0.lock().await;
+        let _ = s.event_sender.send(InstanceEvent::PartyUpdated { id, members });
+    }
+
+    pub async fn update_clan_membership(&self, character_id: Uuid, clan: Option<ClanMembership>) {
+        let s = self.0.lock().await;
+        let _ = s.event_sender.send(InstanceEvent::ClanMembershipUpdated { character_id, clan });
+    }
+
+    pub async fn update_ignore_list(&self, character_id: Uuid, ignored: Vec<Uuid>) {
+        let s = self.0.lock().await;
+        let _ = s.event_sender.send(InstanceEvent::IgnoreListUpdated { character_id, ignored });
+    }
+
     pub fn report_instance_stopped(&self, key: InstanceKey) {
         let s = self.0.blocking_lock();
         let _ = s.event_sender.send(InstanceEvent::InstanceStopped(InstanceLabel::new(key.zone(), key.instance())));
@@ -270,6 +311,31 @@ impl InstanceManager {
         }
     }
 
+    /// Gracefully empties this node. The realm re-provisions our zones elsewhere,
+    /// after which connected players get moved over using the regular travel flow.
+    pub async fn drain_world(&self) {
+        let mut s = self.0.lock().await;
+        if s.draining { return; }
+
+        s.limit = 0; // Don't offer any new instances while draining.
+        s.draining = true;
+
+        if s.instances.is_empty() {
+            let _ = s.event_sender.send(InstanceEvent::WorldShutdown);
+        } else {
+            info!("Draining {} instances...", s.instances.len());
+            let _ = s.realm_client.send(RealmRequest::NodeDraining).await;
+        }
+    }
+
+    pub async fn migrate_instances(&self) {
+        let s = self.0.lock().await;
+        for label in s.instances.iter() {
+            trace!("Migrating players of instance {label:?}");
+            let _ = s.event_sender.send(InstanceEvent::InstanceDraining(label.clone()));
+        }
+    }
+
     #[allow(dead_code)]
     pub fn get_world_def(&self, id: &Uuid) -> Option<Arc<WorldDef>> {
         let s = self.0.blocking_lock();
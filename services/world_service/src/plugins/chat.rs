@@ -13,31 +13,41 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use bevy::{app::Plugin, ecs::system::Commands, prelude::{App, Entity, In, Query, Res, With}};
+use bevy::{app::Plugin, ecs::{error::{BevyError, Result}, system::Commands}, prelude::{App, Entity, In, Query, Res, With}};
+use log::error;
 use obj_params::{tags::PlayerTag, GameObjectData, Player};
 use protocol::{CPktChat, CpktChatChatType};
-use realm_api::proto::{Destination, RealmRequest};
-use toolkit::types::Uuid;
+use realm_api::{proto::{Destination, RealmRequest}, ChatChannel, RealmApi, RealmApiError};
+use toolkit::{types::Uuid, NativeParam};
 
-use crate::{instance::ZoneInstance, plugins::{AsyncOperationEntityCommandsExt, player_error_handler_system}};
+use crate::{instance::ZoneInstance, plugins::{AsyncOperationEntityCommandsExt, CommandExtPriv, MessageType, PermissionLevel, player_error_handler_system}};
 
-use super::{Avatar, Movement, NetworkExtPriv, PlayerController};
+use super::{Avatar, IgnoreList, Movement, NetworkExtPriv, PlayerController};
 
 pub struct ChatPlugin;
 
 impl Plugin for ChatPlugin {
     fn build(&self, app: &mut App) {
         app.register_message_handler(handle_chat_msg);
+
+        app.register_command("mute", PermissionLevel::GameMaster, cmd_mute);
+        app.register_command("unmute", PermissionLevel::GameMaster, cmd_unmute);
     }
 }
 
+/// Local chat after it went through the realm's moderation.
+enum ModeratedChat {
+    Deliver(CpktChatChatType, String),
+    Rejected(String),
+}
+
 fn handle_chat_msg(
     In((ent, pkt)): In<(Entity, CPktChat)>,
     instance: Res<ZoneInstance>,
-    query: Query<(&PlayerController, &Avatar, &GameObjectData, &Movement), With<PlayerTag>>,
+    query: Query<(&PlayerController, &GameObjectData), With<PlayerTag>>,
     mut commands: Commands
 ) {
-    if let Ok((send_controller, avatar, sender_data, sender_movement)) = query.get(ent) {
+    if let Ok((send_controller, sender_data)) = query.get(ent) {
         // Local messages are directly handled by this world node,
         // all other messages are relayed via the realm service to
         // cluster nodes.
@@ -46,27 +56,35 @@ fn handle_chat_msg(
             matches!(pkt.chat_type, CpktChatChatType::LocalYell) || 
             matches!(pkt.chat_type, CpktChatChatType::Shout)
         {
-        
-            let pkt = CPktChat {
-                chat_type: pkt.chat_type,
-                field_2: avatar.id,
-                sender: avatar.name.clone(),
-                message: pkt.message.clone(),
-                ..Default::default()
-            };
-            
-            for (controller, _, data, movement) in query.iter() {
-                let awarenes = data.get::<_, f32>(Player::AwareDist)
-                    .unwrap() * match pkt.chat_type {
-                        CpktChatChatType::LocalYell => 2.0,
-                        CpktChatChatType::Shout => 2.0,
-                        _ => 1.0
+            let session = *send_controller.session().id();
+            let chat_type = pkt.chat_type;
+            let message = pkt.message;
+
+            // Local chat is moderated by the realm as well, even though
+            // it's delivered by this node.
+            commands
+                .entity(ent)
+                .perform_async_operation(async move {
+                    let channel = match chat_type {
+                        CpktChatChatType::LocalYell => ChatChannel::Yell,
+                        CpktChatChatType::Shout => ChatChannel::Shout,
+                        _ => ChatChannel::Local,
                     };
-    
-                if sender_movement.position.distance(movement.position) <= awarenes {
-                    controller.send_packet(pkt.clone());
-                }
-            }
+
+                    match RealmApi::get().moderate_chat(session, channel, &message).await {
+                        Ok(message) => Ok(ModeratedChat::Deliver(chat_type, message)),
+                        Err(RealmApiError::GraphQl(errors)) => Ok(ModeratedChat::Rejected(
+                            errors.into_iter()
+                                .next()
+                                .map(|err| err.message)
+                                .unwrap_or_default()
+                        )),
+                        Err(e) => Err(e.into()),
+                    }
+                })
+                .on_finish_run_system(broadcast_local_chat)
+                // The message is dropped, but the player stays connected
+                .on_error_run_system(moderation_error_handler);
         } else {
             let client = instance.realm_client.clone();
             let session = *send_controller.session().id();
@@ -97,4 +115,151 @@ fn handle_chat_msg(
                 .on_error_run_system(player_error_handler_system);
         }
     }
-}
\ No newline at end of file
+}
+
+fn broadcast_local_chat(
+    In((ent, chat)): In<(Entity, ModeratedChat)>,
+    query: Query<(&PlayerController, &Avatar, &GameObjectData, &Movement, Option<&IgnoreList>), With<PlayerTag>>,
+) {
+    let Ok((send_controller, avatar, _, sender_movement, _)) = query.get(ent) else {
+        return;
+    };
+
+    let (chat_type, message) = match chat {
+        ModeratedChat::Deliver(chat_type, message) => (chat_type, message),
+        ModeratedChat::Rejected(reason) => {
+            send_controller.send_message(MessageType::Normal, reason);
+            return;
+        }
+    };
+
+    let range_factor = match chat_type {
+        CpktChatChatType::LocalYell => 2.0,
+        CpktChatChatType::Shout => 2.0,
+        _ => 1.0
+    };
+
+    let pkt = CPktChat {
+        chat_type,
+        field_2: avatar.id,
+        sender: avatar.name.clone(),
+        message,
+        ..Default::default()
+    };
+    
+    let sender_character = send_controller.character_id();
+
+    for (controller, _, data, movement, ignore_list) in query.iter() {
+        if ignore_list.is_some_and(|list| list.is_ignoring(sender_character)) {
+            continue;
+        }
+
+        let awarenes = data.get::<_, f32>(Player::AwareDist)
+            .unwrap() * range_factor;
+
+        if sender_movement.position.distance(movement.position) <= awarenes {
+            controller.send_packet(pkt.clone());
+        }
+    }
+}
+
+// Errors reported by the moderation are meant for the game master,
+// everything else is a server side problem.
+fn moderation_reply(res: std::result::Result<String, RealmApiError>) -> Result<String> {
+    match res {
+        Ok(message) => Ok(message),
+        Err(RealmApiError::GraphQl(errors)) => Ok(
+            errors.into_iter()
+                .next()
+                .map(|err| err.message)
+                .unwrap_or_else(|| "Request failed.".to_string())
+        ),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn cmd_mute(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<&PlayerController>,
+    mut commands: Commands,
+) {
+    let Ok(controller) = query.get(ent) else {
+        return;
+    };
+
+    let mut args = args.into_iter();
+
+    let (Some(NativeParam::String(character_name)), Some(minutes)) = (args.next(), args.next()) else {
+        controller.send_message(MessageType::Normal, "Usage: mute <character> <minutes> [reason]");
+        return;
+    };
+
+    let minutes = match minutes {
+        NativeParam::Int(minutes) => Some(minutes),
+        NativeParam::String(minutes) => minutes.parse().ok(),
+        _ => None,
+    };
+
+    let Some(minutes) = minutes.filter(|minutes| *minutes > 0) else {
+        controller.send_message(MessageType::Normal, "Mute duration must be a positive number of minutes.");
+        return;
+    };
+
+    let reason = args
+        .filter_map(|arg| if let NativeParam::String(word) = arg { Some(word) } else { None })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let issued_by = controller.character_id();
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            moderation_reply(RealmApi::get().mute_character(&character_name, issued_by, minutes, &reason).await)
+        })
+        .on_finish_run_system(send_moderation_reply)
+        .on_error_run_system(moderation_error_handler);
+}
+
+fn cmd_unmute(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<&PlayerController>,
+    mut commands: Commands,
+) {
+    let Ok(controller) = query.get(ent) else {
+        return;
+    };
+
+    let Some(NativeParam::String(character_name)) = args.into_iter().next() else {
+        controller.send_message(MessageType::Normal, "Usage: unmute <character>");
+        return;
+    };
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            moderation_reply(RealmApi::get().unmute_character(&character_name).await)
+        })
+        .on_finish_run_system(send_moderation_reply)
+        .on_error_run_system(moderation_error_handler);
+}
+
+fn send_moderation_reply(
+    In((ent, message)): In<(Entity, String)>,
+    query: Query<&PlayerController>,
+) {
+    if let Ok(controller) = query.get(ent) {
+        controller.send_message(MessageType::Normal, message);
+    }
+}
+
+fn moderation_error_handler(
+    In((ent, err)): In<(Entity, BevyError)>,
+    query: Query<&PlayerController>,
+) {
+    error!("Moderation request failed: {err:?}");
+
+    if let Ok(controller) = query.get(ent) {
+        controller.send_message(MessageType::Normal, "Chat moderation is currently unavailable.");
+    }
+}
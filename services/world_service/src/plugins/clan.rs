@@ -0,0 +1,110 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bevy::{app::{First, Plugin}, ecs::{message::{Message, MessageReader}, query::Added, system::{Commands, In, Query}}, prelude::{App, Entity, With}};
+use obj_params::{tags::PlayerTag, GameObjectData, Player};
+use realm_api::{proto::{ClanMembership, RealmRequest}, RealmApi};
+use toolkit::types::{Uuid, UUID_NIL};
+
+use crate::plugins::{AsyncOperationEntityCommandsExt, CommandExtPriv, player_error_handler_system};
+
+use super::PlayerController;
+
+pub struct ClanPlugin;
+
+impl Plugin for ClanPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<ClanMembershipUpdated>();
+
+        app.add_systems(First, (
+            load_player_clan,
+            apply_clan_updates,
+        ));
+
+        app.register_realm_command_with_name("clan_create", |session_id, name| RealmRequest::ClanCreate { session_id, name });
+        app.register_realm_command_with_name("clan_invite", |session_id, character_name| RealmRequest::ClanInvite { session_id, character_name });
+        app.register_realm_command("clan_accept", |session_id| RealmRequest::ClanAccept { session_id });
+        app.register_realm_command("clan_decline", |session_id| RealmRequest::ClanDecline { session_id });
+        app.register_realm_command_with_name("clan_kick", |session_id, character_name| RealmRequest::ClanKick { session_id, character_name });
+        app.register_realm_command("clan_leave", |session_id| RealmRequest::ClanLeave { session_id });
+        app.register_realm_command_with_name("clan_promote", |session_id, character_name| RealmRequest::ClanPromote { session_id, character_name });
+        app.register_realm_command_with_name("clan_demote", |session_id, character_name| RealmRequest::ClanDemote { session_id, character_name });
+        app.register_realm_command("clan_disband", |session_id| RealmRequest::ClanDisband { session_id });
+    }
+}
+
+/// Written into every zone when the realm reports a change
+/// of a characters clan membership.
+#[derive(Message, Clone)]
+pub struct ClanMembershipUpdated {
+    pub character_id: Uuid,
+    pub clan: Option<ClanMembership>,
+}
+
+fn apply_membership(data: &mut GameObjectData, clan: Option<&ClanMembership>) {
+    if let Some(clan) = clan {
+        data.set(Player::ClanGuid, clan.id);
+        data.set(Player::ClanName, clan.name.clone());
+        data.set(Player::ClanRank, i32::from(clan.rank));
+        data.set(Player::ClanRatified, true);
+    } else {
+        data.set(Player::ClanGuid, UUID_NIL);
+        data.set(Player::ClanName, String::default());
+        data.set(Player::ClanRank, 0);
+        data.set(Player::ClanRatified, false);
+    }
+}
+
+// Clan membership might have changed while the character
+// was offline, so always refresh it on zone entry.
+fn load_player_clan(
+    query: Query<(Entity, &PlayerController), Added<PlayerTag>>,
+    mut commands: Commands,
+) {
+    for (ent, controller) in query.iter() {
+        let character_id = controller.character_id();
+
+        commands
+            .entity(ent)
+            .perform_async_operation(async move {
+                Ok(RealmApi::get()
+                    .get_clan_membership(character_id).await?)
+            })
+            .on_finish_run_system(set_player_clan)
+            .on_error_run_system(player_error_handler_system);
+    }
+}
+
+fn set_player_clan(
+    In((ent, clan)): In<(Entity, Option<ClanMembership>)>,
+    mut query: Query<&mut GameObjectData, With<PlayerTag>>,
+) {
+    if let Ok(mut data) = query.get_mut(ent) {
+        apply_membership(&mut data, clan.as_ref());
+    }
+}
+
+fn apply_clan_updates(
+    mut messages: MessageReader<ClanMembershipUpdated>,
+    mut query: Query<(&PlayerController, &mut GameObjectData), With<PlayerTag>>,
+) {
+    for update in messages.read() {
+        for (controller, mut data) in query.iter_mut() {
+            if controller.character_id() == update.character_id {
+                apply_membership(&mut data, update.clan.as_ref());
+            }
+        }
+    }
+}
@@ -13,16 +13,26 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use bevy::{app::{App, Plugin}, ecs::{resource::Resource, system::SystemId}, platform::collections::HashMap, prelude::{Commands, Entity, In, IntoSystem, Res}};
-use log::warn;
+use bevy::{app::{App, Plugin}, ecs::{error::BevyError, resource::Resource, system::SystemId}, platform::collections::HashMap, prelude::{Commands, Entity, In, IntoSystem, Query, Res}};
+use log::{error, info, warn};
 use protocol::oaPktCheatingClusterNode;
-use toolkit::NativeParam;
+use realm_api::{proto::RealmRequest, RealmApi};
+use toolkit::{types::Uuid, NativeParam};
 
+use crate::{instance::ZoneInstance, plugins::{AsyncOperationEntityCommandsExt, PlayerController, player_error_handler_system}};
 
 use super::NetworkExtPriv;
 
+/// Required account privileges to run a command.
+/// Levels are ordered, so a higher level includes all lower ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PermissionLevel {
+    Player,
+    GameMaster,
+}
+
 #[derive(Resource, Default)]
-struct CommandHandlers(HashMap<String, SystemId<CommandInput>>);
+struct CommandHandlers(HashMap<String, (PermissionLevel, SystemId<CommandInput>)>);
 
 type CommandInput = In<(Entity, Vec<NativeParam>)>;
 
@@ -37,25 +47,86 @@ impl Plugin for CommandsPlugin {
 }
 
 pub trait CommandExtPriv {
-    fn register_command<T: IntoSystem<CommandInput, (), Marker> + 'static, Marker>(&mut self, name: &str, system: T);
+    fn register_command<T: IntoSystem<CommandInput, (), Marker> + 'static, Marker>(&mut self, name: &str, permission: PermissionLevel, system: T);
+
+    /// Registers a player command, which is forwarded to the realm
+    /// on behalf of the player's session.
+    fn register_realm_command(&mut self, name: &str, request: fn(Uuid) -> RealmRequest);
+
+    /// Like `register_realm_command`, for commands taking a single name argument.
+    fn register_realm_command_with_name(&mut self, name: &str, request: fn(Uuid, String) -> RealmRequest);
 }
 
 impl CommandExtPriv for App {
-    fn register_command<T: IntoSystem<CommandInput, (), Marker> + 'static, Marker>(&mut self, name: &str, system: T) {
+    fn register_command<T: IntoSystem<CommandInput, (), Marker> + 'static, Marker>(&mut self, name: &str, permission: PermissionLevel, system: T) {
         let system = self.world_mut().register_system(system);
 
         self.world_mut()
             .resource_mut::<CommandHandlers>()
             .0
-            .insert(name.to_owned(), system);
+            .insert(name.to_owned(), (permission, system));
+    }
+
+    fn register_realm_command(&mut self, name: &str, request: fn(Uuid) -> RealmRequest) {
+        self.register_command(name, PermissionLevel::Player, move |
+            In((ent, _)): CommandInput,
+            query: Query<&PlayerController>,
+            instance: Res<ZoneInstance>,
+            mut commands: Commands,
+        | {
+            if let Ok(controller) = query.get(ent) {
+                send_realm_request(ent, &instance, &mut commands, request(*controller.session().id()));
+            }
+        });
+    }
+
+    fn register_realm_command_with_name(&mut self, name: &str, request: fn(Uuid, String) -> RealmRequest) {
+        self.register_command(name, PermissionLevel::Player, move |
+            In((ent, args)): CommandInput,
+            query: Query<&PlayerController>,
+            instance: Res<ZoneInstance>,
+            mut commands: Commands,
+        | {
+            if 
+                let Some(NativeParam::String(name)) = args.into_iter().next() &&
+                let Ok(controller) = query.get(ent)
+            {
+                send_realm_request(ent, &instance, &mut commands, request(*controller.session().id(), name));
+            }
+        });
     }
 }
 
+/// Sends a request to the realm. Only failures to deliver the request
+/// are handled here, the realm answers players through the chat.
+pub fn send_realm_request(
+    ent: Entity,
+    instance: &ZoneInstance,
+    commands: &mut Commands,
+    request: RealmRequest,
+) {
+    let client = instance.realm_client.clone();
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            client.send(request).await?;
+            Ok(())
+        })
+        .on_error_run_system(player_error_handler_system);
+}
+
 fn handle_command_request(
     In((ent, pkt)): In<(Entity, oaPktCheatingClusterNode)>,
     cmd_handlers: Res<CommandHandlers>,
+    query: Query<&PlayerController>,
+    instance: Res<ZoneInstance>,
     mut commands: Commands,
 ) {
+    let Ok(controller) = query.get(ent) else {
+        return;
+    };
+
     if let NativeParam::Struct(args) = pkt.command {
         let mut iter = args.into_iter();
 
@@ -63,13 +134,48 @@ fn handle_command_request(
             let Some(_node) = iter.next() &&
             let Some(NativeParam::String(cmd)) = iter.next()
         {
-            if let Some(system) = cmd_handlers.0.get(&cmd) {
-                commands.run_system_with(*system, (ent, iter.collect()));
-            } else {
+            let Some((permission, system)) = cmd_handlers.0.get(&cmd) else {
                 warn!("Unknown command: {cmd}");
+                return;
+            };
+
+            if controller.permission_level() < *permission {
+                warn!("Player {} tried to run command {cmd} without permission", controller.avatar_id());
+                return;
+            }
+
+            let args: Vec<NativeParam> = iter.collect();
+
+            // Every command above player level is written to the audit log.
+            if *permission > PermissionLevel::Player {
+                info!("Player {} runs GM command {cmd} {:?}", controller.avatar_id(), args);
+
+                let account_id = *controller.session().account().id();
+                let character_id = controller.character_id();
+                let zone_id = *instance.zone.guid();
+                let arguments = args.iter()
+                    .map(|arg| format!("{arg:?}"))
+                    .collect();
+
+                commands
+                    .entity(ent)
+                    .perform_async_operation(async move {
+                        RealmApi::get()
+                            .log_gm_command(account_id, character_id, zone_id, cmd, arguments).await?;
+                        Ok(())
+                    })
+                    .on_error_run_system(audit_error_handler);
             }
+
+            commands.run_system_with(*system, (ent, args));
         }
     } else {
         warn!("Invalid request format: {:?}", pkt.command);
     }
+}
+
+fn audit_error_handler(
+    In((_, err)): In<(Entity, BevyError)>,
+) {
+    error!("Failed to write GM audit log entry: {err:?}");
 }
\ No newline at end of file
@@ -43,6 +43,7 @@ impl LoadableComponent for Inventory {
                     storage.name, 
                     storage.bling, 
                     storage.game_cash,
+                    storage.soma,
                     storage.capacity,
                 );
 
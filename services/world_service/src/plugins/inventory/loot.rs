@@ -13,14 +13,20 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use bevy::{ecs::{component::Component, error::Result, system::EntityCommands}, math::Vec3};
 use obj_params::{Class, EdnaFunction, GameObjectData, ItemBase, ItemEdna, LootScatterContainer};
 use realm_api::ObjectTemplate;
 use toolkit::types::{AvatarId, UUID_NIL, Uuid};
 
-use crate::plugins::{LoadableComponent, NonPlayerGameObjectLoader, NonPlayerGameObjectLoaderParams, VirtualComponent, WeakCache, content_cache::{ContentCache, ContentCacheRef}};
+use crate::plugins::{LifetimeTracker, LoadableComponent, NonPlayerGameObjectLoader, NonPlayerGameObjectLoaderParams, VirtualComponent, WeakCache, content_cache::{ContentCache, ContentCacheRef}};
+
+/// Seconds until unclaimed loot is removed from the world.
+const LOOT_LIFETIME: f32 = 300.0;
+
+/// Scatter loot visual used for soma drops.
+const SOMA_VISUAL_TYPE: i32 = 7;
 
 #[derive(Component)]
 pub struct LootLoader {
@@ -41,11 +47,12 @@ pub struct LootParams {
 #[allow(dead_code)]
 pub enum Loot {
     Item(String, i32),
-    Soma(i32),
+    Soma(i32, i32),
 }
 
 enum LoadedLoot {
-    Item(Arc<ObjectTemplate>)
+    Item(Arc<ObjectTemplate>),
+    Soma(i32, i32),
 }
 
 impl LoadableComponent for LootLoader {
@@ -65,8 +72,15 @@ impl LoadableComponent for LootLoader {
                     loot: LoadedLoot::Item(item_template),
                 })
             },
-            Loot::Soma(_amount) => {
-                todo!()
+            Loot::Soma(soma_type, amount) => {
+                if amount <= 0 {
+                    return Err(bevy::ecs::error::BevyError::from("Invalid soma amount"));
+                }
+
+                Ok(LootLoader {
+                    params: parameters,
+                    loot: LoadedLoot::Soma(soma_type, amount),
+                })
             }
         }
     }
@@ -104,7 +118,12 @@ impl LoadableComponent for LootLoader {
                 } else {
                     data.set(LootScatterContainer::ScatterLootVisualType, 2);
                 }
-            }
+            },
+            LoadedLoot::Soma(soma_type, amount) => {
+                data.set(LootScatterContainer::SomaType, soma_type);
+                data.set(LootScatterContainer::SomaAmount, amount);
+                data.set(LootScatterContainer::ScatterLootVisualType, SOMA_VISUAL_TYPE);
+            },
         }
 
         context
@@ -119,4 +138,13 @@ impl LoadableComponent for LootLoader {
 
         Ok(())
     }
+
+    fn post_load(&mut self, commands: &mut EntityCommands<'_>, _data: Option<Self::ContextData>) -> Result<()> {
+        commands.insert(LifetimeTracker {
+            created: Instant::now(),
+            lifetime: LOOT_LIFETIME,
+        });
+
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -30,17 +30,16 @@ use bevy::{app::{Last, Plugin, PostUpdate, PreUpdate, Update}, ecs::{component::
 use bitstream_io::{ByteWriter, LittleEndian};
 use futures::{future::join_all};
 use log::{debug, error, warn};
-use mlua::Table;
-use obj_params::{Class, GameObjectData, GenericParamSet, ItemBase, ParamWriter, Player, tags::{ItemBaseTag, PlayerTag}};
+use obj_params::{Class, ContentRefList, GameObjectData, GenericParamSet, ItemBase, NpcOtherland, ParamWriter, Player, tags::{ItemBaseTag, PlayerTag}};
 use protocol::{oaPktItemStorage, oaPktShopCartBuyRequest, oaPktSteamMicroTxn, CPktItemNotify, CPktItemUpdate, ItemStorageParams, OaPktItemStorageUpdateType};
-use realm_api::{Condition, ItemRef, ObjectTemplate, Price, RealmApi};
+use realm_api::{Condition, ItemRef, ObjectTemplate, RealmApi};
 use scripting::{EntityScriptCommandsExt, LuaEntity,  ScriptAppExt, ScriptObject};
 use serde::{Deserialize, Serialize};
-use toolkit::{types::Uuid, NativeParam};
+use toolkit::{types::{AvatarId, Uuid, UUID_NIL}, NativeParam};
 
 use crate::{error::WorldResult, instance::ZoneInstance, plugins::{AsyncOperationEntityCommandsExt, Avatar, ComponentLoaderCommandsTrait, ContentCache, ContentCacheRef, InitialInventoryTransfer, Movement, QuestState, QuestStateUpdated, Quests, RecalculateAttributes, RemoveObject, WeakCache, player_error_handler_system}};
 
-use super::{BehaviorExt, CommandExtPriv, ConnectionState, ContentInfo, CurrentState, MessageType, NetworkExtPriv, PlayerController, StringBehavior};
+use super::{AvatarIdManager, BehaviorExt, CommandExtPriv, PermissionLevel, ConnectionState, ContentInfo, CurrentState, MessageType, NetworkExtPriv, PlayerController, StringBehavior};
 
 #[derive(Default)]
 pub struct EquipmentResult {
@@ -71,6 +70,7 @@ pub struct StorageResult {
     storage_id: Uuid,
     bling: Option<i32>,
     game_cash: Option<i32>,
+    soma: Option<Vec<i32>>,
     changed_items: Option<Vec<(realm_api::Item, Arc<ObjectTemplate>)>>,
     removed_items: Option<Vec<Uuid>>,
     error: Option<(String, Option<NativeParam>)>,
@@ -84,6 +84,10 @@ impl StorageResult {
         }
     }
 
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
     pub async fn from_result(result: realm_api::StorageResult) -> Result<Self> {
         let changed_items = if let Some(changed_items) = result.changed_items {
             Some(
@@ -107,6 +111,7 @@ impl StorageResult {
             storage_id: result.storage_id,
             bling: result.bling,
             game_cash: result.game_cash,
+            soma: result.soma,
             changed_items,
             removed_items: result.removed_items,
             error: result.error,
@@ -148,8 +153,10 @@ impl Plugin for InventoryPlugin {
         ));
         app.add_systems(Last, send_item_updates);
 
-        app.register_command("add_item", command_add_item);
-        app.register_command("apply_item_template", command_apply_class_preset);
+        app.register_command("add_item", PermissionLevel::GameMaster, command_add_item);
+        app.register_command("apply_item_template", PermissionLevel::GameMaster, command_apply_class_preset);
+        app.register_command("sell_item", PermissionLevel::Player, command_sell_item);
+        app.register_command("buyback_item", PermissionLevel::Player, command_buyback_item);
 
         app.register_string_behavior(Class::Player, "inventoryitempos", behavior_inventory_item_pos);
         app.register_string_behavior(Class::Player, "requestdiscarditem", behavior_inventory_discard_item);
@@ -251,8 +258,8 @@ fn insert_inventory_api(app: &mut App) {
         })
         .add_lua_api("inventory", "DropItem",
                 |
-            In((source, allow_avatar, _allow_party, item, quantity)): In<(LuaEntity, Option<LuaEntity>, Option<Table>, String, i32)>,
-            player: Query<&Avatar>,
+            In((source, allow_avatar, allow_party, item, quantity)): In<(LuaEntity, Option<LuaEntity>, Option<LuaEntity>, String, i32)>,
+            player: Query<(&Avatar, &GameObjectData)>,
             spawner: Query<(&Avatar, &ContentInfo, &Movement)>,
             mut commands: Commands
         | -> WorldResult<()> {
@@ -273,10 +280,19 @@ fn insert_inventory_api(app: &mut App) {
                         .and_then(|ent| {
                             player
                                 .get(ent)
-                                .map(|avatar| avatar.id)
+                                .map(|(avatar, _)| avatar.id)
                                 .ok()
                         }),
-                    allow_party: None,
+                    // Party loot is bound to the party of the given player
+                    allow_party: allow_party
+                        .map(LuaEntity::take)
+                        .and_then(|ent| {
+                            player
+                                .get(ent)
+                                .ok()
+                                .and_then(|(_, data)| data.get::<_, Uuid>(Player::PartyGuid).ok().copied())
+                        })
+                        .filter(|party| *party != UUID_NIL),
                     loot: Loot::Item(item, quantity),
                     pos,
                 });
@@ -285,11 +301,50 @@ fn insert_inventory_api(app: &mut App) {
         })
         .add_lua_api("inventory", "DropSoma",
                 |
-            In((_allow_avatar, _allow_party, _item, _quantity)): In<(Option<Table>, Option<Table>, String, i32)>
+            In((source, allow_avatar, allow_party, soma_type, amount)): In<(LuaEntity, Option<LuaEntity>, Option<LuaEntity>, i32, i32)>,
+            player: Query<(&Avatar, &GameObjectData)>,
+            spawner: Query<(&Avatar, &ContentInfo, &Movement)>,
+            mut commands: Commands
         | -> WorldResult<()> {
+            let Ok((avatar, content, movement)) = spawner.get(source.entity()) else {
+                return Err(anyhow!("source not found").into());
+            };
 
+            if amount <= 0 {
+                return Err(anyhow!("invalid soma amount").into());
+            }
 
-            todo!()
+            let spawner_id = avatar.id;
+            let spawner_guid = content.placement_id;
+            let pos = movement.position;
+
+            commands
+                .spawn_empty()
+                .load_component::<LootLoader>(LootParams {
+                    spawner: (spawner_id, spawner_guid),
+                    allow_player: allow_avatar
+                        .map(LuaEntity::take)
+                        .and_then(|ent| {
+                            player
+                                .get(ent)
+                                .map(|(avatar, _)| avatar.id)
+                                .ok()
+                        }),
+                    // Party loot is bound to the party of the given player
+                    allow_party: allow_party
+                        .map(LuaEntity::take)
+                        .and_then(|ent| {
+                            player
+                                .get(ent)
+                                .ok()
+                                .and_then(|(_, data)| data.get::<_, Uuid>(Player::PartyGuid).ok().copied())
+                        })
+                        .filter(|party| *party != UUID_NIL),
+                    loot: Loot::Soma(soma_type, amount),
+                    pos,
+                });
+
+            Ok(())
         });
 }
 
@@ -312,13 +367,14 @@ pub struct Inventory {
 
     pub bling: Option<i32>,
     pub game_cash: Option<i32>,
+    pub soma: Option<Vec<i32>>,
     pub max_slots: i32,
 
     observing_players: HashSet<Entity>,
 }
 
 impl Inventory {
-    fn new(id: Uuid, name: String, bling: Option<i32>, game_cash: Option<i32>, max_slots: i32) -> Self {
+    fn new(id: Uuid, name: String, bling: Option<i32>, game_cash: Option<i32>, soma: Option<Vec<i32>>, max_slots: i32) -> Self {
         Self {
             id,
             name,
@@ -326,6 +382,7 @@ impl Inventory {
 
             bling,
             game_cash,
+            soma,
             max_slots,
 
             observing_players: HashSet::new(),
@@ -644,6 +701,14 @@ pub fn apply_storage_result(
             }
         }
 
+        if let Some(soma) = result.soma {
+            if let Ok(mut player) = players.get_mut(storage_ent) {
+                player.set(Player::SomaCarried, soma.clone());
+            }
+
+            storage.soma = Some(soma);
+        }
+
         if let Some(changed_items) = result.changed_items {
             for (item, template) in changed_items {
                 let mut instance = item.instance;
@@ -811,34 +876,56 @@ fn send_item_updates(
     }
 }
 
+/// Returns the items sold by the vendor the player has targeted,
+/// or `None` if the player isn't targeting a vendor.
+fn target_vendor_items(
+    player: &GameObjectData,
+    avatar_manager: &AvatarIdManager,
+    vendors: &Query<&GameObjectData, Without<PlayerTag>>,
+) -> Option<Vec<Uuid>> {
+    player.get::<_, AvatarId>(Player::Target).ok()
+        .and_then(|&target| avatar_manager.resolve_avatar_id(target))
+        .and_then(|target| vendors.get(target).ok())
+        .and_then(|vendor| vendor.get::<_, ContentRefList>(NpcOtherland::SellExacts).ok())
+        .map(|items| items.iter().map(|item| item.id).collect())
+}
+
 fn handle_shop_cart_buy_request(
     In((ent, pkt)): In<(Entity, oaPktShopCartBuyRequest)>,
-    query: Query<&Inventory>,
+    query: Query<(&Inventory, &GameObjectData)>,
+    vendors: Query<&GameObjectData, Without<PlayerTag>>,
+    avatar_manager: Res<AvatarIdManager>,
     mut commands: Commands,
 ) {
-    if let Ok(storage) = query.get(ent) {
+    if let Ok((storage, player)) = query.get(ent) {
         let storage_id = storage.id;
+        let vendor_items = target_vendor_items(player, &avatar_manager, &vendors)
+            .unwrap_or_default();
 
-        if let Some(entry) = pkt.shopping_cart.first().cloned() {
+        let items = pkt.shopping_cart.iter()
+            .map(|entry| entry.id)
+            .collect::<Vec<_>>();
+
+        if !items.is_empty() {
             commands
                 .entity(ent)
                 .perform_async_operation(async move {
                     match RealmApi::get()
                         .item_storage_access(&storage_id)
-                        .purchase_item(ItemRef::Uuid(entry.id), None, Price::Bling(0))
+                        .purchase_cart(items, vendor_items, None)
                         .await
                     {
                         Ok(res) => {
                             match StorageResult::from_result(res).await {
                                 Ok(result) => Ok(result),
                                 Err(e) => {
-                                    error!("Failed to purchase item: {e}");
+                                    error!("Failed to purchase items: {e}");
                                     Ok(StorageResult::default())
                                 }
                             }
                         },
                         Err(e) => {
-                            error!("Failed to purchase item: {e:?}");
+                            error!("Failed to purchase items: {e:?}");
                             Ok(StorageResult::error("#Shop.false_buymultiple#"))
                         }
                     }
@@ -850,6 +937,108 @@ fn handle_shop_cart_buy_request(
     }
 }
 
+fn command_sell_item(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<(&Inventory, &GameObjectData)>,
+    vendors: Query<&GameObjectData, Without<PlayerTag>>,
+    avatar_manager: Res<AvatarIdManager>,
+    mut commands: Commands,
+) {
+    let mut args = args.into_iter();
+
+    if 
+        let Some(NativeParam::String(item_id)) = args.next() &&
+        let Ok(item_id) = item_id.parse::<Uuid>() &&
+        let Ok((storage, player)) = query.get(ent)
+    {
+        let storage_id = storage.id;
+
+        // Items can only be sold to a vendor
+        let at_vendor = target_vendor_items(player, &avatar_manager, &vendors).is_some();
+
+        commands
+            .entity(ent)
+            .perform_async_operation(async move {
+                if !at_vendor {
+                    return Ok(StorageResult::error("#Shop.false_sell#"));
+                }
+
+                match RealmApi::get()
+                    .item_storage_access(&storage_id)
+                    .sell_item(item_id, Some(ent.to_string()))
+                    .await
+                {
+                    Ok(res) => {
+                        match StorageResult::from_result(res).await {
+                            Ok(result) => Ok(result),
+                            Err(e) => {
+                                error!("Failed to sell item: {e}");
+                                Ok(StorageResult::default())
+                            }
+                        }
+                    },
+                    Err(e) => {
+                        warn!("Failed to sell item: {e:?}");
+                        Ok(StorageResult::default())
+                    }
+                }
+            })
+            .on_finish_run_system(apply_storage_result)
+            .on_error_run_system(player_error_handler_system);
+    }
+}
+
+fn command_buyback_item(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<(&Inventory, &GameObjectData)>,
+    vendors: Query<&GameObjectData, Without<PlayerTag>>,
+    avatar_manager: Res<AvatarIdManager>,
+    mut commands: Commands,
+) {
+    let mut args = args.into_iter();
+
+    if 
+        let Some(NativeParam::String(item_id)) = args.next() &&
+        let Ok(item_id) = item_id.parse::<Uuid>() &&
+        let Ok((storage, player)) = query.get(ent)
+    {
+        let storage_id = storage.id;
+
+        // Items can only be bought back from a vendor
+        let at_vendor = target_vendor_items(player, &avatar_manager, &vendors).is_some();
+
+        commands
+            .entity(ent)
+            .perform_async_operation(async move {
+                if !at_vendor {
+                    return Ok(StorageResult::error("#Shop.false_buymultiple#"));
+                }
+
+                match RealmApi::get()
+                    .item_storage_access(&storage_id)
+                    .buyback_item(item_id, Some(ent.to_string()))
+                    .await
+                {
+                    Ok(res) => {
+                        match StorageResult::from_result(res).await {
+                            Ok(result) => Ok(result),
+                            Err(e) => {
+                                error!("Failed to buy back item: {e}");
+                                Ok(StorageResult::default())
+                            }
+                        }
+                    },
+                    Err(e) => {
+                        warn!("Failed to buy back item: {e:?}");
+                        Ok(StorageResult::default())
+                    }
+                }
+            })
+            .on_finish_run_system(apply_storage_result)
+            .on_error_run_system(player_error_handler_system);
+    }
+}
+
 fn handle_purchase_result(
     In((instigator, result)): In<(Entity, StorageResult)>,
     query: Query<&PlayerController>,
@@ -32,6 +32,8 @@ impl Plugin for LifetimePlugin {
 #[derive(Component)]
 pub struct LifetimeTracker {
     pub created: Instant,
+    /// Seconds until the avatar is despawned.
+    pub lifetime: f32,
 }
 
 fn create_lifetime_trackers(
@@ -45,20 +47,19 @@ fn create_lifetime_trackers(
             *lifetime > 0.0
         {
             commands.entity(entity)
-                .insert(LifetimeTracker { created: Instant::now() });
+                .insert(LifetimeTracker { created: Instant::now(), lifetime: *lifetime });
         }
     }
 }
 
 fn check_lifetime(
-    query: Query<(Entity, &LifetimeTracker, &GameObjectData, &SpawnState)>,
+    query: Query<(Entity, &LifetimeTracker, &SpawnState)>,
     mut commands: Commands,
 ) {
-    for (entity, tracker, obj, state) in query.iter() {
+    for (entity, tracker, state) in query.iter() {
         if 
             matches!(state, SpawnState::Alive) &&
-            let Ok(lifetime) = obj.get_named::<f32>("aliveTime") &&
-            tracker.created.elapsed().as_secs_f32() > *lifetime
+            tracker.created.elapsed().as_secs_f32() > tracker.lifetime
         {
             commands
                 .entity(entity)
@@ -32,7 +32,7 @@ pub use component_loader::*;
 pub use cache::*;
 use toolkit::{NativeParam, OtherlandQuatExt, types::Uuid};
 
-use crate::{instance::{InstanceState, ZoneInstance}, plugins::{Avatar, CommandExtPriv, Movement, NonPlayerGameObjectLoader, NonPlayerGameObjectLoaderParams, ZoneLoader, ZoneLoaderParameter, navigation}};
+use crate::{instance::{InstanceState, ZoneInstance}, plugins::{Avatar, CommandExtPriv, PermissionLevel, Movement, NonPlayerGameObjectLoader, NonPlayerGameObjectLoaderParams, ZoneLoader, ZoneLoaderParameter, navigation}};
 
 pub struct LoaderPlugin;
 
@@ -62,8 +62,8 @@ impl Plugin for LoaderPlugin {
         app.add_systems(Update, sync_debug_pos.after(navigation::update));
         app.add_systems(Last, cleanup_dynamic_instances);
 
-        app.register_command("get_avatar_info", command_get_avatar_info);
-        app.register_command("show_hidden_structures", |
+        app.register_command("get_avatar_info", PermissionLevel::GameMaster, command_get_avatar_info);
+        app.register_command("show_hidden_structures", PermissionLevel::GameMaster, |
             _: In<(Entity, Vec<NativeParam>)>,
             query: Query<(Entity, &Avatar, &Movement), With<StructureTag>>,
             mut commands: Commands,
@@ -0,0 +1,271 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bevy::{app::{Plugin, PostStartup, Update}, ecs::{error::BevyError, message::MessageReader, resource::Resource, system::{Commands, In, Query, Res}}, prelude::{App, Entity, With}};
+use futures::TryStreamExt;
+use log::{debug, error};
+use obj_params::{GameObjectData, NpcOtherland, Player, tags::{NpcOtherlandTag, PlayerTag}};
+use rand::{Rng, thread_rng};
+use realm_api::{LootKind, LootTable, LootTableEntry, RealmApi};
+use toolkit::types::{UUID_NIL, Uuid};
+
+use crate::plugins::{apply_storage_result, AsyncOperationCommandsExt, AsyncOperationEntityCommandsExt, Avatar, CombatEvent, CombatEventType, ComponentLoaderCommandsTrait, ContentInfo, Inventory, Loot, LootLoader, LootParams, MessageType, Movement, PlayerController, QuestLog, StorageResult};
+
+pub struct LootTablesPlugin;
+
+impl Plugin for LootTablesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LootTables>();
+
+        app.add_systems(PostStartup, load_loot_tables);
+        app.add_systems(Update, roll_loot_tables);
+    }
+}
+
+/// Loot tables of the realm, rolled whenever a player kills an NPC.
+/// 
+/// Tables are loaded once on startup. Tables imported afterwards, e.g. by
+/// the quest-compiler, only apply after the world node has been restarted.
+#[derive(Resource, Default)]
+pub struct LootTables(Vec<LootTable>);
+
+fn load_loot_tables(mut commands: Commands) {
+    commands
+        .perform_async_operation(async move {
+            let mut cursor = RealmApi::get()
+                .query_loot_tables()
+                .query()
+                .await?;
+
+            let mut tables = vec![];
+            while let Some(table) = cursor.try_next().await? {
+                tables.push(table);
+            }
+
+            Ok(tables)
+        })
+        .on_finish_run_system(|
+            In(tables): In<Vec<LootTable>>,
+            mut commands: Commands,
+        | {
+            debug!("Loaded {} loot tables", tables.len());
+            commands.insert_resource(LootTables(tables));
+        })
+        .on_error_run_system(|In(e): In<bevy::ecs::error::BevyError>| {
+            error!("Failed to load loot tables: {e:?}");
+        });
+}
+
+/// Party members further away from a kill than this don't get to roll loot.
+const LOOT_RANGE: f32 = 5000.0;
+
+fn roll_loot_tables(
+    mut events: MessageReader<CombatEvent>,
+    loot_tables: Res<LootTables>,
+    players: Query<(Entity, &Avatar, &GameObjectData, &QuestLog, &Inventory, &Movement), With<PlayerTag>>,
+    npcs: Query<(&Avatar, &ContentInfo, &GameObjectData, &Movement), With<NpcOtherlandTag>>,
+    mut commands: Commands,
+) {
+    for &CombatEvent { target, instigator, update, .. } in events.read() {
+        if !matches!(update, CombatEventType::Death) {
+            continue;
+        }
+
+        let Some(killer) = instigator else {
+            continue;
+        };
+
+        let Ok((_, _, killer_data, ..)) = players.get(killer) else {
+            continue;
+        };
+
+        let Ok((npc_avatar, content, npc_data, movement)) = npcs.get(target) else {
+            continue;
+        };
+
+        let level = *npc_data.get::<_, i32>(NpcOtherland::Lvl).unwrap_or(&0);
+        let party = killer_data.get::<_, Uuid>(Player::PartyGuid).ok().copied()
+            .filter(|party| *party != UUID_NIL);
+
+        // The killer and every party member close to the kill hold loot rights,
+        // each of them rolls their own loot.
+        let looters = players.iter()
+            .filter(|(ent, _, data, _, _, player_movement)| {
+                *ent == killer || (
+                    party.is_some() &&
+                    data.get::<_, Uuid>(Player::PartyGuid).ok().copied() == party &&
+                    player_movement.position.distance(movement.position) <= LOOT_RANGE
+                )
+            });
+
+        for (looter, player_avatar, _, quest_log, inventory, _) in looters {
+            let mut bits = 0;
+
+            for table in loot_tables.0.iter().filter(|table| table.applies_to(content.template.id, level)) {
+                if !thread_rng().gen_bool(table.drop_chance.clamp(0.0, 1.0)) {
+                    continue;
+                }
+
+                // Quest drops only roll for looters currently on that quest
+                let entries = table.entries.iter()
+                    .filter(|entry| entry.quest_id.is_none_or(|quest_id| quest_log.in_progress.contains(&quest_id)))
+                    .collect::<Vec<_>>();
+
+                for _ in 0..table.rolls {
+                    let Some(entry) = pick_entry(&mut thread_rng(), &entries) else {
+                        break;
+                    };
+
+                    let amount = thread_rng().gen_range(entry.min_amount..=entry.max_amount.max(entry.min_amount));
+                    if amount <= 0 {
+                        continue;
+                    }
+
+                    let loot = match entry.kind {
+                        LootKind::Item => {
+                            let Some(item_name) = entry.item_name.clone() else {
+                                continue;
+                            };
+
+                            Loot::Item(item_name, amount)
+                        },
+                        LootKind::Soma => Loot::Soma(entry.soma_type.unwrap_or_default(), amount),
+                        LootKind::Bits => {
+                            bits += amount;
+                            continue;
+                        },
+                    };
+
+                    debug!("Loot table {} dropped {:?} for {}", table.name, entry.kind, player_avatar.id);
+
+                    commands
+                        .spawn_empty()
+                        .load_component::<LootLoader>(LootParams {
+                            spawner: (npc_avatar.id, content.placement_id),
+                            allow_player: Some(player_avatar.id),
+                            allow_party: None,
+                            loot,
+                            pos: movement.position,
+                        });
+                }
+            }
+
+            // Bits don't drop into the world, they are credited directly.
+            if bits > 0 {
+                let storage_id = inventory.id;
+
+                commands
+                    .entity(looter)
+                    .perform_async_operation(async move {
+                        let result = RealmApi::get()
+                            .item_storage_access(&storage_id)
+                            .add_bits(bits, Some(looter.to_string()))
+                            .await?;
+
+                        StorageResult::from_result(result).await
+                    })
+                    .on_finish_run_system(apply_storage_result)
+                    .on_error_run_system(bits_error_handler);
+            }
+        }
+    }
+}
+
+fn bits_error_handler(
+    In((ent, err)): In<(Entity, BevyError)>,
+    players: Query<&PlayerController>,
+) {
+    error!("Failed to credit looted bits: {err:?}");
+
+    if let Ok(controller) = players.get(ent) {
+        controller.send_message(MessageType::Normal, "Your looted bits couldn't be credited.");
+    }
+}
+
+fn pick_entry<'a>(rng: &mut impl Rng, entries: &[&'a LootTableEntry]) -> Option<&'a LootTableEntry> {
+    let total = entries.iter()
+        .map(|entry| entry.weight.max(0))
+        .sum::<i32>();
+
+    if total <= 0 {
+        return None;
+    }
+
+    let mut pick = rng.gen_range(0..total);
+
+    for &entry in entries {
+        let weight = entry.weight.max(0);
+        if pick < weight {
+            return Some(entry);
+        }
+
+        pick -= weight;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+    use realm_api::{LootKind, LootTableEntry};
+
+    use super::pick_entry;
+
+    fn entry(weight: i32) -> LootTableEntry {
+        LootTableEntry {
+            kind: LootKind::Bits,
+            item_name: None,
+            soma_type: None,
+            min_amount: 1,
+            max_amount: 1,
+            weight,
+            quest_id: None,
+        }
+    }
+
+    #[test]
+    fn no_entries_picks_nothing() {
+        assert!(pick_entry(&mut StdRng::seed_from_u64(0), &[]).is_none());
+    }
+
+    #[test]
+    fn zero_and_negative_weights_are_never_picked() {
+        let zero = entry(0);
+        let negative = entry(-5);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        assert!(pick_entry(&mut rng, &[&zero, &negative]).is_none());
+
+        let weighted = entry(1);
+        for _ in 0..100 {
+            let picked = pick_entry(&mut rng, &[&zero, &weighted, &negative]).unwrap();
+            assert!(std::ptr::eq(picked, &weighted));
+        }
+    }
+
+    #[test]
+    fn picks_follow_weights() {
+        let common = entry(3);
+        let rare = entry(1);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let rare_picks = (0..4000)
+            .filter(|_| std::ptr::eq(pick_entry(&mut rng, &[&common, &rare]).unwrap(), &rare))
+            .count();
+
+        assert!((800..1200).contains(&rare_picks), "rare entry picked {rare_picks} times");
+    }
+}
@@ -0,0 +1,422 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bevy::{app::Plugin, ecs::{error::{BevyError, Result}, system::{Commands, In, Query}}, prelude::{App, Entity}};
+use log::error;
+use realm_api::{ItemRef, Mail, RealmApi, RealmApiError};
+use toolkit::{types::Uuid, NativeParam};
+
+use crate::plugins::{apply_storage_result, AsyncOperationEntityCommandsExt, Avatar, CommandExtPriv, Inventory, MessageType, PermissionLevel, StorageResult};
+
+use super::PlayerController;
+
+pub struct MailPlugin;
+
+impl Plugin for MailPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_command("mail_list", PermissionLevel::Player, cmd_mail_list);
+        app.register_command("mail_read", PermissionLevel::Player, cmd_mail_read);
+        app.register_command("mail_send", PermissionLevel::Player, cmd_mail_send);
+        app.register_command("mail_take", PermissionLevel::Player, cmd_mail_take);
+        app.register_command("mail_return", PermissionLevel::Player, cmd_mail_return);
+        app.register_command("mail_delete", PermissionLevel::Player, cmd_mail_delete);
+        app.register_command("mail_grant", PermissionLevel::GameMaster, cmd_mail_grant);
+    }
+}
+
+/// Outcome of a mail command, reported back to the player.
+struct MailReply {
+    message: String,
+    storage_results: Vec<StorageResult>,
+}
+
+impl From<String> for MailReply {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            storage_results: vec![],
+        }
+    }
+}
+
+// Errors reported by the mail system are meant for the player,
+// everything else is a server side problem.
+fn client_error(err: RealmApiError) -> Result<MailReply> {
+    match err {
+        RealmApiError::GraphQl(errors) => Ok(
+            errors.into_iter()
+                .next()
+                .map(|err| err.message)
+                .unwrap_or_else(|| "Mail failed.".to_string())
+                .into()
+        ),
+        err => Err(err.into()),
+    }
+}
+
+async fn storage_results(results: Vec<realm_api::StorageResult>) -> Result<Vec<StorageResult>> {
+    let mut storage_results = Vec::new();
+    for res in results {
+        storage_results.push(StorageResult::from_result(res).await?);
+    }
+
+    Ok(storage_results)
+}
+
+// Mails are addressed by their position in the mailbox, as shown by mail_list.
+async fn mail_at(character_id: Uuid, index: usize) -> Result<Option<Mail>> {
+    Ok(RealmApi::get()
+        .get_mailbox(character_id).await?
+        .into_iter()
+        .nth(index.saturating_sub(1)))
+}
+
+fn mail_index(args: &[NativeParam]) -> Option<usize> {
+    match args.first() {
+        Some(NativeParam::Int(idx)) => usize::try_from(*idx).ok(),
+        Some(NativeParam::String(idx)) => idx.parse().ok(),
+        _ => None,
+    }
+    .filter(|idx| *idx > 0)
+}
+
+fn describe_attachments(mail: &Mail) -> Option<String> {
+    match (mail.attached_items, mail.attached_bits) {
+        (0, 0) => None,
+        (items, 0) => Some(format!("{items} item(s)")),
+        (0, bits) => Some(format!("{bits} bits")),
+        (items, bits) => Some(format!("{items} item(s) and {bits} bits")),
+    }
+}
+
+fn cmd_mail_list(
+    In((ent, _)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<&PlayerController>,
+    mut commands: Commands,
+) {
+    let Ok(controller) = query.get(ent) else {
+        return;
+    };
+
+    let character_id = controller.character_id();
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            let mailbox = RealmApi::get().get_mailbox(character_id).await?;
+
+            if mailbox.is_empty() {
+                return Ok(MailReply::from("Your mailbox is empty.".to_string()));
+            }
+
+            let lines = mailbox.iter()
+                .enumerate()
+                .map(|(idx, mail)| {
+                    let mut line = format!("[{}] {}{}: {}", idx + 1, if mail.read { "" } else { "(new) " }, mail.sender_name, mail.subject);
+
+                    if let Some(attachments) = describe_attachments(mail) {
+                        line.push_str(&format!(" - {attachments}"));
+                    }
+
+                    line
+                })
+                .collect::<Vec<_>>();
+
+            Ok(MailReply::from(lines.join("\n")))
+        })
+        .on_finish_run_system(send_mail_reply)
+        .on_error_run_system(mail_error_handler);
+}
+
+fn cmd_mail_read(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<&PlayerController>,
+    mut commands: Commands,
+) {
+    let Ok(controller) = query.get(ent) else {
+        return;
+    };
+
+    let Some(index) = mail_index(&args) else {
+        controller.send_message(MessageType::Normal, "Usage: mail_read <number>");
+        return;
+    };
+
+    let character_id = controller.character_id();
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            let Some(mail) = mail_at(character_id, index).await? else {
+                return Ok(MailReply::from("Mail not found.".to_string()));
+            };
+
+            match RealmApi::get().read_mail(character_id, mail.id).await {
+                Ok(mail) => {
+                    let mut message = format!("From {}: {}\n{}", mail.sender_name, mail.subject, mail.body);
+
+                    if let Some(attachments) = describe_attachments(&mail) {
+                        message.push_str(&format!("\nAttached: {attachments}"));
+                    }
+
+                    Ok(MailReply::from(message))
+                },
+                Err(e) => client_error(e),
+            }
+        })
+        .on_finish_run_system(send_mail_reply)
+        .on_error_run_system(mail_error_handler);
+}
+
+// mail_send <recipient> <subject> <body> [bits] [item ids...]
+fn cmd_mail_send(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<(&PlayerController, &Inventory)>,
+    mut commands: Commands,
+) {
+    let Ok((controller, inventory)) = query.get(ent) else {
+        return;
+    };
+
+    let mut args = args.into_iter();
+
+    let (
+        Some(NativeParam::String(recipient)),
+        Some(NativeParam::String(subject)),
+        Some(NativeParam::String(body)),
+    ) = (args.next(), args.next(), args.next()) else {
+        controller.send_message(MessageType::Normal, "Usage: mail_send <recipient> <subject> <body> [bits] [item ids...]");
+        return;
+    };
+
+    let mut bits = 0;
+    let mut items = Vec::new();
+
+    for arg in args {
+        match arg {
+            NativeParam::Int(amount) => bits = amount,
+            NativeParam::String(arg) => {
+                if let Ok(item_id) = arg.parse::<Uuid>() {
+                    items.push(item_id);
+                } else if let Ok(amount) = arg.parse::<i32>() {
+                    bits = amount;
+                } else {
+                    controller.send_message(MessageType::Normal, format!("Invalid attachment: {arg}"));
+                    return;
+                }
+            },
+            _ => (),
+        }
+    }
+
+    let character_id = controller.character_id();
+    let storage_id = inventory.id;
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            match RealmApi::get()
+                .send_mail(character_id, storage_id, &recipient, &subject, &body, items, bits, None)
+                .await
+            {
+                Ok(results) => Ok(MailReply {
+                    message: format!("Mail sent to {recipient}."),
+                    storage_results: storage_results(results).await?,
+                }),
+                Err(e) => client_error(e),
+            }
+        })
+        .on_finish_run_system(send_mail_reply)
+        .on_error_run_system(mail_error_handler);
+}
+
+fn cmd_mail_take(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<(&PlayerController, &Inventory)>,
+    mut commands: Commands,
+) {
+    let Ok((controller, inventory)) = query.get(ent) else {
+        return;
+    };
+
+    let Some(index) = mail_index(&args) else {
+        controller.send_message(MessageType::Normal, "Usage: mail_take <number>");
+        return;
+    };
+
+    let character_id = controller.character_id();
+    let storage_id = inventory.id;
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            let Some(mail) = mail_at(character_id, index).await? else {
+                return Ok(MailReply::from("Mail not found.".to_string()));
+            };
+
+            match RealmApi::get()
+                .take_mail_attachments(character_id, mail.id, storage_id, None)
+                .await
+            {
+                Ok(results) => Ok(MailReply {
+                    message: "Attachments taken.".to_string(),
+                    storage_results: storage_results(results).await?,
+                }),
+                Err(e) => client_error(e),
+            }
+        })
+        .on_finish_run_system(send_mail_reply)
+        .on_error_run_system(mail_error_handler);
+}
+
+fn cmd_mail_return(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<&PlayerController>,
+    mut commands: Commands,
+) {
+    let Ok(controller) = query.get(ent) else {
+        return;
+    };
+
+    let Some(index) = mail_index(&args) else {
+        controller.send_message(MessageType::Normal, "Usage: mail_return <number>");
+        return;
+    };
+
+    let character_id = controller.character_id();
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            let Some(mail) = mail_at(character_id, index).await? else {
+                return Ok(MailReply::from("Mail not found.".to_string()));
+            };
+
+            match RealmApi::get().return_mail(character_id, mail.id).await {
+                Ok(_) => Ok(MailReply::from(format!("Mail returned to {}.", mail.sender_name))),
+                Err(e) => client_error(e),
+            }
+        })
+        .on_finish_run_system(send_mail_reply)
+        .on_error_run_system(mail_error_handler);
+}
+
+fn cmd_mail_delete(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<&PlayerController>,
+    mut commands: Commands,
+) {
+    let Ok(controller) = query.get(ent) else {
+        return;
+    };
+
+    let Some(index) = mail_index(&args) else {
+        controller.send_message(MessageType::Normal, "Usage: mail_delete <number>");
+        return;
+    };
+
+    let character_id = controller.character_id();
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            let Some(mail) = mail_at(character_id, index).await? else {
+                return Ok(MailReply::from("Mail not found.".to_string()));
+            };
+
+            match RealmApi::get().delete_mail(character_id, mail.id).await {
+                Ok(_) => Ok(MailReply::from("Mail deleted.".to_string())),
+                Err(e) => client_error(e),
+            }
+        })
+        .on_finish_run_system(send_mail_reply)
+        .on_error_run_system(mail_error_handler);
+}
+
+// mail_grant <recipient> <bits> [item names...]
+fn cmd_mail_grant(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    query: Query<(&PlayerController, &Avatar)>,
+    mut commands: Commands,
+) {
+    let Ok((controller, avatar)) = query.get(ent) else {
+        return;
+    };
+
+    let mut args = args.into_iter();
+
+    let Some(NativeParam::String(recipient)) = args.next() else {
+        controller.send_message(MessageType::Normal, "Usage: mail_grant <recipient> <bits> [item names...]");
+        return;
+    };
+
+    let bits = match args.next() {
+        Some(NativeParam::Int(bits)) => bits,
+        Some(NativeParam::String(bits)) => bits.parse().unwrap_or(0),
+        _ => 0,
+    };
+
+    let items = args
+        .filter_map(|arg| if let NativeParam::String(name) = arg { Some(name) } else { None })
+        .collect::<Vec<_>>();
+
+    let sender_name = avatar.name.clone();
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            match RealmApi::get()
+                .send_system_mail(
+                    &sender_name,
+                    &recipient,
+                    "A gift for you",
+                    "Please find your reward attached.",
+                    items.iter().map(|name| ItemRef::Name(name.as_str())).collect(),
+                    bits
+                )
+                .await
+            {
+                Ok(_) => Ok(MailReply::from(format!("Mail sent to {recipient}."))),
+                Err(e) => client_error(e),
+            }
+        })
+        .on_finish_run_system(send_mail_reply)
+        .on_error_run_system(mail_error_handler);
+}
+
+fn send_mail_reply(
+    In((ent, reply)): In<(Entity, MailReply)>,
+    query: Query<&PlayerController>,
+    mut commands: Commands,
+) {
+    if let Ok(controller) = query.get(ent) {
+        controller.send_message(MessageType::Normal, reply.message);
+    }
+
+    for result in reply.storage_results {
+        commands.run_system_cached_with(apply_storage_result, (ent, result));
+    }
+}
+
+fn mail_error_handler(
+    In((ent, err)): In<(Entity, BevyError)>,
+    query: Query<&PlayerController>,
+) {
+    error!("Mail request failed: {err:?}");
+
+    if let Ok(controller) = query.get(ent) {
+        controller.send_message(MessageType::Normal, "Mail is currently unavailable.");
+    }
+}
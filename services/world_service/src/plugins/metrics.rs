@@ -0,0 +1,116 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::{sync::LazyLock, time::{Duration, Instant}};
+
+use bevy::{app::{First, Last, Plugin, PreStartup}, ecs::{entity::Entity, resource::Resource, schedule::IntoScheduleConfigs, system::{Commands, Query, Res, ResMut}}, time::common_conditions::on_timer};
+use prometheus::{register_histogram_vec, register_int_gauge_vec, Histogram, HistogramVec, IntGauge, IntGaugeVec};
+
+use crate::{instance::{InstanceShutdown, ZoneInstance}, plugins::PlayerController};
+
+static TICK_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "world_zone_tick_duration_seconds",
+        "Time spent updating a zone instance",
+        &["zone", "instance"],
+        vec![0.001, 0.0025, 0.005, 0.01, 0.015, 0.02, 0.03, 0.05, 0.1, 0.25]
+    ).expect("metric can be registered")
+});
+
+static ENTITIES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!("world_zone_entities", "Number of entities in a zone instance", &["zone", "instance"])
+        .expect("metric can be registered")
+});
+
+static PLAYERS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!("world_zone_players", "Number of players in a zone instance", &["zone", "instance"])
+        .expect("metric can be registered")
+});
+
+pub struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_systems(PreStartup, init_zone_metrics);
+        app.add_systems(First, begin_tick);
+        app.add_systems(Last, (
+            end_tick,
+            update_population
+                .run_if(on_timer(Duration::from_secs(1))),
+        ));
+        app.add_systems(InstanceShutdown, remove_zone_metrics);
+    }
+}
+
+#[derive(Resource)]
+struct ZoneMetrics {
+    labels: [String; 2],
+    tick_started: Instant,
+    tick_duration: Histogram,
+    entities: IntGauge,
+    players: IntGauge,
+}
+
+fn init_zone_metrics(
+    instance: Res<ZoneInstance>,
+    mut commands: Commands,
+) {
+    let labels = [
+        instance.zone.zone().to_string(),
+        instance.instance_id
+            .map(|id| id.to_string())
+            .unwrap_or_default(),
+    ];
+
+    commands.insert_resource(ZoneMetrics {
+        tick_started: Instant::now(),
+        tick_duration: TICK_DURATION.with_label_values(&labels),
+        entities: ENTITIES.with_label_values(&labels),
+        players: PLAYERS.with_label_values(&labels),
+        labels,
+    });
+}
+
+fn begin_tick(metrics: Option<ResMut<ZoneMetrics>>) {
+    if let Some(mut metrics) = metrics {
+        metrics.tick_started = Instant::now();
+    }
+}
+
+fn end_tick(metrics: Option<Res<ZoneMetrics>>) {
+    if let Some(metrics) = metrics {
+        metrics.tick_duration.observe(metrics.tick_started.elapsed().as_secs_f64());
+    }
+}
+
+fn update_population(
+    metrics: Option<Res<ZoneMetrics>>,
+    entities: Query<Entity>,
+    players: Query<&PlayerController>,
+) {
+    if let Some(metrics) = metrics {
+        metrics.entities.set(entities.iter().count() as i64);
+        metrics.players.set(players.iter().count() as i64);
+    }
+}
+
+fn remove_zone_metrics(metrics: Option<Res<ZoneMetrics>>) {
+    if let Some(metrics) = metrics {
+        let _ = TICK_DURATION.remove_label_values(&metrics.labels);
+        let _ = ENTITIES.remove_label_values(&metrics.labels);
+        let _ = PLAYERS.remove_label_values(&metrics.labels);
+    }
+}
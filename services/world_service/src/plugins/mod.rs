@@ -19,6 +19,7 @@ mod util;
 mod player;
 mod avatar;
 mod movement;
+mod movement_validation;
 mod social;
 mod server_action;
 mod interests;
@@ -45,6 +46,12 @@ mod non_player;
 mod content_cache;
 mod async_operation;
 mod attributes;
+mod metrics;
+mod party;
+mod clan;
+mod trade;
+mod mail;
+mod loot_tables;
 
 pub use network::*;
 pub use loader::*;
@@ -52,6 +59,7 @@ pub use util::*;
 pub use player::*;
 pub use avatar::*;
 pub use movement::*;
+pub use movement_validation::*;
 pub use social::*;
 pub use server_action::*;
 pub use interests::*;
@@ -78,3 +86,9 @@ pub use non_player::*;
 pub use content_cache::*;
 pub use async_operation::*;
 pub use attributes::*;
+pub use metrics::*;
+pub use party::*;
+pub use clan::*;
+pub use trade::*;
+pub use mail::*;
+pub use loot_tables::*;
@@ -14,14 +14,15 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use bevy::{app::{Plugin, PostUpdate, Update}, ecs::{component::Component, event::EntityEvent, observer::On, query::Has, system::Res}, math::{Quat, Vec3}, prelude::{App, Changed, Commands, Entity, In, Query, With}, time::{Real, Time, Virtual}};
-use log::{debug, error};
+use log::{debug, error, warn};
 use obj_params::{Class, GameObjectData, NonClientBase, NpcOtherland, Player, tags::{NonClientBaseTag, PlayerTag}};
 use protocol::{oaPktMoveManagerPosUpdate, oaPktMoveManagerStateChanged, Physics, PhysicsState};
+use realm_api::proto::RealmRequest;
 use scripting::{EntityScriptCommandsExt, LuaEntity, ScriptAppExt};
 use toolkit::{OtherlandQuatExt, QuatWrapper, Vec3Wrapper};
 use anyhow::anyhow;
 
-use crate::{error::WorldResult, plugins::{InitializeObject, Interruption, Kind, Navmesh}};
+use crate::{error::WorldResult, instance::ZoneInstance, plugins::{send_realm_request, CommandExtPriv, InitializeObject, Interruption, Kind, MovementLimits, MovementValidation, Navmesh, PLAYER_MOVER_TYPE, PermissionLevel, movement_validation::cmd_movement_violations}};
 
 use super::{Avatar, Interests, NetworkExtPriv, PlayerController};
 
@@ -36,6 +37,8 @@ impl Plugin for MovementPlugin {
         app.register_message_handler(handle_move_manager_state_changed);
         app.register_message_handler(handle_move_manager_pos_update);
 
+        app.register_command("movement_violations", PermissionLevel::GameMaster, cmd_movement_violations);
+
         app.add_observer(setup_non_client_movement);
 
         insert_movement_api(app);
@@ -117,13 +120,62 @@ pub struct Movement {
 
 pub fn handle_move_manager_pos_update(
     In((ent, pkt)): In<(Entity, oaPktMoveManagerPosUpdate)>,
-    mut query: Query<(&mut GameObjectData, &mut Movement), With<PlayerTag>>,
+    mut query: Query<(&mut GameObjectData, &mut Movement, &mut MovementValidation, &Avatar, &PlayerController), With<PlayerTag>>,
     navmesh: Res<Navmesh>,
+    instance: Res<ZoneInstance>,
     mut commands: Commands,
 ) {
-    if let Ok((mut obj, mut movement)) = query.get_mut(ent) {
+    if let Ok((mut obj, mut movement, mut validation, avatar, controller)) = query.get_mut(ent) {
+        let position: Vec3 = pkt.pos.into();
+        let floor_height = navmesh.get_floor_height(position);
+        let limits = MovementLimits::new(
+            obj.get::<_, f32>(Player::MoveSpeed).copied().unwrap_or_default(),
+            obj.get::<_, f32>(Player::JumpVelocity).copied().unwrap_or_default(),
+        );
+
+        if let Err(violation) = validation.validate(
+            position, 
+            pkt.seconds, 
+            movement.mover_type, 
+            limits, 
+            floor_height
+        ) {
+            warn!("Rejected movement of player {} ({}): {violation}", avatar.name, avatar.id);
+
+            send_realm_request(ent, &instance, &mut commands, RealmRequest::MovementViolation { 
+                session_id: *controller.session().id(), 
+                kind: violation.kind(),
+            });
+
+            // Rubber-band the player back to the last valid position
+            movement.position = validation.last_valid_position();
+            movement.velocity = Vec3::ZERO;
+            movement.seconds = pkt.seconds;
+
+            obj.set(Player::Pos, (0u32, movement.position));
+
+            controller.send_packet(oaPktMoveManagerPosUpdate {
+                avatar_id: avatar.id,
+                pos: movement.position.into(),
+                rot: movement.rotation.into(),
+                vel: movement.velocity.into(),
+                physics: Physics {
+                    state: movement.mode,
+                },
+                mover_key: movement.mover_key,
+                seconds: movement.seconds,
+                ..Default::default()
+            });
+
+            commands
+                .entity(ent)
+                .insert(ForceSyncPositionUpdate);
+
+            return;
+        }
+
         movement.mode = pkt.physics.state;
-        movement.position = pkt.pos.into();
+        movement.position = position;
         movement.rotation = pkt.rot.clone().into();
         movement.velocity = pkt.vel.into();
         movement.seconds = pkt.seconds;
@@ -132,12 +184,9 @@ pub fn handle_move_manager_pos_update(
         obj.set(Player::Pos, (0u32, movement.position));
         obj.set(Player::Rot, movement.rotation.as_unit_vector());
 
-        let floor_height = navmesh.get_floor_height(movement.position).unwrap_or(f32::NAN);
-
         debug!("Avatar ID: {}", pkt.avatar_id);
         debug!("New Pos: {}", movement.position);
-        debug!("Floor height at new pos: {}", floor_height);
-        debug!("Height diff: {}", movement.position.y - floor_height);
+        debug!("Floor height at new pos: {:?}", floor_height);
         debug!("New Rot: {:?} / {} / {}", pkt.rot, movement.rotation, movement.rotation.as_unit_vector());
         debug!("New Vel: {}", movement.velocity);
         debug!("New key: {}", movement.mover_key);
@@ -161,11 +210,24 @@ pub fn handle_move_manager_pos_update(
 
 pub fn handle_move_manager_state_changed(
     In((ent, pkt)): In<(Entity, oaPktMoveManagerStateChanged)>,
-    mut query: Query<&mut Movement>,
+    mut query: Query<(&mut Movement, &mut MovementValidation)>,
     mut commands: Commands,
 ) {
-    if let Ok(mut movement) = query.get_mut(ent) {
-        movement.mover_type = pkt.mover_type;
+    if let Ok((mut movement, mut validation)) = query.get_mut(ent) {
+        // The mover type decides which movement checks apply,
+        // so only mover types the server allowed are taken over.
+        if pkt.mover_type != movement.mover_type {
+            if validation.accepts_mover_type(pkt.mover_type) {
+                if pkt.mover_type == PLAYER_MOVER_TYPE {
+                    validation.end_spline_surfing();
+                }
+
+                movement.mover_type = pkt.mover_type;
+            } else {
+                warn!("Ignoring mover type {} requested by client, keeping {}", pkt.mover_type, movement.mover_type);
+            }
+        }
+
         movement.mover_replication_policy = pkt.mover_replication_policy;
         movement.version = pkt.new_version;
         movement.mover_key = pkt.mover_key;
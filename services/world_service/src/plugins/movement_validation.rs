@@ -0,0 +1,425 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fmt::Display, time::{Duration, Instant}};
+
+use bevy::{ecs::{component::Component, entity::Entity, system::{Commands, In, Query}}, math::{Vec2, Vec3}};
+use realm_api::{proto::MovementViolationKind, RealmApi};
+use toolkit::{types::Uuid, NativeParam};
+
+use crate::plugins::{AsyncOperationEntityCommandsExt, Avatar, MessageType, PlayerController, player_error_handler_system};
+
+/// Mover type used by players walking on their own feet. 
+/// Other movers (vehicles, spline surfing) are only assigned by the server
+/// and are not speed checked.
+pub const PLAYER_MOVER_TYPE: u8 = 1;
+
+/// Factor applied to the avatar's move speed, to account for rounding on the client.
+const SPEED_TOLERANCE: f32 = 1.5;
+
+/// Jumps and falls keep the momentum they started with, so airborne
+/// players get a bit more leeway.
+const AIRBORNE_SPEED_TOLERANCE: f32 = 2.0;
+
+/// Absolute distance a single update may exceed the allowed speed by.
+const DISTANCE_SLACK: f32 = 50.0;
+
+/// Maximum time a client can save up, e.g. while its packets are delayed.
+const MAX_TIME_BUDGET: f32 = 1.0;
+
+/// Distance a player may be above or below the navmesh floor, 
+/// before being considered airborne or below the floor.
+const FLOOR_TOLERANCE: f32 = 500.0;
+
+/// Unreal's default gravity, used to derive the jump height from the jump velocity.
+const GRAVITY: f32 = 520.0;
+
+/// Time after a server initiated relocation in which updates are accepted unchecked.
+const RELOCATION_GRACE: Duration = Duration::from_secs(5);
+
+/// Longest time a player may surf a spline, before updates are checked again.
+const SPLINE_SURF_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MovementViolation {
+    Speed { distance: f32, allowed: f32 },
+    Height { height: f32 },
+}
+
+impl MovementViolation {
+    pub fn kind(&self) -> MovementViolationKind {
+        match self {
+            Self::Speed { .. } => MovementViolationKind::Speed,
+            Self::Height { .. } => MovementViolationKind::Height,
+        }
+    }
+}
+
+impl Display for MovementViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Speed { distance, allowed } => write!(f, "moved {distance:.1} units, allowed {allowed:.1}"),
+            Self::Height { height } => write!(f, "{height:.1} units off the floor"),
+        }
+    }
+}
+
+/// Stance of a player, as derived by the server from the navmesh.
+/// The physics state reported by the client is not trusted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MovementStance {
+    Grounded,
+    Airborne,
+}
+
+impl MovementStance {
+    fn speed_tolerance(&self) -> f32 {
+        match self {
+            Self::Grounded => SPEED_TOLERANCE,
+            Self::Airborne => AIRBORNE_SPEED_TOLERANCE,
+        }
+    }
+}
+
+/// Movement capabilities of a player, taken from its params.
+#[derive(Clone, Copy, Debug)]
+pub struct MovementLimits {
+    pub move_speed: f32,
+    pub jump_height: f32,
+}
+
+impl MovementLimits {
+    pub fn new(move_speed: f32, jump_velocity: f32) -> Self {
+        Self {
+            move_speed,
+            jump_height: jump_velocity * jump_velocity / (2.0 * GRAVITY),
+        }
+    }
+}
+
+/// Server side state used to validate client position updates.
+/// Violations are counted per session by the realm, since this
+/// component is rebuilt on every zone change.
+#[derive(Component)]
+pub struct MovementValidation {
+    last_valid: Vec3,
+    last_seconds: Option<f64>,
+    last_update: Instant,
+    time_budget: f32,
+    grace_until: Option<Instant>,
+    spline_until: Option<Instant>,
+    takeoff_height: f32,
+}
+
+impl MovementValidation {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            last_valid: position,
+            last_seconds: None,
+            last_update: Instant::now(),
+            time_budget: 0.0,
+            grace_until: Some(Instant::now() + RELOCATION_GRACE),
+            spline_until: None,
+            takeoff_height: position.y,
+        }
+    }
+
+    pub fn last_valid_position(&self) -> Vec3 { self.last_valid }
+
+    /// Must be called whenever the server moves the player, 
+    /// so the jump to the new position isn't treated as a violation.
+    pub fn allow_relocation(&mut self) {
+        self.grace_until = Some(Instant::now() + RELOCATION_GRACE);
+    }
+
+    /// Must be called when the server sends the player along a spline, e.g.
+    /// through a flight tube. Updates aren't checked until the client 
+    /// switches back to the player mover, or the surf times out.
+    pub fn begin_spline_surfing(&mut self) {
+        self.spline_until = Some(Instant::now() + SPLINE_SURF_TIMEOUT);
+    }
+
+    pub fn is_spline_surfing(&self) -> bool {
+        self.spline_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Whether the client may switch to the given mover type. Players walk 
+    /// on their own feet, unless the server sent them surfing a spline.
+    pub fn accepts_mover_type(&self, mover_type: u8) -> bool {
+        mover_type == PLAYER_MOVER_TYPE || self.is_spline_surfing()
+    }
+
+    /// Called when the client switches back to the player mover.
+    /// The landing spot is accepted as is.
+    pub fn end_spline_surfing(&mut self) {
+        if self.spline_until.take().is_some() {
+            self.allow_relocation();
+        }
+    }
+
+    /// Checks a position update reported by the client.
+    /// 
+    /// The distance moved must fit into the time passed on the client's clock, 
+    /// as well as into the time the server has seen pass since the last update.
+    /// The latter catches clients that speed up their own clock.
+    /// 
+    /// Players may not sink below the navmesh floor, and while airborne they may
+    /// not rise higher above their takeoff point than they are able to jump.
+    pub fn validate(
+        &mut self,
+        position: Vec3,
+        seconds: f64,
+        mover_type: u8,
+        limits: MovementLimits,
+        floor_height: Option<f32>,
+    ) -> Result<(), MovementViolation> {
+        let now = Instant::now();
+        let server_dt = now.duration_since(self.last_update).as_secs_f32();
+        let client_dt = self.last_seconds
+            .map(|last| (seconds - last).max(0.0) as f32);
+
+        self.last_update = now;
+        self.last_seconds = Some(seconds);
+        self.time_budget = (self.time_budget + server_dt).min(MAX_TIME_BUDGET);
+
+        let stance = match floor_height {
+            Some(floor_height) if position.y - floor_height > FLOOR_TOLERANCE => MovementStance::Airborne,
+            _ => MovementStance::Grounded,
+        };
+
+        if self.is_spline_surfing() {
+            self.accept(position, stance);
+            self.time_budget = 0.0;
+            return Ok(());
+        }
+
+        if let Some(grace_until) = self.grace_until {
+            if now < grace_until {
+                self.accept(position, stance);
+                self.time_budget = 0.0;
+                return Ok(());
+            }
+
+            self.grace_until = None;
+        }
+
+        if 
+            mover_type == PLAYER_MOVER_TYPE &&
+            let Some(client_dt) = client_dt
+        {
+            let delta = position - self.last_valid;
+            let distance = Vec2::new(delta.x, delta.z).length();
+            let allowed_speed = limits.move_speed * stance.speed_tolerance();
+            let allowed = allowed_speed * client_dt.min(self.time_budget) + DISTANCE_SLACK;
+
+            if distance > allowed {
+                return Err(MovementViolation::Speed { distance, allowed });
+            }
+
+            if allowed_speed > 0.0 {
+                self.time_budget = (self.time_budget - distance / allowed_speed).max(0.0);
+            }
+        }
+
+        if let Some(floor_height) = floor_height {
+            let height = position.y - floor_height;
+
+            // Fell through the world, or walking below the terrain
+            if height < -FLOOR_TOLERANCE {
+                return Err(MovementViolation::Height { height });
+            }
+
+            // Gaining height without touching the floor
+            if 
+                mover_type == PLAYER_MOVER_TYPE &&
+                stance == MovementStance::Airborne &&
+                position.y > self.takeoff_height + limits.jump_height + FLOOR_TOLERANCE
+            {
+                return Err(MovementViolation::Height { height });
+            }
+        }
+
+        self.accept(position, stance);
+        Ok(())
+    }
+
+    fn accept(&mut self, position: Vec3, stance: MovementStance) {
+        self.last_valid = position;
+
+        if stance == MovementStance::Grounded {
+            self.takeoff_height = position.y;
+        }
+    }
+}
+
+pub(super) fn cmd_movement_violations(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    players: Query<(&Avatar, &PlayerController)>,
+    mut commands: Commands,
+) {
+    let name = args.into_iter().next();
+
+    let sessions: Vec<(String, Uuid)> = players.iter()
+        .filter(|(avatar, _)| {
+            if let Some(NativeParam::String(name)) = &name {
+                avatar.name == *name
+            } else {
+                true
+            }
+        })
+        .map(|(avatar, controller)| (avatar.name.clone(), *controller.session().id()))
+        .collect();
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            let mut lines = Vec::with_capacity(sessions.len());
+
+            for (name, session_id) in sessions {
+                if let Some(state) = RealmApi::get().get_session_state(session_id).await? {
+                    lines.push(format!(
+                        "{name}: {} speed, {} height violations",
+                        state.speed_violations(), state.height_violations()
+                    ));
+                }
+            }
+
+            Ok(lines)
+        })
+        .on_finish_run_system(send_violation_report)
+        .on_error_run_system(player_error_handler_system);
+}
+
+fn send_violation_report(
+    In((ent, lines)): In<(Entity, Vec<String>)>,
+    query: Query<&PlayerController>,
+) {
+    if let Ok(controller) = query.get(ent) {
+        for line in lines {
+            controller.send_message(MessageType::Normal, line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: MovementLimits = MovementLimits { move_speed: 400.0, jump_height: 200.0 };
+
+    /// Validation state right after the relocation grace ran out, 
+    /// with one second passed on the server.
+    fn validation(position: Vec3) -> MovementValidation {
+        let mut validation = MovementValidation::new(position);
+        validation.grace_until = None;
+        validation.last_seconds = Some(0.0);
+        validation.last_update = Instant::now() - Duration::from_secs(1);
+        validation
+    }
+
+    #[test]
+    fn accepts_movement_within_speed() {
+        let mut validation = validation(Vec3::ZERO);
+        let target = Vec3::new(600.0, 0.0, 0.0);
+
+        assert_eq!(validation.validate(target, 1.0, PLAYER_MOVER_TYPE, LIMITS, Some(0.0)), Ok(()));
+        assert_eq!(validation.last_valid_position(), target);
+    }
+
+    #[test]
+    fn rejects_movement_above_speed() {
+        let mut validation = validation(Vec3::ZERO);
+
+        // 400 * 1.5 * 1s + 50 allowed
+        let res = validation.validate(Vec3::new(700.0, 0.0, 0.0), 1.0, PLAYER_MOVER_TYPE, LIMITS, Some(0.0));
+
+        assert!(matches!(res, Err(MovementViolation::Speed { allowed, .. }) if allowed == 650.0));
+        assert_eq!(validation.last_valid_position(), Vec3::ZERO);
+    }
+
+    #[test]
+    fn rejects_speedup_of_client_clock() {
+        let mut validation = validation(Vec3::ZERO);
+
+        // The client claims 10 seconds passed, the server only saw one
+        let res = validation.validate(Vec3::new(3000.0, 0.0, 0.0), 10.0, PLAYER_MOVER_TYPE, LIMITS, Some(0.0));
+
+        assert!(matches!(res, Err(MovementViolation::Speed { .. })));
+    }
+
+    #[test]
+    fn ignores_client_physics_for_height() {
+        let mut validation = validation(Vec3::ZERO);
+
+        // Rising higher than a jump can reach
+        let res = validation.validate(Vec3::new(0.0, 800.0, 0.0), 1.0, PLAYER_MOVER_TYPE, LIMITS, Some(0.0));
+        assert!(matches!(res, Err(MovementViolation::Height { .. })));
+
+        // Sinking below the floor
+        let res = validation.validate(Vec3::new(0.0, -600.0, 0.0), 2.0, PLAYER_MOVER_TYPE, LIMITS, Some(0.0));
+        assert!(matches!(res, Err(MovementViolation::Height { .. })));
+    }
+
+    #[test]
+    fn accepts_jumps_and_falls() {
+        let mut validation = validation(Vec3::new(0.0, 1000.0, 0.0));
+
+        // Jumping off a ledge
+        assert_eq!(validation.validate(Vec3::new(0.0, 1650.0, 0.0), 1.0, PLAYER_MOVER_TYPE, LIMITS, Some(0.0)), Ok(()));
+
+        // Falling down again
+        assert_eq!(validation.validate(Vec3::new(0.0, 900.0, 0.0), 1.0, PLAYER_MOVER_TYPE, LIMITS, Some(0.0)), Ok(()));
+    }
+
+    #[test]
+    fn allows_more_speed_while_airborne() {
+        let mut validation = validation(Vec3::new(0.0, 1000.0, 0.0));
+
+        // 400 * 2.0 * 1s + 50 allowed in the air
+        let res = validation.validate(Vec3::new(800.0, 1000.0, 0.0), 1.0, PLAYER_MOVER_TYPE, LIMITS, Some(0.0));
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn skips_checks_for_server_assigned_movers() {
+        let mut validation = validation(Vec3::ZERO);
+
+        let res = validation.validate(Vec3::new(5000.0, 2000.0, 0.0), 1.0, 2, LIMITS, Some(0.0));
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn accepts_spline_surfing_until_player_mover_returns() {
+        let mut validation = validation(Vec3::ZERO);
+        assert!(!validation.accepts_mover_type(2));
+
+        validation.begin_spline_surfing();
+        assert!(validation.accepts_mover_type(2));
+        assert_eq!(validation.validate(Vec3::new(5000.0, 2000.0, 0.0), 1.0, PLAYER_MOVER_TYPE, LIMITS, Some(0.0)), Ok(()));
+
+        validation.end_spline_surfing();
+        assert!(!validation.accepts_mover_type(2));
+        assert!(validation.accepts_mover_type(PLAYER_MOVER_TYPE));
+    }
+
+    #[test]
+    fn accepts_anything_during_relocation_grace() {
+        let mut validation = MovementValidation::new(Vec3::ZERO);
+        let target = Vec3::new(100000.0, 0.0, 0.0);
+
+        assert_eq!(validation.validate(target, 0.0, PLAYER_MOVER_TYPE, LIMITS, Some(0.0)), Ok(()));
+        assert_eq!(validation.last_valid_position(), target);
+    }
+}
@@ -124,6 +124,13 @@ fn insert_navigation_api(app: &mut App) {
             })?;
 
             Ok(Some(Vec3Wrapper(Vec3::from_slice(&pos))))
+        })
+        .add_lua_api("navigation", "HasLineOfSight",
+        |
+            In((from, to)): In<(Vec3Wrapper, Vec3Wrapper)>,
+            navmesh: Res<Navmesh>,
+        | -> WorldResult<bool> {
+            Ok(navmesh.has_line_of_sight(from.0, to.0))
         });
 }
 
@@ -199,6 +206,24 @@ impl Navmesh {
         }
     }
 
+    /// Casts a ray along the navmesh surface and returns true if it reaches
+    /// the target without crossing a wall or leaving the mesh.
+    pub fn has_line_of_sight(&self, from: Vec3, to: Vec3) -> bool {
+        let recast = self.recast.lock().unwrap();
+
+        let Ok((start_ref, start_pos)) = recast.query.find_nearest_poly_1(&from.to_array(), &[100.0, 1000.0, 100.0], &self.filter) else {
+            return false;
+        };
+
+        let mut path = vec![DtPolyRef::default(); 256];
+
+        match recast.query.raycast_1(start_ref, &start_pos, &to.to_array(), &self.filter, &mut path) {
+            // t is set to f32::MAX if the ray reached the end position
+            Ok((t, _, _)) => t >= 1.0,
+            Err(_) => false,
+        }
+    }
+
     pub async fn load(world: &WorldDef) -> WorldResult<Self> {
         let navmesh = RealmApi::get()
             .query_navmeshs()
@@ -13,14 +13,14 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use bevy::ecs::{entity::Entity, message::MessageReader, system::{Commands, In, Query}};
-use log::debug;
+use bevy::ecs::{component::Component, entity::Entity, error::BevyError, message::MessageReader, query::Without, system::{Commands, In, Query}};
+use log::{debug, error};
 use obj_params::{GameObjectData, LootScatterContainer, Player};
 use realm_api::{ItemRef, RealmApi};
 use scripting::{EntityScriptCommandsExt, LuaEntity};
 use toolkit::types::{AvatarId, UUID_NIL, Uuid};
 
-use crate::plugins::{AsyncOperationEntityCommandsExt, Avatar, Interaction, InteractionEvent, Inventory, RemoveObject, StorageResult, StringBehavior, apply_storage_result, player_error_handler_system};
+use crate::plugins::{AsyncOperationEntityCommandsExt, Avatar, Interaction, InteractionEvent, Inventory, MessageType, PlayerController, RemoveObject, StorageResult, StringBehavior, apply_storage_result};
 
 pub(super) fn handle_interactions(
     mut events: MessageReader<InteractionEvent>,
@@ -35,10 +35,15 @@ pub(super) fn handle_interactions(
     }
 }
 
+/// Marks a loot container that is being looted, so nobody else
+/// can loot it while the loot is credited.
+#[derive(Component)]
+pub(super) struct LootClaimed;
+
 pub(super) fn behavior_loot_scatter_container_interact(
     In((player_ent, target_ent, _behavior)): In<(Entity, Entity, StringBehavior)>,
     players: Query<(&Avatar, &GameObjectData, &Inventory)>,
-    container: Query<&GameObjectData>,
+    container: Query<&GameObjectData, Without<LootClaimed>>,
     mut commands: Commands,
 ) {
     let Ok((player_avatar, player_data, inventory)) = players.get(player_ent) else {
@@ -54,32 +59,81 @@ pub(super) fn behavior_loot_scatter_container_interact(
 
     let player_party = player_data.get::<_, Uuid>(Player::PartyGuid).cloned().unwrap_or_default();
 
-    // Check if the player is allowed to loot this container
+    // Check if the player is allowed to loot this container.
+    // Containers restricted to a player and a party can be looted
+    // by either of them.
     if 
-        (allow_avatar.is_none() || allow_avatar == player_avatar.id) &&
-        (allow_party == UUID_NIL || allow_party == player_party)
+        (allow_avatar.is_none() && allow_party == UUID_NIL) ||
+        (!allow_avatar.is_none() && allow_avatar == player_avatar.id) ||
+        (allow_party != UUID_NIL && allow_party == player_party)
     {
         let storage_id = inventory.id;
+        let soma_type = container_data.get::<_, i32>(LootScatterContainer::SomaType).cloned().unwrap_or_default();
+        let soma_amount = container_data.get::<_, i32>(LootScatterContainer::SomaAmount).cloned().unwrap_or_default();
         let item_name = container_data.get::<_, String>(LootScatterContainer::ItemContentName).cloned().unwrap_or_default();
-        let _item_count = container_data.get::<_, i32>(LootScatterContainer::ItemCount).cloned().unwrap_or_default();
+        let item_count = container_data.get::<_, i32>(LootScatterContainer::ItemCount).cloned().unwrap_or_default();
+
+        commands
+            .entity(target_ent)
+            .insert(LootClaimed);
 
         commands
             .entity(player_ent)
             .perform_async_operation(async move {
-                debug!("Loot item {item_name}");
+                let storage = RealmApi::get().item_storage_access(&storage_id);
+
+                // Soma drops are credited to the characters balance
+                // instead of creating an item.
+                let result = if soma_amount > 0 {
+                    debug!("Loot {soma_amount} soma of type {soma_type}");
+
+                    storage
+                        .add_soma(soma_type, soma_amount, Some(player_ent.to_string()))
+                        .await?
+                } else {
+                    debug!("Loot {item_count}x item {item_name}");
 
-                StorageResult::from_result(
-                    RealmApi::get()
-                        .item_storage_access(&storage_id)
-                        .insert_item(ItemRef::Name(&item_name), Some(player_ent.to_string()))
+                    storage
+                        .batch_insert_items(
+                            (0..item_count.max(1))
+                                .map(|_| ItemRef::Name(&item_name))
+                                .collect(),
+                            Some(player_ent.to_string())
+                        )
                         .await?
-                ).await
+                };
+
+                StorageResult::from_result(result).await
             })
-            .on_finish_run_system(apply_storage_result)
-            .on_error_run_system(player_error_handler_system);
-    }
+            .on_finish_run_system(move |
+                In((ent, result)): In<(Entity, StorageResult)>,
+                mut commands: Commands,
+            | {
+                // The container stays, if the loot couldn't be stored
+                if let Ok(mut container) = commands.get_entity(target_ent) {
+                    if result.is_ok() {
+                        container.trigger(RemoveObject);
+                    } else {
+                        container.try_remove::<LootClaimed>();
+                    }
+                }
+
+                commands.run_system_cached_with(apply_storage_result, (ent, result));
+            })
+            .on_error_run_system(move |
+                In((ent, err)): In<(Entity, BevyError)>,
+                players: Query<&PlayerController>,
+                mut commands: Commands,
+            | {
+                error!("Failed to loot container: {err:?}");
+
+                if let Ok(mut container) = commands.get_entity(target_ent) {
+                    container.try_remove::<LootClaimed>();
+                }
 
-    commands
-        .entity(target_ent)
-        .trigger(RemoveObject);
+                if let Ok(controller) = players.get(ent) {
+                    controller.send_message(MessageType::Normal, "Looting failed, please try again.");
+                }
+            });
+    }
 }
\ No newline at end of file
@@ -0,0 +1,101 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bevy::{app::{First, Plugin}, ecs::{message::{Message, MessageReader}, query::Added, system::{Commands, In, Query}}, prelude::{App, Entity, With}};
+use obj_params::{tags::PlayerTag, GameObjectData, Player};
+use realm_api::{proto::RealmRequest, RealmApi};
+use toolkit::types::{Uuid, UUID_NIL};
+
+use crate::plugins::{AsyncOperationEntityCommandsExt, CommandExtPriv, player_error_handler_system};
+
+use super::PlayerController;
+
+pub struct PartyPlugin;
+
+impl Plugin for PartyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<PartyUpdated>();
+
+        app.add_systems(First, (
+            load_player_party,
+            apply_party_updates,
+        ));
+
+        app.register_realm_command_with_name("party_invite", |session_id, character_name| RealmRequest::PartyInvite { session_id, character_name });
+        app.register_realm_command("party_accept", |session_id| RealmRequest::PartyAccept { session_id });
+        app.register_realm_command("party_decline", |session_id| RealmRequest::PartyDecline { session_id });
+        app.register_realm_command_with_name("party_kick", |session_id, character_name| RealmRequest::PartyKick { session_id, character_name });
+        app.register_realm_command("party_leave", |session_id| RealmRequest::PartyLeave { session_id });
+        app.register_realm_command_with_name("party_promote", |session_id, character_name| RealmRequest::PartyPromote { session_id, character_name });
+    }
+}
+
+/// Written into every zone when the realm reports a party change.
+/// An empty member list means the party was disbanded.
+#[derive(Message, Clone)]
+pub struct PartyUpdated {
+    pub id: Uuid,
+    pub members: Vec<Uuid>,
+}
+
+// Parties are tracked by the realm, so they survive zone changes.
+// Restore party membership when a player enters this zone.
+fn load_player_party(
+    query: Query<(Entity, &PlayerController), Added<PlayerTag>>,
+    mut commands: Commands,
+) {
+    for (ent, controller) in query.iter() {
+        let character_id = controller.character_id();
+
+        commands
+            .entity(ent)
+            .perform_async_operation(async move {
+                Ok(RealmApi::get()
+                    .get_party_for_character(character_id).await?
+                    .map(|party| *party.id())
+                    .unwrap_or(UUID_NIL))
+            })
+            .on_finish_run_system(set_player_party)
+            .on_error_run_system(player_error_handler_system);
+    }
+}
+
+fn set_player_party(
+    In((ent, party_id)): In<(Entity, Uuid)>,
+    mut query: Query<&mut GameObjectData, With<PlayerTag>>,
+) {
+    if let Ok(mut data) = query.get_mut(ent) {
+        data.set(Player::PartyGuid, party_id);
+    }
+}
+
+fn apply_party_updates(
+    mut messages: MessageReader<PartyUpdated>,
+    mut query: Query<(&PlayerController, &mut GameObjectData), With<PlayerTag>>,
+) {
+    for party in messages.read() {
+        for (controller, mut data) in query.iter_mut() {
+            let current = *data.get::<_, Uuid>(Player::PartyGuid).unwrap_or(&UUID_NIL);
+
+            if party.members.contains(&controller.character_id()) {
+                if current != party.id {
+                    data.set(Player::PartyGuid, party.id);
+                }
+            } else if current == party.id {
+                data.set(Player::PartyGuid, UUID_NIL);
+            }
+        }
+    }
+}
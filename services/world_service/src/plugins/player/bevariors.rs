@@ -20,11 +20,11 @@ use regex::Regex;
 use scripting::{EntityScriptCommandsExt, LuaEntity};
 use toolkit::NativeParam;
 
-use crate::plugins::{AvatarIdManager, BinaryBehavior, PlayerController, StringBehavior};
+use crate::plugins::{AvatarIdManager, BinaryBehavior, MovementValidation, PlayerController, StringBehavior};
 
 pub(super) fn behavior_flight_tube(
     In((ent, _, behavior)): In<(Entity, Entity, StringBehavior)>,
-    query: Query<&PlayerController>,
+    mut query: Query<(&PlayerController, &mut MovementValidation)>,
 ) {
     debug!("FlightTube beahavior: {:?}", behavior.args);
 
@@ -39,7 +39,10 @@ pub(super) fn behavior_flight_tube(
         );
 
 
-        if let Ok(controller) = query.get(ent) {
+        if let Ok((controller, mut validation)) = query.get_mut(ent) {
+            // The client moves along the spline on its own
+            validation.begin_spline_surfing();
+
             controller.send_packet(
                 oaPkt_SplineSurfing_Acknowledge {
                     avatar_id: controller.avatar_id(),
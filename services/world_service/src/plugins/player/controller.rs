@@ -23,7 +23,7 @@ use realm_api::{RealmApi, SessionState};
 use tokio::sync::mpsc::{self, Receiver, Sender, UnboundedSender};
 use toolkit::types::{AvatarId, Uuid};
 
-use crate::{error::WorldResult, instance::ZoneInstance, plugins::{ComponentLoaderCommandsTrait, CurrentState, DespawnAvatar, DynamicInstance, ForeignResource, MessageHandlers, MessageType, ScriptingEntityCommandsExt, SpawnState, Travelling, WorldEvent, player::loader::{PlayerLoader, disconnect_player_error_handler}}, proto::{TravelMode, TravelRejectReason}};
+use crate::{error::WorldResult, instance::ZoneInstance, plugins::{ComponentLoaderCommandsTrait, CurrentState, DespawnAvatar, DynamicInstance, ForeignResource, MessageHandlers, MessageType, Migrating, PermissionLevel, ScriptingEntityCommandsExt, SpawnState, Travelling, WorldEvent, player::loader::{PlayerLoader, disconnect_player_error_handler}}, proto::{TravelMode, TravelRejectReason}};
 
 #[derive(Component, Clone)]
 pub struct PlayerController {
@@ -34,11 +34,13 @@ pub struct PlayerController {
     state: Arc<SessionState>,
     sender: UnboundedSender<WorldEvent>,
     travel_mode: Option<TravelMode>,
+    permission_level: PermissionLevel,
 }
 
 impl PlayerController {
     pub fn avatar_id(&self) -> AvatarId { self.avatar_id }
     pub fn character_id(&self) -> Uuid { self.character_id }
+    pub fn permission_level(&self) -> PermissionLevel { self.permission_level }
 
     pub fn session(&self) -> Arc<Session> { self.session.clone() }
     pub fn state(&self) -> Arc<SessionState> { self.state.clone() }
@@ -189,6 +191,11 @@ pub fn process_join_requests(
                     state: session_state.clone(),
                     sender: sender.clone(),
                     travel_mode: Some(*travel_mode),
+                    permission_level: if session.account().is_gm() {
+                        PermissionLevel::GameMaster
+                    } else {
+                        PermissionLevel::Player
+                    },
                 },
                 CurrentState::default(),
                 SpawnState::Alive,
@@ -281,7 +288,12 @@ pub fn handle_controller_events(
             ControllerEvent::TravelAccepted => {
                 commands.entity(ent).insert(Travelling);
             },
-            ControllerEvent::TravelRejected(_travel_reject_reason) => todo!(),
+            ControllerEvent::TravelRejected(reason) => {
+                warn!("Travel rejected: {reason:?}");
+
+                // Allow draining instances to retry the migration
+                commands.entity(ent).remove::<Migrating>();
+            },
         }
     }
 }
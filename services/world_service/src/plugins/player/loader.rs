@@ -22,7 +22,7 @@ use protocol::{AbilityBarReference, CPktAvatarUpdate, CPktBlob, CPktServerNotify
 use realm_api::{Character, RealmApi};
 use toolkit::{OtherlandQuatExt, types::Uuid};
 
-use crate::{instance::ZoneInstance, plugins::{Avatar, CombatStyle, ComponentLoaderCommandsTrait, ContentInfo, Cooldowns, Factions, FactionsParameters, InitializeObject, LoadContext, LoadableComponent, Movement, Navmesh, PlayerController, QuestLog, Scripted, Skillbook, SkillbookParams, VirtualComponent, player::stance::Stance}, proto::TravelMode};
+use crate::{instance::ZoneInstance, plugins::{Avatar, CombatStyle, ComponentLoaderCommandsTrait, ContentInfo, Cooldowns, Factions, FactionsParameters, InitializeObject, LoadContext, LoadableComponent, Movement, MovementValidation, Navmesh, PlayerController, QuestLog, Scripted, Skillbook, SkillbookParams, VirtualComponent, player::stance::Stance}, proto::TravelMode};
 
 #[derive(Component)]
 pub struct InGame;
@@ -152,6 +152,7 @@ impl LoadableComponent for PlayerLoader {
                             id,
                             name,
                         },
+                        MovementValidation::new(movement.position),
                         movement,
                         stance,
                         Scripted
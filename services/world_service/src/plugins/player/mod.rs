@@ -34,7 +34,7 @@ pub use skillbook::*;
 pub use initial_inventory_transfer::*;
 use toolkit::NativeParam;
 
-use crate::{instance::{InstanceShutdown, InstanceState}, plugins::{Avatar, BehaviorExt, CommandExtPriv, InitializeObject, Movement, NetworkExtPriv, ServerAction, clear_obj_changes, player::{bevariors::{behavior_flight_tube, behavior_loot_avatar}, loader::TransmitAsyncPlayerData, stance::sync_class_stance}}};
+use crate::{instance::{InstanceShutdown, InstanceState}, plugins::{Avatar, BehaviorExt, CommandExtPriv, PermissionLevel, InitializeObject, Movement, NetworkExtPriv, ServerAction, clear_obj_changes, player::{bevariors::{behavior_flight_tube, behavior_loot_avatar}, loader::TransmitAsyncPlayerData, stance::sync_class_stance}}};
 
 pub struct PlayerPlugin;
 
@@ -70,9 +70,9 @@ impl Plugin for PlayerPlugin {
         
         app.register_message_handler(handle_avatar_update);
 
-        app.register_command("instantKill", cmd_instant_kill);
-        app.register_command("travel_to_portal", cmd_travel_to_portal);
-        app.register_command("play_cinematic", |
+        app.register_command("instantKill", PermissionLevel::GameMaster, cmd_instant_kill);
+        app.register_command("travel_to_portal", PermissionLevel::GameMaster, cmd_travel_to_portal);
+        app.register_command("play_cinematic", PermissionLevel::GameMaster, |
                 In((ent, params)): In<(Entity, Vec<NativeParam>)>,
                 query: Query<(&Avatar, &Movement, &PlayerController)>,
             | {
@@ -90,7 +90,7 @@ impl Plugin for PlayerPlugin {
                 }
             });
 
-        app.register_command("trigger_remote_event", |
+        app.register_command("trigger_remote_event", PermissionLevel::GameMaster, |
                 In((ent, params)): In<(Entity, Vec<NativeParam>)>,
                 query: Query<(&Movement, &PlayerController)>,
             | {
@@ -24,7 +24,7 @@ use realm_api::{ObjectPlacement, RealmApi, Zone};
 use scripting::{LuaEntity, ScriptAppExt};
 use toolkit::{QuatWrapper, Vec3Wrapper, types::Uuid};
 
-use crate::{error::{WorldError, WorldResult}, plugins::{Active, AsyncOperationEntityCommandsExt, Avatar, ConnectionState, ContentCache, ContentCacheRef, CurrentState, EquipmentResult, MessageType, Movement, PlayerController, ServerAction, WeakCache, apply_class_item_result, player::{loader::InGame, stance::Stance}, player_error_handler_system, travel_to_portal}, proto::TravelMode};
+use crate::{error::{WorldError, WorldResult}, plugins::{Active, AsyncOperationEntityCommandsExt, Avatar, ConnectionState, ContentCache, ContentCacheRef, CurrentState, EquipmentResult, MessageType, Movement, MovementValidation, PlayerController, ServerAction, WeakCache, apply_class_item_result, player::{loader::InGame, stance::Stance}, player_error_handler_system, travel_to_portal}, proto::TravelMode};
 
 pub(super) fn insert_player_api(app: &mut App,) {
     app
@@ -251,12 +251,14 @@ pub(super) fn insert_player_api(app: &mut App,) {
             .add_lua_api("player", "Respawn",
             |
                 In((player, position, rotation)): In<(LuaEntity, Vec3Wrapper, QuatWrapper)>,
-                query: Query<&PlayerController>,
+                mut query: Query<(&PlayerController, &mut MovementValidation)>,
             | -> WorldResult<()> {
-                let Ok(controller) = query.get(player.entity()) else {
+                let Ok((controller, mut validation)) = query.get_mut(player.entity()) else {
                     return Err(anyhow!("Player not found").into());
                 };
 
+                validation.allow_relocation();
+
                 controller.send_packet(
                     ServerAction::Respawn(controller.avatar_id(), (
                         position.0, 
@@ -22,7 +22,7 @@ use realm_api::{AbilitySlot, ObjectPlacement, RealmApi};
 use scripting::{EntityScriptCommandsExt, LuaEntity};
 use toolkit::{NativeParam, OtherlandQuatExt, types::Uuid};
 
-use crate::{error::{WorldError, WorldResult}, instance::ZoneInstance, plugins::{Active, AsyncOperationEntityCommandsExt, Avatar, ConnectionState, ContentCache, ContentCacheRef, CurrentState, EquipmentResult, HealthUpdateRequest, InitialInventoryTransfer, InitializeObject, Inventory, MessageType, PlayerController, RecalculateAttributes, RemoveObject, ServerAction, WeakCache, apply_equipment_result, player_error_handler_system}, proto::TravelMode};
+use crate::{error::{WorldError, WorldResult}, instance::ZoneInstance, plugins::{Active, AsyncOperationEntityCommandsExt, Avatar, ConnectionState, ContentCache, ContentCacheRef, CurrentState, EquipmentResult, HealthUpdateRequest, InitialInventoryTransfer, InitializeObject, Inventory, MessageType, MovementValidation, PlayerController, RecalculateAttributes, RemoveObject, ServerAction, WeakCache, apply_equipment_result, player_error_handler_system}, proto::TravelMode};
 
 #[allow(clippy::type_complexity)]
 pub fn spawn_player(
@@ -217,10 +217,10 @@ pub fn apply_class_item_result(
 #[allow(clippy::type_complexity)]
 pub fn travel_to_portal(
     In((ent, (portal, exit_point))): In<(Entity, (WorldResult<Option<ObjectPlacement>>, WorldResult<Option<ObjectPlacement>>))>,
-    query: Query<(&Avatar, &PlayerController)>,
+    mut query: Query<(&Avatar, &PlayerController, &mut MovementValidation)>,
     instance: Res<ZoneInstance>,
 ) {
-    if let Ok((avatar, controller)) = query.get(ent) {
+    if let Ok((avatar, controller, mut validation)) = query.get_mut(ent) {
         if let Ok(portal) = portal {
             if let Some(portal) = portal {
                 controller.send_packet(ServerAction::Cinematic { 
@@ -239,6 +239,8 @@ pub fn travel_to_portal(
                         &portal
                     };
 
+                    validation.allow_relocation();
+
                     controller.send_packet(
                         ServerAction::LocalPortal(controller.avatar_id(), (
                             *exit_point.data.get::<_, Vec3>(NonClientBase::Pos).unwrap(), 
@@ -42,7 +42,7 @@ use std::sync::Arc;
 
 use bevy::{app::{Plugin, PostStartup, PreUpdate, Update}, ecs::{error::Result, schedule::IntoScheduleConfigs, system::{In, Res, ResMut}}, platform::collections::HashMap, prelude::{App, Commands}, state::commands::CommandsStatesExt};
 
-use crate::{instance::{InstanceState, ZoneInstance}, plugins::{AsyncOperationCommandsExt, CommandExtPriv, NetworkExtPriv, WeakCache, quests::{cache::QuestTemplateCache, commands::{command_accept_quest, command_complete_quest, command_fail_quest, command_finish_quest}, lua::{hot_reload_quests, insert_questlog_api}}}};
+use crate::{instance::{InstanceState, ZoneInstance}, plugins::{AsyncOperationCommandsExt, CommandExtPriv, PermissionLevel, NetworkExtPriv, WeakCache, quests::{cache::QuestTemplateCache, commands::{command_accept_quest, command_complete_quest, command_fail_quest, command_finish_quest}, lua::{hot_reload_quests, insert_questlog_api}}}};
 pub struct QuestsPlugin;
 
 impl Plugin for QuestsPlugin {
@@ -82,10 +82,10 @@ impl Plugin for QuestsPlugin {
 
         app.init_resource::<Quests>();
 
-        app.register_command("accept_quest", command_accept_quest);
-        app.register_command("complete_quest", command_complete_quest);
-        app.register_command("finish_quest", command_finish_quest);
-        app.register_command("fail_quest", command_fail_quest);
+        app.register_command("accept_quest", PermissionLevel::GameMaster, command_accept_quest);
+        app.register_command("complete_quest", PermissionLevel::GameMaster, command_complete_quest);
+        app.register_command("finish_quest", PermissionLevel::GameMaster, command_finish_quest);
+        app.register_command("fail_quest", PermissionLevel::GameMaster, command_fail_quest);
 
         app.register_message_handler(handle_quest_request);
         app.register_message_handler(handle_quest_action_request);
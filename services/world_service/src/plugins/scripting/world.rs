@@ -13,13 +13,152 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use bevy::{app::App, ecs::{entity::Entity, query::With, system::{In, Query, Res}}, time::{Time, Virtual}};
-use mlua::Lua;
+use std::cmp::Ordering;
+
+use bevy::{app::App, ecs::{entity::Entity, query::With, system::{In, Query, Res}}, math::Vec3, time::{Time, Virtual}};
+use mlua::{FromLua, Lua};
+use obj_params::Class;
 use scripting::{LuaEntity, LuaRuntime, ScriptAppExt};
-use toolkit::types::AvatarId;
+use toolkit::{types::AvatarId, Vec3Wrapper};
 use anyhow::anyhow;
 
-use crate::{error::WorldResult, instance::WorldController, plugins::{AvatarIdManager, Avatar, ContentInfo, InstanceManager}};
+use crate::{error::WorldResult, instance::WorldController, plugins::{AvatarIdManager, Avatar, ContentInfo, Factions, InstanceManager, Movement, Navmesh, PlayerController, SpawnState, WorldSpace}};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Relationship {
+    Hostile,
+    Neutral,
+    Friendly,
+}
+
+impl Relationship {
+    fn from_standing(standing: i32) -> Self {
+        match standing.cmp(&0) {
+            Ordering::Less => Relationship::Hostile,
+            Ordering::Equal => Relationship::Neutral,
+            Ordering::Greater => Relationship::Friendly,
+        }
+    }
+}
+
+/// Filter table accepted by the spatial world queries.
+/// 
+/// All fields are optional:
+/// - `class`: only match entities of this class
+/// - `relationship`: "Hostile", "Neutral" or "Friendly", relative to `relative_to`
+/// - `relative_to`: entity used for faction checks, defaults to the query origin
+/// - `alive`: only match entities that are currently spawned and alive
+/// - `players`: only match player controlled entities
+/// - `exclude`: entity to skip
+/// - `line_of_sight`: only match entities visible on the navmesh from the query position
+#[derive(Default)]
+struct EntityFilter {
+    class: Option<Class>,
+    relationship: Option<Relationship>,
+    relative_to: Option<Entity>,
+    alive: bool,
+    players: bool,
+    exclude: Option<Entity>,
+    line_of_sight: bool,
+}
+
+impl FromLua for EntityFilter {
+    fn from_lua(value: mlua::Value, _lua: &Lua) -> mlua::Result<Self> {
+        let table = value.as_table().ok_or(mlua::Error::runtime("table expected"))?;
+
+        Ok(Self {
+            class: table.get::<Option<String>>("class")?
+                .map(|class| class.parse())
+                .transpose()
+                .map_err(|_| mlua::Error::runtime("invalid class"))?,
+            relationship: match table.get::<Option<String>>("relationship")?.as_deref() {
+                Some("Hostile") => Some(Relationship::Hostile),
+                Some("Neutral") => Some(Relationship::Neutral),
+                Some("Friendly") => Some(Relationship::Friendly),
+                Some(_) => return Err(mlua::Error::runtime("invalid relationship")),
+                None => None,
+            },
+            relative_to: table.get::<Option<LuaEntity>>("relative_to")?.map(|ent| ent.take()),
+            alive: table.get::<Option<bool>>("alive")?.unwrap_or_default(),
+            players: table.get::<Option<bool>>("players")?.unwrap_or_default(),
+            exclude: table.get::<Option<LuaEntity>>("exclude")?.map(|ent| ent.take()),
+            line_of_sight: table.get::<Option<bool>>("line_of_sight")?.unwrap_or_default(),
+        })
+    }
+}
+
+type SpatialQuery<'w, 's> = Query<'w, 's, (&'static Movement, &'static ContentInfo, Option<&'static Factions>, Option<&'static SpawnState>, Option<&'static PlayerController>)>;
+
+/// Returns all entities within `radius` of `pos` that pass the filter,
+/// together with their distance to `pos`.
+fn find_filtered(
+    pos: Vec3,
+    radius: f32,
+    origin: Option<Entity>,
+    filter: &EntityFilter,
+    space: &WorldSpace,
+    navmesh: Option<&Navmesh>,
+    query: &SpatialQuery,
+) -> WorldResult<Vec<(Entity, f32)>> {
+    let reference = if filter.relationship.is_some() {
+        let reference = filter.relative_to.or(origin)
+            .ok_or(anyhow!("relationship filter requires an entity to compare against"))?;
+
+        Some(query.get(reference)
+            .ok()
+            .and_then(|(_, _, factions, _, _)| factions)
+            .ok_or(anyhow!("reference entity has no factions"))?)
+    } else {
+        None
+    };
+
+    let mut result = vec![];
+
+    for ent in space.find_in_range(pos, radius) {
+        if Some(ent) == origin || Some(ent) == filter.exclude {
+            continue;
+        }
+
+        let Ok((movement, info, factions, spawn_state, controller)) = query.get(ent) else {
+            continue;
+        };
+
+        if 
+            let Some(class) = &filter.class &&
+            info.template.class != *class
+        {
+            continue;
+        }
+
+        if filter.players && controller.is_none() {
+            continue;
+        }
+
+        if filter.alive && !matches!(spawn_state, Some(SpawnState::Alive)) {
+            continue;
+        }
+
+        if 
+            let Some(relationship) = filter.relationship &&
+            let Some(reference) = reference &&
+            factions.map(|factions| Relationship::from_standing(reference.relation_to(factions))) != Some(relationship)
+        {
+            continue;
+        }
+
+        if 
+            filter.line_of_sight &&
+            let Some(navmesh) = navmesh &&
+            !navmesh.has_line_of_sight(pos, movement.position)
+        {
+            continue;
+        }
+
+        result.push((ent, pos.distance(movement.position)));
+    }
+
+    Ok(result)
+}
 
 pub fn insert_world_api(app: &mut App) {
     let runtime = app.world_mut().get_resource::<LuaRuntime>().unwrap();
@@ -105,6 +244,37 @@ pub fn insert_world_api(app: &mut App) {
 
             Ok(result)
         })
+        .add_lua_api("world", "FindEntitiesInRadius",
+        |
+            In((pos, radius, filter)): In<(Vec3Wrapper, f32, Option<EntityFilter>)>,
+            space: Res<WorldSpace>,
+            navmesh: Option<Res<Navmesh>>,
+            query: SpatialQuery,
+        | -> WorldResult<Vec<LuaEntity>> {
+            let mut result = find_filtered(pos.0, radius, None, &filter.unwrap_or_default(), &space, navmesh.as_deref(), &query)?;
+            result.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            Ok(result.into_iter()
+                .map(|(ent, _)| LuaEntity(ent))
+                .collect())
+        })
+        .add_lua_api("world", "FindNearest",
+        |
+            In((origin, radius, filter)): In<(LuaEntity, f32, Option<EntityFilter>)>,
+            space: Res<WorldSpace>,
+            navmesh: Option<Res<Navmesh>>,
+            query: SpatialQuery,
+        | -> WorldResult<Option<LuaEntity>> {
+            let origin = origin.take();
+            let (movement, ..) = query.get(origin)
+                .map_err(|_| anyhow!("origin entity has no position"))?;
+
+            let nearest = find_filtered(movement.position, radius, Some(origin), &filter.unwrap_or_default(), &space, navmesh.as_deref(), &query)?
+                .into_iter()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            Ok(nearest.map(|(ent, _)| LuaEntity(ent)))
+        })
         .add_lua_api("world", "GetCurrentTime", 
         |
             timer: Res<Time<Virtual>>,
@@ -13,10 +13,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use bevy::{app::Plugin, prelude::{App, Entity, In, Query}};
+use std::collections::HashSet;
+
+use bevy::{app::{First, Plugin}, ecs::{component::Component, message::{Message, MessageReader}, query::Added, system::{Commands, Res}}, prelude::{App, Entity, In, Query, With}};
+use obj_params::tags::PlayerTag;
 use protocol::{oaPktFriendRequest, CPktStream_167_0};
+use realm_api::{proto::RealmRequest, RealmApi};
+use toolkit::types::Uuid;
 
-use crate::plugins::NetworkExtPriv;
+use crate::{instance::ZoneInstance, plugins::{send_realm_request, AsyncOperationEntityCommandsExt, CommandExtPriv, NetworkExtPriv, player_error_handler_system}};
 
 use super::PlayerController;
 
@@ -24,15 +29,95 @@ pub struct SocialPlugin;
 
 impl Plugin for SocialPlugin {
     fn build(&self, app: &mut App) {
+        app.add_message::<IgnoreListUpdated>();
+
+        app.add_systems(First, (
+            load_player_ignore_list,
+            apply_ignore_list_updates,
+        ));
+
         app.register_message_handler(handle_oapkt_friend_request);
+
+        app.register_realm_command_with_name("friend_add", |session_id, character_name| RealmRequest::FriendAdd { session_id, character_name });
+        app.register_realm_command_with_name("friend_remove", |session_id, character_name| RealmRequest::FriendRemove { session_id, character_name });
+        app.register_realm_command("friend_list", |session_id| RealmRequest::SocialListRequest { session_id });
+        app.register_realm_command_with_name("ignore", |session_id, character_name| RealmRequest::IgnoreAdd { session_id, character_name });
+        app.register_realm_command_with_name("unignore", |session_id, character_name| RealmRequest::IgnoreRemove { session_id, character_name });
+    }
+}
+
+/// Character ids a player doesn't want to hear from.
+#[derive(Component, Default)]
+pub struct IgnoreList(HashSet<Uuid>);
+
+impl IgnoreList {
+    pub fn is_ignoring(&self, character_id: Uuid) -> bool {
+        self.0.contains(&character_id)
+    }
+}
+
+/// Written into every zone when the realm reports a changed ignore list.
+#[derive(Message, Clone)]
+pub struct IgnoreListUpdated {
+    pub character_id: Uuid,
+    pub ignored: Vec<Uuid>,
+}
+
+fn load_player_ignore_list(
+    query: Query<(Entity, &PlayerController), Added<PlayerTag>>,
+    mut commands: Commands,
+) {
+    for (ent, controller) in query.iter() {
+        let character_id = controller.character_id();
+
+        commands.entity(ent).insert(IgnoreList::default());
+        commands
+            .entity(ent)
+            .perform_async_operation(async move {
+                Ok(RealmApi::get()
+                    .get_ignored_characters(character_id).await?)
+            })
+            .on_finish_run_system(set_player_ignore_list)
+            .on_error_run_system(player_error_handler_system);
+    }
+}
+
+fn set_player_ignore_list(
+    In((ent, ignored)): In<(Entity, Vec<Uuid>)>,
+    mut query: Query<&mut IgnoreList, With<PlayerTag>>,
+) {
+    if let Ok(mut ignore_list) = query.get_mut(ent) {
+        ignore_list.0 = ignored.into_iter().collect();
+    }
+}
+
+fn apply_ignore_list_updates(
+    mut messages: MessageReader<IgnoreListUpdated>,
+    mut query: Query<(&PlayerController, &mut IgnoreList), With<PlayerTag>>,
+) {
+    for update in messages.read() {
+        for (controller, mut ignore_list) in query.iter_mut() {
+            if controller.character_id() == update.character_id {
+                ignore_list.0 = update.ignored.iter().copied().collect();
+            }
+        }
     }
 }
 
 fn handle_oapkt_friend_request(
     In((ent, _pkt)): In<(Entity, oaPktFriendRequest)>,
     query: Query<&PlayerController>,
+    instance: Res<ZoneInstance>,
+    mut commands: Commands,
 ) {
     if let Ok(controller) = query.get(ent) {
+        // Populating the client's friend list window is out of scope, until the
+        // layout of this stream is known. The empty stream only answers the request,
+        // the list itself is delivered by the realm as system messages.
         controller.send_packet(CPktStream_167_0::default());
+
+        send_realm_request(ent, &instance, &mut commands, RealmRequest::SocialListRequest { 
+            session_id: *controller.session().id(),
+        });
     }
-}
\ No newline at end of file
+}
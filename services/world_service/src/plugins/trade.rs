@@ -0,0 +1,429 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bevy::{app::{Plugin, Update}, ecs::{component::Component, error::BevyError, resource::Resource, system::{Commands, In, Query, ResMut}}, platform::collections::HashMap, prelude::{App, Entity, With}};
+use log::{error, info};
+use obj_params::{GameObjectData, ItemBase, tags::PlayerTag};
+use realm_api::{RealmApi, TradeOffer};
+use toolkit::{types::Uuid, NativeParam};
+
+use crate::plugins::{apply_storage_result, AsyncOperationEntityCommandsExt, Avatar, CommandExtPriv, Inventory, MessageType, PermissionLevel, StorageResult};
+
+use super::PlayerController;
+
+pub struct TradePlugin;
+
+impl Plugin for TradePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TradeInvites>();
+
+        app.add_systems(Update, cancel_abandoned_trades);
+
+        app.register_command("trade_invite", PermissionLevel::Player, cmd_trade_invite);
+        app.register_command("trade_accept", PermissionLevel::Player, cmd_trade_accept);
+        app.register_command("trade_decline", PermissionLevel::Player, cmd_trade_decline);
+        app.register_command("trade_offer_item", PermissionLevel::Player, cmd_trade_offer_item);
+        app.register_command("trade_offer_bits", PermissionLevel::Player, cmd_trade_offer_bits);
+        app.register_command("trade_lock", PermissionLevel::Player, cmd_trade_lock);
+        app.register_command("trade_confirm", PermissionLevel::Player, cmd_trade_confirm);
+        app.register_command("trade_cancel", PermissionLevel::Player, cmd_trade_cancel);
+    }
+}
+
+/// Pending trade invites, keyed by the invited player.
+#[derive(Resource, Default)]
+struct TradeInvites(HashMap<Entity, Entity>);
+
+/// One side of a running trade. Both partners carry this component.
+/// 
+/// Changing an offer unlocks both sides again, so nobody can confirm a trade
+/// the partner changed after they looked at it. The exchange itself only
+/// happens after both sides locked and confirmed.
+#[derive(Component)]
+pub struct Trade {
+    partner: Entity,
+    items: Vec<Uuid>,
+    bits: i32,
+    locked: bool,
+    confirmed: bool,
+    executing: bool,
+}
+
+impl Trade {
+    fn new(partner: Entity) -> Self {
+        Self {
+            partner,
+            items: Vec::new(),
+            bits: 0,
+            locked: false,
+            confirmed: false,
+            executing: false,
+        }
+    }
+
+    fn unlock(&mut self) {
+        self.locked = false;
+        self.confirmed = false;
+    }
+}
+
+fn cmd_trade_invite(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    players: Query<(Entity, &Avatar, &PlayerController), With<PlayerTag>>,
+    trades: Query<&Trade>,
+    mut invites: ResMut<TradeInvites>,
+) {
+    let Ok((_, inviter, controller)) = players.get(ent) else {
+        return;
+    };
+
+    let Some(NativeParam::String(character_name)) = args.into_iter().next() else {
+        controller.send_message(MessageType::Normal, "Usage: trade_invite <character name>");
+        return;
+    };
+
+    let Some((target, _, target_controller)) = players.iter()
+        .find(|(_, avatar, _)| avatar.name.eq_ignore_ascii_case(&character_name)) 
+    else {
+        controller.send_message(MessageType::Normal, format!("{character_name} is not in this zone."));
+        return;
+    };
+
+    if target == ent {
+        controller.send_message(MessageType::Normal, "You can't trade with yourself.");
+    } else if trades.contains(ent) {
+        controller.send_message(MessageType::Normal, "You are already trading.");
+    } else if trades.contains(target) {
+        controller.send_message(MessageType::Normal, format!("{character_name} is already trading."));
+    } else {
+        invites.0.insert(target, ent);
+
+        controller.send_message(MessageType::Normal, format!("Trade invite sent to {character_name}."));
+        target_controller.send_message(MessageType::Normal, format!("{} wants to trade with you. Use trade_accept or trade_decline.", inviter.name));
+    }
+}
+
+fn cmd_trade_accept(
+    In((ent, _)): In<(Entity, Vec<NativeParam>)>,
+    players: Query<(&Avatar, &PlayerController), With<PlayerTag>>,
+    trades: Query<&Trade>,
+    mut invites: ResMut<TradeInvites>,
+    mut commands: Commands,
+) {
+    let Ok((avatar, controller)) = players.get(ent) else {
+        return;
+    };
+
+    let Some(inviter) = invites.0.remove(&ent) else {
+        controller.send_message(MessageType::Normal, "You have no pending trade invite.");
+        return;
+    };
+
+    let Ok((inviter_avatar, inviter_controller)) = players.get(inviter) else {
+        controller.send_message(MessageType::Normal, "Your trade partner left the zone.");
+        return;
+    };
+
+    if trades.contains(ent) || trades.contains(inviter) {
+        controller.send_message(MessageType::Normal, format!("{} is already trading.", inviter_avatar.name));
+        return;
+    }
+
+    commands.entity(ent).insert(Trade::new(inviter));
+    commands.entity(inviter).insert(Trade::new(ent));
+
+    controller.send_message(MessageType::Normal, format!("You are now trading with {}.", inviter_avatar.name));
+    inviter_controller.send_message(MessageType::Normal, format!("You are now trading with {}.", avatar.name));
+}
+
+fn cmd_trade_decline(
+    In((ent, _)): In<(Entity, Vec<NativeParam>)>,
+    players: Query<(&Avatar, &PlayerController), With<PlayerTag>>,
+    mut invites: ResMut<TradeInvites>,
+) {
+    if 
+        let Some(inviter) = invites.0.remove(&ent) &&
+        let Ok((avatar, _)) = players.get(ent) &&
+        let Ok((_, inviter_controller)) = players.get(inviter)
+    {
+        inviter_controller.send_message(MessageType::Normal, format!("{} declined your trade invite.", avatar.name));
+    }
+}
+
+fn cmd_trade_offer_item(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    players: Query<(&PlayerController, &Inventory)>,
+    items: Query<&GameObjectData>,
+    mut trades: Query<&mut Trade>,
+) {
+    let Ok((controller, inventory)) = players.get(ent) else {
+        return;
+    };
+
+    let Some(item_id) = args.into_iter().next()
+        .and_then(|arg| if let NativeParam::String(arg) = arg { arg.parse::<Uuid>().ok() } else { None })
+    else {
+        controller.send_message(MessageType::Normal, "Usage: trade_offer_item <item id>");
+        return;
+    };
+
+    let Some(item) = inventory.items.get(&item_id).and_then(|ent| items.get(*ent).ok()) else {
+        controller.send_message(MessageType::Normal, "You don't own that item.");
+        return;
+    };
+
+    if *item.get::<_, i32>(ItemBase::ContainerId).unwrap_or(&0) != 0 {
+        controller.send_message(MessageType::Normal, "Equipped items can't be traded.");
+        return;
+    }
+
+    update_offer(ent, controller, &mut trades, |trade| {
+        if !trade.items.contains(&item_id) {
+            trade.items.push(item_id);
+        }
+    });
+}
+
+fn cmd_trade_offer_bits(
+    In((ent, args)): In<(Entity, Vec<NativeParam>)>,
+    players: Query<(&PlayerController, &Inventory)>,
+    mut trades: Query<&mut Trade>,
+) {
+    let Ok((controller, inventory)) = players.get(ent) else {
+        return;
+    };
+
+    let amount = match args.into_iter().next() {
+        Some(NativeParam::Int(amount)) => Some(amount),
+        Some(NativeParam::String(amount)) => amount.parse::<i32>().ok(),
+        _ => None,
+    };
+
+    let Some(amount) = amount.filter(|amount| *amount >= 0) else {
+        controller.send_message(MessageType::Normal, "Usage: trade_offer_bits <amount>");
+        return;
+    };
+
+    if amount > inventory.game_cash.unwrap_or(0) {
+        controller.send_message(MessageType::Normal, "You don't have that many bits.");
+        return;
+    }
+
+    update_offer(ent, controller, &mut trades, |trade| {
+        trade.bits = amount;
+    });
+}
+
+fn update_offer(
+    ent: Entity,
+    controller: &PlayerController,
+    trades: &mut Query<&mut Trade>,
+    update: impl FnOnce(&mut Trade),
+) {
+    let Ok(partner) = trades.get(ent).map(|trade| trade.partner) else {
+        controller.send_message(MessageType::Normal, "You are not trading.");
+        return;
+    };
+
+    let Ok([mut trade, mut partner]) = trades.get_many_mut([ent, partner]) else {
+        return;
+    };
+
+    if trade.executing {
+        return;
+    }
+
+    update(&mut trade);
+
+    trade.unlock();
+    partner.unlock();
+
+    controller.send_message(MessageType::Normal, format!("Offering {} item(s) and {} bits.", trade.items.len(), trade.bits));
+}
+
+fn cmd_trade_lock(
+    In((ent, _)): In<(Entity, Vec<NativeParam>)>,
+    players: Query<(&Avatar, &PlayerController)>,
+    mut trades: Query<&mut Trade>,
+) {
+    let Ok((avatar, controller)) = players.get(ent) else {
+        return;
+    };
+
+    let Ok(mut trade) = trades.get_mut(ent) else {
+        controller.send_message(MessageType::Normal, "You are not trading.");
+        return;
+    };
+
+    trade.locked = true;
+    controller.send_message(MessageType::Normal, "Offer locked. Use trade_confirm once your partner locked too.");
+
+    if let Ok((_, partner_controller)) = players.get(trade.partner) {
+        partner_controller.send_message(MessageType::Normal, format!("{} locked their offer of {} item(s) and {} bits.", avatar.name, trade.items.len(), trade.bits));
+    }
+}
+
+fn cmd_trade_confirm(
+    In((ent, _)): In<(Entity, Vec<NativeParam>)>,
+    players: Query<(&PlayerController, &Inventory)>,
+    mut trades: Query<&mut Trade>,
+    mut commands: Commands,
+) {
+    let Ok((controller, inventory)) = players.get(ent) else {
+        return;
+    };
+
+    let Ok(partner) = trades.get(ent).map(|trade| trade.partner) else {
+        controller.send_message(MessageType::Normal, "You are not trading.");
+        return;
+    };
+
+    let Ok([mut trade, mut partner]) = trades.get_many_mut([ent, partner]) else {
+        return;
+    };
+
+    if !trade.locked || !partner.locked {
+        controller.send_message(MessageType::Normal, "Both sides have to lock their offers first.");
+        return;
+    }
+
+    trade.confirmed = true;
+
+    if !partner.confirmed || trade.executing {
+        controller.send_message(MessageType::Normal, "Waiting for your partner to confirm.");
+        return;
+    }
+
+    let Ok((partner_controller, partner_inventory)) = players.get(trade.partner) else {
+        return;
+    };
+
+    trade.executing = true;
+    partner.executing = true;
+
+    let left = TradeOffer {
+        character_id: controller.character_id(),
+        storage_id: inventory.id,
+        items: trade.items.clone(),
+        bits: trade.bits,
+    };
+
+    let right = TradeOffer {
+        character_id: partner_controller.character_id(),
+        storage_id: partner_inventory.id,
+        items: partner.items.clone(),
+        bits: partner.bits,
+    };
+
+    info!("Executing trade between {} and {}", left.character_id, right.character_id);
+
+    commands
+        .entity(ent)
+        .perform_async_operation(async move {
+            let mut results = Vec::new();
+
+            for res in RealmApi::get().execute_trade(left, right, None).await? {
+                results.push(StorageResult::from_result(res).await?);
+            }
+
+            Ok(results)
+        })
+        .on_finish_run_system(finish_trade)
+        .on_error_run_system(trade_error_handler);
+}
+
+fn finish_trade(
+    In((ent, results)): In<(Entity, Vec<StorageResult>)>,
+    players: Query<&PlayerController>,
+    trades: Query<&Trade>,
+    mut commands: Commands,
+) {
+    let succeeded = results.iter().all(StorageResult::is_ok);
+    let partner = trades.get(ent).map(|trade| trade.partner).ok();
+
+    for result in results {
+        commands.run_system_cached_with(apply_storage_result, (ent, result));
+    }
+
+    for ent in [Some(ent), partner].into_iter().flatten() {
+        commands.entity(ent).try_remove::<Trade>();
+
+        if let Ok(controller) = players.get(ent) {
+            controller.send_message(MessageType::Normal, if succeeded { "Trade completed." } else { "Trade failed." });
+        }
+    }
+}
+
+fn trade_error_handler(
+    In((ent, err)): In<(Entity, BevyError)>,
+    players: Query<&PlayerController>,
+    trades: Query<&Trade>,
+    mut commands: Commands,
+) {
+    error!("Trade failed: {err:?}");
+
+    let partner = trades.get(ent).map(|trade| trade.partner).ok();
+
+    for ent in [Some(ent), partner].into_iter().flatten() {
+        commands.entity(ent).try_remove::<Trade>();
+
+        if let Ok(controller) = players.get(ent) {
+            controller.send_message(MessageType::Normal, "Trade failed.");
+        }
+    }
+}
+
+fn cmd_trade_cancel(
+    In((ent, _)): In<(Entity, Vec<NativeParam>)>,
+    players: Query<&PlayerController>,
+    trades: Query<&Trade>,
+    mut commands: Commands,
+) {
+    let Ok(trade) = trades.get(ent) else {
+        return;
+    };
+
+    if trade.executing {
+        return;
+    }
+
+    for ent in [ent, trade.partner] {
+        commands.entity(ent).try_remove::<Trade>();
+
+        if let Ok(controller) = players.get(ent) {
+            controller.send_message(MessageType::Normal, "Trade cancelled.");
+        }
+    }
+}
+
+// Partners leaving the zone end the trade.
+fn cancel_abandoned_trades(
+    trades: Query<(Entity, &Trade)>,
+    players: Query<&PlayerController>,
+    mut invites: ResMut<TradeInvites>,
+    mut commands: Commands,
+) {
+    for (ent, trade) in trades.iter() {
+        if !trades.contains(trade.partner) {
+            commands.entity(ent).remove::<Trade>();
+
+            if let Ok(controller) = players.get(ent) {
+                controller.send_message(MessageType::Normal, "Trade cancelled.");
+            }
+        }
+    }
+
+    invites.0.retain(|invitee, inviter| players.contains(*invitee) && players.contains(*inviter));
+}
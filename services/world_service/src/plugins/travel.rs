@@ -13,15 +13,20 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::time::Duration;
+
 use anyhow::anyhow;
-use bevy::{app::Plugin, ecs::system::Commands, prelude::{App, Component, Entity, In, Query, Res}};
+use bevy::{app::{Plugin, Update}, ecs::{schedule::IntoScheduleConfigs, system::Commands}, prelude::{App, Component, Entity, In, Query, Res, ResMut, Resource, With, Without, resource_exists}, time::common_conditions::on_timer};
 use futures_util::TryStreamExt;
-use realm_api::{RealmApi, ZoneType};
-use toolkit::{types::AvatarId, IterExt, NativeParam};
+use log::debug;
+use realm_api::{RealmApi, RealmApiError, Zone, ZoneType};
+use toolkit::{types::{AvatarId, Uuid}, IterExt, NativeParam};
+
+use toolkit::OtherlandQuatExt;
 
-use crate::{error::{WorldResult}, instance::ZoneInstance, plugins::{AsyncOperationEntityCommandsExt, player_error_handler_system}, proto::TravelMode};
+use crate::{error::{WorldResult}, instance::{self, InstanceLabel, ZoneConfig, ZoneInstance}, plugins::{AsyncOperationCommandsExt, AsyncOperationEntityCommandsExt, player_error_handler_system}, proto::TravelMode};
 
-use super::{CommandExtPriv, CommandMessage, NetworkExtPriv, PlayerController};
+use super::{CommandExtPriv, PermissionLevel, CommandMessage, MessageType, Movement, NetworkExtPriv, PlayerController};
 
 pub struct TravelPlugin;
 
@@ -31,7 +36,13 @@ impl Plugin for TravelPlugin {
         app.register_community_command_handler(handle_leave_dungeon);
         app.register_community_command_handler(handle_social_travel);
 
-        app.register_command("teleport_to_world", |
+        app.add_systems(Update, 
+            migrate_players
+                .run_if(resource_exists::<Draining>)
+                .run_if(on_timer(Duration::from_secs(1)))
+        );
+
+        app.register_command("teleport_to_world", PermissionLevel::GameMaster, |
             In((ent, args)): In<(Entity, Vec<NativeParam>)>,
             players: Query<&PlayerController>,
             mut commands: Commands,
@@ -73,6 +84,55 @@ impl Plugin for TravelPlugin {
 // away from this zone.
 pub struct Travelling;
 
+#[derive(Component)]
+// Mark players who have been asked to move to
+// another node, because this instance is draining.
+pub struct Migrating;
+
+#[derive(Resource, Default)]
+pub struct Draining {
+    unregister_requested: bool,
+}
+
+fn migrate_players(
+    mut draining: ResMut<Draining>,
+    instance: Res<ZoneInstance>,
+    players: Query<(Entity, &PlayerController, &Movement), (Without<Travelling>, Without<Migrating>)>,
+    controllers: Query<(), With<PlayerController>>,
+    mut commands: Commands,
+) {
+    // The realm already handed this zone to another node, so travelling
+    // to the same zone and instance moves players over there.
+    for (ent, controller, movement) in players.iter() {
+        debug!("Migrating player {} away from draining instance", controller.avatar_id());
+
+        controller.request_travel(
+            *instance.zone.guid(), 
+            instance.instance_id, 
+            TravelMode::Position { 
+                pos: movement.position, 
+                rot: movement.rotation.as_unit_vector(),
+            }, 
+            None
+        );
+
+        commands.entity(ent).insert(Migrating);
+    }
+
+    if controllers.is_empty() && !draining.unregister_requested {
+        draining.unregister_requested = true;
+
+        let label = InstanceLabel::new(*instance.zone.guid(), instance.instance_id);
+        let manager = instance.manager.clone();
+
+        commands
+            .perform_async_operation(async move {
+                manager.request_unregister_instance(label).await;
+                Ok(())
+            });
+    }
+}
+
 #[allow(dead_code)]
 struct JoinDungeon {
     avatar: AvatarId,
@@ -108,22 +168,69 @@ fn handle_join_dungeon(
                         .query_worlddefs()
                         .name(cmd.map_name.clone())
                         .query().await?.try_next().await? &&
-                    let Some(zone) = RealmApi::get()
-                        .query_zones()
-                        .zone_type(ZoneType::World)
-                        .worlddef_guid(*world_def.guid())
-                        .query().await?.try_next().await?
+                    let Some((zone, config)) = find_dungeon_zone(*world_def.guid()).await?
                 {
-                    controller.request_travel(*zone.guid(), None, TravelMode::EntryPoint, None);
-                    Ok(())
+                    // Dungeons get a private instance per party or character
+                    let instance = if config.is_private() {
+                        match RealmApi::get().enter_dungeon(controller.character_id(), *zone.guid(), config.dungeon_scope()).await {
+                            Ok(instance) => Some(instance),
+                            Err(RealmApiError::GraphQl(errors)) => {
+                                return Ok(errors.into_iter()
+                                    .next()
+                                    .map(|err| err.message));
+                            },
+                            Err(e) => return Err(e.into()),
+                        }
+                    } else {
+                        None
+                    };
+
+                    controller.request_travel(*zone.guid(), instance, TravelMode::EntryPoint, None);
+                    Ok(None)
                 } else {
                     Err(anyhow!("Map '{}' not found!", cmd.map_name).into())
                 }
             })
+            .on_finish_run_system(|
+                In((ent, rejection)): In<(Entity, Option<String>)>,
+                players: Query<&PlayerController>,
+            | {
+                if 
+                    let Some(message) = rejection &&
+                    let Ok(controller) = players.get(ent)
+                {
+                    controller.send_message(MessageType::PopUp, message);
+                }
+            })
             .on_error_run_system(player_error_handler_system);
     }
 }
 
+/// Prefers the zone configured as dungeon, if the map has several zones.
+async fn find_dungeon_zone(world_def: Uuid) -> WorldResult<Option<(Zone, ZoneConfig)>> {
+    let zones: Vec<Zone> = RealmApi::get()
+        .query_zones()
+        .zone_type(ZoneType::World)
+        .worlddef_guid(world_def)
+        .query().await?
+        .try_collect().await?;
+
+    let mut fallback = None;
+
+    for zone in zones {
+        let config = ZoneConfig::load(&zone).await?
+            .unwrap_or_default();
+
+        if matches!(config.zone_type, instance::ZoneType::Dungeon) {
+            return Ok(Some((zone, config)));
+        }
+
+        fallback.get_or_insert((zone, config));
+    }
+
+    Ok(fallback)
+}
+
 #[allow(dead_code)]
 struct LeaveDungeon {
     avatar: AvatarId,
@@ -0,0 +1,44 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use realm_api::RealmApiError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ArchiveToolError {
+    #[error("realm api error: {0}")]
+    RealmApi(RealmApiError),
+
+    #[error("{0}")]
+    Rejected(String),
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl From<RealmApiError> for ArchiveToolError {
+    fn from(value: RealmApiError) -> Self {
+        // Surface the realm's explanation instead of a generic graphql error
+        match value {
+            RealmApiError::GraphQl(errors) if !errors.is_empty() => Self::Rejected(errors[0].message.clone()),
+            e => Self::RealmApi(e),
+        }
+    }
+}
+
+pub type ArchiveToolResult<T> = std::result::Result<T, ArchiveToolError>;
@@ -0,0 +1,111 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fs::File, io::{self, BufReader, BufWriter}, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+use error::ArchiveToolResult;
+use log::{info, warn};
+use realm_api::{NameConflict, RealmApi};
+use reqwest::Url;
+use toolkit::types::Uuid;
+
+mod error;
+
+/// Exports characters of a realm into archives and imports them
+/// into another realm, e.g. to migrate or back up characters.
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[arg(long, env = "SERVICE_REALM_API_URL", default_value = "http://127.0.0.1:8001")]
+    service_realm_url: Url,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Writes a character, or all characters of an account, into an archive
+    Export {
+        #[arg(long, required_unless_present = "account", conflicts_with = "account")]
+        character: Option<Uuid>,
+
+        #[arg(long)]
+        account: Option<Uuid>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Imports all characters of an archive into an account
+    Import {
+        archive: PathBuf,
+
+        #[arg(long)]
+        account: Uuid,
+
+        /// Append a number to names that are already taken, instead of aborting
+        #[arg(long, default_value_t = false)]
+        rename: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ArchiveToolResult<()> {
+    let _ = toolkit::dotenvy::dotenv();
+    toolkit::env_logger::Builder::from_env(
+        toolkit::env_logger::Env::default()
+        .default_filter_or("info")
+    ).init();
+
+    let cli = Cli::parse();
+    let realm_api = RealmApi::init(cli.service_realm_url);
+
+    match cli.command {
+        Commands::Export { character, account, output } => {
+            let archive = if let Some(character) = character {
+                realm_api.export_character(character).await?
+            } else {
+                realm_api.export_account(account.unwrap()).await?
+            };
+
+            if let Some(output) = output {
+                serde_json::to_writer_pretty(BufWriter::new(File::create(&output)?), &archive)?;
+                info!("Wrote archive to {}", output.display());
+            } else {
+                serde_json::to_writer_pretty(io::stdout().lock(), &archive)?;
+            }
+        },
+        Commands::Import { archive, account, rename } => {
+            let archive = serde_json::from_reader(BufReader::new(File::open(archive)?))?;
+            let name_conflict = if rename { NameConflict::Rename } else { NameConflict::Fail };
+
+            for character in realm_api.import_characters(account, archive, name_conflict).await? {
+                if character.renamed {
+                    info!("Imported {} as {} ({}), renamed", character.original_id, character.name, character.id);
+                } else {
+                    info!("Imported {} as {} ({})", character.original_id, character.name, character.id);
+                }
+
+                if character.dropped_items > 0 {
+                    warn!("Dropped {} items of {} unknown to this realm", character.dropped_items, character.name);
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
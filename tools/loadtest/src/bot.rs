@@ -0,0 +1,414 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, f32::consts::TAU, net::{Ipv4Addr, SocketAddrV4}, sync::Arc, time::{Duration, Instant}};
+
+use core_api::{AccountQuery, CoreApi};
+use glam::{Quat, Vec3};
+use log::{debug, trace, warn};
+use protocol::{oaPktC2SConnectionState, oaPktCharacterCreate, oaPktCharacterSelect, oaPktMoveManagerPosUpdate, oaPktRealmSelect, oaPktRequestCharacterList, oaPktRequestEnterGame, oaPktRequestSelectWorld, CPkt, CPktChat, CPktLogin, CPktLoginResult, CpktChatChatType, CpktLoginLoginType, OaPktC2sconnectionStateState, OaPktS2xconnectionStateState, OtherlandPacket, Physics, PhysicsState};
+use raknet::{RakNetSocket, Reliability};
+use tokio::{net::ToSocketAddrs, select, time::{interval, timeout}};
+use tokio_util::sync::CancellationToken;
+use toolkit::{types::{AvatarId, Uuid}, OtherlandQuatExt};
+
+use crate::{error::{LoadtestError, LoadtestResult}, stats::{Phase, Stats}, ARGS};
+
+struct Connection(RakNetSocket);
+
+impl Connection {
+    async fn connect(addr: impl ToSocketAddrs) -> LoadtestResult<Self> {
+        Ok(Self(RakNetSocket::connect(addr, None).await?))
+    }
+
+    async fn send(&self, pkt: impl OtherlandPacket) -> LoadtestResult<()> {
+        self.0.send(&pkt.into_pkt().to_bytes(), Reliability::ReliableOrdered).await?;
+        Ok(())
+    }
+
+    async fn recv(&self) -> LoadtestResult<CPkt> {
+        loop {
+            let buf = self.0.recv().await?;
+            match CPkt::from_bytes(&buf) {
+                Ok((_, pkt)) => return Ok(pkt),
+                Err(_) => trace!("Skipping undecodable packet"),
+            }
+        }
+    }
+
+    /// Waits for the first packet `f` returns a result for, ignoring everything else.
+    async fn expect<T>(&self, what: &'static str, mut f: impl FnMut(CPkt) -> Option<LoadtestResult<T>>) -> LoadtestResult<T> {
+        timeout(Duration::from_secs(ARGS.timeout), async {
+            loop {
+                if let Some(res) = f(self.recv().await?) {
+                    return res;
+                }
+            }
+        }).await
+        .map_err(|_| LoadtestError::Timeout(what))?
+    }
+
+    async fn close(self) {
+        self.0.close().await;
+    }
+}
+
+fn login_result(pkt: CPkt) -> Option<LoadtestResult<CPktLoginResult>> {
+    match pkt {
+        CPkt::CPktLoginResult(pkt) if pkt.login_success => Some(Ok(pkt)),
+        CPkt::CPktLoginResult(pkt) => Some(Err(LoadtestError::Rejected(
+            String::from_utf8_lossy(&pkt.message.unwrap_or_default()).to_string()
+        ))),
+        _ => None,
+    }
+}
+
+fn addr_from_client(ip: u32, port: u16) -> SocketAddrV4 {
+    // Servers send addresses in little-endian byte order
+    SocketAddrV4::new(Ipv4Addr::from(ip.to_le_bytes()), port)
+}
+
+struct Login {
+    session_id: Uuid,
+    frontend: SocketAddrV4,
+}
+
+/// What the bot knows about its own avatar.
+#[derive(Default)]
+struct AvatarState {
+    in_game: bool,
+    avatar_id: AvatarId,
+    anchor: Vec3,
+    pos: Vec3,
+    rot: Quat,
+}
+
+impl AvatarState {
+    /// Plays along with the client side of the loading sequence,
+    /// which is repeated every time the server moves us to a new map.
+    async fn handle(&mut self, conn: &Connection, pkt: &CPkt) -> LoadtestResult<()> {
+        match pkt {
+            CPkt::CPktResourceNotify(_) => {
+                self.in_game = false;
+
+                for state in [
+                    OaPktC2sconnectionStateState::MapLoaded,
+                    OaPktC2sconnectionStateState::PlayerLoaded,
+                    OaPktC2sconnectionStateState::WaitingForInitialInterests,
+                ] {
+                    conn.send(oaPktC2SConnectionState {
+                        state,
+                        ..Default::default()
+                    }).await?;
+                }
+            },
+            CPkt::oaPktS2XConnectionState(pkt) => {
+                match pkt.state {
+                    OaPktS2xconnectionStateState::ReceivedInitialInterests => {
+                        conn.send(oaPktC2SConnectionState {
+                            state: OaPktC2sconnectionStateState::InitialInterestsLoaded,
+                            ..Default::default()
+                        }).await?;
+                    },
+                    OaPktS2xconnectionStateState::InGame => {
+                        self.in_game = true;
+                    },
+                    _ => (),
+                }
+            },
+            CPkt::oaPktServerAction(pkt) if pkt.override_teleport => {
+                self.avatar_id = pkt.instigator;
+                self.anchor = pkt.pos.into();
+                self.pos = self.anchor;
+                self.rot = pkt.rot.clone().into();
+            },
+            _ => (),
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Bot {
+    username: String,
+    core_api: Option<CoreApi>,
+    stats: Arc<Stats>,
+}
+
+impl Bot {
+    pub fn new(index: usize, core_api: Option<CoreApi>, stats: Arc<Stats>) -> Self {
+        Self {
+            username: format!("{}{:04}", ARGS.username_prefix, index),
+            core_api,
+            stats,
+        }
+    }
+
+    pub async fn run(self, cancel: CancellationToken) {
+        let entered = select! {
+            entered = self.enter() => entered,
+            _ = cancel.cancelled() => None,
+        };
+
+        let Some((conn, avatar)) = entered else {
+            return;
+        };
+
+        self.stats.bot_online();
+
+        if let Err(e) = self.play(&conn, avatar, &cancel).await {
+            warn!("{}: disconnected: {e}", self.username);
+            self.stats.fail(Phase::InGame, e);
+        }
+
+        self.stats.bot_offline();
+        conn.close().await;
+    }
+
+    async fn enter(&self) -> Option<(Connection, AvatarState)> {
+        let login = self.phase(Phase::Login, self.login()).await?;
+        let cluster = self.phase(Phase::CharacterSelect, self.select_character(&login)).await?;
+        self.phase(Phase::EnterGame, self.enter_game(&login, cluster)).await
+    }
+
+    /// Runs one step of the login sequence, recording how long it took or why it failed.
+    async fn phase<T>(&self, phase: Phase, step: impl Future<Output = LoadtestResult<T>>) -> Option<T> {
+        let started = Instant::now();
+
+        match step.await {
+            Ok(res) => {
+                self.stats.record(phase, started.elapsed());
+                Some(res)
+            },
+            Err(e) => {
+                warn!("{}: {phase} failed: {e}", self.username);
+                self.stats.fail(phase, e);
+                None
+            }
+        }
+    }
+
+    async fn ensure_account(&self, core_api: &CoreApi) -> LoadtestResult<()> {
+        if core_api.find_account(AccountQuery::Username(self.username.clone())).await?.is_none() {
+            debug!("Registering account {}", self.username);
+            core_api.register_email_account(&self.username, None, &ARGS.password).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn login(&self) -> LoadtestResult<Login> {
+        if let Some(core_api) = &self.core_api {
+            self.ensure_account(core_api).await?;
+        }
+
+        let conn = Connection::connect(&ARGS.login_addr).await?;
+
+        conn.send(CPktLogin {
+            login_type: CpktLoginLoginType::Normal,
+            username: self.username.clone(),
+            password: ARGS.password.clone(),
+            fingerprint: vec![0; 16],
+            ..Default::default()
+        }).await?;
+
+        let mut result = conn.expect("login result", login_result).await?;
+        let session_id = result.session_id
+            .ok_or(anyhow::Error::msg("login result without session"))?;
+
+        if result.realm_ip.is_none() {
+            // More than one realm, so we have to pick one ourselves
+            let realms = conn.expect("realm list", |pkt| match pkt {
+                CPkt::oaPktRealmStatusList(pkt) => Some(Ok(pkt.realms)),
+                _ => None,
+            }).await?;
+
+            let realm = realms.into_iter()
+                .find(|realm| 
+                    ARGS.realm.is_none_or(|id| realm.id == id) &&
+                    !realm.name.starts_with("[OFFLINE]")
+                )
+                .ok_or(LoadtestError::Rejected("no realm available".to_string()))?;
+
+            conn.send(oaPktRealmSelect {
+                channel_id: realm.channel_id[0] as i32,
+                ..Default::default()
+            }).await?;
+
+            result = conn.expect("login result", login_result).await?;
+        }
+
+        conn.close().await;
+
+        if 
+            let Some(ip) = result.realm_ip &&
+            let Some(port) = result.realm_port
+        {
+            Ok(Login {
+                session_id,
+                frontend: addr_from_client(ip, port),
+            })
+        } else {
+            Err(anyhow::Error::msg("login result without realm endpoint").into())
+        }
+    }
+
+    async fn select_character(&self, login: &Login) -> LoadtestResult<SocketAddrV4> {
+        let conn = Connection::connect(login.frontend).await?;
+
+        conn.send(oaPktRequestCharacterList {
+            session_id: login.session_id,
+            ..Default::default()
+        }).await?;
+
+        let characters = conn.expect("character list", |pkt| match pkt {
+            CPkt::CPktStream_126_1(pkt) => Some(Ok(pkt.characters)),
+            _ => None,
+        }).await?;
+
+        let character_id = if let Some(character) = characters.into_iter().next() {
+            character.id
+        } else {
+            conn.send(oaPktCharacterCreate {
+                character_name: self.username.clone(),
+                ..Default::default()
+            }).await?;
+
+            conn.expect("character creation", |pkt| match pkt {
+                CPkt::CPktStream_126_5(pkt) => Some(Ok(pkt.character.id)),
+                CPkt::oaPktCharacterFailure(_) => Some(Err(LoadtestError::Rejected("character creation failed".to_string()))),
+                _ => None,
+            }).await?
+        };
+
+        conn.send(oaPktRequestSelectWorld::default()).await?;
+        conn.expect("world selection", |pkt| match pkt {
+            CPkt::oaPktResponseSelectWorld(pkt) if pkt.success => Some(Ok(())),
+            CPkt::oaPktResponseSelectWorld(_) => Some(Err(LoadtestError::Rejected("world selection failed".to_string()))),
+            _ => None,
+        }).await?;
+
+        conn.send(oaPktCharacterSelect {
+            character_id,
+            ..Default::default()
+        }).await?;
+
+        let cluster = conn.expect("character selection", |pkt| match pkt {
+            CPkt::oaPktCharacterSelectSuccess(pkt) => Some(Ok(addr_from_client(pkt.cluster_ip, pkt.cluster_port))),
+            _ => None,
+        }).await?;
+
+        conn.close().await;
+
+        Ok(cluster)
+    }
+
+    async fn enter_game(&self, login: &Login, cluster: SocketAddrV4) -> LoadtestResult<(Connection, AvatarState)> {
+        let conn = Connection::connect(cluster).await?;
+
+        conn.send(oaPktRequestEnterGame {
+            session_id: login.session_id,
+            ..Default::default()
+        }).await?;
+
+        let mut avatar = AvatarState::default();
+
+        timeout(Duration::from_secs(ARGS.timeout), async {
+            // The spawn action follows the InGame state
+            while !avatar.in_game || avatar.avatar_id == AvatarId::default() {
+                let pkt = conn.recv().await?;
+                avatar.handle(&conn, &pkt).await?;
+            }
+
+            Ok::<_, LoadtestError>(())
+        }).await
+        .map_err(|_| LoadtestError::Timeout("spawn"))??;
+
+        Ok((conn, avatar))
+    }
+
+    async fn play(&self, conn: &Connection, mut avatar: AvatarState, cancel: &CancellationToken) -> LoadtestResult<()> {
+        let started = Instant::now();
+        let mut movement = interval(Duration::from_millis(ARGS.move_interval));
+        let mut chat = interval(Duration::from_secs(ARGS.chat_interval.max(1)));
+        let mut pending_chat = HashMap::<String, Instant>::new();
+        let mut chat_seq = 0u32;
+        let mut angle = 0.0f32;
+
+        loop {
+            select! {
+                pkt = conn.recv() => {
+                    let pkt = pkt?;
+
+                    // Local chat is echoed back to the sender
+                    if 
+                        let CPkt::CPktChat(msg) = &pkt &&
+                        let Some(sent) = pending_chat.remove(&msg.message)
+                    {
+                        self.stats.record(Phase::Chat, sent.elapsed());
+                    }
+
+                    avatar.handle(conn, &pkt).await?;
+                },
+                _ = movement.tick(), if avatar.in_game && ARGS.move_speed > 0.0 => {
+                    // Walk in circles around the spawn point
+                    let dt = movement.period().as_secs_f32();
+                    angle = (angle + ARGS.move_speed * dt / ARGS.move_radius) % TAU;
+
+                    let pos = avatar.anchor + Vec3::new(angle.cos(), 0.0, angle.sin()) * ARGS.move_radius;
+                    let vel = (pos - avatar.pos) / dt;
+
+                    avatar.pos = pos;
+                    avatar.rot = Quat::from_unit_vector(vel.normalize_or(Vec3::X));
+
+                    conn.send(oaPktMoveManagerPosUpdate {
+                        avatar_id: avatar.avatar_id,
+                        pos: avatar.pos.into(),
+                        rot: avatar.rot.into(),
+                        vel: vel.into(),
+                        physics: Physics {
+                            state: PhysicsState::Walking,
+                        },
+                        seconds: started.elapsed().as_secs_f64(),
+                        ..Default::default()
+                    }).await?;
+                },
+                _ = chat.tick(), if avatar.in_game && ARGS.chat_interval > 0 => {
+                    pending_chat.retain(|_, sent| {
+                        if sent.elapsed() > Duration::from_secs(ARGS.timeout) {
+                            self.stats.fail(Phase::Chat, "no echo received");
+                            false
+                        } else {
+                            true
+                        }
+                    });
+
+                    chat_seq += 1;
+                    let message = format!("{} #{}", self.username, chat_seq);
+                    pending_chat.insert(message.clone(), Instant::now());
+
+                    conn.send(CPktChat {
+                        chat_type: CpktChatChatType::Local,
+                        message,
+                        ..Default::default()
+                    }).await?;
+                },
+                _ = cancel.cancelled() => break,
+            }
+        }
+
+        Ok(())
+    }
+}
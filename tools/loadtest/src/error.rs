@@ -0,0 +1,38 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use core_api::CoreApiError;
+use raknet::RakNetError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum LoadtestError {
+    #[error("core api error")]
+    CoreApi(#[from] CoreApiError),
+
+    #[error("raknet error: {0}")]
+    RakNet(#[from] RakNetError),
+
+    #[error("timed out waiting for {0}")]
+    Timeout(&'static str),
+
+    #[error("rejected: {0}")]
+    Rejected(String),
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+pub type LoadtestResult<T> = std::result::Result<T, LoadtestError>;
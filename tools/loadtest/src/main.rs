@@ -0,0 +1,165 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{sync::Arc, time::Duration};
+
+use bot::Bot;
+use clap::Parser;
+use core_api::CoreApi;
+use error::LoadtestResult;
+use log::info;
+use once_cell::sync::Lazy;
+use reqwest::Url;
+use stats::Stats;
+use tokio::{select, signal, task::JoinSet, time};
+use tokio_util::sync::CancellationToken;
+use toolkit::print_banner;
+
+mod bot;
+mod error;
+mod stats;
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[arg(long, env = "LOGIN_ADDR", default_value = "127.0.0.1:6112")]
+    login_addr: String,
+
+    #[arg(long, env = "SERVICE_CORE_API_URL", default_value = "http://127.0.0.1:8000")]
+    service_core_url: Url,
+
+    /// Number of bots to spawn
+    #[arg(short = 'n', long, default_value_t = 10)]
+    bots: usize,
+
+    /// Index of the first bot, to run multiple harnesses side by side
+    #[arg(long, default_value_t = 0)]
+    first_index: usize,
+
+    #[arg(long, default_value = "loadtest")]
+    username_prefix: String,
+
+    #[arg(long, default_value = "loadtest")]
+    password: String,
+
+    /// Realm to join, if the login server offers more than one
+    #[arg(long)]
+    realm: Option<i32>,
+
+    /// Create missing accounts through the core api
+    #[arg(long, default_value_t = false)]
+    register: bool,
+
+    /// Milliseconds between bot spawns
+    #[arg(long, default_value_t = 100)]
+    spawn_interval: u64,
+
+    /// Seconds to run, 0 runs until interrupted
+    #[arg(long, default_value_t = 60)]
+    duration: u64,
+
+    /// Milliseconds between movement updates
+    #[arg(long, default_value_t = 250)]
+    move_interval: u64,
+
+    /// Units per second, 0 disables movement
+    #[arg(long, default_value_t = 200.0)]
+    move_speed: f32,
+
+    #[arg(long, default_value_t = 500.0)]
+    move_radius: f32,
+
+    /// Seconds between local chat messages, 0 disables chat
+    #[arg(long, default_value_t = 10)]
+    chat_interval: u64,
+
+    /// Seconds to wait for any expected server response
+    #[arg(long, default_value_t = 30)]
+    timeout: u64,
+
+    /// Seconds between progress reports
+    #[arg(long, default_value_t = 10)]
+    report_interval: u64,
+}
+
+static ARGS: Lazy<Cli> = Lazy::new(Cli::parse);
+
+#[tokio::main]
+async fn main() -> LoadtestResult<()> {
+    Lazy::force(&ARGS);
+
+    let _ = toolkit::dotenvy::dotenv();
+    toolkit::env_logger::Builder::from_env(
+        toolkit::env_logger::Env::default()
+        .default_filter_or("info")
+    ).init();
+
+    print_banner();
+
+    let core_api = ARGS.register.then(|| CoreApi::new(ARGS.service_core_url.clone()));
+    let stats = Arc::new(Stats::default());
+    let cancel = CancellationToken::new();
+
+    {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            let _ = signal::ctrl_c().await;
+            info!("Stopping bots...");
+            cancel.cancel();
+        });
+    }
+
+    if ARGS.duration > 0 {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            time::sleep(Duration::from_secs(ARGS.duration)).await;
+            info!("Test duration elapsed, stopping bots...");
+            cancel.cancel();
+        });
+    }
+
+    {
+        let stats = stats.clone();
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            let mut report = time::interval(Duration::from_secs(ARGS.report_interval.max(1)));
+
+            loop {
+                select! {
+                    _ = report.tick() => info!("{} bots in game", stats.online()),
+                    _ = cancel.cancelled() => break,
+                }
+            }
+        });
+    }
+
+    info!("Spawning {} bots...", ARGS.bots);
+
+    let mut bots = JoinSet::new();
+    for index in ARGS.first_index..ARGS.first_index + ARGS.bots {
+        bots.spawn(Bot::new(index, core_api.clone(), stats.clone()).run(cancel.clone()));
+
+        select! {
+            _ = time::sleep(Duration::from_millis(ARGS.spawn_interval)) => (),
+            _ = cancel.cancelled() => break,
+        }
+    }
+
+    while bots.join_next().await.is_some() {}
+
+    println!("{}", stats.report());
+
+    Ok(())
+}
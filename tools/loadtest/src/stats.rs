@@ -0,0 +1,115 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::{BTreeMap, HashMap}, fmt::Display, sync::{atomic::{AtomicUsize, Ordering}, Mutex}, time::Duration};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Phase {
+    Login,
+    CharacterSelect,
+    EnterGame,
+    Chat,
+    InGame,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Login => f.write_str("login"),
+            Phase::CharacterSelect => f.write_str("character select"),
+            Phase::EnterGame => f.write_str("enter game"),
+            Phase::Chat => f.write_str("chat roundtrip"),
+            Phase::InGame => f.write_str("in game"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Stats {
+    latencies: Mutex<BTreeMap<Phase, Vec<Duration>>>,
+    failures: Mutex<BTreeMap<Phase, HashMap<String, usize>>>,
+    online: AtomicUsize,
+    peak_online: AtomicUsize,
+}
+
+impl Stats {
+    pub fn record(&self, phase: Phase, latency: Duration) {
+        self.latencies.lock().unwrap()
+            .entry(phase)
+            .or_default()
+            .push(latency);
+    }
+
+    pub fn fail(&self, phase: Phase, reason: impl ToString) {
+        *self.failures.lock().unwrap()
+            .entry(phase)
+            .or_default()
+            .entry(reason.to_string())
+            .or_default() += 1;
+    }
+
+    pub fn bot_online(&self) {
+        let online = self.online.fetch_add(1, Ordering::Relaxed) + 1;
+        self.peak_online.fetch_max(online, Ordering::Relaxed);
+    }
+
+    pub fn bot_offline(&self) {
+        self.online.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn online(&self) -> usize {
+        self.online.load(Ordering::Relaxed)
+    }
+
+    pub fn report(&self) -> String {
+        let mut report = format!("Peak players online: {}\n", self.peak_online.load(Ordering::Relaxed));
+
+        report.push_str("\nLatencies:\n");
+        for (phase, latencies) in self.latencies.lock().unwrap().iter_mut() {
+            latencies.sort();
+
+            report.push_str(&format!(
+                "  {:<18} n={:<6} min={:>8.1?} p50={:>8.1?} p95={:>8.1?} p99={:>8.1?} max={:>8.1?}\n",
+                phase.to_string(),
+                latencies.len(),
+                latencies[0],
+                percentile(latencies, 0.50),
+                percentile(latencies, 0.95),
+                percentile(latencies, 0.99),
+                latencies[latencies.len() - 1],
+            ));
+        }
+
+        let failures = self.failures.lock().unwrap();
+        if failures.is_empty() {
+            report.push_str("\nNo failures.\n");
+        } else {
+            report.push_str("\nFailures:\n");
+            for (phase, reasons) in failures.iter() {
+                for (reason, count) in reasons {
+                    report.push_str(&format!("  {:<18} {:>6}x {}\n", phase.to_string(), count, reason));
+                }
+            }
+        }
+
+        report
+    }
+}
+
+/// Expects a sorted, non-empty slice.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[idx]
+}
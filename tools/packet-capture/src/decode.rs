@@ -0,0 +1,85 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Write;
+
+use protocol::CPkt;
+use raknet::{CaptureDirection, CaptureReader, CaptureRecord};
+use serde_json::{json, Value};
+
+use crate::error::CaptureToolResult;
+
+/// Name of the packet variant, taken from its debug representation.
+pub fn packet_name(pkt: &CPkt) -> String {
+    let debug = format!("{pkt:?}");
+    debug.split(['(', ' ', '{'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+pub fn record_to_json(record: &CaptureRecord, first_timestamp: u64) -> Value {
+    let direction = match record.direction {
+        CaptureDirection::Received => "received",
+        CaptureDirection::Sent => "sent",
+    };
+
+    let offset_ms = record.timestamp.saturating_sub(first_timestamp) as f64 / 1000.0;
+
+    match CPkt::from_bytes(&record.payload) {
+        Ok((rest, pkt)) => json!({
+            "timestamp": record.timestamp,
+            "offset_ms": offset_ms,
+            "direction": direction,
+            "id": pkt.get_id(),
+            "name": packet_name(&pkt),
+            "packet": format!("{pkt:?}"),
+            "trailing_bytes": rest.len(),
+        }),
+        // Keep undecodable payloads, they are usually what we are looking for
+        Err(_) => json!({
+            "timestamp": record.timestamp,
+            "offset_ms": offset_ms,
+            "direction": direction,
+            "raw": hex(&record.payload),
+        }),
+    }
+}
+
+/// Writes every record of the capture file as a line of JSON.
+pub fn decode(path: &str, output: &mut impl Write) -> CaptureToolResult<usize> {
+    let mut first_timestamp = None;
+    let mut count = 0;
+
+    for record in CaptureReader::open(path)? {
+        let record = record?;
+        let first_timestamp = *first_timestamp.get_or_insert(record.timestamp);
+
+        serde_json::to_writer(&mut *output, &record_to_json(&record, first_timestamp))?;
+        writeln!(output)?;
+
+        count += 1;
+    }
+
+    output.flush()?;
+
+    Ok(count)
+}
@@ -0,0 +1,101 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fs::File, io::{self, BufWriter}, path::PathBuf};
+
+use clap::{Args, Parser, Subcommand};
+use error::CaptureToolResult;
+use log::info;
+use toolkit::types::Uuid;
+
+mod decode;
+mod error;
+mod replay;
+
+/// Inspects and replays RakNet session captures, as written by
+/// the cluster server when started with `--capture-dir`.
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Decodes a capture file into one JSON object per packet
+    Decode {
+        capture: String,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Replays the client side of a capture against a cluster server
+    Replay(ReplayArgs),
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    capture: String,
+
+    #[arg(long, env = "CLUSTER_ADDR", default_value = "127.0.0.1:6114")]
+    cluster_addr: String,
+
+    /// Session of a logged in account with a selected character,
+    /// replaces the session of the captured client.
+    #[arg(long)]
+    session_id: Uuid,
+
+    /// Playback speed, 2.0 replays twice as fast as captured
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// Seconds to wait for server responses after the last packet
+    #[arg(long, default_value_t = 5)]
+    linger: u64,
+
+    /// The capture was recorded on the client side of the connection
+    #[arg(long, default_value_t = false)]
+    client_capture: bool,
+
+    /// Capture the replayed session into this file
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> CaptureToolResult<()> {
+    let _ = toolkit::dotenvy::dotenv();
+    toolkit::env_logger::Builder::from_env(
+        toolkit::env_logger::Env::default()
+        .default_filter_or("info")
+    ).init();
+
+    match Cli::parse().command {
+        Commands::Decode { capture, output } => {
+            let count = if let Some(output) = output {
+                decode::decode(&capture, &mut BufWriter::new(File::create(output)?))?
+            } else {
+                decode::decode(&capture, &mut io::stdout().lock())?
+            };
+
+            info!("Decoded {count} records");
+        },
+        Commands::Replay(args) => replay::replay(args).await?,
+    }
+
+    Ok(())
+}
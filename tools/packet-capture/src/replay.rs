@@ -0,0 +1,139 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::BTreeMap, sync::{Arc, Mutex}, time::Duration};
+
+use log::{debug, info, warn};
+use protocol::{CPkt, OtherlandPacket};
+use raknet::{CaptureDirection, CaptureReader, RakNetSocket, Reliability};
+use tokio::time::{sleep, sleep_until, Instant};
+use toolkit::types::{AvatarId, Uuid};
+
+use crate::{decode::packet_name, error::CaptureToolResult, ReplayArgs};
+
+#[derive(Default)]
+struct ReplayState {
+    avatar_id: Option<AvatarId>,
+    received: BTreeMap<String, usize>,
+    undecodable: usize,
+}
+
+/// Session and avatar ids of the captured session are meaningless
+/// to the server we replay against, so swap them for the current ones.
+fn rewrite(payload: &[u8], session_id: Uuid, avatar_id: Option<AvatarId>) -> Vec<u8> {
+    match (CPkt::from_bytes(payload), avatar_id) {
+        (Ok((_, CPkt::oaPktRequestEnterGame(mut pkt))), _) => {
+            pkt.session_id = session_id;
+            pkt.into_pkt().to_bytes()
+        },
+        (Ok((_, CPkt::oaPktMoveManagerPosUpdate(mut pkt))), Some(avatar_id)) => {
+            pkt.avatar_id = avatar_id;
+            pkt.into_pkt().to_bytes()
+        },
+        _ => payload.to_vec(),
+    }
+}
+
+/// Sends the client side of a captured session to a cluster server, keeping
+/// the original timing, and reports what the server sent back.
+pub async fn replay(args: ReplayArgs) -> CaptureToolResult<()> {
+    // Captures are written by servers by default, so the client stream
+    // is what the server received.
+    let client_direction = if args.client_capture {
+        CaptureDirection::Sent
+    } else {
+        CaptureDirection::Received
+    };
+
+    let records = CaptureReader::open(&args.capture)?
+        .filter(|record| record.as_ref().map_or(true, |record| record.direction == client_direction))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let Some(first_timestamp) = records.first().map(|record| record.timestamp) else {
+        info!("Nothing to replay");
+        return Ok(());
+    };
+
+    info!("Replaying {} packets against {}", records.len(), args.cluster_addr);
+
+    let mut socket = RakNetSocket::connect(&args.cluster_addr, None).await?;
+    if let Some(output) = &args.output {
+        socket.capture_to(output)?;
+    }
+
+    let socket = Arc::new(socket);
+    let state = Arc::new(Mutex::new(ReplayState::default()));
+
+    let receiver = {
+        let socket = socket.clone();
+        let state = state.clone();
+
+        tokio::spawn(async move {
+            while let Ok(payload) = socket.recv().await {
+                let mut state = state.lock().unwrap();
+
+                match CPkt::from_bytes(&payload) {
+                    Ok((_, pkt)) => {
+                        if let CPkt::oaPktServerAction(action) = &pkt && action.override_teleport {
+                            state.avatar_id = Some(action.instigator);
+                        }
+
+                        *state.received.entry(packet_name(&pkt)).or_default() += 1;
+                    },
+                    Err(_) => state.undecodable += 1,
+                }
+            }
+        })
+    };
+
+    let started = Instant::now();
+
+    for record in &records {
+        let offset = Duration::from_micros(record.timestamp.saturating_sub(first_timestamp))
+            .div_f64(args.speed.max(f64::EPSILON));
+
+        sleep_until(started + offset).await;
+
+        if receiver.is_finished() {
+            warn!("Connection closed by server after {:.3}s", started.elapsed().as_secs_f64());
+            break;
+        }
+
+        let avatar_id = state.lock().unwrap().avatar_id;
+        let payload = rewrite(&record.payload, args.session_id, avatar_id);
+
+        debug!("Sending {} bytes at {:.3}s", payload.len(), offset.as_secs_f64());
+        socket.send(&payload, Reliability::ReliableOrdered).await?;
+    }
+
+    // Give the server some time to respond to the last packets
+    sleep(Duration::from_secs(args.linger)).await;
+
+    let closed = receiver.is_finished();
+    socket.close().await;
+
+    let state = state.lock().unwrap();
+    for (name, count) in state.received.iter() {
+        info!("{count:>6} {name}");
+    }
+
+    info!("Received {} packets, {} undecodable", state.received.values().sum::<usize>(), state.undecodable);
+
+    if closed {
+        Err(anyhow::Error::msg("server closed the connection during replay").into())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright (C) 2026 AnotherlandServer
+// 
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+// 
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{fs, path::Path};
+
+use anyhow::anyhow;
+use content::get_content_path;
+use futures_util::future::try_join_all;
+use log::info;
+use realm_api::{LootKind, LootTableBuilder, LootTableEntry, RealmApi};
+use serde::Deserialize;
+use toolkit::types::Uuid;
+
+use crate::{QuestCompilerError, Result};
+
+#[derive(Deserialize)]
+struct YamlLootTable {
+    id: Uuid,
+    name: String,
+    #[serde(default)]
+    npcs: Vec<Uuid>,
+    min_level: Option<i32>,
+    max_level: Option<i32>,
+    #[serde(default = "default_drop_chance")]
+    drop_chance: f64,
+    #[serde(default = "default_rolls")]
+    rolls: i32,
+    entries: Vec<YamlLootEntry>,
+}
+
+/// A single drop. Exactly one of `item`, `soma` or `bits` has to be set.
+#[derive(Deserialize)]
+struct YamlLootEntry {
+    item: Option<String>,
+    soma: Option<i32>,
+    bits: Option<YamlAmount>,
+    amount: Option<YamlAmount>,
+    #[serde(default = "default_weight")]
+    weight: i32,
+    quest: Option<i32>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(untagged)]
+enum YamlAmount {
+    Fixed(i32),
+    Range(i32, i32),
+}
+
+impl YamlAmount {
+    fn bounds(self) -> (i32, i32) {
+        match self {
+            YamlAmount::Fixed(amount) => (amount, amount),
+            YamlAmount::Range(min, max) => (min.min(max), min.max(max)),
+        }
+    }
+}
+
+fn default_drop_chance() -> f64 { 1.0 }
+fn default_rolls() -> i32 { 1 }
+fn default_weight() -> i32 { 1 }
+
+impl TryFrom<YamlLootEntry> for LootTableEntry {
+    type Error = QuestCompilerError;
+
+    fn try_from(value: YamlLootEntry) -> Result<Self> {
+        let (kind, item_name, soma_type, amount) = match (value.item, value.soma, value.bits) {
+            (Some(item), None, None) => (LootKind::Item, Some(item), None, value.amount),
+            (None, Some(soma), None) => (LootKind::Soma, None, Some(soma), value.amount),
+            (None, None, Some(bits)) => (LootKind::Bits, None, None, Some(bits)),
+            _ => return Err(anyhow!("loot entry needs exactly one of item, soma or bits").into()),
+        };
+
+        let (min_amount, max_amount) = amount
+            .unwrap_or(YamlAmount::Fixed(1))
+            .bounds();
+
+        Ok(LootTableEntry {
+            kind,
+            item_name,
+            soma_type,
+            min_amount,
+            max_amount,
+            weight: value.weight,
+            quest_id: value.quest,
+        })
+    }
+}
+
+pub async fn import_loot_table_file(path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+
+    info!("Importing loot table file {:?}", path);
+
+    let content = fs::read(path)
+        .map_err(|e| QuestCompilerError::Other(e.into()))?;
+
+    try_join_all(
+        serde_saphyr::from_multiple::<YamlLootTable>(
+            str::from_utf8(&content)
+                .map_err(|e| QuestCompilerError::Other(e.into()))?
+        )
+        .map_err(|e| QuestCompilerError::Other(e.into()))?
+        .into_iter()
+        .map(import_loot_table_yaml)
+        .collect::<Vec<_>>()
+    ).await?;
+
+    Ok(())
+}
+
+async fn import_loot_table_yaml(doc: YamlLootTable) -> Result<()> {
+    let loot_table = LootTableBuilder::default()
+        .id(doc.id)
+        .name(doc.name)
+        .npcs(doc.npcs)
+        .min_level(doc.min_level)
+        .max_level(doc.max_level)
+        .drop_chance(doc.drop_chance)
+        .rolls(doc.rolls)
+        .entries(
+            doc.entries
+                .into_iter()
+                .map(LootTableEntry::try_from)
+                .collect::<Result<Vec<_>>>()?
+        )
+        .build()
+        .map_err(|e| QuestCompilerError::Other(e.into()))?;
+
+    if RealmApi::get()
+        .get_loot_table(loot_table.id)
+        .await?
+        .is_some()
+    {
+        info!("Updating loot table {}", loot_table.name);
+
+        loot_table.save().await?;
+    } else {
+        info!("Importing loot table {}", loot_table.name);
+
+        RealmApi::get()
+            .create_loot_table(loot_table)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn import_loot_tables() -> Result<()> {
+    let loot_table_folder = get_content_path("loot")?;
+
+    info!("Updating loot tables from folder {:?}", loot_table_folder);
+
+    try_join_all(
+        loot_table_folder
+            .read_dir()
+                .map_err(|e| QuestCompilerError::Other(e.into()))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().and_then(|ext| ext.to_str()) == Some("yaml"))
+            .map(|entry| import_loot_table_file(entry.path()))
+    ).await?;
+
+    Ok(())
+}
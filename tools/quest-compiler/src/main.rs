@@ -26,10 +26,11 @@ use toolkit::{once_cell::sync::Lazy, print_banner};
 mod error;
 mod dialogue_importer;
 mod quest_importer;
+mod loot_table_importer;
 
 pub use error::*;
 
-use crate::{dialogue_importer::{import_dialogues, watch_dialogue_changes}, quest_importer::{import_quest_templates, watch_quest_template_changes}};
+use crate::{dialogue_importer::{import_dialogues, watch_dialogue_changes}, loot_table_importer::import_loot_tables, quest_importer::{import_quest_templates, watch_quest_template_changes}};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -79,6 +80,10 @@ async fn main() -> Result<()> {
         watch_quest_template_changes()?;
     }
 
+    if let Err(e) = import_loot_tables().await {
+        error!("Failed to import loot tables: {:?}", e);
+    }
+
     if ARGS.hot_reload_dialogues || ARGS.hot_reload_quests {
         info!("Watching for changes...");
 